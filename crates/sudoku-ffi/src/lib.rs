@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use sudoku_core::{
     canonical_puzzle_hash_str, BitSet, Difficulty, Generator, Grid, Hint, HintType, Polarity,
     Position, ProofCertificate, PuzzleId, Solver,
 };
+use sudoku_shared::{achievements, records};
 
 uniffi::setup_scaffolding!();
 
@@ -1099,3 +1101,235 @@ pub fn game_deserialize(json: String) -> Option<Arc<SudokuGame>> {
         se_rating_cache: Mutex::new(None),
     }))
 }
+
+/// Game history after merging an import
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RecordsMerge {
+    /// Merged history as a "sudoku-game-records" JSON export
+    pub records_json: String,
+    /// Games that were new
+    pub added: u32,
+    /// Games already in the history
+    pub duplicates: u32,
+}
+
+/// Aggregates recomputed from game records
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RecordsSummary {
+    pub games_played: u32,
+    pub games_won: u32,
+    pub games_lost: u32,
+    pub games_abandoned: u32,
+    pub total_play_time_secs: u64,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    /// Best winning time in seconds, keyed by difficulty name
+    pub best_times: HashMap<String, u64>,
+    /// Wins keyed by difficulty name
+    pub wins_per_difficulty: HashMap<String, u32>,
+}
+
+/// Convert any supported stats export (records JSON or CSV, a TUI stats file,
+/// or the encoded `PuzzleRecord` library) to a "sudoku-game-records" JSON export
+/// written by `source` (e.g. "ios"). Returns nil if the input isn't recognised.
+#[uniffi::export]
+pub fn export_records_json(input: String, source: String, exported_at: u64) -> Option<String> {
+    let records = records::parse(&input).ok()?;
+    Some(records::to_json(&records, &source, exported_at))
+}
+
+/// Convert any supported stats export to CSV. Returns nil if the input isn't recognised.
+#[uniffi::export]
+pub fn export_records_csv(input: String) -> Option<String> {
+    let records = records::parse(&input).ok()?;
+    Some(records::to_csv(&records))
+}
+
+/// Merge an export from another platform into `existing` (any supported
+/// format, or empty), skipping games already present. The merged export is
+/// written by `source`. Returns nil if either input isn't recognised.
+#[uniffi::export]
+pub fn merge_records(existing: String, incoming: String, source: String) -> Option<RecordsMerge> {
+    let mut history = if existing.trim().is_empty() {
+        Vec::new()
+    } else {
+        records::parse(&existing).ok()?
+    };
+    let incoming = records::parse(&incoming).ok()?;
+    let (added, summary) = records::merge(&history, incoming);
+    history.extend(added);
+    history.sort_by_key(|r| r.timestamp);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Some(RecordsMerge {
+        records_json: records::to_json(&history, &source, now),
+        added: summary.added as u32,
+        duplicates: summary.duplicates as u32,
+    })
+}
+
+/// Recompute aggregate statistics from game records (any supported format).
+/// Returns nil if the input isn't recognised.
+#[uniffi::export]
+pub fn summarize_records(input: String) -> Option<RecordsSummary> {
+    let summary = records::summarize(&records::parse(&input).ok()?);
+    Some(RecordsSummary {
+        games_played: summary.games as u32,
+        games_won: summary.wins as u32,
+        games_lost: summary.losses as u32,
+        games_abandoned: summary.abandoned as u32,
+        total_play_time_secs: summary.total_time_secs,
+        current_streak: summary.current_streak as u32,
+        best_streak: summary.best_streak as u32,
+        best_times: summary
+            .by_difficulty
+            .iter()
+            .filter_map(|(name, d)| Some((name.clone(), d.best_time_secs?)))
+            .collect(),
+        wins_per_difficulty: summary
+            .by_difficulty
+            .iter()
+            .map(|(name, d)| (name.clone(), d.wins as u32))
+            .collect(),
+    })
+}
+
+/// An achievement tier that was just reached
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct AchievementUnlock {
+    pub id: String,
+    /// Display name including the tier, e.g. "Century (Silver)"
+    pub title: String,
+    pub description: String,
+    /// Tier reached, from 1
+    pub tier: u32,
+    pub tiers: u32,
+    /// This tier unlocks the Master and Extreme difficulties
+    pub unlocks_secret_difficulties: bool,
+}
+
+/// Achievement state after a game or event
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct AchievementsUpdate {
+    /// Updated state to persist and pass to the next call
+    pub state_json: String,
+    /// Tiers reached by this call, to announce
+    pub unlocked: Vec<AchievementUnlock>,
+    /// Whether the secret difficulties are unlocked now
+    pub secret_difficulties: bool,
+}
+
+/// Progress toward one achievement. Hidden achievements have no name,
+/// description or targets until reached.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct AchievementProgress {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub value: u64,
+    /// Tiers reached, from 1
+    pub tier: u32,
+    pub targets: Vec<u64>,
+    pub next: Option<u64>,
+    pub unlocked_at: Option<u64>,
+    /// Tier (from 1) that unlocks the secret difficulties
+    pub reward_tier: Option<u32>,
+}
+
+fn achievement_state(state_json: &str) -> Option<achievements::AchievementState> {
+    if state_json.trim().is_empty() {
+        Some(Default::default())
+    } else {
+        serde_json::from_str(state_json).ok()
+    }
+}
+
+fn achievements_update(
+    state: &achievements::AchievementState,
+    unlocks: Vec<achievements::Unlock>,
+) -> AchievementsUpdate {
+    AchievementsUpdate {
+        state_json: serde_json::to_string(state).unwrap_or_default(),
+        unlocked: unlocks
+            .iter()
+            .map(|u| AchievementUnlock {
+                id: u.id.to_string(),
+                title: u.to_string(),
+                description: u.description.to_string(),
+                tier: u.tier as u32,
+                tiers: u.tiers as u32,
+                unlocks_secret_difficulties: u.reward
+                    == Some(achievements::Reward::SecretDifficulties),
+            })
+            .collect(),
+        secret_difficulties: state.has_reward(achievements::Reward::SecretDifficulties),
+    }
+}
+
+/// Count a finished game (one record of the "sudoku-game-records" format, as
+/// JSON) toward achievements. `state_json` is the previous state, or empty.
+/// Returns nil if either input can't be read.
+#[uniffi::export]
+pub fn achievements_record_game(
+    state_json: String,
+    record_json: String,
+) -> Option<AchievementsUpdate> {
+    let mut state = achievement_state(&state_json)?;
+    let record: records::GameRecord = serde_json::from_str(&record_json).ok()?;
+    let unlocks = state.record_game(&record);
+    Some(achievements_update(&state, unlocks))
+}
+
+/// Report an achievement event ("konami", "no_notes_win", ...). `now` is
+/// the Unix time in seconds. Returns nil if the state can't be read.
+#[uniffi::export]
+pub fn achievements_trigger(
+    state_json: String,
+    event: String,
+    now: u64,
+) -> Option<AchievementsUpdate> {
+    let mut state = achievement_state(&state_json)?;
+    let unlocks = state.trigger(&event, now);
+    Some(achievements_update(&state, unlocks))
+}
+
+/// Achievement state rebuilt from past games (any supported stats export),
+/// for players who have history from before achievements existed. Returns
+/// nil if the input isn't recognised.
+#[uniffi::export]
+pub fn achievements_from_records(input: String) -> Option<String> {
+    let mut history = records::parse(&input).ok()?;
+    history.sort_by_key(|r| r.timestamp);
+    let state = achievements::AchievementState::from_games(&history);
+    serde_json::to_string(&state).ok()
+}
+
+/// Progress toward every achievement, in display order. Returns nil if the
+/// state can't be read.
+#[uniffi::export]
+pub fn achievements_progress(state_json: String) -> Option<Vec<AchievementProgress>> {
+    let state = achievement_state(&state_json)?;
+    Some(
+        state
+            .progress()
+            .iter()
+            .map(|p| {
+                let s = p.summary();
+                AchievementProgress {
+                    id: s.id.to_string(),
+                    name: s.name.map(str::to_string),
+                    description: s.description.map(str::to_string),
+                    value: s.value,
+                    tier: s.tier as u32,
+                    targets: s.targets,
+                    next: s.next,
+                    unlocked_at: s.unlocked_at,
+                    reward_tier: s.reward_tier.map(|t| t as u32),
+                }
+            })
+            .collect(),
+    )
+}
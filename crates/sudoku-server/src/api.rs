@@ -0,0 +1,964 @@
+//! Request handling for the ukodus-compatible HTTP API.
+//!
+//! ```text
+//! POST   /api/v1/token                      {"player_id", "player_secret"?}
+//!                                                                  -> {token, expires_at, player_secret?}
+//! POST   /api/v1/results                    result body            -> {ok, verified, issues, duplicate}
+//! POST   /api/v1/leaderboard/submit         {"entry":{...}}        -> {success, message, rank}
+//! GET    /api/v1/leaderboard?limit=&offset=&difficulty=&puzzle=&verified=&since=
+//!                                                                  -> {entries, total, next_offset}
+//! GET    /api/v1/leaderboard/rank/{player}/{difficulty}            -> {rank}
+//! GET    /api/v1/leaderboard/player/{player}?limit=                -> {entries}
+//! DELETE /api/v1/players/{player_id}                               -> {deleted, removed}
+//! GET    /api/v1/health                                            -> {ok, results}
+//! ```
+//!
+//! The first token request for a player id registers it and returns a
+//! `player_secret`; later requests for that id must send the secret back.
+//! Scores keep the name of the first player to submit under it.
+//!
+//! Errors are returned as `{"error": message}`. Handlers are plain functions
+//! of a [`Request`] so they can be tested without a socket.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sudoku_core::{canonical_puzzle_hash_str, Difficulty};
+use sudoku_verify::antibot::{self, GameSummary};
+use sudoku_verify::{Claim, MoveLogEntry};
+
+use crate::store::{ResultRow, ScoreFilter, ScoreRow, Store};
+
+/// Default page size for leaderboard listings
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Largest page a client may request
+pub const MAX_LIMIT: usize = 100;
+
+/// Longest accepted player name
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Longest accepted player id
+pub const MAX_PLAYER_ID_LEN: usize = 128;
+
+/// Server behaviour knobs
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    /// How long issued tokens stay valid
+    pub token_ttl_secs: u64,
+    /// Accept submissions without a bearer token
+    pub allow_anonymous: bool,
+    /// Keys accepted as trusted bearers (e.g. for CI or admin tools)
+    pub api_keys: Vec<String>,
+    /// Requests per player per minute; 0 disables limiting
+    pub rate_limit_per_min: u32,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            token_ttl_secs: 24 * 60 * 60,
+            allow_anonymous: false,
+            api_keys: Vec::new(),
+            rate_limit_per_min: 60,
+        }
+    }
+}
+
+/// An incoming HTTP request, reduced to what the API needs
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// Path including query string
+    pub url: String,
+    /// Raw `Authorization` header
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn new(method: &str, url: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn with_bearer(mut self, token: &str) -> Self {
+        self.authorization = Some(format!("Bearer {}", token));
+        self
+    }
+
+    fn bearer(&self) -> Option<&str> {
+        self.authorization
+            .as_deref()
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    }
+}
+
+/// A JSON response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+    /// Seconds for the `Retry-After` header
+    pub retry_after: Option<u64>,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            body,
+            retry_after: None,
+        }
+    }
+}
+
+/// A failed request, rendered as `{"error": message}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+    pub retry_after: Option<u64>,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, message)
+    }
+
+    fn unauthorized() -> Self {
+        Self::new(401, "Missing or invalid token")
+    }
+
+    fn forbidden() -> Self {
+        Self::new(403, "Token does not belong to this player")
+    }
+
+    fn not_found() -> Self {
+        Self::new(404, "Not found")
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        eprintln!("storage error: {}", e);
+        Self::new(500, "Storage error")
+    }
+}
+
+impl From<ApiError> for Response {
+    fn from(e: ApiError) -> Self {
+        Self {
+            status: e.status,
+            body: json!({ "error": e.message }),
+            retry_after: e.retry_after,
+        }
+    }
+}
+
+type ApiResult = Result<Value, ApiError>;
+
+/// Who is making a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Caller {
+    /// Holder of a token issued for this player id
+    Player(String),
+    /// Holder of a configured API key
+    Trusted,
+    /// No credentials, accepted because anonymous access is enabled
+    Anonymous,
+}
+
+/// Leaderboard entry as exchanged with the clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub player_name: String,
+    #[serde(default)]
+    pub score: u64,
+    pub time_secs: u64,
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub hints_used: usize,
+    #[serde(default)]
+    pub mistakes: usize,
+    pub timestamp: u64,
+    pub puzzle_hash: String,
+    #[serde(default)]
+    pub rank: Option<usize>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+impl Entry {
+    fn from_row(row: ScoreRow, rank: Option<usize>) -> Option<Self> {
+        Some(Self {
+            difficulty: parse_difficulty(&row.difficulty).ok()?,
+            player_name: row.player_name,
+            score: row.score,
+            time_secs: row.time_secs,
+            hints_used: row.hints_used,
+            mistakes: row.mistakes,
+            timestamp: row.timestamp,
+            puzzle_hash: row.puzzle_hash,
+            rank,
+            verified: row.verified,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenBody {
+    player_id: String,
+    /// Secret returned when the player id was registered
+    #[serde(default)]
+    player_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitBody {
+    entry: Entry,
+}
+
+/// Body of `POST /api/v1/results`, as sent by the TUI and WASM clients
+#[derive(Debug, Deserialize)]
+struct ResultBody {
+    player_id: String,
+    #[serde(default)]
+    record_id: Option<u64>,
+    puzzle_hash: String,
+    #[serde(default)]
+    puzzle_string: Option<String>,
+    difficulty: String,
+    result: String,
+    time_secs: u64,
+    #[serde(default)]
+    hints_used: usize,
+    #[serde(default)]
+    mistakes: usize,
+    #[serde(default)]
+    moves_count: usize,
+    #[serde(default)]
+    avg_move_time_ms: u64,
+    #[serde(default)]
+    min_move_time_ms: u64,
+    #[serde(default)]
+    move_time_std_dev: f32,
+    /// Optional move log for full replay verification
+    #[serde(default)]
+    move_log: Option<Vec<MoveLogEntry>>,
+}
+
+/// Clients send difficulties in their `Debug` form ("Hard", "Expert", ...)
+fn parse_difficulty(s: &str) -> Result<Difficulty, ApiError> {
+    serde_json::from_value(Value::String(s.to_string()))
+        .map_err(|_| ApiError::bad_request(format!("Unknown difficulty: {}", s)))
+}
+
+fn parse_json<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("Invalid body: {}", e)))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(k), decode(v))
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    let s = s.replace('+', " ");
+    urlencoding::decode(&s).map(|d| d.into_owned()).unwrap_or(s)
+}
+
+fn query_num<T: std::str::FromStr>(
+    query: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<T>, ApiError> {
+    query
+        .get(key)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| ApiError::bad_request(format!("Invalid {}: {}", key, v)))
+        })
+        .transpose()
+}
+
+/// Leaderboard filter from `difficulty`, `puzzle`, `verified` and `since` parameters
+fn score_filter(query: &HashMap<String, String>) -> Result<ScoreFilter, ApiError> {
+    let difficulty = query
+        .get("difficulty")
+        .map(|d| parse_difficulty(d).map(|d| format!("{:?}", d)))
+        .transpose()?;
+    Ok(ScoreFilter {
+        difficulty,
+        puzzle_hash: query.get("puzzle").cloned(),
+        verified_only: matches!(
+            query.get("verified").map(String::as_str),
+            Some("1" | "true")
+        ),
+        since: query_num(query, "since")?,
+    })
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Player secrets are stored hashed, so a leaked database can't mint tokens
+fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The API: storage plus per-player rate limits
+pub struct Api {
+    store: Mutex<Store>,
+    config: ApiConfig,
+    /// player key -> (window start, requests in window)
+    windows: Mutex<HashMap<String, (u64, u32)>>,
+}
+
+impl Api {
+    pub fn new(store: Store, config: ApiConfig) -> Self {
+        Self {
+            store: Mutex::new(store),
+            config,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ApiConfig {
+        &self.config
+    }
+
+    /// Handle one request at time `now` (unix seconds)
+    pub fn handle(&self, req: &Request, now: u64) -> Response {
+        let (path, query) = req.url.split_once('?').unwrap_or((&req.url, ""));
+        let query = parse_query(query);
+        let segments: Vec<String> = path.trim_matches('/').split('/').map(decode).collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let result = match (req.method.as_str(), segments.as_slice()) {
+            ("GET", ["api", "v1", "health"]) => self.health(),
+            ("POST", ["api", "v1", "token"]) => self.issue_token(req, now),
+            ("POST", ["api", "v1", "results"]) => self.submit_result(req, now),
+            ("POST", ["api", "v1", "leaderboard", "submit"]) => self.submit_score(req, now),
+            ("GET", ["api", "v1", "leaderboard"]) => self.leaderboard(&query),
+            ("GET", ["api", "v1", "leaderboard", "rank", player, difficulty]) => {
+                self.rank(player, difficulty, &query)
+            }
+            ("GET", ["api", "v1", "leaderboard", "player", player]) => {
+                self.player_scores(player, &query)
+            }
+            ("DELETE", ["api", "v1", "players", player_id]) => {
+                self.delete_player(req, player_id, now)
+            }
+            _ => Err(ApiError::not_found()),
+        };
+
+        match result {
+            Ok(body) => Response::ok(body),
+            Err(e) => e.into(),
+        }
+    }
+
+    fn store(&self) -> std::sync::MutexGuard<'_, Store> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn caller(&self, req: &Request, now: u64) -> Result<Caller, ApiError> {
+        match req.bearer() {
+            Some(key) if self.config.api_keys.iter().any(|k| k == key) => Ok(Caller::Trusted),
+            Some(token) => self
+                .store()
+                .token_player(token, now)?
+                .map(Caller::Player)
+                .ok_or_else(ApiError::unauthorized),
+            None if self.config.allow_anonymous => Ok(Caller::Anonymous),
+            None => Err(ApiError::unauthorized()),
+        }
+    }
+
+    /// Count a request against `key`'s per-minute budget
+    fn check_rate(&self, key: &str, now: u64) -> Result<(), ApiError> {
+        let limit = self.config.rate_limit_per_min;
+        if limit == 0 {
+            return Ok(());
+        }
+        let window_start = now - now % 60;
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        windows.retain(|_, (start, _)| *start == window_start);
+        let (_, count) = windows.entry(key.to_string()).or_insert((window_start, 0));
+        if *count >= limit {
+            return Err(ApiError {
+                retry_after: Some(window_start + 60 - now),
+                ..ApiError::new(429, "Rate limit exceeded")
+            });
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    fn health(&self) -> ApiResult {
+        Ok(json!({ "ok": true, "results": self.store().result_count()? }))
+    }
+
+    fn issue_token(&self, req: &Request, now: u64) -> ApiResult {
+        let body: TokenBody = parse_json(&req.body)?;
+        let player_id = body.player_id.trim();
+        if player_id.is_empty() || player_id.len() > MAX_PLAYER_ID_LEN {
+            return Err(ApiError::bad_request("Invalid player_id"));
+        }
+        self.check_rate(&format!("token:{}", player_id), now)?;
+
+        let store = self.store();
+        // A new id is registered to this caller; a known one needs its secret
+        let secret = match store.player_secret_hash(player_id)? {
+            None => {
+                let secret = new_token();
+                store.insert_player(player_id, &hash_secret(&secret), now)?;
+                Some(secret)
+            }
+            Some(hash) => {
+                if body.player_secret.as_deref().map(hash_secret) != Some(hash) {
+                    return Err(ApiError::new(403, "Wrong or missing player secret"));
+                }
+                None
+            }
+        };
+
+        let token = new_token();
+        let expires_at = now + self.config.token_ttl_secs;
+        store.purge_tokens(now)?;
+        store.insert_token(&token, player_id, expires_at)?;
+        let mut response = json!({ "token": token, "expires_at": expires_at });
+        if let Some(secret) = secret {
+            response["player_secret"] = json!(secret);
+        }
+        Ok(response)
+    }
+
+    fn submit_result(&self, req: &Request, now: u64) -> ApiResult {
+        let caller = self.caller(req, now)?;
+        let body: ResultBody = parse_json(&req.body)?;
+        if matches!(&caller, Caller::Player(id) if *id != body.player_id) {
+            return Err(ApiError::forbidden());
+        }
+        self.check_rate(&body.player_id, now)?;
+
+        let difficulty = parse_difficulty(&body.difficulty)?;
+        let won = body.result == "Win";
+        let mut issues = Vec::new();
+
+        if won {
+            issues.extend(antibot::check(&GameSummary {
+                difficulty,
+                time_secs: body.time_secs,
+                hints_used: body.hints_used,
+                mistakes: body.mistakes,
+                moves_count: body.moves_count,
+                avg_move_time_ms: body.avg_move_time_ms,
+                min_move_time_ms: body.min_move_time_ms,
+                move_time_std_dev: body.move_time_std_dev,
+            }));
+        }
+
+        if let Some(ref puzzle) = body.puzzle_string {
+            if canonical_puzzle_hash_str(puzzle) != body.puzzle_hash {
+                issues.push("Puzzle hash does not match puzzle".to_string());
+            }
+        }
+
+        match (&body.move_log, &body.puzzle_string) {
+            (Some(log), Some(puzzle)) if won => {
+                let claim = Claim {
+                    time_secs: body.time_secs,
+                    mistakes: body.mistakes,
+                    hints_used: body.hints_used,
+                };
+                let verdict = sudoku_verify::verify(puzzle, log, &claim);
+                issues.extend(verdict.issues.iter().map(|i| i.to_string()));
+            }
+            (Some(_), None) => issues.push("Move log sent without puzzle".to_string()),
+            _ => {}
+        }
+
+        let verified = won && issues.is_empty();
+        let row = ResultRow {
+            player_id: body.player_id.clone(),
+            record_id: body.record_id,
+            puzzle_hash: body.puzzle_hash.clone(),
+            difficulty: format!("{:?}", difficulty),
+            result: body.result.clone(),
+            time_secs: body.time_secs,
+            hints_used: body.hints_used,
+            mistakes: body.mistakes,
+            verified,
+            issues: issues.clone(),
+            body: req.body.clone(),
+            received_at: now,
+        };
+
+        let store = self.store();
+        let inserted = store.insert_result(&row)?;
+        if inserted && verified {
+            store.verify_scores(&row.player_id, &row.puzzle_hash, row.time_secs)?;
+        }
+
+        Ok(json!({
+            "ok": true,
+            "verified": verified,
+            "issues": issues,
+            "duplicate": !inserted,
+        }))
+    }
+
+    fn submit_score(&self, req: &Request, now: u64) -> ApiResult {
+        let caller = self.caller(req, now)?;
+        let SubmitBody { entry } = parse_json(&req.body)?;
+
+        let name = entry.player_name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(ApiError::new(
+                422,
+                format!("Player name must be 1-{} characters", MAX_NAME_LEN),
+            ));
+        }
+        let min_time = antibot::min_time_secs(entry.difficulty);
+        if entry.time_secs < min_time {
+            return Err(ApiError::new(
+                422,
+                format!(
+                    "Time too fast: {}s (min {}s for {:?})",
+                    entry.time_secs, min_time, entry.difficulty
+                ),
+            ));
+        }
+
+        // Anonymous and API-key submissions are keyed by name
+        let trusted = caller == Caller::Trusted;
+        let player_id = match caller {
+            Caller::Player(id) => id,
+            Caller::Trusted | Caller::Anonymous => format!("name:{}", name),
+        };
+        self.check_rate(&player_id, now)?;
+
+        let store = self.store();
+        if !trusted && store.name_taken(name, &player_id)? {
+            return Err(ApiError::new(409, "Player name is taken"));
+        }
+        let row = ScoreRow {
+            verified: store.has_verified_result(&player_id, &entry.puzzle_hash, entry.time_secs)?,
+            player_id,
+            player_name: name.to_string(),
+            difficulty: format!("{:?}", entry.difficulty),
+            puzzle_hash: entry.puzzle_hash,
+            // Never trust the client's score
+            score: antibot::score(entry.time_secs, entry.hints_used, entry.mistakes),
+            time_secs: entry.time_secs,
+            hints_used: entry.hints_used,
+            mistakes: entry.mistakes,
+            timestamp: entry.timestamp,
+        };
+        let inserted = store.insert_score(&row)?;
+        let rank = store.player_rank(
+            &row.player_name,
+            &ScoreFilter {
+                difficulty: Some(row.difficulty.clone()),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(json!({
+            "success": true,
+            "message": (!inserted).then_some("Score already submitted"),
+            "rank": rank,
+        }))
+    }
+
+    fn leaderboard(&self, query: &HashMap<String, String>) -> ApiResult {
+        let filter = score_filter(query)?;
+        let limit = query_num(query, "limit")?
+            .unwrap_or(DEFAULT_LIMIT)
+            .min(MAX_LIMIT);
+        let offset = query_num(query, "offset")?.unwrap_or(0);
+
+        let (rows, total) = self.store().leaderboard(&filter, limit, offset)?;
+        let next_offset = offset + rows.len();
+        let entries: Vec<Entry> = rows
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, row)| Entry::from_row(row, Some(offset + i + 1)))
+            .collect();
+
+        Ok(json!({
+            "entries": entries,
+            "total": total,
+            "next_offset": (next_offset < total).then_some(next_offset),
+        }))
+    }
+
+    fn rank(&self, player: &str, difficulty: &str, query: &HashMap<String, String>) -> ApiResult {
+        let filter = ScoreFilter {
+            difficulty: Some(format!("{:?}", parse_difficulty(difficulty)?)),
+            ..score_filter(query)?
+        };
+        Ok(json!({ "rank": self.store().player_rank(player, &filter)? }))
+    }
+
+    fn player_scores(&self, player: &str, query: &HashMap<String, String>) -> ApiResult {
+        let limit = query_num(query, "limit")?
+            .unwrap_or(DEFAULT_LIMIT)
+            .min(MAX_LIMIT);
+        let entries: Vec<Entry> = self
+            .store()
+            .player_scores(player, limit)?
+            .into_iter()
+            .filter_map(|row| Entry::from_row(row, None))
+            .collect();
+        Ok(json!({ "entries": entries }))
+    }
+
+    fn delete_player(&self, req: &Request, player_id: &str, now: u64) -> ApiResult {
+        match self.caller(req, now)? {
+            Caller::Trusted => {}
+            Caller::Player(id) if id == player_id => {}
+            Caller::Player(_) => return Err(ApiError::forbidden()),
+            Caller::Anonymous => return Err(ApiError::unauthorized()),
+        }
+        let removed = self.store().delete_player(player_id)?;
+        Ok(json!({ "deleted": true, "removed": removed }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn api(config: ApiConfig) -> Api {
+        Api::new(Store::in_memory().unwrap(), config)
+    }
+
+    fn request_token(api: &Api, body: Value) -> Response {
+        api.handle(
+            &Request::new("POST", "/api/v1/token").with_body(body.to_string()),
+            NOW,
+        )
+    }
+
+    /// Register `player_id` and return its first token
+    fn token(api: &Api, player_id: &str) -> String {
+        let resp = request_token(api, json!({ "player_id": player_id }));
+        assert_eq!(resp.status, 200);
+        resp.body["token"].as_str().unwrap().to_string()
+    }
+
+    fn result_body(player_id: &str, record_id: u64) -> Value {
+        json!({
+            "record_id": record_id,
+            "puzzle_hash": "abc",
+            "difficulty": "Hard",
+            "result": "Win",
+            "time_secs": 600,
+            "hints_used": 1,
+            "mistakes": 2,
+            "moves_count": 55,
+            "avg_move_time_ms": 10_000,
+            "min_move_time_ms": 800,
+            "move_time_std_dev": 4_000.0,
+            "player_id": player_id,
+            "platform": "tui",
+        })
+    }
+
+    fn entry(name: &str, time_secs: u64, timestamp: u64) -> Value {
+        json!({ "entry": {
+            "player_name": name,
+            "score": 1,
+            "time_secs": time_secs,
+            "difficulty": "Hard",
+            "hints_used": 1,
+            "mistakes": 2,
+            "timestamp": timestamp,
+            "puzzle_hash": "abc",
+        }})
+    }
+
+    fn post(api: &Api, url: &str, token: &str, body: &Value) -> Response {
+        api.handle(
+            &Request::new("POST", url)
+                .with_bearer(token)
+                .with_body(body.to_string()),
+            NOW,
+        )
+    }
+
+    #[test]
+    fn test_results_require_matching_token() {
+        let api = api(ApiConfig::default());
+        let body = result_body("p1", 1);
+
+        let resp = api.handle(
+            &Request::new("POST", "/api/v1/results").with_body(body.to_string()),
+            NOW,
+        );
+        assert_eq!(resp.status, 401);
+        assert_eq!(post(&api, "/api/v1/results", "bogus", &body).status, 401);
+
+        let other = token(&api, "p2");
+        assert_eq!(post(&api, "/api/v1/results", &other, &body).status, 403);
+
+        let own = token(&api, "p1");
+        let resp = post(&api, "/api/v1/results", &own, &body);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["verified"], true);
+        assert_eq!(resp.body["duplicate"], false);
+
+        let resp = post(&api, "/api/v1/results", &own, &body);
+        assert_eq!(resp.body["duplicate"], true);
+    }
+
+    #[test]
+    fn test_results_flag_bot_timing() {
+        let api = api(ApiConfig::default());
+        let token = token(&api, "p1");
+        let mut body = result_body("p1", 1);
+        body["time_secs"] = json!(20);
+        body["move_time_std_dev"] = json!(1.0);
+
+        let resp = post(&api, "/api/v1/results", &token, &body);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["verified"], false);
+        assert_eq!(resp.body["issues"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_submit_ranks_by_server_score() {
+        let api = api(ApiConfig::default());
+        let ada = token(&api, "ada-id");
+        let bob = token(&api, "bob-id");
+
+        // Verified by a matching result
+        post(&api, "/api/v1/results", &ada, &result_body("ada-id", 1));
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &ada,
+            &entry("ada", 600, 1),
+        );
+        assert_eq!(resp.body["rank"], 1);
+
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &bob,
+            &entry("bob", 300, 2),
+        );
+        assert_eq!(resp.body["success"], true);
+        assert_eq!(resp.body["rank"], 1);
+
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &bob,
+            &entry("bob", 300, 2),
+        );
+        assert_eq!(resp.body["message"], "Score already submitted");
+
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &bob,
+            &entry("bob", 10, 3),
+        );
+        assert_eq!(resp.status, 422);
+
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard?limit=1&difficulty=Hard"),
+            NOW,
+        );
+        assert_eq!(resp.body["total"], 2);
+        assert_eq!(resp.body["next_offset"], 1);
+        let top = &resp.body["entries"][0];
+        assert_eq!(top["player_name"], "bob");
+        assert_eq!(top["score"], 300 + 30 + 30);
+        assert_eq!(top["verified"], false);
+
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard?offset=1&limit=1"),
+            NOW,
+        );
+        assert_eq!(resp.body["entries"][0]["rank"], 2);
+        assert_eq!(resp.body["next_offset"], Value::Null);
+
+        let resp = api.handle(&Request::new("GET", "/api/v1/leaderboard?verified=1"), NOW);
+        assert_eq!(resp.body["entries"][0]["player_name"], "ada");
+
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard/rank/ada/Hard"),
+            NOW,
+        );
+        assert_eq!(resp.body["rank"], 2);
+
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard?puzzle=other&difficulty=Hard"),
+            NOW,
+        );
+        assert_eq!(resp.body["total"], 0);
+
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard/player/bob?limit=5"),
+            NOW,
+        );
+        assert_eq!(resp.body["entries"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_player_ids_belong_to_their_first_client() {
+        let api = api(ApiConfig::default());
+        let resp = request_token(&api, json!({ "player_id": "p1" }));
+        let secret = resp.body["player_secret"].as_str().unwrap().to_string();
+        let p1 = resp.body["token"].as_str().unwrap().to_string();
+        post(&api, "/api/v1/results", &p1, &result_body("p1", 1));
+
+        // Another client can't get a token for p1, with or without a guess
+        let resp = request_token(&api, json!({ "player_id": "p1" }));
+        assert_eq!(resp.status, 403);
+        let resp = request_token(&api, json!({ "player_id": "p1", "player_secret": "guess" }));
+        assert_eq!(resp.status, 403);
+
+        // ...so it can't delete p1's data either
+        let intruder = token(&api, "p2");
+        let resp = api.handle(
+            &Request::new("DELETE", "/api/v1/players/p1").with_bearer(&intruder),
+            NOW,
+        );
+        assert_eq!(resp.status, 403);
+        assert_eq!(api.store().result_count().unwrap(), 1);
+
+        // The owner gets a new token with its secret, and no new secret
+        let resp = request_token(&api, json!({ "player_id": "p1", "player_secret": secret }));
+        assert_eq!(resp.status, 200);
+        assert!(resp.body.get("player_secret").is_none());
+    }
+
+    #[test]
+    fn test_names_belong_to_their_first_player() {
+        let api = api(ApiConfig::default());
+        let ada = token(&api, "ada-id");
+        let mallory = token(&api, "mallory-id");
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &ada,
+            &entry("ada", 600, 1),
+        );
+        assert_eq!(resp.status, 200);
+
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &mallory,
+            &entry("ada", 300, 2),
+        );
+        assert_eq!(resp.status, 409);
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            &ada,
+            &entry("ada", 500, 3),
+        );
+        assert_eq!(resp.status, 200);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let api = api(ApiConfig {
+            rate_limit_per_min: 2,
+            ..Default::default()
+        });
+        let token = token(&api, "p1");
+        for id in 0..2 {
+            let resp = post(&api, "/api/v1/results", &token, &result_body("p1", id));
+            assert_eq!(resp.status, 200);
+        }
+        let resp = post(&api, "/api/v1/results", &token, &result_body("p1", 3));
+        assert_eq!(resp.status, 429);
+        assert_eq!(resp.retry_after, Some(60 - NOW % 60));
+    }
+
+    #[test]
+    fn test_api_keys_and_deletion() {
+        let api = api(ApiConfig {
+            api_keys: vec!["ci-key".to_string()],
+            ..Default::default()
+        });
+        let resp = post(
+            &api,
+            "/api/v1/leaderboard/submit",
+            "ci-key",
+            &entry("ci", 600, 1),
+        );
+        assert_eq!(resp.status, 200);
+
+        let p1 = token(&api, "p1");
+        post(&api, "/api/v1/results", &p1, &result_body("p1", 1));
+
+        let delete = |id: &str, bearer: &str| {
+            api.handle(
+                &Request::new("DELETE", &format!("/api/v1/players/{}", id)).with_bearer(bearer),
+                NOW,
+            )
+        };
+        assert_eq!(delete("p2", &p1).status, 403);
+        let resp = delete("p1", &p1);
+        assert_eq!(resp.status, 200);
+        // The result, the token and the registration
+        assert_eq!(resp.body["removed"], 3);
+        assert_eq!(delete("name:ci", "ci-key").body["removed"], 1);
+    }
+
+    #[test]
+    fn test_unknown_routes_and_bad_input() {
+        let api = api(ApiConfig::default());
+        assert_eq!(api.handle(&Request::new("GET", "/nope"), NOW).status, 404);
+        let resp = api.handle(
+            &Request::new("GET", "/api/v1/leaderboard?difficulty=Silly"),
+            NOW,
+        );
+        assert_eq!(resp.status, 400);
+        assert!(resp.body["error"].as_str().unwrap().contains("Silly"));
+    }
+}
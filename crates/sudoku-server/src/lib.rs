@@ -0,0 +1,125 @@
+//! Self-hostable results and leaderboard server.
+//!
+//! Implements the subset of the ukodus HTTP API the clients talk to (tokens,
+//! result telemetry and the leaderboard), backed by an embedded SQLite
+//! database. Submissions go through the same [`sudoku_verify::antibot`]
+//! checks and score formula the clients use, plus a full replay when a move
+//! log is included, so scores from a private server rank exactly like the
+//! local leaderboard.
+//!
+//! ```text
+//! sudoku-server --bind 127.0.0.1:8787 --db sudoku-server.db
+//! sudoku --server local
+//! ```
+
+pub mod api;
+pub mod store;
+
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use api::{Api, ApiConfig};
+pub use store::Store;
+
+/// Largest request body accepted, in bytes
+pub const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// HTTP front end for an [`Api`]
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    api: Arc<Api>,
+}
+
+impl Server {
+    /// Bind to `addr` ("127.0.0.1:0" picks a free port)
+    pub fn bind(addr: &str, api: Api) -> io::Result<Self> {
+        let http = tiny_http::Server::http(addr).map_err(io::Error::other)?;
+        Ok(Self {
+            http: Arc::new(http),
+            api: Arc::new(api),
+        })
+    }
+
+    /// Address actually bound
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Serve requests on `workers` threads until the process exits
+    pub fn run(self, workers: usize) {
+        let handles: Vec<_> = (0..workers.max(1))
+            .map(|_| {
+                let http = Arc::clone(&self.http);
+                let api = Arc::clone(&self.api);
+                thread::spawn(move || {
+                    for request in http.incoming_requests() {
+                        if let Err(e) = respond(&api, request) {
+                            eprintln!("connection error: {}", e);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn header(value: &str, field: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(field.as_bytes(), value.as_bytes())
+        .expect("static header is valid")
+}
+
+fn respond(api: &Api, mut request: tiny_http::Request) -> io::Result<()> {
+    let mut body = String::new();
+    let read = request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body);
+
+    let response = if read.is_err() || body.len() as u64 > MAX_BODY_BYTES {
+        api::Response {
+            status: 413,
+            body: serde_json::json!({ "error": "Request body too large or not UTF-8" }),
+            retry_after: None,
+        }
+    } else {
+        let req = api::Request {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            authorization: request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string()),
+            body,
+        };
+        api.handle(&req, now())
+    };
+
+    println!(
+        "{} {} -> {}",
+        request.method(),
+        request.url().split('?').next().unwrap_or(""),
+        response.status
+    );
+
+    let mut http_response = tiny_http::Response::from_string(response.body.to_string())
+        .with_status_code(response.status)
+        .with_header(header("application/json", "Content-Type"));
+    if let Some(secs) = response.retry_after {
+        http_response.add_header(header(&secs.to_string(), "Retry-After"));
+    }
+    request.respond(http_response)
+}
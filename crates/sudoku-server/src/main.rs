@@ -0,0 +1,73 @@
+//! Command-line entry point for the self-hosted server.
+
+use clap::Parser;
+use sudoku_server::{Api, ApiConfig, Server, Store};
+
+#[derive(Parser)]
+#[command(name = "sudoku-server")]
+#[command(about = "Self-hostable results and leaderboard server for the sudoku clients")]
+struct Cli {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    bind: String,
+
+    /// SQLite database path (":memory:" for a throwaway server)
+    #[arg(long, default_value = "sudoku-server.db")]
+    db: String,
+
+    /// Lifetime of issued tokens in seconds
+    #[arg(long, default_value_t = 24 * 60 * 60)]
+    token_ttl_secs: u64,
+
+    /// Accept submissions without a token
+    #[arg(long)]
+    allow_anonymous: bool,
+
+    /// API key accepted as a trusted bearer (repeatable)
+    #[arg(long = "api-key")]
+    api_keys: Vec<String>,
+
+    /// Requests per player per minute (0 = unlimited)
+    #[arg(long, default_value_t = 60)]
+    rate_limit: u32,
+
+    /// Worker threads
+    #[arg(long, default_value_t = 4)]
+    workers: usize,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let store = match Store::open(&cli.db) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Error: cannot open database {}: {}", cli.db, e);
+            std::process::exit(1);
+        }
+    };
+
+    let api = Api::new(
+        store,
+        ApiConfig {
+            token_ttl_secs: cli.token_ttl_secs,
+            allow_anonymous: cli.allow_anonymous,
+            api_keys: cli.api_keys,
+            rate_limit_per_min: cli.rate_limit,
+        },
+    );
+
+    let server = match Server::bind(&cli.bind, api) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error: cannot listen on {}: {}", cli.bind, e);
+            std::process::exit(1);
+        }
+    };
+
+    match server.local_addr() {
+        Some(addr) => println!("sudoku-server listening on http://{}", addr),
+        None => println!("sudoku-server listening on {}", cli.bind),
+    }
+    server.run(cli.workers);
+}
@@ -0,0 +1,480 @@
+//! SQLite storage for players, tokens, results and leaderboard scores.
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS players (
+    player_id   TEXT PRIMARY KEY,
+    secret_hash TEXT NOT NULL,
+    created_at  INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tokens (
+    token      TEXT PRIMARY KEY,
+    player_id  TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    id          INTEGER PRIMARY KEY,
+    player_id   TEXT NOT NULL,
+    record_id   INTEGER,
+    puzzle_hash TEXT NOT NULL,
+    difficulty  TEXT NOT NULL,
+    result      TEXT NOT NULL,
+    time_secs   INTEGER NOT NULL,
+    hints_used  INTEGER NOT NULL,
+    mistakes    INTEGER NOT NULL,
+    verified    INTEGER NOT NULL,
+    issues      TEXT NOT NULL,
+    body        TEXT NOT NULL,
+    received_at INTEGER NOT NULL,
+    UNIQUE (player_id, record_id)
+);
+CREATE TABLE IF NOT EXISTS scores (
+    id          INTEGER PRIMARY KEY,
+    player_id   TEXT NOT NULL,
+    player_name TEXT NOT NULL,
+    difficulty  TEXT NOT NULL,
+    puzzle_hash TEXT NOT NULL,
+    score       INTEGER NOT NULL,
+    time_secs   INTEGER NOT NULL,
+    hints_used  INTEGER NOT NULL,
+    mistakes    INTEGER NOT NULL,
+    timestamp   INTEGER NOT NULL,
+    verified    INTEGER NOT NULL,
+    UNIQUE (player_id, puzzle_hash, timestamp)
+);
+CREATE INDEX IF NOT EXISTS scores_by_rank ON scores (difficulty, score, timestamp, id);
+CREATE INDEX IF NOT EXISTS scores_by_player ON scores (player_name);
+CREATE INDEX IF NOT EXISTS scores_by_puzzle ON scores (puzzle_hash, score);
+";
+
+/// Ordering used everywhere scores are ranked: lowest score first, earlier
+/// submissions win ties
+const RANK_ORDER: &str = "score ASC, timestamp ASC, id ASC";
+
+/// A stored game result
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultRow {
+    pub player_id: String,
+    pub record_id: Option<u64>,
+    pub puzzle_hash: String,
+    pub difficulty: String,
+    pub result: String,
+    pub time_secs: u64,
+    pub hints_used: usize,
+    pub mistakes: usize,
+    pub verified: bool,
+    pub issues: Vec<String>,
+    /// Original request body
+    pub body: String,
+    pub received_at: u64,
+}
+
+/// A leaderboard score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreRow {
+    pub player_id: String,
+    pub player_name: String,
+    pub difficulty: String,
+    pub puzzle_hash: String,
+    pub score: u64,
+    pub time_secs: u64,
+    pub hints_used: usize,
+    pub mistakes: usize,
+    pub timestamp: u64,
+    pub verified: bool,
+}
+
+/// Which scores a leaderboard query covers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScoreFilter {
+    pub difficulty: Option<String>,
+    /// Only scores for this canonical puzzle hash
+    pub puzzle_hash: Option<String>,
+    pub verified_only: bool,
+    /// Only scores with `timestamp >= since`
+    pub since: Option<u64>,
+}
+
+impl ScoreFilter {
+    /// SQL conditions (joined with AND, prefixed by `alias.`) and their values
+    fn clause(&self, alias: &str) -> (String, Vec<Value>) {
+        let mut conds = vec!["1 = 1".to_string()];
+        let mut values = Vec::new();
+        if let Some(ref d) = self.difficulty {
+            conds.push(format!("{alias}.difficulty = ?"));
+            values.push(Value::Text(d.clone()));
+        }
+        if let Some(ref h) = self.puzzle_hash {
+            conds.push(format!("{alias}.puzzle_hash = ?"));
+            values.push(Value::Text(h.clone()));
+        }
+        if self.verified_only {
+            conds.push(format!("{alias}.verified = 1"));
+        }
+        if let Some(since) = self.since {
+            conds.push(format!("{alias}.timestamp >= ?"));
+            values.push(Value::Integer(since as i64));
+        }
+        (conds.join(" AND "), values)
+    }
+}
+
+/// SQLite-backed store
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open (or create) the database at `path`; ":memory:" keeps it in RAM
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// In-memory store for tests and throwaway runs
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::open(":memory:")
+    }
+
+    // ==================== Tokens ====================
+
+    /// Register a player id with the hash of its secret
+    pub fn insert_player(
+        &self,
+        player_id: &str,
+        secret_hash: &str,
+        now: u64,
+    ) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO players (player_id, secret_hash, created_at) VALUES (?1, ?2, ?3)",
+            params![player_id, secret_hash, now as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Secret hash a player id was registered with, if it was
+    pub fn player_secret_hash(&self, player_id: &str) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT secret_hash FROM players WHERE player_id = ?1",
+                params![player_id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn insert_token(
+        &self,
+        token: &str,
+        player_id: &str,
+        expires_at: u64,
+    ) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO tokens (token, player_id, expires_at) VALUES (?1, ?2, ?3)",
+            params![token, player_id, expires_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Player a token belongs to, if it exists and hasn't expired
+    pub fn token_player(&self, token: &str, now: u64) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT player_id FROM tokens WHERE token = ?1 AND expires_at > ?2",
+                params![token, now as i64],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Drop expired tokens
+    pub fn purge_tokens(&self, now: u64) -> rusqlite::Result<usize> {
+        self.conn.execute(
+            "DELETE FROM tokens WHERE expires_at <= ?1",
+            params![now as i64],
+        )
+    }
+
+    // ==================== Results ====================
+
+    /// Store a result. Returns false if the player already sent this record id.
+    pub fn insert_result(&self, r: &ResultRow) -> rusqlite::Result<bool> {
+        let issues = serde_json::to_string(&r.issues).unwrap_or_else(|_| "[]".to_string());
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO results (player_id, record_id, puzzle_hash, difficulty, result,
+                time_secs, hints_used, mistakes, verified, issues, body, received_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                r.player_id,
+                r.record_id.map(|id| id as i64),
+                r.puzzle_hash,
+                r.difficulty,
+                r.result,
+                r.time_secs as i64,
+                r.hints_used as i64,
+                r.mistakes as i64,
+                r.verified,
+                issues,
+                r.body,
+                r.received_at as i64,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Whether the player has a verified win matching this score's game
+    pub fn has_verified_result(
+        &self,
+        player_id: &str,
+        puzzle_hash: &str,
+        time_secs: u64,
+    ) -> rusqlite::Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM results WHERE player_id = ?1 AND puzzle_hash = ?2
+                AND time_secs = ?3 AND verified = 1)",
+            params![player_id, puzzle_hash, time_secs as i64],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn result_count(&self) -> rusqlite::Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM results", [], |row| row.get(0))
+    }
+
+    // ==================== Scores ====================
+
+    /// Store a score. Returns false if it was already submitted.
+    pub fn insert_score(&self, s: &ScoreRow) -> rusqlite::Result<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO scores (player_id, player_name, difficulty, puzzle_hash, score,
+                time_secs, hints_used, mistakes, timestamp, verified)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                s.player_id,
+                s.player_name,
+                s.difficulty,
+                s.puzzle_hash,
+                s.score as i64,
+                s.time_secs as i64,
+                s.hints_used as i64,
+                s.mistakes as i64,
+                s.timestamp as i64,
+                s.verified,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Mark scores verified once their game's result has passed verification
+    pub fn verify_scores(
+        &self,
+        player_id: &str,
+        puzzle_hash: &str,
+        time_secs: u64,
+    ) -> rusqlite::Result<usize> {
+        self.conn.execute(
+            "UPDATE scores SET verified = 1
+             WHERE player_id = ?1 AND puzzle_hash = ?2 AND time_secs = ?3",
+            params![player_id, puzzle_hash, time_secs as i64],
+        )
+    }
+
+    fn score_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScoreRow> {
+        Ok(ScoreRow {
+            player_id: row.get("player_id")?,
+            player_name: row.get("player_name")?,
+            difficulty: row.get("difficulty")?,
+            puzzle_hash: row.get("puzzle_hash")?,
+            score: row.get::<_, i64>("score")? as u64,
+            time_secs: row.get::<_, i64>("time_secs")? as u64,
+            hints_used: row.get::<_, i64>("hints_used")? as usize,
+            mistakes: row.get::<_, i64>("mistakes")? as usize,
+            timestamp: row.get::<_, i64>("timestamp")? as u64,
+            verified: row.get("verified")?,
+        })
+    }
+
+    /// One page of ranked scores plus the total matching the filter
+    pub fn leaderboard(
+        &self,
+        filter: &ScoreFilter,
+        limit: usize,
+        offset: usize,
+    ) -> rusqlite::Result<(Vec<ScoreRow>, usize)> {
+        let (clause, mut values) = filter.clause("s");
+
+        let total: usize = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM scores s WHERE {clause}"),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push(Value::Integer(limit as i64));
+        values.push(Value::Integer(offset as i64));
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT * FROM scores s WHERE {clause} ORDER BY {RANK_ORDER} LIMIT ? OFFSET ?"
+        ))?;
+        let rows = stmt
+            .query_map(params_from_iter(values.iter()), Self::score_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok((rows, total))
+    }
+
+    /// Rank (1-based) of the player's best score within the filter
+    pub fn player_rank(
+        &self,
+        player_name: &str,
+        filter: &ScoreFilter,
+    ) -> rusqlite::Result<Option<usize>> {
+        let (best_clause, best_values) = filter.clause("b");
+        let best: Option<(i64, i64, i64)> = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT score, timestamp, id FROM scores b
+                     WHERE {best_clause} AND b.player_name = ?
+                     ORDER BY {RANK_ORDER} LIMIT 1"
+                ),
+                params_from_iter(
+                    best_values
+                        .into_iter()
+                        .chain(std::iter::once(Value::Text(player_name.to_string()))),
+                ),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let Some((score, timestamp, id)) = best else {
+            return Ok(None);
+        };
+
+        let (clause, values) = filter.clause("s");
+        let better: usize = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM scores s
+                 WHERE {clause} AND (s.score, s.timestamp, s.id) < (?, ?, ?)"
+            ),
+            params_from_iter(values.into_iter().chain([
+                Value::Integer(score),
+                Value::Integer(timestamp),
+                Value::Integer(id),
+            ])),
+            |row| row.get(0),
+        )?;
+
+        Ok(Some(better + 1))
+    }
+
+    /// A player's best scores
+    pub fn player_scores(
+        &self,
+        player_name: &str,
+        limit: usize,
+    ) -> rusqlite::Result<Vec<ScoreRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT * FROM scores WHERE player_name = ?1 ORDER BY {RANK_ORDER} LIMIT ?2"
+        ))?;
+        let rows = stmt
+            .query_map(params![player_name, limit as i64], Self::score_from_row)?
+            .collect();
+        rows
+    }
+
+    // ==================== Players ====================
+
+    /// Whether someone other than `player_id` has scores under `name`
+    pub fn name_taken(&self, name: &str, player_id: &str) -> rusqlite::Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM scores WHERE player_name = ?1 AND player_id != ?2 LIMIT 1",
+                params![name, player_id],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    /// Delete everything stored for a player, including its registration.
+    /// Returns the number of rows removed.
+    pub fn delete_player(&self, player_id: &str) -> rusqlite::Result<usize> {
+        let mut removed = 0;
+        for table in ["results", "scores", "tokens", "players"] {
+            removed += self.conn.execute(
+                &format!("DELETE FROM {table} WHERE player_id = ?1"),
+                params![player_id],
+            )?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(name: &str, score: u64, timestamp: u64, verified: bool) -> ScoreRow {
+        ScoreRow {
+            player_id: format!("id-{name}"),
+            player_name: name.to_string(),
+            difficulty: "Hard".to_string(),
+            puzzle_hash: format!("hash-{timestamp}"),
+            score,
+            time_secs: score,
+            hints_used: 0,
+            mistakes: 0,
+            timestamp,
+            verified,
+        }
+    }
+
+    #[test]
+    fn test_ranking_and_filters() {
+        let store = Store::in_memory().unwrap();
+        store.insert_score(&score("ada", 300, 10, true)).unwrap();
+        store.insert_score(&score("bob", 200, 20, false)).unwrap();
+        store.insert_score(&score("cy", 300, 5, true)).unwrap();
+        assert!(!store.insert_score(&score("cy", 300, 5, true)).unwrap());
+
+        let all = ScoreFilter::default();
+        let (rows, total) = store.leaderboard(&all, 10, 0).unwrap();
+        assert_eq!(total, 3);
+        let names: Vec<_> = rows.iter().map(|r| r.player_name.as_str()).collect();
+        assert_eq!(names, ["bob", "cy", "ada"]);
+        assert_eq!(store.player_rank("ada", &all).unwrap(), Some(3));
+
+        let verified = ScoreFilter {
+            verified_only: true,
+            ..Default::default()
+        };
+        assert_eq!(store.player_rank("ada", &verified).unwrap(), Some(2));
+        assert_eq!(store.player_rank("bob", &verified).unwrap(), None);
+
+        let recent = ScoreFilter {
+            since: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(store.leaderboard(&recent, 10, 0).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_tokens_expire_and_players_delete() {
+        let store = Store::in_memory().unwrap();
+        store.insert_token("t", "p1", 100).unwrap();
+        assert_eq!(store.token_player("t", 50).unwrap().as_deref(), Some("p1"));
+        assert_eq!(store.token_player("t", 100).unwrap(), None);
+
+        let mut s = score("ada", 300, 10, false);
+        s.player_id = "p1".to_string();
+        store.insert_score(&s).unwrap();
+        assert_eq!(store.delete_player("p1").unwrap(), 2);
+        assert_eq!(
+            store.leaderboard(&ScoreFilter::default(), 10, 0).unwrap().1,
+            0
+        );
+    }
+}
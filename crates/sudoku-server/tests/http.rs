@@ -0,0 +1,73 @@
+//! End-to-end test over a real socket, the way CI drives the server.
+
+use serde_json::{json, Value};
+use std::thread;
+use sudoku_server::{Api, ApiConfig, Server, Store};
+
+fn start() -> String {
+    let server = Server::bind(
+        "127.0.0.1:0",
+        Api::new(Store::in_memory().unwrap(), ApiConfig::default()),
+    )
+    .unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run(2));
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_token_submit_and_list() {
+    let base = start();
+
+    let token: Value = serde_json::from_str(
+        &ureq::post(&format!("{}/api/v1/token", base))
+            .send_string(&json!({ "player_id": "ci-player" }).to_string())
+            .unwrap()
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    let token = token["token"].as_str().unwrap();
+
+    let submit: Value = serde_json::from_str(
+        &ureq::post(&format!("{}/api/v1/leaderboard/submit", base))
+            .set("Authorization", &format!("Bearer {}", token))
+            .send_string(
+                &json!({ "entry": {
+                    "player_name": "ci",
+                    "score": 0,
+                    "time_secs": 400,
+                    "difficulty": "Medium",
+                    "hints_used": 0,
+                    "mistakes": 0,
+                    "timestamp": 1,
+                    "puzzle_hash": "abc",
+                }})
+                .to_string(),
+            )
+            .unwrap()
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(submit["rank"], 1);
+
+    let page: Value = serde_json::from_str(
+        &ureq::get(&format!("{}/api/v1/leaderboard?difficulty=Medium", base))
+            .call()
+            .unwrap()
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["entries"][0]["score"], 400);
+
+    match ureq::post(&format!("{}/api/v1/results", base)).send_string("{}") {
+        Err(ureq::Error::Status(401, resp)) => {
+            let body: Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+            assert!(body["error"].is_string());
+        }
+        other => panic!("expected 401, got {:?}", other.map(|r| r.status())),
+    }
+}
@@ -0,0 +1,622 @@
+//! Achievements shared by every frontend, defined as data.
+//!
+//! [`ACHIEVEMENTS`] lists each achievement with the [`Condition`] that counts
+//! toward it and one or more tier targets. A frontend keeps an
+//! [`AchievementState`] next to its stats, feeds it every finished game with
+//! [`AchievementState::record_game`] and reports outside events (easter eggs,
+//! facts a [`GameRecord`] doesn't carry) with [`AchievementState::trigger`].
+//! Both return the tiers newly reached so the frontend can announce them.
+//!
+//! Unlocking the secret difficulties is a [`Reward`] attached to a tier of
+//! some achievements; [`AchievementState::has_reward`] tells whether any of
+//! them has been reached.
+
+use crate::records::{GameRecord, Outcome};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use sudoku_core::Difficulty;
+
+/// Something granted when an achievement reaches a tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reward {
+    /// Master and Extreme become playable
+    SecretDifficulties,
+}
+
+/// Which finished games count toward [`Condition::Games`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameFilter {
+    /// Only wins count
+    pub wins_only: bool,
+    pub min_difficulty: Option<Difficulty>,
+    /// Finished in under this many seconds
+    pub under_secs: Option<u64>,
+    pub max_hints: Option<usize>,
+    pub max_mistakes: Option<usize>,
+}
+
+impl GameFilter {
+    /// Every game, whatever the result
+    pub const ANY: Self = Self {
+        wins_only: false,
+        min_difficulty: None,
+        under_secs: None,
+        max_hints: None,
+        max_mistakes: None,
+    };
+
+    /// Every win
+    pub const WIN: Self = Self {
+        wins_only: true,
+        ..Self::ANY
+    };
+
+    pub fn matches(&self, game: &GameRecord) -> bool {
+        (!self.wins_only || game.result == Outcome::Win)
+            && self.min_difficulty.is_none_or(|d| game.difficulty >= d)
+            && self.under_secs.is_none_or(|s| game.time_secs < s)
+            && self.max_hints.is_none_or(|h| game.hints_used <= h)
+            && self.max_mistakes.is_none_or(|m| game.mistakes <= m)
+    }
+}
+
+/// What an achievement's progress counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Games matching the filter
+    Games(GameFilter),
+    /// Longest run of consecutive wins
+    WinStreak,
+    /// Seconds played in total
+    PlayTime,
+    /// How many of these difficulties have been won at least once
+    DifficultiesWon(&'static [Difficulty]),
+    /// Games finished on this day of the year (UTC)
+    PlayedOn { month: u32, day: u32 },
+    /// Times the frontend reported this event
+    Event(&'static str),
+}
+
+/// One achievement and its tiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Achievement {
+    /// Stable id, used in saved state
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+    /// Progress needed for each tier, ascending
+    pub tiers: &'static [u64],
+    /// Name and description stay secret until the first tier is reached
+    pub hidden: bool,
+    /// Reward and the tier (index into `tiers`) that grants it
+    pub reward: Option<(usize, Reward)>,
+}
+
+/// Frontend event names for [`Condition::Event`]
+pub mod events {
+    /// Konami code on the new game menu
+    pub const KONAMI: &str = "konami";
+    /// Reverse Konami code on the lose screen
+    pub const REVERSE_KONAMI: &str = "reverse_konami";
+    /// "42" typed on an end screen
+    pub const THE_ANSWER: &str = "the_answer";
+    /// Won Hard or above without pencil marks
+    pub const NO_NOTES_WIN: &str = "no_notes_win";
+}
+
+const STANDARD_DIFFICULTIES: &[Difficulty] = &[
+    Difficulty::Beginner,
+    Difficulty::Easy,
+    Difficulty::Medium,
+    Difficulty::Intermediate,
+    Difficulty::Hard,
+    Difficulty::Expert,
+];
+
+const HOUR: u64 = 60 * 60;
+
+/// Every achievement, in display order
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "games_played",
+        name: "Regular",
+        description: "Finish games",
+        condition: Condition::Games(GameFilter::ANY),
+        tiers: &[10, 100, 1000],
+        hidden: false,
+        reward: None,
+    },
+    Achievement {
+        id: "century",
+        name: "Century",
+        description: "Win games",
+        condition: Condition::Games(GameFilter::WIN),
+        tiers: &[1, 10, 100, 1000],
+        hidden: false,
+        reward: Some((2, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "expert_master",
+        name: "Expert Master",
+        description: "Win on Expert or above",
+        condition: Condition::Games(GameFilter {
+            min_difficulty: Some(Difficulty::Expert),
+            ..GameFilter::WIN
+        }),
+        tiers: &[1, 10, 50],
+        hidden: false,
+        reward: Some((1, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "perfect_game",
+        name: "Perfect Game",
+        description: "Win Expert or above with no hints or mistakes",
+        condition: Condition::Games(GameFilter {
+            min_difficulty: Some(Difficulty::Expert),
+            max_hints: Some(0),
+            max_mistakes: Some(0),
+            ..GameFilter::WIN
+        }),
+        tiers: &[1, 10],
+        hidden: false,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "speed_demon",
+        name: "Speed Demon",
+        description: "Win Hard or above in under 5 minutes",
+        condition: Condition::Games(GameFilter {
+            min_difficulty: Some(Difficulty::Hard),
+            under_secs: Some(5 * 60),
+            ..GameFilter::WIN
+        }),
+        tiers: &[1, 10],
+        hidden: false,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "clean_sheet",
+        name: "Clean Sheet",
+        description: "Win without mistakes",
+        condition: Condition::Games(GameFilter {
+            max_mistakes: Some(0),
+            ..GameFilter::WIN
+        }),
+        tiers: &[1, 25, 100],
+        hidden: false,
+        reward: None,
+    },
+    Achievement {
+        id: "win_streak",
+        name: "Win Streak",
+        description: "Win games in a row",
+        condition: Condition::WinStreak,
+        tiers: &[5, 10, 25],
+        hidden: false,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "completionist",
+        name: "Completionist",
+        description: "Win on every standard difficulty",
+        condition: Condition::DifficultiesWon(STANDARD_DIFFICULTIES),
+        tiers: &[STANDARD_DIFFICULTIES.len() as u64],
+        hidden: false,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "marathon",
+        name: "Marathon",
+        description: "Hours of total play time",
+        condition: Condition::PlayTime,
+        tiers: &[HOUR, 10 * HOUR, 100 * HOUR],
+        hidden: false,
+        reward: Some((1, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "no_notes_master",
+        name: "No Notes Master",
+        description: "Win Hard or above without pencil marks",
+        condition: Condition::Event(events::NO_NOTES_WIN),
+        tiers: &[1],
+        hidden: false,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "secret_date",
+        name: "Secret Date",
+        description: "Finish a game on 9/9",
+        condition: Condition::PlayedOn { month: 9, day: 9 },
+        tiers: &[1],
+        hidden: true,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "konami",
+        name: "Konami Code",
+        description: "↑↑↓↓←→←→BA on the new game menu",
+        condition: Condition::Event(events::KONAMI),
+        tiers: &[1],
+        hidden: true,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "reverse_konami",
+        name: "Reverse Konami",
+        description: "AB→←→←↓↓↑↑ after a loss",
+        condition: Condition::Event(events::REVERSE_KONAMI),
+        tiers: &[1],
+        hidden: true,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+    Achievement {
+        id: "the_answer",
+        name: "The Answer",
+        description: "Type 42 when a game ends",
+        condition: Condition::Event(events::THE_ANSWER),
+        tiers: &[1],
+        hidden: true,
+        reward: Some((0, Reward::SecretDifficulties)),
+    },
+];
+
+/// Look up an achievement by id
+pub fn get(id: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|a| a.id == id)
+}
+
+/// Tier names, used when an achievement has more than one tier
+const TIER_NAMES: [&str; 4] = ["Bronze", "Silver", "Gold", "Platinum"];
+
+/// An achievement tier that was just reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Unlock {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Tier reached, from 1
+    pub tier: usize,
+    pub tiers: usize,
+    pub reward: Option<Reward>,
+}
+
+impl std::fmt::Display for Unlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.tiers > 1 {
+            let tier = TIER_NAMES.get(self.tier - 1).copied().unwrap_or("Max");
+            write!(f, "{} ({})", self.name, tier)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// Where an achievement stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub achievement: &'static Achievement,
+    /// Current value of the condition
+    pub value: u64,
+    /// Tiers reached so far
+    pub tier: usize,
+    /// Target of the next tier, if any is left
+    pub next: Option<u64>,
+    /// When the highest reached tier was reached (Unix seconds)
+    pub unlocked_at: Option<u64>,
+}
+
+impl Progress {
+    /// Hidden and not reached yet
+    pub fn is_secret(&self) -> bool {
+        self.achievement.hidden && self.tier == 0
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// What a frontend may show, with hidden achievements kept secret
+    pub fn summary(&self) -> ProgressSummary {
+        let secret = self.is_secret();
+        let a = self.achievement;
+        ProgressSummary {
+            id: a.id,
+            name: (!secret).then_some(a.name),
+            description: (!secret).then_some(a.description),
+            value: if secret { 0 } else { self.value },
+            tier: self.tier,
+            targets: if secret { Vec::new() } else { a.tiers.to_vec() },
+            next: if secret { None } else { self.next },
+            unlocked_at: self.unlocked_at,
+            reward_tier: a.reward.filter(|_| !secret).map(|(tier, _)| tier + 1),
+        }
+    }
+}
+
+/// Serializable view of [`Progress`] for frontends
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgressSummary {
+    pub id: &'static str,
+    /// `None` while the achievement is hidden
+    pub name: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub value: u64,
+    /// Tiers reached, from 1
+    pub tier: usize,
+    /// Target of each tier (empty while hidden)
+    pub targets: Vec<u64>,
+    pub next: Option<u64>,
+    pub unlocked_at: Option<u64>,
+    /// Tier (from 1) that unlocks the secret difficulties
+    pub reward_tier: Option<usize>,
+}
+
+/// A reached tier, as saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reached {
+    /// Tiers reached, from 1
+    pub tier: usize,
+    /// Unix seconds when it was reached
+    pub at: u64,
+}
+
+/// A player's progress toward every achievement
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AchievementState {
+    /// Counters for [`Condition::Games`], [`Condition::PlayedOn`] and
+    /// [`Condition::Event`] achievements, by achievement id
+    #[serde(default)]
+    counts: BTreeMap<String, u64>,
+    #[serde(default)]
+    streak: u64,
+    #[serde(default)]
+    best_streak: u64,
+    #[serde(default)]
+    play_time_secs: u64,
+    /// Names of the difficulties won at least once
+    #[serde(default)]
+    won: BTreeSet<String>,
+    /// Events reported so far
+    #[serde(default)]
+    events: BTreeSet<String>,
+    /// Reached tiers, by achievement id
+    #[serde(default)]
+    reached: BTreeMap<String, Reached>,
+}
+
+impl AchievementState {
+    /// Progress rebuilt from past games (oldest first), without reporting
+    /// unlocks
+    pub fn from_games<I>(games: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<GameRecord>,
+    {
+        let mut state = Self::default();
+        for game in games {
+            state.record_game(game.borrow());
+        }
+        state
+    }
+
+    /// Whether nothing has been counted yet
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Count a finished game. Returns the tiers it reached.
+    pub fn record_game(&mut self, game: &GameRecord) -> Vec<Unlock> {
+        self.play_time_secs += game.time_secs;
+        match game.result {
+            Outcome::Win => {
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+                self.won.insert(format!("{:?}", game.difficulty));
+            }
+            Outcome::Loss | Outcome::Abandoned => self.streak = 0,
+        }
+
+        let (_, month, day) = crate::dates::civil(game.timestamp);
+        for a in ACHIEVEMENTS {
+            let counts = match a.condition {
+                Condition::Games(filter) => filter.matches(game),
+                Condition::PlayedOn { month: m, day: d } => (m, d) == (month, day),
+                _ => false,
+            };
+            if counts {
+                *self.counts.entry(a.id.to_string()).or_default() += 1;
+            }
+        }
+        self.check(game.timestamp)
+    }
+
+    /// Report a frontend event (see [`events`]). Returns the tiers it reached.
+    pub fn trigger(&mut self, event: &str, now: u64) -> Vec<Unlock> {
+        self.events.insert(event.to_string());
+        for a in ACHIEVEMENTS {
+            if a.condition == Condition::Event(event_name(event)) {
+                *self.counts.entry(a.id.to_string()).or_default() += 1;
+            }
+        }
+        self.check(now)
+    }
+
+    /// Events reported so far
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.events.iter().map(String::as_str)
+    }
+
+    /// Current value of an achievement's condition
+    pub fn value(&self, achievement: &Achievement) -> u64 {
+        match achievement.condition {
+            Condition::Games(_) | Condition::PlayedOn { .. } | Condition::Event(_) => {
+                self.counts.get(achievement.id).copied().unwrap_or(0)
+            }
+            Condition::WinStreak => self.best_streak,
+            Condition::PlayTime => self.play_time_secs,
+            Condition::DifficultiesWon(difficulties) => difficulties
+                .iter()
+                .filter(|d| self.won.contains(&format!("{:?}", d)))
+                .count() as u64,
+        }
+    }
+
+    /// Progress toward every achievement, in display order
+    pub fn progress(&self) -> Vec<Progress> {
+        ACHIEVEMENTS
+            .iter()
+            .map(|a| {
+                let reached = self.reached.get(a.id);
+                let tier = reached.map_or(0, |r| r.tier);
+                Progress {
+                    achievement: a,
+                    value: self.value(a),
+                    tier,
+                    next: a.tiers.get(tier).copied(),
+                    unlocked_at: reached.map(|r| r.at),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a reached tier grants `reward`
+    pub fn has_reward(&self, reward: Reward) -> bool {
+        ACHIEVEMENTS.iter().any(|a| match a.reward {
+            Some((tier, r)) if r == reward => self.reached.get(a.id).is_some_and(|t| t.tier > tier),
+            _ => false,
+        })
+    }
+
+    /// Record tiers whose target is now met
+    fn check(&mut self, now: u64) -> Vec<Unlock> {
+        let mut unlocks = Vec::new();
+        for a in ACHIEVEMENTS {
+            let value = self.value(a);
+            let before = self.reached.get(a.id).map_or(0, |r| r.tier);
+            let after = a.tiers.iter().take_while(|&&t| value >= t).count();
+            if after <= before {
+                continue;
+            }
+            self.reached.insert(
+                a.id.to_string(),
+                Reached {
+                    tier: after,
+                    at: now,
+                },
+            );
+            for tier in before + 1..=after {
+                unlocks.push(Unlock {
+                    id: a.id,
+                    name: a.name,
+                    description: a.description,
+                    tier,
+                    tiers: a.tiers.len(),
+                    reward: a.reward.filter(|(t, _)| *t == tier - 1).map(|(_, r)| r),
+                });
+            }
+        }
+        unlocks
+    }
+}
+
+/// The `'static` name of a known event, so it can be compared to conditions
+fn event_name(event: &str) -> &'static str {
+    ACHIEVEMENTS
+        .iter()
+        .find_map(|a| match a.condition {
+            Condition::Event(name) if name == event => Some(name),
+            _ => None,
+        })
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(
+        difficulty: Difficulty,
+        result: Outcome,
+        time_secs: u64,
+        mistakes: usize,
+    ) -> GameRecord {
+        GameRecord {
+            timestamp: 1_700_000_000,
+            difficulty,
+            result,
+            time_secs,
+            hints_used: 0,
+            mistakes,
+            puzzle_hash: String::new(),
+            puzzle: String::new(),
+            short_code: None,
+            verified: true,
+            source: None,
+        }
+    }
+
+    fn ids(unlocks: &[Unlock]) -> Vec<(&str, usize)> {
+        unlocks.iter().map(|u| (u.id, u.tier)).collect()
+    }
+
+    #[test]
+    fn test_tiers_and_rewards() {
+        let mut state = AchievementState::default();
+        let unlocks = state.record_game(&game(Difficulty::Easy, Outcome::Win, 600, 0));
+        assert_eq!(ids(&unlocks), vec![("century", 1), ("clean_sheet", 1)]);
+        assert!(!state.has_reward(Reward::SecretDifficulties));
+
+        for _ in 0..3 {
+            state.record_game(&game(Difficulty::Easy, Outcome::Win, 600, 1));
+        }
+        let unlocks = state.record_game(&game(Difficulty::Easy, Outcome::Win, 600, 1));
+        assert_eq!(ids(&unlocks), vec![("win_streak", 1)]);
+        assert_eq!(unlocks[0].reward, Some(Reward::SecretDifficulties));
+        assert_eq!(unlocks[0].to_string(), "Win Streak (Bronze)");
+        assert!(state.has_reward(Reward::SecretDifficulties));
+
+        // Losses end the streak but the best run is kept
+        state.record_game(&game(Difficulty::Easy, Outcome::Loss, 600, 3));
+        let progress = state.progress();
+        let streak = progress
+            .iter()
+            .find(|p| p.achievement.id == "win_streak")
+            .unwrap();
+        assert_eq!((streak.value, streak.tier, streak.next), (5, 1, Some(10)));
+        let games = progress
+            .iter()
+            .find(|p| p.achievement.id == "games_played")
+            .unwrap();
+        assert_eq!((games.value, games.tier, games.next), (6, 0, Some(10)));
+    }
+
+    #[test]
+    fn test_filters_events_and_hidden() {
+        let mut state = AchievementState::default();
+        // Hard under 5 minutes, but Expert is needed for a perfect game
+        let unlocks = state.record_game(&game(Difficulty::Hard, Outcome::Win, 200, 0));
+        assert!(ids(&unlocks).contains(&("speed_demon", 1)));
+        assert!(!ids(&unlocks).iter().any(|(id, _)| *id == "perfect_game"));
+
+        let konami = state
+            .progress()
+            .into_iter()
+            .find(|p| p.achievement.id == "konami")
+            .unwrap();
+        assert!(konami.is_secret());
+
+        let unlocks = state.trigger(events::KONAMI, 5);
+        assert_eq!(ids(&unlocks), vec![("konami", 1)]);
+        // Reaching a tier only reports once
+        assert!(state.trigger(events::KONAMI, 6).is_empty());
+        assert!(state.trigger("unknown", 7).is_empty());
+        assert_eq!(state.events().collect::<Vec<_>>(), ["konami", "unknown"]);
+
+        let replayed =
+            AchievementState::from_games(&[game(Difficulty::Hard, Outcome::Win, 200, 0)]);
+        assert!(replayed.has_reward(Reward::SecretDifficulties));
+        assert!(!replayed.is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+//! Unix time and UTC calendar dates, shared by the achievements rules and the
+//! frontends' stats screens.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds in a day
+pub const DAY: u64 = 86_400;
+
+/// Current Unix time in seconds (0 if the clock is before 1970)
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// UTC year, month (1-12) and day of a Unix timestamp
+pub fn civil(timestamp: u64) -> (i64, u32, u32) {
+    // Civil-from-days (Howard Hinnant), days counted from 1970-01-01
+    let z = (timestamp / DAY) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil(timestamp);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_dates() {
+        assert_eq!(civil(0), (1970, 1, 1));
+        assert_eq!(civil(951_782_400), (2000, 2, 29));
+        // 2025-09-09 12:00 UTC
+        assert_eq!(civil(1_757_419_200), (2025, 9, 9));
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31");
+    }
+}
@@ -0,0 +1,12 @@
+//! Data formats and rules every Sudoku frontend (TUI, web, iOS) shares.
+//!
+//! [`records`] is the portable game record format used to export and import
+//! stats between platforms, [`achievements`] the achievement rules every
+//! frontend evaluates over those records, and [`themes`] the color theme file
+//! format the TUI and web build both load. [`dates`] has the Unix time and
+//! calendar helpers they share.
+
+pub mod achievements;
+pub mod dates;
+pub mod records;
+pub mod themes;
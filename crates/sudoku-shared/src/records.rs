@@ -0,0 +1,581 @@
+//! Portable game records for moving stats between the TUI, web and iOS builds.
+//!
+//! Every frontend can export its history as CSV or as the JSON document
+//! described by [`RecordsExport`] (schema `sudoku-game-records`, version 1),
+//! and import any of these formats:
+//!
+//! - a [`RecordsExport`] document or a bare JSON array of [`GameRecord`]s
+//! - CSV written by [`to_csv`]
+//! - the TUI's `sudoku_stats.json` (its `history` array)
+//! - the iOS puzzle library (an array of `PuzzleRecord`s). It only keeps a best
+//!   time per puzzle, so each solved puzzle becomes a single win, stamped with
+//!   when the puzzle was first played so re-exports dedupe.
+//!
+//! [`merge`] dedupes by canonical puzzle hash and timestamp, so importing the
+//! same file twice is harmless. Merged records are never `verified`: the flag
+//! only says the exporting client checked the game, and a file can claim anything. [`summarize`] recomputes aggregates from a set of
+//! records.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use sudoku_core::{canonical_puzzle_hash_str, Difficulty};
+
+/// `schema` field of a JSON export
+pub const SCHEMA: &str = "sudoku-game-records";
+
+/// Current version of the JSON export
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Seconds between the Unix epoch and Apple's reference date (2001-01-01),
+/// which is how Swift's `JSONEncoder` writes `Date`s by default
+const APPLE_EPOCH_OFFSET: f64 = 978_307_200.0;
+
+/// Columns written by [`to_csv`], in order
+pub const CSV_COLUMNS: [&str; 11] = [
+    "timestamp",
+    "difficulty",
+    "result",
+    "time_secs",
+    "hints_used",
+    "mistakes",
+    "puzzle_hash",
+    "puzzle",
+    "short_code",
+    "verified",
+    "source",
+];
+
+/// How a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Abandoned,
+}
+
+/// One played game, as exchanged between platforms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// Unix timestamp (seconds) when the game ended
+    pub timestamp: u64,
+    pub difficulty: Difficulty,
+    pub result: Outcome,
+    pub time_secs: u64,
+    #[serde(default)]
+    pub hints_used: usize,
+    #[serde(default)]
+    pub mistakes: usize,
+    /// Canonical puzzle hash (see `canonical_puzzle_hash_str`)
+    pub puzzle_hash: String,
+    /// 81-character puzzle, when known
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub puzzle: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_code: Option<String>,
+    /// Passed the exporting client's anti-bot checks
+    #[serde(default)]
+    pub verified: bool,
+    /// Platform the game was played on ("tui", "web", "ios")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl GameRecord {
+    /// Key used to recognise the same game in two exports
+    pub fn dedupe_key(&self) -> (String, u64) {
+        (self.puzzle_hash.clone(), self.timestamp)
+    }
+}
+
+/// A JSON export document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordsExport {
+    /// Always [`SCHEMA`]
+    pub schema: String,
+    pub version: u32,
+    /// Unix timestamp of the export
+    #[serde(default)]
+    pub exported_at: u64,
+    /// Platform that wrote the export
+    #[serde(default)]
+    pub source: String,
+    pub records: Vec<GameRecord>,
+}
+
+/// Why an import couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// Neither JSON nor CSV in a known shape
+    UnknownFormat,
+    /// A JSON export from a newer version of the schema
+    UnsupportedVersion(u32),
+    /// A CSV line that couldn't be parsed (1-based line number)
+    BadCsvLine(usize, String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "not a recognised stats export"),
+            Self::UnsupportedVersion(v) => write!(
+                f,
+                "export version {} is newer than supported ({})",
+                v, SCHEMA_VERSION
+            ),
+            Self::BadCsvLine(line, e) => write!(f, "CSV line {}: {}", line, e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Result of merging an import into existing records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub duplicates: usize,
+}
+
+/// Per-difficulty aggregates recomputed from records
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultySummary {
+    pub games: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub abandoned: usize,
+    pub best_time_secs: Option<u64>,
+    /// Sum of winning times
+    pub total_win_time_secs: u64,
+    pub total_hints: usize,
+    pub total_mistakes: usize,
+}
+
+/// Aggregates recomputed from records
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub games: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub abandoned: usize,
+    pub total_time_secs: u64,
+    /// Consecutive wins ending with the most recent game
+    pub current_streak: usize,
+    pub best_streak: usize,
+    /// Keyed by difficulty name ("Easy", "Hard", ...)
+    pub by_difficulty: BTreeMap<String, DifficultySummary>,
+}
+
+/// Wrap records in a JSON export document
+pub fn to_json(records: &[GameRecord], source: &str, exported_at: u64) -> String {
+    let export = RecordsExport {
+        schema: SCHEMA.to_string(),
+        version: SCHEMA_VERSION,
+        exported_at,
+        source: source.to_string(),
+        records: records.to_vec(),
+    };
+    serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+/// Records as CSV with a header row of [`CSV_COLUMNS`]
+pub fn to_csv(records: &[GameRecord]) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+    for r in records {
+        let fields = [
+            r.timestamp.to_string(),
+            format!("{:?}", r.difficulty),
+            format!("{:?}", r.result),
+            r.time_secs.to_string(),
+            r.hints_used.to_string(),
+            r.mistakes.to_string(),
+            r.puzzle_hash.clone(),
+            r.puzzle.clone(),
+            r.short_code.clone().unwrap_or_default(),
+            r.verified.to_string(),
+            r.source.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split one CSV line into fields (no embedded newlines)
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Read records from any supported export format
+pub fn parse(input: &str) -> Result<Vec<GameRecord>, ImportError> {
+    let input = input.trim_start_matches('\u{feff}').trim();
+    let mut records = match serde_json::from_str::<Value>(input) {
+        Ok(value) => parse_json(value)?,
+        Err(_) => parse_csv(input)?,
+    };
+    for record in &mut records {
+        // Hashes from other platforms aren't canonical; recompute when we can
+        if record.puzzle.len() == 81 {
+            record.puzzle_hash = canonical_puzzle_hash_str(&record.puzzle);
+        }
+    }
+    Ok(records)
+}
+
+fn parse_json(value: Value) -> Result<Vec<GameRecord>, ImportError> {
+    if value.get("schema").and_then(Value::as_str) == Some(SCHEMA) {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+        if version > SCHEMA_VERSION {
+            return Err(ImportError::UnsupportedVersion(version));
+        }
+        return serde_json::from_value::<RecordsExport>(value)
+            .map(|e| e.records)
+            .map_err(|_| ImportError::UnknownFormat);
+    }
+
+    // TUI stats file
+    if let Some(history) = value.get("history").and_then(Value::as_array) {
+        return Ok(history.iter().filter_map(from_tui_record).collect());
+    }
+
+    let Some(items) = value.as_array() else {
+        return Err(ImportError::UnknownFormat);
+    };
+    if let Ok(records) = serde_json::from_value::<Vec<GameRecord>>(value.clone()) {
+        return Ok(records);
+    }
+    // iOS puzzle library
+    if items.iter().all(|v| v.get("puzzleString").is_some()) {
+        return Ok(items.iter().filter_map(from_ios_puzzle).collect());
+    }
+    Err(ImportError::UnknownFormat)
+}
+
+/// A `GameRecord` from the TUI's `sudoku_stats.json`
+fn from_tui_record(value: &Value) -> Option<GameRecord> {
+    #[derive(Deserialize)]
+    struct Tui {
+        puzzle_hash: String,
+        #[serde(default)]
+        puzzle: String,
+        difficulty: Difficulty,
+        result: Outcome,
+        time_secs: u64,
+        hints_used: usize,
+        mistakes: usize,
+        timestamp: u64,
+        #[serde(default)]
+        verified: bool,
+        #[serde(default)]
+        short_code: Option<String>,
+    }
+    let r: Tui = serde_json::from_value(value.clone()).ok()?;
+    Some(GameRecord {
+        timestamp: r.timestamp,
+        difficulty: r.difficulty,
+        result: r.result,
+        time_secs: r.time_secs,
+        hints_used: r.hints_used,
+        mistakes: r.mistakes,
+        puzzle_hash: r.puzzle_hash,
+        puzzle: r.puzzle,
+        short_code: r.short_code,
+        verified: r.verified,
+        source: Some("tui".to_string()),
+    })
+}
+
+/// A win for a solved puzzle in the iOS library, at its best time. The
+/// timestamp comes from `firstPlayedAt`, which later plays don't change.
+fn from_ios_puzzle(value: &Value) -> Option<GameRecord> {
+    let wins = value.get("wins").and_then(Value::as_u64).unwrap_or(0);
+    let best = value.get("bestTime").and_then(Value::as_f64)?;
+    if wins == 0 {
+        return None;
+    }
+    let puzzle = value.get("puzzleString")?.as_str()?.replace('0', ".");
+    let difficulty = from_name(value.get("difficulty")?.as_str()?)?;
+    let first_played = value.get("firstPlayedAt").and_then(Value::as_f64)?;
+    Some(GameRecord {
+        timestamp: (first_played + APPLE_EPOCH_OFFSET).max(0.0) as u64,
+        difficulty,
+        result: Outcome::Win,
+        time_secs: best.round() as u64,
+        hints_used: 0,
+        mistakes: 0,
+        puzzle_hash: canonical_puzzle_hash_str(&puzzle),
+        puzzle,
+        short_code: None,
+        verified: false,
+        source: Some("ios".to_string()),
+    })
+}
+
+fn parse_csv(input: &str) -> Result<Vec<GameRecord>, ImportError> {
+    let mut lines = input.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, header)) => split_csv_line(header).map_err(|_| ImportError::UnknownFormat)?,
+        None => return Err(ImportError::UnknownFormat),
+    };
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let required = ["timestamp", "difficulty", "result", "time_secs"];
+    if required.iter().any(|c| column(c).is_none()) {
+        return Err(ImportError::UnknownFormat);
+    }
+
+    let mut records = Vec::new();
+    for (i, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bad = |e: String| ImportError::BadCsvLine(i + 1, e);
+        let fields = split_csv_line(line).map_err(bad)?;
+        let get = |name: &str| {
+            column(name)
+                .and_then(|c| fields.get(c))
+                .map(|f| f.trim())
+                .unwrap_or("")
+        };
+        let number = |name: &str| -> Result<u64, ImportError> {
+            let value = get(name);
+            if value.is_empty() {
+                return Ok(0);
+            }
+            value
+                .parse()
+                .map_err(|_| bad(format!("{} is not a number: {}", name, value)))
+        };
+        let puzzle = get("puzzle").to_string();
+        let puzzle_hash = match get("puzzle_hash") {
+            "" if puzzle.len() == 81 => canonical_puzzle_hash_str(&puzzle),
+            hash => hash.to_string(),
+        };
+        let optional = |name: &str| Some(get(name).to_string()).filter(|s| !s.is_empty());
+
+        records.push(GameRecord {
+            timestamp: number("timestamp")?,
+            difficulty: from_name(get("difficulty"))
+                .ok_or_else(|| bad(format!("unknown difficulty: {}", get("difficulty"))))?,
+            result: from_name(get("result"))
+                .ok_or_else(|| bad(format!("unknown result: {}", get("result"))))?,
+            time_secs: number("time_secs")?,
+            hints_used: number("hints_used")? as usize,
+            mistakes: number("mistakes")? as usize,
+            puzzle_hash,
+            puzzle,
+            short_code: optional("short_code"),
+            verified: get("verified").eq_ignore_ascii_case("true"),
+            source: optional("source"),
+        });
+    }
+    Ok(records)
+}
+
+/// An enum variant from its name, e.g. "Hard" or "Win"
+fn from_name<T: serde::de::DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(Value::String(name.to_string())).ok()
+}
+
+/// Add `incoming` records not already in `existing`. Returns the records that
+/// were added, oldest first, with `verified` cleared.
+pub fn merge(
+    existing: &[GameRecord],
+    incoming: Vec<GameRecord>,
+) -> (Vec<GameRecord>, MergeSummary) {
+    let mut seen: HashSet<(String, u64)> = existing.iter().map(GameRecord::dedupe_key).collect();
+    let mut summary = MergeSummary::default();
+    let mut added = Vec::new();
+    for mut record in incoming {
+        if seen.insert(record.dedupe_key()) {
+            record.verified = false;
+            added.push(record);
+        } else {
+            summary.duplicates += 1;
+        }
+    }
+    added.sort_by_key(|r| r.timestamp);
+    summary.added = added.len();
+    (added, summary)
+}
+
+/// Recompute aggregates from records in any order
+pub fn summarize(records: &[GameRecord]) -> Summary {
+    let mut sorted: Vec<&GameRecord> = records.iter().collect();
+    sorted.sort_by_key(|r| r.timestamp);
+
+    let mut summary = Summary::default();
+    for r in sorted {
+        summary.games += 1;
+        summary.total_time_secs += r.time_secs;
+        let diff = summary
+            .by_difficulty
+            .entry(format!("{:?}", r.difficulty))
+            .or_default();
+        diff.games += 1;
+        diff.total_hints += r.hints_used;
+        diff.total_mistakes += r.mistakes;
+        match r.result {
+            Outcome::Win => {
+                summary.wins += 1;
+                summary.current_streak += 1;
+                summary.best_streak = summary.best_streak.max(summary.current_streak);
+                diff.wins += 1;
+                diff.total_win_time_secs += r.time_secs;
+                diff.best_time_secs = Some(
+                    diff.best_time_secs
+                        .map_or(r.time_secs, |b| b.min(r.time_secs)),
+                );
+            }
+            Outcome::Loss => {
+                summary.losses += 1;
+                summary.current_streak = 0;
+                diff.losses += 1;
+            }
+            Outcome::Abandoned => {
+                summary.abandoned += 1;
+                summary.current_streak = 0;
+                diff.abandoned += 1;
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, result: Outcome, time_secs: u64) -> GameRecord {
+        GameRecord {
+            timestamp,
+            difficulty: Difficulty::Medium,
+            result,
+            time_secs,
+            hints_used: 1,
+            mistakes: 0,
+            puzzle_hash: format!("hash-{}", timestamp),
+            puzzle: String::new(),
+            short_code: Some("AB,CD".to_string()),
+            verified: true,
+            source: Some("tui".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_json_and_csv_round_trip() {
+        let records = vec![record(1, Outcome::Win, 300), record(2, Outcome::Loss, 50)];
+
+        let json = to_json(&records, "tui", 10);
+        assert_eq!(parse(&json).unwrap(), records);
+
+        let csv = to_csv(&records);
+        assert!(csv.starts_with("timestamp,difficulty,result,"));
+        assert!(csv.contains("\"AB,CD\""));
+        assert_eq!(parse(&csv).unwrap(), records);
+
+        let newer = json.replace("\"version\": 1", "\"version\": 9");
+        assert_eq!(parse(&newer), Err(ImportError::UnsupportedVersion(9)));
+        assert_eq!(parse("hello"), Err(ImportError::UnknownFormat));
+        assert!(matches!(
+            parse("timestamp,difficulty,result,time_secs\n1,Medium,Win,abc"),
+            Err(ImportError::BadCsvLine(2, _))
+        ));
+    }
+
+    #[test]
+    fn test_import_other_platforms() {
+        let tui = r#"{"player": {}, "history": [{
+            "id": 1, "puzzle_hash": "h", "puzzle": "", "difficulty": "Hard",
+            "result": "Abandoned", "time_secs": 5, "hints_used": 0, "mistakes": 0,
+            "moves_count": 0, "timestamp": 7, "avg_move_time_ms": 0,
+            "min_move_time_ms": 0, "move_time_std_dev": 0.0, "verified": false
+        }]}"#;
+        let records = parse(tui).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].result, Outcome::Abandoned);
+        assert_eq!(records[0].source.as_deref(), Some("tui"));
+
+        let puzzle = "5".repeat(81);
+        let ios = format!(
+            r#"[{{"puzzleHash": "x", "puzzleString": "{}", "difficulty": "Easy",
+                "firstPlayedAt": 0, "lastPlayedAt": 100.5, "playCount": 2,
+                "bestTime": 321.4, "wins": 1, "losses": 1}},
+               {{"puzzleHash": "y", "puzzleString": "{}", "difficulty": "Easy",
+                "firstPlayedAt": 0, "lastPlayedAt": 0, "playCount": 1,
+                "wins": 0, "losses": 1}}]"#,
+            puzzle, puzzle
+        );
+        let records = parse(&ios).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, 978_307_200);
+        assert_eq!(records[0].time_secs, 321);
+        assert_eq!(records[0].puzzle_hash, canonical_puzzle_hash_str(&puzzle));
+
+        // Playing the puzzle again doesn't make it a new game on re-import
+        let replayed = ios
+            .replace("\"lastPlayedAt\": 100.5", "\"lastPlayedAt\": 900.0")
+            .replace("321.4", "250.0");
+        let (added, summary) = merge(&records, parse(&replayed).unwrap());
+        assert!(added.is_empty());
+        assert_eq!(summary.duplicates, 1);
+    }
+
+    #[test]
+    fn test_merge_and_summarize() {
+        let existing = vec![record(1, Outcome::Win, 300), record(2, Outcome::Loss, 50)];
+        let incoming = vec![
+            record(4, Outcome::Win, 200),
+            record(2, Outcome::Loss, 50),
+            record(3, Outcome::Win, 250),
+        ];
+        let (added, merged) = merge(&existing, incoming);
+        assert_eq!(
+            merged,
+            MergeSummary {
+                added: 2,
+                duplicates: 1
+            }
+        );
+        assert_eq!(added[0].timestamp, 3);
+        // Only the exporting client vouched for these
+        assert!(added.iter().all(|r| !r.verified));
+
+        let all: Vec<GameRecord> = existing.into_iter().chain(added).collect();
+        let summary = summarize(&all);
+        assert_eq!((summary.games, summary.wins, summary.losses), (4, 3, 1));
+        assert_eq!((summary.current_streak, summary.best_streak), (2, 2));
+        let medium = &summary.by_difficulty["Medium"];
+        assert_eq!(medium.best_time_secs, Some(200));
+        assert_eq!(medium.total_win_time_secs, 750);
+    }
+}
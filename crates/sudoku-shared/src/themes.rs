@@ -0,0 +1,245 @@
+//! Color theme files shared by the TUI and the web build.
+//!
+//! A theme names a built-in `base` and overrides any of the [`Palette`]
+//! roles with `#rrggbb` colors, so a file only lists what it changes:
+//!
+//! ```toml
+//! name = "Solarized Dark"
+//! base = "dark"
+//!
+//! [colors]
+//! background = "#002b36"
+//! text = "#839496"
+//! given = "#eee8d5"
+//! player = "#268bd2"
+//! ```
+//!
+//! The TUI reads TOML or JSON files, the web build JSON, into a
+//! [`ThemeFile`]; each frontend then maps the roles onto its own color type.
+//! Roles a frontend doesn't draw (the terminal has no hint overlays) are
+//! ignored there.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Names of the built-in themes every frontend provides
+pub const BUILTIN: [&str; 5] = [
+    "dark",
+    "light",
+    "high-contrast",
+    "colorblind",
+    "colorblind-light",
+];
+
+/// An sRGB color, written `#rrggbb` (or `#rgb`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl std::str::FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| format!("'{}' is not a #rrggbb color", s))?;
+        let channel = |i: usize, len: usize| {
+            let v = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).unwrap_or(0);
+            if len == 1 {
+                v * 17
+            } else {
+                v
+            }
+        };
+        match hex.len() {
+            3 => Ok(Self {
+                r: channel(0, 1),
+                g: channel(1, 1),
+                b: channel(2, 1),
+            }),
+            6 => Ok(Self {
+                r: channel(0, 2),
+                g: channel(1, 2),
+                b: channel(2, 2),
+            }),
+            _ => Err(format!("'{}' is not a #rrggbb color", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Colors by role; unset roles come from the base theme
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    pub background: Option<Rgb>,
+    /// Default text
+    pub text: Option<Rgb>,
+    pub grid_lines: Option<Rgb>,
+    /// Thicker lines between boxes
+    pub box_border: Option<Rgb>,
+    pub cell_bg: Option<Rgb>,
+    /// Selected cell
+    pub cursor_bg: Option<Rgb>,
+    /// Cells in the selected cell's row, column and box
+    pub highlight_bg: Option<Rgb>,
+    /// Cells holding the selected cell's value
+    pub same_value_bg: Option<Rgb>,
+    /// Puzzle clues
+    pub given: Option<Rgb>,
+    /// Values the player entered
+    pub player: Option<Rgb>,
+    /// Pencil marks
+    pub candidate: Option<Rgb>,
+    pub error: Option<Rgb>,
+    /// Completed units, wins
+    pub success: Option<Rgb>,
+    /// Timer and secondary text
+    pub info: Option<Rgb>,
+    /// Key names and messages
+    pub accent: Option<Rgb>,
+    /// Completed-digit indicator
+    pub completed_bg: Option<Rgb>,
+    pub hint_involved: Option<Rgb>,
+    pub hint_target: Option<Rgb>,
+    pub hint_chain_on: Option<Rgb>,
+    pub hint_chain_off: Option<Rgb>,
+    pub hint_fish_base: Option<Rgb>,
+    pub hint_fish_cover: Option<Rgb>,
+    pub hint_fish_fin: Option<Rgb>,
+    pub hint_ur_floor: Option<Rgb>,
+    pub hint_ur_roof: Option<Rgb>,
+    pub hint_als_group: Option<Rgb>,
+    pub hint_panel: Option<Rgb>,
+    pub hint_technique: Option<Rgb>,
+    pub hint_explain: Option<Rgb>,
+}
+
+/// A theme as written in a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeFile {
+    /// Display name; frontends fall back to the file name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Built-in theme providing the roles not in `colors` (default "dark")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub colors: Palette,
+}
+
+/// Why a theme couldn't be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThemeError {
+    /// Not valid TOML/JSON, an unknown role or a bad color
+    Parse(String),
+    /// `base` isn't one of [`BUILTIN`]
+    UnknownBase(String),
+}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "invalid theme: {}", e),
+            Self::UnknownBase(base) => write!(
+                f,
+                "unknown base theme '{}' (expected one of {})",
+                base,
+                BUILTIN.join(", ")
+            ),
+        }
+    }
+}
+
+impl ThemeFile {
+    pub fn from_json(json: &str) -> Result<Self, ThemeError> {
+        let file: Self =
+            serde_json::from_str(json).map_err(|e| ThemeError::Parse(e.to_string()))?;
+        file.base_name()?;
+        Ok(file)
+    }
+
+    /// The built-in theme to start from
+    pub fn base_name(&self) -> Result<&str, ThemeError> {
+        match self.base.as_deref() {
+            None => Ok("dark"),
+            Some(base) if BUILTIN.contains(&base) => Ok(base),
+            Some(base) => Err(ThemeError::UnknownBase(base.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(
+            "#ff8000".parse::<Rgb>(),
+            Ok(Rgb {
+                r: 255,
+                g: 128,
+                b: 0
+            })
+        );
+        assert_eq!("#0f0".parse::<Rgb>().unwrap().to_string(), "#00ff00");
+        assert!("ff8000".parse::<Rgb>().is_err());
+        assert!("#ff80".parse::<Rgb>().is_err());
+        assert!("#gg0000".parse::<Rgb>().is_err());
+    }
+
+    #[test]
+    fn test_theme_file() {
+        let file = ThemeFile::from_json(
+            r##"{"name": "Ocean", "base": "light", "colors": {"background": "#001122"}}"##,
+        )
+        .unwrap();
+        assert_eq!(file.base_name(), Ok("light"));
+        assert_eq!(
+            file.colors.background,
+            Some(Rgb {
+                r: 0,
+                g: 0x11,
+                b: 0x22
+            })
+        );
+        assert_eq!(file.colors.text, None);
+
+        let round_trip = ThemeFile::from_json(&serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(round_trip, file);
+
+        assert_eq!(ThemeFile::from_json("{}").unwrap().base_name(), Ok("dark"));
+        assert!(matches!(
+            ThemeFile::from_json(r#"{"base": "neon"}"#),
+            Err(ThemeError::UnknownBase(_))
+        ));
+        assert!(matches!(
+            ThemeFile::from_json(r##"{"colors": {"backgorund": "#000000"}}"##),
+            Err(ThemeError::Parse(e)) if e.contains("backgorund")
+        ));
+        assert!(ThemeFile::from_json(r#"{"colors": {"text": "red"}}"#).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Local stand-in for the ukodus API, for testing telemetry and privacy flows.
+//!
+//! ```text
+//! cargo run -p sudoku-tui --example telemetry_stub -- 127.0.0.1:8787
+//! cargo run -p sudoku-tui --bin sudoku -- --server local
+//! ```
+//!
+//! Every request is printed to stdout and answered with a canned response,
+//! so you can see exactly what the TUI would send.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() -> std::io::Result<()> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8787".to_string());
+    let listener = TcpListener::bind(&addr)?;
+    println!("telemetry stub listening on http://{}", addr);
+
+    for stream in listener.incoming() {
+        if let Err(e) = stream.and_then(handle) {
+            eprintln!("connection error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+            content_length = v.trim().parse().unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    println!("{} {}", method, path);
+    if !body.is_empty() {
+        println!("{}", String::from_utf8_lossy(&body));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (status, response) = match (method.as_str(), path.split('?').next().unwrap_or("")) {
+        ("POST", "/api/v1/token") => (
+            200,
+            format!(r#"{{"token":"stub-token","expires_at":{}}}"#, now + 3600),
+        ),
+        ("POST", "/api/v1/results") => (200, r#"{"ok":true}"#.to_string()),
+        ("DELETE", p) if p.starts_with("/api/v1/players/") => {
+            (200, r#"{"deleted":true}"#.to_string())
+        }
+        ("POST", "/api/v1/leaderboard/submit") => (
+            200,
+            r#"{"success":true,"message":null,"rank":1}"#.to_string(),
+        ),
+        ("GET", "/api/v1/leaderboard") => (
+            200,
+            r#"{"entries":[],"total":0,"next_offset":null}"#.to_string(),
+        ),
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        if status == 200 { "OK" } else { "Not Found" },
+        response.len(),
+        response
+    )
+}
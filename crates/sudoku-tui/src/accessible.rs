@@ -0,0 +1,422 @@
+//! Screen reader mode.
+//!
+//! The normal renderer repaints the whole screen every tick with absolute
+//! cursor moves, which a screen reader can't follow. In this mode nothing is
+//! drawn; instead [`Narrator`] compares the app with what it last said and
+//! prints a plain line of text for each change (the cell under the cursor,
+//! menu choices, messages, hints), so the terminal's scrollback reads like a
+//! transcript. The `describe_*` helpers are also bound to keys in both
+//! modes to read a row, column or box on demand.
+
+use crate::app::{App, MenuState, ScreenState, MAX_MISTAKES, SETTINGS};
+use crate::config::Action;
+use crate::game::Game;
+use crate::stats::format_time;
+use std::io::{self, Write};
+use sudoku_core::{Hint, HintType, Position};
+
+/// "row 5 column 3"
+fn place(pos: Position) -> String {
+    format!("row {} column {}", pos.row + 1, pos.col + 1)
+}
+
+/// A cell as spoken when the cursor lands on it, e.g.
+/// "row 5 column 3, empty, candidates 2 7"
+pub fn describe_cell(game: &Game, pos: Position) -> String {
+    let cell = game.grid().cell(pos);
+    match cell.value() {
+        Some(value) if cell.is_given() => format!("{}, {} given", place(pos), value),
+        Some(value) if game.has_conflict(pos) => {
+            format!("{}, {}, conflict", place(pos), value)
+        }
+        Some(value) => format!("{}, {}", place(pos), value),
+        None => {
+            let notes = game.get_cell_candidates(pos);
+            if notes.is_empty() {
+                format!("{}, empty", place(pos))
+            } else {
+                format!("{}, empty, candidates {}", place(pos), join(&notes))
+            }
+        }
+    }
+}
+
+fn join(values: &[u8]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Values of nine cells, "blank" for empty ones
+fn describe_unit(game: &Game, name: String, cells: impl Iterator<Item = Position>) -> String {
+    let values: Vec<String> = cells
+        .map(|pos| match game.grid().get(pos) {
+            Some(value) => value.to_string(),
+            None => "blank".to_string(),
+        })
+        .collect();
+    format!("{}: {}", name, values.join(", "))
+}
+
+/// "Row 5: 1, blank, 3, ..." for row index `row` (0-8)
+pub fn describe_row(game: &Game, row: usize) -> String {
+    describe_unit(
+        game,
+        format!("Row {}", row + 1),
+        (0..9).map(|col| Position::new(row, col)),
+    )
+}
+
+/// Like [`describe_row`], top to bottom
+pub fn describe_column(game: &Game, col: usize) -> String {
+    describe_unit(
+        game,
+        format!("Column {}", col + 1),
+        (0..9).map(|row| Position::new(row, col)),
+    )
+}
+
+/// Like [`describe_row`] for box `index` (0-8, left to right then down),
+/// read row by row
+pub fn describe_box(game: &Game, index: usize) -> String {
+    let (top, left) = ((index / 3) * 3, (index % 3) * 3);
+    describe_unit(
+        game,
+        format!("Box {}", index + 1),
+        (0..9).map(|i| Position::new(top + i / 3, left + i % 3)),
+    )
+}
+
+/// Difficulty, progress, time and input mode in one sentence
+pub fn describe_status(app: &App) -> String {
+    let game = &app.game;
+    let filled = (0..81)
+        .filter(|&i| game.grid().get(Position::new(i / 9, i % 9)).is_some())
+        .count();
+    let mode = match app.mode {
+        crate::app::InputMode::Candidate => "notes",
+        _ => "normal",
+    };
+    let mut status = format!(
+        "{} puzzle, {} of 81 cells filled, time {}, {} of {} mistakes, {} hints, {} mode",
+        game.difficulty(),
+        filled,
+        game.elapsed_string(),
+        game.mistakes(),
+        MAX_MISTAKES,
+        game.hints_used(),
+        mode
+    );
+    if game.is_paused() {
+        status.push_str(", paused");
+    }
+    status
+}
+
+/// A hint with its move spelled out and cell references like "r5c3" read
+/// as "row 5 column 3"
+pub fn describe_hint(hint: &Hint) -> String {
+    let action = match &hint.hint_type {
+        HintType::SetValue { pos, value } => format!("place {} at {}", value, place(*pos)),
+        HintType::EliminateCandidates { pos, values } => {
+            format!("remove {} from {}", join(values), place(*pos))
+        }
+    };
+    let explanation = spell_cells(hint.explanation.trim());
+    if explanation.is_empty() {
+        format!("Hint, {}: {}", hint.technique, action)
+    } else {
+        format!("Hint, {}: {}. {}", hint.technique, action, explanation)
+    }
+}
+
+/// Replace "r5c3" / "R5C3" with "row 5 column 3"
+fn spell_cells(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_word = i == 0 || !chars[i - 1].is_ascii_alphanumeric();
+        let ends_word = chars.get(i + 4).is_none_or(|c| !c.is_ascii_alphanumeric());
+        if starts_word
+            && ends_word
+            && chars[i].eq_ignore_ascii_case(&'r')
+            && chars
+                .get(i + 2)
+                .is_some_and(|c| c.eq_ignore_ascii_case(&'c'))
+            && chars.get(i + 1).is_some_and(|c| ('1'..='9').contains(c))
+            && chars.get(i + 3).is_some_and(|c| ('1'..='9').contains(c))
+        {
+            out.push_str(&format!("row {} column {}", chars[i + 1], chars[i + 3]));
+            i += 4;
+        } else {
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Turns app state changes into lines of text
+#[derive(Default)]
+pub struct Narrator {
+    screen: Option<ScreenState>,
+    menu: Option<(MenuState, usize)>,
+    cell: Option<String>,
+    hint: Option<String>,
+    message: Option<(u32, String)>,
+    setting: Option<String>,
+    lobby: Option<String>,
+}
+
+impl Narrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What to say about the changes since the last call
+    pub fn update(&mut self, app: &App) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if self.screen != Some(app.screen_state) {
+            if self.screen.is_none() {
+                lines.push("Sudoku, screen reader mode.".to_string());
+            }
+            self.screen = Some(app.screen_state);
+            self.menu = None;
+            self.cell = None;
+            self.setting = None;
+            self.lobby = None;
+            lines.push(screen_intro(app));
+        }
+
+        match app.screen_state {
+            ScreenState::Playing => self.update_playing(app, &mut lines),
+            ScreenState::Settings => {
+                let line = describe_setting(app);
+                if self.setting.as_ref() != Some(&line) {
+                    lines.push(line.clone());
+                    self.setting = Some(line);
+                }
+            }
+            ScreenState::Lobby => {
+                let line = describe_lobby(app);
+                if self.lobby.as_ref() != Some(&line) {
+                    lines.push(line.clone());
+                    self.lobby = Some(line);
+                }
+            }
+            _ => {}
+        }
+
+        match &app.message {
+            Some(text) => {
+                let message = (app.message_seq, text.clone());
+                if self.message.as_ref() != Some(&message) {
+                    lines.push(text.clone());
+                    self.message = Some(message);
+                }
+            }
+            None => self.message = None,
+        }
+
+        lines
+    }
+
+    fn update_playing(&mut self, app: &App, lines: &mut Vec<String>) {
+        if app.menu != MenuState::None {
+            let state = (app.menu, app.menu_selection);
+            if self.menu != Some(state) {
+                let options = app.menu_options();
+                let option = options.get(app.menu_selection).map_or("", String::as_str);
+                let position = format!(
+                    "{}, {} of {}",
+                    option,
+                    app.menu_selection + 1,
+                    options.len()
+                );
+                if self.menu.map(|(menu, _)| menu) == Some(app.menu) {
+                    lines.push(position);
+                } else {
+                    lines.push(format!("{}: {}", app.menu_title(), position));
+                }
+                self.menu = Some(state);
+            }
+            return;
+        }
+        if self.menu.take().is_some() {
+            // Back on the board after a menu: say where the cursor is again
+            self.cell = None;
+        }
+
+        let hint = app.current_hint.as_ref().map(describe_hint);
+        if hint.is_some() && hint != self.hint {
+            lines.extend(hint.clone());
+        }
+        self.hint = hint;
+
+        let cell = describe_cell(&app.game, app.cursor);
+        if self.cell.as_ref() != Some(&cell) {
+            lines.push(cell.clone());
+            self.cell = Some(cell);
+        }
+    }
+
+    /// Print the new lines. Raw mode needs explicit carriage returns.
+    pub fn render(&mut self, out: &mut impl Write, app: &App) -> io::Result<()> {
+        for line in self.update(app) {
+            write!(out, "{}\r\n", line)?;
+        }
+        out.flush()
+    }
+}
+
+/// What a screen is and how to use it, said when it opens
+fn screen_intro(app: &App) -> String {
+    let key = |action| app.keymap.label(action);
+    match app.screen_state {
+        ScreenState::Playing => format!(
+            "{}. Move with the arrow keys and type 1 to 9 to fill a cell. \
+             Press {} for the status, and {}, {} or {} to read the row, column or box.",
+            describe_status(app),
+            key(Action::ReadStatus),
+            key(Action::ReadRow),
+            key(Action::ReadColumn),
+            key(Action::ReadBox),
+        ),
+        ScreenState::Win => format!(
+            "Solved! Time {}, {} hints, {} mistakes, {}. \
+             Press n for a new game, p for this puzzle's leaderboard or q to quit.",
+            app.game.elapsed_string(),
+            app.game.hints_used(),
+            app.game.mistakes(),
+            app.game.difficulty()
+        ),
+        ScreenState::Lose => format!(
+            "Game over, {} mistakes. Press n for a new game or q to quit.",
+            MAX_MISTAKES
+        ),
+        ScreenState::Stats => {
+            let player = &app.stats.player;
+            let best = app
+                .stats
+                .player
+                .get_difficulty_stats(app.stats_difficulty)
+                .best_time_secs
+                .map_or("none".to_string(), format_time);
+            format!(
+                "Statistics for {}. {} games, {} won, {:.0} percent. \
+                 Current streak {}, best streak {}. Best {} time {}. Press Escape to go back.",
+                player.player_name,
+                player.total_games,
+                player.total_wins,
+                player.overall_win_rate(),
+                player.current_streak,
+                player.best_streak,
+                app.stats_difficulty,
+                best
+            )
+        }
+        ScreenState::Settings => {
+            "Settings. Up and down choose, left and right change, Escape goes back.".to_string()
+        }
+        ScreenState::Leaderboard => "Leaderboard. Press Escape to go back.".to_string(),
+        ScreenState::History => "Game history. Press Escape to go back.".to_string(),
+        ScreenState::Profiles => "Profiles. Press Escape to go back.".to_string(),
+        ScreenState::Achievements => "Achievements. Press Escape to go back.".to_string(),
+        ScreenState::Lobby => match app.lobby() {
+            Some(lobby) => match lobby.address {
+                Some(address) => format!(
+                    "{} lobby {}. Others join with: sudoku {} join {} {}. \
+                     Press Enter to start or Escape to leave.",
+                    lobby.name, lobby.code, lobby.mode, address, lobby.code
+                ),
+                None => format!(
+                    "{} lobby {}. Waiting for the host to start. Press Escape to leave.",
+                    lobby.name, lobby.code
+                ),
+            },
+            None => "Lobby.".to_string(),
+        },
+    }
+}
+
+/// Who is waiting in the lobby
+fn describe_lobby(app: &App) -> String {
+    let players = app.lobby().map(|lobby| lobby.players).unwrap_or_default();
+    let names: Vec<&str> = players.iter().map(|(name, _)| name.as_str()).collect();
+    match names.len() {
+        0 => "Connecting.".to_string(),
+        1 => format!("1 player: {}.", names[0]),
+        n => format!("{} players: {}.", n, names.join(", ")),
+    }
+}
+
+/// The selected row of the settings screen
+fn describe_setting(app: &App) -> String {
+    let row = app.settings_selection;
+    if let Some(name) = SETTINGS.get(row) {
+        return format!("{}: {}", name, app.setting_values()[row]);
+    }
+    let Some(&action) = Action::ALL.get(row - SETTINGS.len()) else {
+        return String::new();
+    };
+    if app.settings_capture.is_some() {
+        return format!("Press a key for {}, or Escape to cancel", action.label());
+    }
+    let keys = app.keymap.label(action);
+    format!(
+        "{}: {}",
+        action.label(),
+        if keys.is_empty() { "no key" } else { &keys }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell_cells() {
+        assert_eq!(
+            spell_cells("r5c3 and R1C9 see each other"),
+            "row 5 column 3 and row 1 column 9 see each other"
+        );
+        assert_eq!(spell_cells("rc5 r0c1 r5c3x"), "rc5 r0c1 r5c3x");
+    }
+
+    #[test]
+    fn test_describe_cells() {
+        let puzzle =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+        let mut game = Game::from_string(puzzle).unwrap();
+        assert_eq!(
+            describe_cell(&game, Position::new(0, 0)),
+            "row 1 column 1, 5 given"
+        );
+        assert_eq!(
+            describe_cell(&game, Position::new(0, 2)),
+            "row 1 column 3, empty"
+        );
+        game.toggle_candidate(Position::new(0, 2), 4);
+        game.toggle_candidate(Position::new(0, 2), 1);
+        assert_eq!(
+            describe_cell(&game, Position::new(0, 2)),
+            "row 1 column 3, empty, candidates 1 4"
+        );
+        assert_eq!(
+            describe_row(&game, 0),
+            "Row 1: 5, 3, blank, blank, 7, blank, blank, blank, blank"
+        );
+        assert_eq!(
+            describe_column(&game, 0),
+            "Column 1: 5, 6, blank, 8, 4, 7, blank, blank, blank"
+        );
+        assert_eq!(
+            describe_box(&game, 4),
+            "Box 5: blank, 6, blank, 8, blank, 3, blank, 2, blank"
+        );
+    }
+}
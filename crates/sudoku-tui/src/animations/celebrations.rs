@@ -71,6 +71,8 @@ pub struct CelebrationManager {
     prev_completed_rows: [bool; 9],
     prev_completed_cols: [bool; 9],
     prev_completed_boxes: [bool; 9],
+    /// Reduced motion: announce completions without flashing
+    still: bool,
 }
 
 impl CelebrationManager {
@@ -78,6 +80,20 @@ impl CelebrationManager {
         Self::default()
     }
 
+    /// Turn the flashing off (the completion messages stay)
+    pub fn set_still(&mut self, still: bool) {
+        self.still = still;
+    }
+
+    /// Start a celebration; a still one only lasts long enough for its message
+    fn celebrate(&mut self, celebration_type: CelebrationType) {
+        let mut celebration = Celebration::new(celebration_type, self.frame_count);
+        if self.still {
+            celebration.duration_frames = 1;
+        }
+        self.celebrations.push(celebration);
+    }
+
     /// Update celebrations based on current game state
     pub fn update(
         &mut self,
@@ -88,40 +104,35 @@ impl CelebrationManager {
         self.frame_count += 1;
 
         // Check for newly completed rows
-        for (i, (&curr, &prev)) in completed_rows
+        for (i, (&curr, prev)) in completed_rows
             .iter()
-            .zip(self.prev_completed_rows.iter())
+            .zip(self.prev_completed_rows)
             .enumerate()
         {
             if curr && !prev {
-                self.celebrations
-                    .push(Celebration::new(CelebrationType::Row(i), self.frame_count));
+                self.celebrate(CelebrationType::Row(i));
             }
         }
 
         // Check for newly completed columns
-        for (i, (&curr, &prev)) in completed_cols
+        for (i, (&curr, prev)) in completed_cols
             .iter()
-            .zip(self.prev_completed_cols.iter())
+            .zip(self.prev_completed_cols)
             .enumerate()
         {
             if curr && !prev {
-                self.celebrations.push(Celebration::new(
-                    CelebrationType::Column(i),
-                    self.frame_count,
-                ));
+                self.celebrate(CelebrationType::Column(i));
             }
         }
 
         // Check for newly completed boxes
-        for (i, (&curr, &prev)) in completed_boxes
+        for (i, (&curr, prev)) in completed_boxes
             .iter()
-            .zip(self.prev_completed_boxes.iter())
+            .zip(self.prev_completed_boxes)
             .enumerate()
         {
             if curr && !prev {
-                self.celebrations
-                    .push(Celebration::new(CelebrationType::Box(i), self.frame_count));
+                self.celebrate(CelebrationType::Box(i));
             }
         }
 
@@ -148,7 +159,7 @@ impl CelebrationManager {
         for c in &self.celebrations {
             if let CelebrationType::Row(r) = c.celebration_type {
                 if r == row {
-                    return c.intensity(self.frame_count);
+                    return self.intensity(c);
                 }
             }
         }
@@ -160,7 +171,7 @@ impl CelebrationManager {
         for c in &self.celebrations {
             if let CelebrationType::Column(c_idx) = c.celebration_type {
                 if c_idx == col {
-                    return c.intensity(self.frame_count);
+                    return self.intensity(c);
                 }
             }
         }
@@ -172,13 +183,21 @@ impl CelebrationManager {
         for c in &self.celebrations {
             if let CelebrationType::Box(b) = c.celebration_type {
                 if b == box_idx {
-                    return c.intensity(self.frame_count);
+                    return self.intensity(c);
                 }
             }
         }
         0.0
     }
 
+    fn intensity(&self, celebration: &Celebration) -> f32 {
+        if self.still {
+            0.0
+        } else {
+            celebration.intensity(self.frame_count)
+        }
+    }
+
     /// Check if there are any active celebrations
     pub fn has_active_celebrations(&self) -> bool {
         !self.celebrations.is_empty()
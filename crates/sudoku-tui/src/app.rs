@@ -1,16 +1,49 @@
+use crate::accessible;
 use crate::animations::{CelebrationManager, LoseScreen, WinScreen};
+use crate::config::{Action, Config, KeyBinding, Keymap};
 use crate::game::Game;
-use crate::stats::{GameResult, StatsManager};
-use crate::theme::Theme;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::lan::coop::{Coop, CoopEvent};
+use crate::lan::race::{self, Race, RaceEvent};
+use crate::lan::LobbyView;
+use crate::leaderboard_view::{LeaderboardView, PuzzleFilter, PuzzleRank};
+use crate::render::{HitMap, Layout, Target};
+use crate::stats::{GameResult, Multiplayer, StatsManager};
+use crate::theme::{Theme, ThemeEntry};
+use crate::trends::TrendView;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::time::Duration;
-use sudoku_core::{Difficulty, Hint, Position, PuzzleId};
+use std::time::{Duration, Instant, SystemTime};
+use sudoku_core::{Difficulty, Hint, Position};
 
 /// Maximum mistakes before game over
 pub const MAX_MISTAKES: usize = 3;
 
+/// Difficulties in menu order; the last two are secret
+const DIFFICULTIES: [Difficulty; 8] = [
+    Difficulty::Beginner,
+    Difficulty::Easy,
+    Difficulty::Medium,
+    Difficulty::Intermediate,
+    Difficulty::Hard,
+    Difficulty::Expert,
+    Difficulty::Master,
+    Difficulty::Extreme,
+];
+
+/// Rows of the settings screen above the key bindings
+pub const SETTINGS: [&str; 8] = [
+    "Theme",
+    "Valid hints",
+    "Ghost hints",
+    "Default difficulty",
+    "Start in",
+    "Screen reader",
+    "Reduced motion",
+    "Layout",
+];
+
 /// Result of handling a key press
 pub enum AppAction {
     Continue,
@@ -32,10 +65,19 @@ pub enum ScreenState {
     Leaderboard,
     /// Game history screen (for replay selection)
     History,
+    /// Profile picker
+    Profiles,
+    /// Achievement progress
+    Achievements,
+    /// Settings and key bindings
+    Settings,
+    /// Waiting for a LAN race or co-op game to start
+    Lobby,
 }
 
 /// Input mode for the app
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 #[allow(dead_code)]
 pub enum InputMode {
     /// Normal mode: numbers set values
@@ -55,6 +97,28 @@ pub enum MenuState {
     Difficulty,
     Theme,
     Confirm,
+    /// First-run prompt asking whether results may be uploaded
+    TelemetryConsent,
+}
+
+/// What the next key press does on the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCapture {
+    /// Becomes the only key for the selected action
+    Replace,
+    /// Is added to the selected action's keys
+    Add,
+}
+
+/// Pending text entry or confirmation on the profile screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileEdit {
+    /// Typing the name of a new profile
+    Create(String),
+    /// Typing a new name for the profile with this id
+    Rename { id: String, name: String },
+    /// Waiting for 'y' to delete the profile with this id
+    ConfirmDelete(String),
 }
 
 /// The main application state
@@ -71,12 +135,20 @@ pub struct App {
     pub menu_selection: usize,
     /// Color theme
     pub theme: Theme,
+    /// Themes offered by the theme menu and settings screen
+    pub themes: Vec<ThemeEntry>,
+    /// Theme file in use and its modification time, reloaded when it changes
+    theme_watch: Option<(ThemeEntry, Option<SystemTime>)>,
+    /// When `theme_watch` was last checked
+    theme_checked: Instant,
     /// Current hint to display
     pub current_hint: Option<Hint>,
     /// Message to display
     pub message: Option<String>,
     /// Message timer
     message_timer: u32,
+    /// Bumped by every `show_message`, so a repeated message is told apart
+    pub message_seq: u32,
     /// Current screen state
     pub screen_state: ScreenState,
     /// Win screen animation
@@ -89,14 +161,50 @@ pub struct App {
     pub show_suggestions: bool,
     /// Whether to show naked singles (cells with only one candidate) as hints
     pub show_naked_singles: bool,
+    /// Settings as saved in the config file
+    pub config: Config,
+    /// Keys for each action during play, built from `config.keys`
+    pub keymap: Keymap,
+    /// Selected row on the settings screen
+    pub settings_selection: usize,
+    /// Waiting for a key to bind to the selected action
+    pub settings_capture: Option<KeyCapture>,
+    /// Clickable regions of the last frame
+    pub hit_map: HitMap,
+    /// Narrate changes as text lines instead of drawing the screen
+    pub screen_reader: bool,
+    /// No particles or flashing, and a steady low refresh rate
+    pub reduced_motion: bool,
+    /// The LAN race being played, if any
+    pub race: Option<Race>,
+    /// The shared board being played, if any
+    pub coop: Option<Coop>,
     /// Statistics manager
     pub stats: StatsManager,
     /// Whether current game has been recorded (to avoid double recording)
     game_recorded: bool,
-    /// Selected difficulty for leaderboard filter
-    pub leaderboard_difficulty: Difficulty,
+    /// Results waiting in the upload outbox (refreshed when opening stats)
+    pub pending_uploads: usize,
+    /// Leaderboard screen filters and fetched page
+    pub leaderboard: LeaderboardView,
+    /// Screen to return to when leaving the leaderboard
+    leaderboard_back: ScreenState,
+    /// Rank on the just-won puzzle's board, for the win screen
+    pub puzzle_rank: PuzzleRank,
     /// Scroll offset for history view
     pub history_scroll: usize,
+    /// Selected row on the achievements screen
+    pub achievement_selection: usize,
+    /// Chart shown on the stats screen
+    pub stats_view: TrendView,
+    /// Difficulty charted by the per-difficulty stats views
+    pub stats_difficulty: Difficulty,
+    /// Profiles listed on the profile screen
+    pub profiles: Vec<crate::profiles::Profile>,
+    /// Selected row on the profile screen
+    pub profile_selection: usize,
+    /// Name being typed or deletion being confirmed on the profile screen
+    pub profile_edit: Option<ProfileEdit>,
     /// Konami code progress (for easter egg)
     konami_progress: usize,
     /// Reverse Konami code progress (lose screen easter egg)
@@ -150,48 +258,108 @@ impl App {
 
     /// Create a new app, optionally loading a puzzle from an 81-character string or 8-character short code
     pub fn new_with_puzzle(puzzle: Option<&str>) -> Self {
-        let game = match puzzle {
-            Some(p) if p.len() == 81 => {
-                Game::from_string(p).unwrap_or_else(|| Game::new(Difficulty::Medium))
+        let (config, mut config_warnings) = Config::load();
+        let (keymap, keymap_warnings) = Keymap::new(&config.keys);
+        config_warnings.extend(keymap_warnings);
+        let themes = Theme::installed();
+        let entry = themes.iter().find(|t| t.id == config.theme).cloned();
+        let theme = match &entry {
+            Some(entry) => entry.load().unwrap_or_else(|e| {
+                config_warnings.push(e);
+                Theme::dark()
+            }),
+            None => {
+                config_warnings.push(format!("Unknown theme '{}'", config.theme));
+                Theme::dark()
             }
-            Some(p) => match PuzzleId::from_short_code(p) {
-                Some(id) => Game::new_with_id(&id),
-                None => {
-                    // Try as puzzle string anyway (fallback)
-                    Game::from_string(p).unwrap_or_else(|| Game::new(Difficulty::Medium))
-                }
-            },
-            None => Game::new(Difficulty::Medium),
         };
-
-        Self {
+        let theme_watch = entry
+            .filter(|e| e.path.is_some())
+            .map(|e| (e.clone(), modified(&e)));
+
+        let stats = Self::load_profile_stats();
+        let (difficulty, locked) = default_difficulty(&config, &stats);
+        config_warnings.extend(locked);
+        let game = puzzle
+            .and_then(Game::from_code)
+            .unwrap_or_else(|| Game::new(difficulty));
+
+        let mut app = Self {
             game,
             cursor: Position::new(4, 4),
-            mode: InputMode::Normal,
-            menu: MenuState::None,
+            mode: config.input_mode,
+            menu: if crate::privacy::needs_prompt() {
+                MenuState::TelemetryConsent
+            } else {
+                MenuState::None
+            },
             menu_selection: 0,
-            theme: Theme::dark(),
+            theme,
+            themes,
+            theme_watch,
+            theme_checked: Instant::now(),
             current_hint: None,
             message: None,
             message_timer: 0,
+            message_seq: 0,
             screen_state: ScreenState::Playing,
             win_screen: WinScreen::new(),
             lose_screen: LoseScreen::new(),
             celebrations: CelebrationManager::new(),
-            show_suggestions: true,
-            show_naked_singles: false, // Off by default - it's basically cheating!
-            stats: StatsManager::load(),
+            show_suggestions: config.show_suggestions,
+            show_naked_singles: config.show_naked_singles,
+            keymap,
+            settings_selection: 0,
+            settings_capture: None,
+            hit_map: HitMap::default(),
+            screen_reader: false,
+            reduced_motion: false,
+            race: None,
+            coop: None,
+            stats,
             game_recorded: false,
-            leaderboard_difficulty: Difficulty::Medium,
+            pending_uploads: crate::outbox::Outbox::new().pending_count(),
+            leaderboard: LeaderboardView::new(difficulty),
+            leaderboard_back: ScreenState::Playing,
+            puzzle_rank: PuzzleRank::default(),
             history_scroll: 0,
+            achievement_selection: 0,
+            stats_view: TrendView::Overview,
+            stats_difficulty: difficulty,
+            profiles: Vec::new(),
+            profile_selection: 0,
+            profile_edit: None,
             konami_progress: 0,
             reverse_konami_progress: 0,
             the_answer_progress: 0,
+            config,
+        };
+        app.set_accessibility(app.config.screen_reader, app.config.reduced_motion);
+        app.report_load_warning();
+        if let Some(first) = config_warnings.first() {
+            let more = match config_warnings.len() {
+                1 => String::new(),
+                n => format!(" (+{} more)", n - 1),
+            };
+            app.show_message(&format!("{}{}", first, more));
+            app.message_timer = 100;
         }
+        app
+    }
+
+    /// Use these accessibility settings for this session, without saving.
+    /// Screen reader mode always reduces motion.
+    pub fn set_accessibility(&mut self, screen_reader: bool, reduced_motion: bool) {
+        self.screen_reader = screen_reader;
+        self.reduced_motion = reduced_motion || screen_reader;
+        self.celebrations.set_still(self.reduced_motion);
     }
 
     /// Get the tick rate based on current screen
     pub fn get_tick_rate(&self) -> Duration {
+        if self.reduced_motion {
+            return Duration::from_millis(250);
+        }
         match self.screen_state {
             ScreenState::Win | ScreenState::Lose => Duration::from_millis(33), // 30 FPS for animations
             ScreenState::Playing => {
@@ -201,7 +369,13 @@ impl App {
                     Duration::from_millis(100) // 10 FPS for normal gameplay
                 }
             }
-            ScreenState::Stats | ScreenState::Leaderboard | ScreenState::History => {
+            ScreenState::Stats
+            | ScreenState::Leaderboard
+            | ScreenState::History
+            | ScreenState::Profiles
+            | ScreenState::Achievements
+            | ScreenState::Settings
+            | ScreenState::Lobby => {
                 Duration::from_millis(100) // 10 FPS for menu screens
             }
         }
@@ -217,13 +391,30 @@ impl App {
             }
         }
 
+        self.reload_theme_if_changed();
+        self.poll_race();
+        self.poll_coop();
+        self.send_coop();
+
+        // Announce achievements one at a time
+        if self.message.is_none() && !self.stats.new_unlocks.is_empty() {
+            let unlock = self.stats.new_unlocks.remove(0);
+            self.show_message(&format!("🏆 Achievement unlocked: {}", unlock));
+        }
+
         // Update animations based on screen state
         match self.screen_state {
             ScreenState::Win => {
-                self.win_screen.update();
+                // Without updates the screen stays on its first, still frame
+                if !self.reduced_motion {
+                    self.win_screen.update();
+                }
+                self.puzzle_rank.poll();
             }
             ScreenState::Lose => {
-                self.lose_screen.update();
+                if !self.reduced_motion {
+                    self.lose_screen.update();
+                }
             }
             ScreenState::Playing => {
                 // Update celebrations for row/column/box completions
@@ -241,18 +432,55 @@ impl App {
                     }
                 }
 
-                // Check for win/lose conditions
+                // Starting another puzzle leaves the race or team
+                let puzzle = self.game.original_puzzle();
+                let other = |shared: Option<&String>| shared.is_some_and(|p| p != puzzle);
+                let left_race = other(self.race.as_ref().and_then(|race| race.puzzle.as_ref()));
+                let left_team = other(self.coop.as_ref().and_then(|coop| coop.puzzle.as_ref()));
+                if left_race {
+                    self.leave_race();
+                }
+                if left_team {
+                    self.leave_coop();
+                }
+                if let Some(race) = self.race.as_mut() {
+                    race.progress(self.game.filled_count(), self.game.mistakes());
+                }
+
+                // Check for win/lose conditions. A race win is recorded once
+                // the host confirms the place, and a team game once the host
+                // calls it.
+                let team = self.coop.as_ref().is_some_and(|coop| coop.started);
                 if self.game.is_completed() {
-                    self.record_game(GameResult::Win);
+                    match self.race.as_mut().filter(|race| race.started) {
+                        Some(race) => race.finished(&self.game.grid().to_string_compact()),
+                        None if team => {}
+                        None => self.record_game(GameResult::Win),
+                    }
                     self.screen_state = ScreenState::Win;
                     self.win_screen.reset();
                 } else if self.game.mistakes() >= MAX_MISTAKES {
-                    self.record_game(GameResult::Loss);
+                    if let Some(race) = self.race.as_mut().filter(|race| race.started) {
+                        race.out();
+                    }
+                    if !team {
+                        self.record_game(GameResult::Loss);
+                    }
                     self.screen_state = ScreenState::Lose;
                     self.lose_screen.reset();
                 }
             }
-            ScreenState::Stats | ScreenState::Leaderboard | ScreenState::History => {
+            ScreenState::Leaderboard => {
+                if let Some(msg) = self.leaderboard.poll() {
+                    self.show_message(&msg);
+                }
+            }
+            ScreenState::Stats
+            | ScreenState::History
+            | ScreenState::Profiles
+            | ScreenState::Achievements
+            | ScreenState::Settings
+            | ScreenState::Lobby => {
                 // No animations for these screens
             }
         }
@@ -265,37 +493,405 @@ impl App {
         }
         self.game_recorded = true;
 
-        let record = self.stats.record_game(
-            self.game.original_puzzle(),
-            self.game.difficulty(),
-            result,
-            self.game.elapsed().as_secs(),
-            self.game.hints_used(),
-            self.game.mistakes(),
-            self.game.move_times_ms(),
-            self.game.notes_used(),
-            self.game.seed(),
-            self.game.short_code(),
+        let record = self
+            .stats
+            .record_game(
+                self.game.original_puzzle(),
+                self.game.difficulty(),
+                result,
+                self.game.elapsed().as_secs(),
+                self.game.hints_used(),
+                self.game.mistakes(),
+                self.game.move_times_ms(),
+                self.game.move_log(),
+                self.game.notes_used(),
+                self.game.seed(),
+                self.game.short_code(),
+                self.multiplayer(),
+            )
+            .clone();
+        self.report_load_warning();
+
+        // A team's solve isn't one player's, so it stays off the leaderboards
+        if record.multiplayer.is_some_and(|m| m.is_team()) {
+            return;
+        }
+
+        // Submit to ukodus API and the leaderboard (fire-and-forget background threads)
+        crate::telemetry::submit_result(&record, self.game.se_rating());
+        let submitted = self.stats.submit_to_remote(&record);
+
+        if result == GameResult::Win {
+            self.puzzle_rank.start(
+                self.stats.leaderboard_manager(),
+                &self.stats.player.player_name,
+                PuzzleFilter::from_record(&record),
+                submitted,
+            );
+        }
+    }
+
+    /// How the current game is shared with others, for its record
+    fn multiplayer(&self) -> Option<Multiplayer> {
+        if let Some(race) = self.race.as_ref().filter(|race| race.started) {
+            return Some(Multiplayer::Race {
+                players: race.racers.len(),
+                place: race.my_place(),
+            });
+        }
+        let coop = self.coop.as_ref().filter(|coop| coop.started)?;
+        Some(Multiplayer::Coop {
+            players: coop.team.len(),
+        })
+    }
+
+    /// The race or co-op lobby we're in
+    pub fn lobby(&self) -> Option<LobbyView> {
+        match (&self.race, &self.coop) {
+            (Some(race), _) => Some(race.lobby()),
+            (_, Some(coop)) => Some(coop.lobby()),
+            _ => None,
+        }
+    }
+
+    /// Wait in the lobby of a shared board we're hosting or joining
+    pub fn enter_coop(&mut self, coop: Coop) {
+        self.coop = Some(coop);
+        self.menu = MenuState::None;
+        self.screen_state = ScreenState::Lobby;
+    }
+
+    /// Stop playing with the team and go back to playing alone
+    fn leave_coop(&mut self) {
+        self.coop = None;
+        if self.screen_state == ScreenState::Lobby {
+            self.screen_state = ScreenState::Playing;
+        }
+    }
+
+    /// Send our moves and cursor to the team
+    fn send_coop(&mut self) {
+        if let Some(coop) = self.coop.as_mut() {
+            coop.send(&mut self.game, self.cursor);
+        }
+    }
+
+    /// Apply what the co-op host sent since the last tick
+    fn poll_coop(&mut self) {
+        let Some(events) = self.coop.as_mut().map(|coop| coop.poll(&mut self.game)) else {
+            return;
+        };
+        for event in events {
+            match event {
+                CoopEvent::Lobby => {}
+                CoopEvent::Started => {
+                    self.cursor = Position::new(4, 4);
+                    self.game_recorded = false;
+                    self.celebrations.reset();
+                    self.menu = MenuState::None;
+                    self.screen_state = ScreenState::Playing;
+                    self.show_message("Solve it together!");
+                }
+                CoopEvent::Joined(name) => self.show_message(&format!("{} joined", name)),
+                CoopEvent::Conflict => self.show_message("A teammate changed that cell first"),
+                CoopEvent::Over { won } => {
+                    if won {
+                        self.record_game(GameResult::Win);
+                        self.show_message("The team solved it!");
+                    } else {
+                        self.record_game(GameResult::Loss);
+                        self.show_message("The team is out of mistakes");
+                    }
+                }
+                CoopEvent::Left(name) => self.show_message(&format!("{} left", name)),
+                CoopEvent::Rejected(reason) => {
+                    self.leave_coop();
+                    self.show_message(&format!("Couldn't join the game: {}", reason));
+                }
+                CoopEvent::Disconnected => {
+                    // A board finished here still counts
+                    if self.game.is_completed() {
+                        self.record_game(GameResult::Win);
+                    }
+                    self.leave_coop();
+                    self.show_message("Lost connection to the team");
+                }
+            }
+        }
+    }
+
+    /// Wait in the lobby of a race we're hosting or joining
+    pub fn enter_race(&mut self, race: Race) {
+        self.race = Some(race);
+        self.menu = MenuState::None;
+        self.screen_state = ScreenState::Lobby;
+    }
+
+    /// Stop racing and go back to playing alone
+    fn leave_race(&mut self) {
+        self.race = None;
+        if self.screen_state == ScreenState::Lobby {
+            self.screen_state = ScreenState::Playing;
+        }
+    }
+
+    /// Apply what the race host sent since the last tick
+    fn poll_race(&mut self) {
+        let Some(events) = self.race.as_mut().map(Race::poll) else {
+            return;
+        };
+        for event in events {
+            match event {
+                RaceEvent::Lobby => {}
+                RaceEvent::Started(puzzle) => match Game::from_code(&puzzle) {
+                    Some(game) => {
+                        if let Some(race) = self.race.as_mut() {
+                            race.puzzle = Some(game.original_puzzle().to_string());
+                        }
+                        self.game = game;
+                        self.cursor = Position::new(4, 4);
+                        self.game_recorded = false;
+                        self.celebrations.reset();
+                        self.menu = MenuState::None;
+                        self.screen_state = ScreenState::Playing;
+                        self.show_message("Go! First to finish wins");
+                    }
+                    None => {
+                        self.leave_race();
+                        self.show_message("Couldn't load the race puzzle");
+                    }
+                },
+                RaceEvent::Placed { name, place, me } => {
+                    if me {
+                        self.record_game(GameResult::Win);
+                        self.show_message(&format!("You finished {}!", race::ordinal(place)));
+                    } else {
+                        self.show_message(&format!("{} finished {}", name, race::ordinal(place)));
+                    }
+                }
+                RaceEvent::NotFinished(reason) => {
+                    self.game.reopen();
+                    self.screen_state = ScreenState::Playing;
+                    self.show_message(&format!("Not finished: {}", reason));
+                }
+                RaceEvent::Out(name) => {
+                    self.show_message(&format!("{} is out of mistakes", name));
+                }
+                RaceEvent::Left(name) => self.show_message(&format!("{} left the race", name)),
+                RaceEvent::Rejected(reason) => {
+                    self.leave_race();
+                    self.show_message(&format!("Couldn't join the race: {}", reason));
+                }
+                RaceEvent::Disconnected => {
+                    // A finish the host never confirmed still counts here
+                    if self.race.as_ref().is_some_and(|race| race.awaiting_place) {
+                        self.record_game(GameResult::Win);
+                    }
+                    self.leave_race();
+                    self.show_message("Lost connection to the race");
+                }
+            }
+        }
+    }
+
+    fn handle_lobby_key(&mut self, key: KeyEvent) -> AppAction {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char(' ') => match (&mut self.race, &mut self.coop) {
+                (Some(race), _) if race.is_host() && race.racers.len() < 2 => {
+                    self.show_message("Waiting for someone to join");
+                }
+                (Some(race), _) if race.is_host() => race.start(),
+                // Teammates can join a game in progress
+                (_, Some(coop)) if coop.is_host() => coop.start(),
+                _ => self.show_message("The host starts the game"),
+            },
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.leave_race();
+                self.leave_coop();
+                self.show_message("Left the lobby");
+            }
+            _ => {}
+        }
+        AppAction::Continue
+    }
+
+    /// Switch to the leaderboard screen and refresh it
+    fn open_leaderboard(&mut self) {
+        self.leaderboard_back = ScreenState::Playing;
+        self.leaderboard.puzzle = None;
+        self.leaderboard.reset_paging();
+        self.screen_state = ScreenState::Leaderboard;
+        self.fetch_leaderboard(false);
+    }
+
+    /// Open one puzzle's leaderboard, returning to the current screen on Esc
+    fn open_puzzle_leaderboard(&mut self, puzzle: PuzzleFilter) {
+        self.leaderboard_back = self.screen_state;
+        self.leaderboard.difficulty = puzzle.difficulty;
+        self.leaderboard.puzzle = Some(puzzle);
+        self.leaderboard.reset_paging();
+        self.screen_state = ScreenState::Leaderboard;
+        self.fetch_leaderboard(true);
+    }
+
+    /// Refetch the leaderboard page in the background
+    fn fetch_leaderboard(&mut self, jump_to_me: bool) {
+        self.leaderboard.fetch(
+            self.stats.leaderboard_manager(),
+            &self.stats.player.player_name,
+            jump_to_me,
+        );
+    }
+
+    /// Stats for the active profile, under its name
+    fn load_profile_stats() -> StatsManager {
+        let mut stats = StatsManager::load();
+        let name = crate::profiles::active().name;
+        if stats.player.player_name != name {
+            stats.set_player_name(&name);
+        }
+        stats
+    }
+
+    /// Switch to the profile screen
+    pub fn open_profiles(&mut self) {
+        self.profiles = crate::profiles::registry().profiles;
+        let active = crate::profiles::active().id;
+        self.profile_selection = self
+            .profiles
+            .iter()
+            .position(|p| p.id == active)
+            .unwrap_or(0);
+        self.profile_edit = None;
+        self.screen_state = ScreenState::Profiles;
+    }
+
+    /// Make another profile active and reload everything stored per profile
+    fn switch_profile(&mut self, profile: &crate::profiles::Profile) {
+        if self.race.is_some() || self.coop.is_some() {
+            self.show_message("Leave the LAN game before switching profiles");
+            return;
+        }
+        // The game in progress belongs to the profile being left
+        let in_progress =
+            !self.game_recorded && !self.game.is_completed() && self.game.mistakes() < MAX_MISTAKES;
+        if in_progress {
+            if let Err(e) = fs::write(Self::save_path(), self.game.serialize()) {
+                self.show_message(&format!("Failed to save the current game: {}", e));
+                return;
+            }
+        }
+        if let Err(e) = crate::profiles::activate(profile) {
+            self.show_message(&e.to_string());
+            return;
+        }
+        let warnings = self.reload_config();
+        self.stats = Self::load_profile_stats();
+        // Pick up the new profile's saved game, or start a fresh one
+        let (difficulty, _) = default_difficulty(&self.config, &self.stats);
+        self.game = fs::read_to_string(Self::save_path())
+            .ok()
+            .and_then(|json| Game::deserialize(&json))
+            .unwrap_or_else(|| Game::new(difficulty));
+        self.cursor = Position::new(4, 4);
+        self.current_hint = None;
+        self.game_recorded = false;
+        self.celebrations.reset();
+        self.report_load_warning();
+        self.pending_uploads = crate::outbox::Outbox::new().pending_count();
+        self.leaderboard = LeaderboardView::new(self.leaderboard.difficulty);
+        self.puzzle_rank = PuzzleRank::default();
+        self.history_scroll = 0;
+        if crate::privacy::needs_prompt() {
+            self.menu = MenuState::TelemetryConsent;
+            self.menu_selection = 0;
+        }
+        crate::outbox::flush_in_background();
+        self.screen_state = ScreenState::Playing;
+        match warnings.first() {
+            Some(warning) => {
+                self.show_message(&format!("Playing as {}; {}", profile.name, warning))
+            }
+            None => self.show_message(&format!("Playing as {}", profile.name)),
+        }
+    }
+
+    /// Use the active profile's settings and keys. Accessibility turned on
+    /// from the command line stays on. Returns any problems with the file.
+    fn reload_config(&mut self) -> Vec<String> {
+        let (config, mut warnings) = Config::load();
+        let (keymap, keymap_warnings) = Keymap::new(&config.keys);
+        warnings.extend(keymap_warnings);
+        let entry = self.themes.iter().find(|t| t.id == config.theme).cloned();
+        self.theme = match entry.as_ref().map(ThemeEntry::load) {
+            Some(Ok(theme)) => theme,
+            Some(Err(e)) => {
+                warnings.push(e);
+                Theme::dark()
+            }
+            None => {
+                warnings.push(format!("Unknown theme '{}'", config.theme));
+                Theme::dark()
+            }
+        };
+        self.theme_watch = entry.filter(|e| e.path.is_some()).map(|e| {
+            let seen = modified(&e);
+            (e, seen)
+        });
+        let screen_reader = self.screen_reader && !self.config.screen_reader;
+        let reduced_motion = self.reduced_motion && !self.config.reduced_motion;
+        self.keymap = keymap;
+        self.mode = config.input_mode;
+        self.show_suggestions = config.show_suggestions;
+        self.show_naked_singles = config.show_naked_singles;
+        self.set_accessibility(
+            config.screen_reader || screen_reader,
+            config.reduced_motion || reduced_motion,
         );
+        self.config = config;
+        warnings
+    }
 
-        // Submit to ukodus API (fire-and-forget background thread)
-        crate::telemetry::submit_result(record, self.game.se_rating());
+    /// Switch to the stats screen
+    fn open_stats(&mut self) {
+        self.pending_uploads = crate::outbox::Outbox::new().pending_count();
+        self.stats_difficulty = self.game.difficulty();
+        self.screen_state = ScreenState::Stats;
     }
 
     /// Show a temporary message
     pub fn show_message(&mut self, msg: &str) {
         self.message = Some(msg.to_string());
         self.message_timer = 30; // ~3 seconds at 100ms poll
+        self.message_seq = self.message_seq.wrapping_add(1);
+    }
+
+    /// Show any problem found while loading or saving stats, for longer than usual
+    fn report_load_warning(&mut self) {
+        if let Some(warning) = self.stats.load_warning.take() {
+            self.show_message(&warning);
+            self.message_timer = 100;
+        }
     }
 
     /// Handle a key press
     pub fn handle_key(&mut self, key: KeyEvent) -> AppAction {
+        let action = self.dispatch_key(key);
+        self.send_coop();
+        action
+    }
+
+    fn dispatch_key(&mut self, key: KeyEvent) -> AppAction {
         // Handle based on screen state
         match self.screen_state {
             ScreenState::Win | ScreenState::Lose => self.handle_endgame_key(key),
             ScreenState::Stats => self.handle_stats_key(key),
             ScreenState::Leaderboard => self.handle_leaderboard_key(key),
             ScreenState::History => self.handle_history_key(key),
+            ScreenState::Profiles => self.handle_profiles_key(key),
+            ScreenState::Achievements => self.handle_achievements_key(key),
+            ScreenState::Settings => self.handle_settings_key(key),
+            ScreenState::Lobby => self.handle_lobby_key(key),
             ScreenState::Playing => {
                 // Clear hint on any key
                 if self.current_hint.is_some() {
@@ -307,10 +903,95 @@ impl App {
                     MenuState::NewGame
                     | MenuState::Difficulty
                     | MenuState::Theme
-                    | MenuState::Confirm => self.handle_menu_key(key),
+                    | MenuState::Confirm
+                    | MenuState::TelemetryConsent => self.handle_menu_key(key),
+                }
+            }
+        }
+    }
+
+    /// Handle a mouse event using the regions drawn last frame. Right-click
+    /// or a modifier-click on the number pad toggles a note, and the wheel
+    /// scrolls lists and menus.
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> AppAction {
+        if self.screen_reader {
+            return AppAction::Continue;
+        }
+        match event.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let code = if event.kind == MouseEventKind::ScrollUp {
+                    KeyCode::Up
+                } else {
+                    KeyCode::Down
+                };
+                let key = KeyEvent::new(code, KeyModifiers::NONE);
+                match self.screen_state {
+                    ScreenState::History
+                    | ScreenState::Leaderboard
+                    | ScreenState::Achievements
+                    | ScreenState::Settings => self.handle_key(key),
+                    ScreenState::Playing if self.menu != MenuState::None => {
+                        self.handle_menu_key(key)
+                    }
+                    _ => AppAction::Continue,
                 }
             }
+            MouseEventKind::Down(button) => {
+                let Some(target) = self.hit_map.at(event.column, event.row) else {
+                    return AppAction::Continue;
+                };
+                let alternate = button == MouseButton::Right
+                    || event.modifiers.intersects(
+                        KeyModifiers::SHIFT | KeyModifiers::CONTROL | KeyModifiers::ALT,
+                    );
+                self.click(target, alternate)
+            }
+            _ => AppAction::Continue,
+        }
+    }
+
+    fn click(&mut self, target: Target, alternate: bool) -> AppAction {
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        match (self.screen_state, target) {
+            // A click selects a row; clicking it again changes it like Enter
+            // (right-click steps a setting back)
+            (ScreenState::Settings, Target::SettingsRow(row)) => {
+                if self.settings_capture.is_some() {
+                    return AppAction::Continue;
+                }
+                if row != self.settings_selection {
+                    self.settings_selection = row;
+                } else if alternate && row < SETTINGS.len() {
+                    self.change_setting(row, false);
+                } else {
+                    return self.handle_settings_key(enter);
+                }
+            }
+            (ScreenState::Playing, Target::MenuItem(i)) if self.menu != MenuState::None => {
+                self.menu_selection = i;
+                return self.handle_menu_key(enter);
+            }
+            // The board is inert while a menu is open
+            (ScreenState::Playing, _) if self.menu != MenuState::None => {}
+            (ScreenState::Playing, target) => {
+                self.current_hint = None;
+                match target {
+                    Target::Cell(pos) => self.cursor = pos,
+                    Target::Digit(value) => {
+                        let modifiers = if alternate {
+                            KeyModifiers::SHIFT
+                        } else {
+                            KeyModifiers::NONE
+                        };
+                        self.enter_digit(value, modifiers);
+                    }
+                    Target::Action(action) => return self.perform(action),
+                    Target::MenuItem(_) | Target::SettingsRow(_) => {}
+                }
+            }
+            _ => {}
         }
+        AppAction::Continue
     }
 
     fn handle_endgame_key(&mut self, key: KeyEvent) -> AppAction {
@@ -376,8 +1057,7 @@ impl App {
             KeyCode::Char('n') => {
                 // Start new game - go to difficulty selection menu
                 self.screen_state = ScreenState::Playing;
-                self.menu = MenuState::NewGame;
-                self.menu_selection = 0;
+                self.open_new_game_menu();
             }
             KeyCode::Enter | KeyCode::Char(' ') => {
                 // Quick restart with same difficulty
@@ -392,15 +1072,40 @@ impl App {
                 // Go back to the (finished) game view
                 self.screen_state = ScreenState::Playing;
             }
+            // This puzzle's leaderboard
+            KeyCode::Char('p') if self.screen_state == ScreenState::Win => {
+                if let Some(puzzle) = self.puzzle_rank.puzzle.clone() {
+                    self.open_puzzle_leaderboard(puzzle);
+                }
+            }
             _ => {}
         }
         AppAction::Continue
     }
 
     fn handle_game_key(&mut self, key: KeyEvent) -> AppAction {
-        match key.code {
+        // Digits always enter values; everything else goes through the keymap
+        if let KeyCode::Char(c @ '1'..='9') = key.code {
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            {
+                self.enter_digit(c.to_digit(10).unwrap() as u8, key.modifiers);
+                return AppAction::Continue;
+            }
+        }
+
+        match self.keymap.action(&key) {
+            Some(action) => self.perform(action),
+            None => AppAction::Continue,
+        }
+    }
+
+    /// Do what a bound key or clicked control asks for during play
+    fn perform(&mut self, action: Action) -> AppAction {
+        match action {
             // Quit - record abandoned game if in progress
-            KeyCode::Char('q') => {
+            Action::Quit => {
                 if !self.game.is_completed() && self.game.moves_count() > 0 {
                     self.record_game(GameResult::Abandoned);
                 }
@@ -408,40 +1113,19 @@ impl App {
             }
 
             // Navigation
-            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
-            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
-            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
-            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+            Action::Up => self.move_cursor(-1, 0),
+            Action::Down => self.move_cursor(1, 0),
+            Action::Left => self.move_cursor(0, -1),
+            Action::Right => self.move_cursor(0, 1),
 
             // Jump to box
-            KeyCode::Char('w') => self.jump_box(-1, 0),
-            KeyCode::Char('s') => self.jump_box(1, 0),
-            KeyCode::Char('a') => self.jump_box(0, -1),
-            KeyCode::Char('d') => self.jump_box(0, 1),
-
-            // Number input
-            KeyCode::Char(c @ '1'..='9') => {
-                let value = c.to_digit(10).unwrap() as u8;
-                if key.modifiers.contains(KeyModifiers::SHIFT) || self.mode == InputMode::Candidate
-                {
-                    self.game.toggle_candidate(self.cursor, value);
-                } else {
-                    let correct = self.game.set_value(self.cursor, value);
-                    if !correct {
-                        let remaining = MAX_MISTAKES.saturating_sub(self.game.mistakes());
-                        if remaining > 0 {
-                            self.show_message(&format!(
-                                "Incorrect! {} {} left",
-                                remaining,
-                                if remaining == 1 { "chance" } else { "chances" }
-                            ));
-                        }
-                    }
-                }
-            }
+            Action::BoxUp => self.jump_box(-1, 0),
+            Action::BoxDown => self.jump_box(1, 0),
+            Action::BoxLeft => self.jump_box(0, -1),
+            Action::BoxRight => self.jump_box(0, 1),
 
             // Clear cell (value and notes)
-            KeyCode::Char('0') | KeyCode::Delete | KeyCode::Backspace => {
+            Action::Clear => {
                 if self.mode == InputMode::Candidate {
                     // In candidate mode, clear all candidates
                     if self.game.clear_candidates(self.cursor) {
@@ -453,32 +1137,32 @@ impl App {
                 }
             }
 
-            // Clear notes from cell (x) or all cells (Shift+X)
-            KeyCode::Char('x') => {
+            // Clear notes from cell or all cells
+            Action::ClearNotes => {
                 if self.game.clear_candidates(self.cursor) {
                     self.show_message("Cleared notes");
                 }
             }
-            KeyCode::Char('X') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            Action::ClearAllNotes => {
                 if self.game.clear_all_candidates() {
                     self.show_message("Cleared all notes");
                 }
             }
 
-            // Fill cell with valid candidates (f) or all cells (Shift+F)
-            KeyCode::Char('f') => {
+            // Fill cell or all cells with valid candidates
+            Action::FillNotes => {
                 if self.game.fill_candidates(self.cursor) {
                     self.show_message("Filled valid notes");
                 }
             }
-            KeyCode::Char('F') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            Action::FillAllNotes => {
                 if self.game.fill_all_candidates() {
                     self.show_message("Filled all notes");
                 }
             }
 
             // Mode toggle
-            KeyCode::Char('c') => {
+            Action::ToggleMode => {
                 self.mode = match self.mode {
                     InputMode::Normal => InputMode::Candidate,
                     InputMode::Candidate => InputMode::Normal,
@@ -493,19 +1177,19 @@ impl App {
             }
 
             // Undo/Redo
-            KeyCode::Char('u') => {
+            Action::Undo => {
                 if self.game.undo() {
                     self.show_message("Undo");
                 }
             }
-            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            Action::Redo => {
                 if self.game.redo() {
                     self.show_message("Redo");
                 }
             }
 
             // Hint
-            KeyCode::Char('?') => {
+            Action::Hint => {
                 if let Some(hint) = self.game.get_hint() {
                     self.current_hint = Some(hint);
                 } else {
@@ -514,7 +1198,7 @@ impl App {
             }
 
             // Apply hint
-            KeyCode::Char('!') => {
+            Action::ApplyHint => {
                 if let Some(pos) = self.game.apply_hint() {
                     self.cursor = pos;
                     self.show_message("Hint applied");
@@ -522,13 +1206,13 @@ impl App {
             }
 
             // New game menu
-            KeyCode::Char('n') => {
-                self.menu = MenuState::NewGame;
-                self.menu_selection = 0;
-            }
+            Action::NewGame => self.open_new_game_menu(),
 
             // Pause
-            KeyCode::Char('p') => {
+            Action::Pause if self.coop.is_some() => {
+                self.show_message("The team's clock can't be paused");
+            }
+            Action::Pause => {
                 self.game.toggle_pause();
                 if self.game.is_paused() {
                     self.show_message("Paused");
@@ -537,57 +1221,172 @@ impl App {
                 }
             }
 
-            // Theme toggle
-            KeyCode::Char('t') => {
+            // Theme menu
+            Action::Theme => {
+                self.themes = Theme::installed();
                 self.menu = MenuState::Theme;
-                self.menu_selection = 0;
+                self.menu_selection = self
+                    .themes
+                    .iter()
+                    .position(|t| t.id == self.config.theme)
+                    .unwrap_or(0);
             }
 
             // Toggle suggestions
-            KeyCode::Char('v') => {
+            Action::ToggleSuggestions => {
                 self.show_suggestions = !self.show_suggestions;
+                self.config.show_suggestions = self.show_suggestions;
+                self.save_config();
                 let state = if self.show_suggestions { "on" } else { "off" };
                 self.show_message(&format!("Suggestions {}", state));
             }
 
             // Toggle naked singles display (shows answer for cells with only one candidate)
-            KeyCode::Char('g') => {
+            Action::ToggleNakedSingles => {
                 self.show_naked_singles = !self.show_naked_singles;
+                self.config.show_naked_singles = self.show_naked_singles;
+                self.save_config();
                 let state = if self.show_naked_singles { "on" } else { "off" };
                 self.show_message(&format!("Auto-fill hints {}", state));
             }
 
             // Save + Share
-            KeyCode::Char('S') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            Action::SaveShare => {
                 self.save_game();
                 self.share_puzzle();
             }
 
-            // Load
-            KeyCode::Char('L') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                self.load_game();
+            Action::Load => self.load_game(),
+            Action::Stats => self.open_stats(),
+            Action::Leaderboard => self.open_leaderboard(),
+
+            // History/Replay
+            Action::History => {
+                self.history_scroll = 0;
+                self.screen_state = ScreenState::History;
             }
 
-            // Stats screen
-            KeyCode::Char('i') => {
-                self.screen_state = ScreenState::Stats;
+            Action::Profiles => self.open_profiles(),
+
+            // Read aloud (shown in the message bar outside screen reader mode)
+            Action::ReadCell => {
+                self.show_message(&accessible::describe_cell(&self.game, self.cursor))
+            }
+            Action::ReadRow => {
+                self.show_message(&accessible::describe_row(&self.game, self.cursor.row))
+            }
+            Action::ReadColumn => {
+                self.show_message(&accessible::describe_column(&self.game, self.cursor.col))
             }
+            Action::ReadBox => self.show_message(&accessible::describe_box(
+                &self.game,
+                self.cursor.box_index(),
+            )),
+            Action::ReadStatus => self.show_message(&accessible::describe_status(self)),
+
+            Action::Settings => {
+                self.settings_selection = 0;
+                self.settings_capture = None;
+                self.screen_state = ScreenState::Settings;
+            }
+        }
 
-            // Leaderboard
-            KeyCode::Char('b') => {
-                self.screen_state = ScreenState::Leaderboard;
+        AppAction::Continue
+    }
+
+    /// Set a value, or toggle a note with Shift or in candidate mode
+    fn enter_digit(&mut self, value: u8, modifiers: KeyModifiers) {
+        if modifiers.contains(KeyModifiers::SHIFT) || self.mode == InputMode::Candidate {
+            self.game.toggle_candidate(self.cursor, value);
+        } else {
+            let correct = self.game.set_value(self.cursor, value);
+            if !correct {
+                let remaining = MAX_MISTAKES.saturating_sub(self.game.mistakes());
+                if remaining > 0 {
+                    self.show_message(&format!(
+                        "Incorrect! {} {} left",
+                        remaining,
+                        if remaining == 1 { "chance" } else { "chances" }
+                    ));
+                }
             }
+        }
+    }
 
-            // History/Replay
-            KeyCode::Char('H') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                self.history_scroll = 0;
-                self.screen_state = ScreenState::History;
+    /// Open the difficulty menu with the default difficulty selected
+    fn open_new_game_menu(&mut self) {
+        self.menu = MenuState::NewGame;
+        let max = if self.stats.secrets_unlocked() { 7 } else { 5 };
+        self.menu_selection = DIFFICULTIES
+            .iter()
+            .position(|&d| d == self.config.default_difficulty)
+            .unwrap_or(2)
+            .min(max);
+    }
+
+    /// Write the config file, reporting failures in the message bar
+    fn save_config(&mut self) {
+        if let Err(e) = self.config.save() {
+            self.show_message(&format!("Cannot save settings: {}", e));
+        }
+    }
+
+    /// Show the theme with this id without saving it. Returns false, with
+    /// the reason in the message bar, if it can't be loaded.
+    fn activate_theme(&mut self, id: &str) -> bool {
+        let Some(entry) = self.themes.iter().find(|t| t.id == id).cloned() else {
+            self.show_message(&format!("Unknown theme '{}'", id));
+            return false;
+        };
+        match entry.load() {
+            Ok(theme) => {
+                self.theme = theme;
+                self.theme_watch = entry.path.is_some().then(|| {
+                    let seen = modified(&entry);
+                    (entry, seen)
+                });
+                true
+            }
+            Err(e) => {
+                self.show_message(&e);
+                false
             }
+        }
+    }
 
-            _ => {}
+    /// Switch theme and remember it
+    fn set_theme(&mut self, id: &str) -> bool {
+        if !self.activate_theme(id) {
+            return false;
         }
+        self.config.theme = id.to_string();
+        self.save_config();
+        true
+    }
 
-        AppAction::Continue
+    /// Re-read the theme file about once a second so edits show up live.
+    /// A broken edit keeps the last good colors.
+    fn reload_theme_if_changed(&mut self) {
+        if self.theme_checked.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        self.theme_checked = Instant::now();
+        let Some((entry, seen)) = &mut self.theme_watch else {
+            return;
+        };
+        let now = modified(entry);
+        if now == *seen {
+            return;
+        }
+        *seen = now;
+        match entry.load() {
+            Ok(theme) => {
+                let label = entry.label.clone();
+                self.theme = theme;
+                self.show_message(&format!("Reloaded theme {}", label));
+            }
+            Err(e) => self.show_message(&e),
+        }
     }
 
     fn handle_menu_key(&mut self, key: KeyEvent) -> AppAction {
@@ -610,6 +1409,11 @@ impl App {
 
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
+                // Leaving the theme menu undoes the preview
+                if self.menu == MenuState::Theme {
+                    let id = self.config.theme.clone();
+                    self.activate_theme(&id);
+                }
                 self.menu = MenuState::None;
                 self.konami_progress = 0;
             }
@@ -617,6 +1421,7 @@ impl App {
             KeyCode::Up | KeyCode::Char('k') => {
                 if self.menu_selection > 0 {
                     self.menu_selection -= 1;
+                    self.preview_theme();
                 }
             }
 
@@ -629,12 +1434,13 @@ impl App {
                             5 // Standard 6 difficulties (0-5)
                         }
                     }
-                    MenuState::Theme => 2,
-                    MenuState::Confirm => 1,
+                    MenuState::Theme => self.themes.len().saturating_sub(1),
+                    MenuState::Confirm | MenuState::TelemetryConsent => 1,
                     MenuState::None => 0,
                 };
                 if self.menu_selection < max {
                     self.menu_selection += 1;
+                    self.preview_theme();
                 }
             }
 
@@ -651,12 +1457,12 @@ impl App {
                         self.menu = MenuState::None;
                     }
                     MenuState::Theme => {
-                        self.theme = match self.menu_selection {
-                            0 => Theme::dark(),
-                            1 => Theme::light(),
-                            _ => Theme::high_contrast(),
-                        };
-                        self.menu = MenuState::None;
+                        if let Some(entry) = self.themes.get(self.menu_selection) {
+                            let id = entry.id.clone();
+                            if self.set_theme(&id) {
+                                self.menu = MenuState::None;
+                            }
+                        }
                     }
                     MenuState::Confirm => {
                         if self.menu_selection == 0 {
@@ -664,6 +1470,18 @@ impl App {
                         }
                         self.menu = MenuState::None;
                     }
+                    MenuState::TelemetryConsent => {
+                        // Option 0 keeps results private, so Enter alone never opts in
+                        let share = self.menu_selection == 1;
+                        crate::privacy::set_consent(share);
+                        self.menu = MenuState::None;
+                        self.menu_selection = 0;
+                        self.show_message(if share {
+                            "Results will be shared with ukodus.now"
+                        } else {
+                            "Results stay on this device"
+                        });
+                    }
                     MenuState::None => {}
                 }
             }
@@ -674,29 +1492,230 @@ impl App {
         AppAction::Continue
     }
 
+    /// Show the theme under the cursor in the theme menu
+    fn preview_theme(&mut self) {
+        if self.menu != MenuState::Theme {
+            return;
+        }
+        if let Some(entry) = self.themes.get(self.menu_selection) {
+            let id = entry.id.clone();
+            self.activate_theme(&id);
+        }
+    }
+
+    /// Heading of the open menu
+    pub fn menu_title(&self) -> &'static str {
+        match self.menu {
+            MenuState::NewGame | MenuState::Difficulty => "Select Difficulty",
+            MenuState::Theme => "Select Theme",
+            MenuState::Confirm => "Confirm",
+            MenuState::TelemetryConsent => "Share game results?",
+            MenuState::None => "",
+        }
+    }
+
+    /// Choices in the open menu, in order
+    pub fn menu_options(&self) -> Vec<String> {
+        match self.menu {
+            MenuState::NewGame | MenuState::Difficulty => {
+                let count = if self.stats.secrets_unlocked() { 8 } else { 6 };
+                DIFFICULTIES[..count]
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect()
+            }
+            MenuState::Theme => self.themes.iter().map(|t| t.label.clone()).collect(),
+            MenuState::Confirm => vec!["Yes".into(), "No".into()],
+            MenuState::TelemetryConsent => {
+                vec!["No, keep private".into(), "Yes, share results".into()]
+            }
+            MenuState::None => Vec::new(),
+        }
+    }
+
     /// Get difficulty from current menu selection
     fn get_difficulty_from_selection(&self) -> Difficulty {
-        if self.stats.secrets_unlocked() {
-            match self.menu_selection {
-                0 => Difficulty::Beginner,
-                1 => Difficulty::Easy,
-                2 => Difficulty::Medium,
-                3 => Difficulty::Intermediate,
-                4 => Difficulty::Hard,
-                5 => Difficulty::Expert,
-                6 => Difficulty::Master,
-                _ => Difficulty::Extreme,
+        let max = if self.stats.secrets_unlocked() { 7 } else { 5 };
+        DIFFICULTIES[self.menu_selection.min(max)]
+    }
+
+    /// Current value of each row in `SETTINGS`
+    pub fn setting_values(&self) -> [String; 8] {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_string();
+        let theme = self
+            .themes
+            .iter()
+            .find(|t| t.id == self.config.theme)
+            .map_or_else(|| self.config.theme.clone(), |t| t.label.clone());
+        [
+            theme,
+            on_off(self.config.show_suggestions),
+            on_off(self.config.show_naked_singles),
+            format!("{:?}", self.config.default_difficulty),
+            match self.config.input_mode {
+                InputMode::Candidate => "Notes mode",
+                _ => "Normal mode",
             }
-        } else {
-            match self.menu_selection {
-                0 => Difficulty::Beginner,
-                1 => Difficulty::Easy,
-                2 => Difficulty::Medium,
-                3 => Difficulty::Intermediate,
-                4 => Difficulty::Hard,
-                _ => Difficulty::Expert,
+            .to_string(),
+            on_off(self.config.screen_reader),
+            on_off(self.config.reduced_motion),
+            self.config.layout.name().to_string(),
+        ]
+    }
+
+    /// Step the setting in row `row` of `SETTINGS` forward or back, apply it
+    /// and save the config
+    fn change_setting(&mut self, row: usize, forward: bool) {
+        let step = |i: usize, len: usize| {
+            if forward {
+                (i + 1) % len
+            } else {
+                (i + len - 1) % len
+            }
+        };
+        match row {
+            0 => {
+                // Skip over theme files that fail to load
+                self.themes = Theme::installed();
+                let len = self.themes.len();
+                let mut i = self.themes.iter().position(|t| t.id == self.config.theme);
+                for _ in 0..len {
+                    let next = i.map_or(0, |i| step(i, len));
+                    let id = self.themes[next].id.clone();
+                    if self.set_theme(&id) {
+                        break;
+                    }
+                    i = Some(next);
+                }
+                return;
+            }
+            1 => {
+                self.config.show_suggestions = !self.config.show_suggestions;
+                self.show_suggestions = self.config.show_suggestions;
+            }
+            2 => {
+                self.config.show_naked_singles = !self.config.show_naked_singles;
+                self.show_naked_singles = self.config.show_naked_singles;
+            }
+            3 => {
+                let count = if self.stats.secrets_unlocked() { 8 } else { 6 };
+                let current = DIFFICULTIES[..count]
+                    .iter()
+                    .position(|&d| d == self.config.default_difficulty);
+                self.config.default_difficulty =
+                    DIFFICULTIES[current.map_or(2, |i| step(i, count))];
+            }
+            4 => {
+                self.config.input_mode = match self.config.input_mode {
+                    InputMode::Candidate => InputMode::Normal,
+                    _ => InputMode::Candidate,
+                };
+                self.mode = self.config.input_mode;
+            }
+            5 => {
+                self.config.screen_reader = !self.config.screen_reader;
+                self.set_accessibility(self.config.screen_reader, self.config.reduced_motion);
+            }
+            6 => {
+                self.config.reduced_motion = !self.config.reduced_motion;
+                self.set_accessibility(self.screen_reader, self.config.reduced_motion);
+            }
+            7 => {
+                let current = Layout::ALL.iter().position(|&l| l == self.config.layout);
+                self.config.layout = Layout::ALL[current.map_or(0, |i| step(i, Layout::ALL.len()))];
             }
+            _ => return,
         }
+        self.save_config();
+    }
+
+    /// Rebuild the keymap after `config.keys` changed, reporting conflicts
+    fn rebuild_keymap(&mut self) {
+        let (keymap, warnings) = Keymap::new(&self.config.keys);
+        self.keymap = keymap;
+        self.save_config();
+        if let Some(warning) = warnings.first() {
+            self.show_message(warning);
+        }
+    }
+
+    /// Bind the key just pressed to the action selected on the settings screen
+    fn capture_key(&mut self, capture: KeyCapture, key: KeyEvent) {
+        let Some(&action) = Action::ALL.get(self.settings_selection.wrapping_sub(SETTINGS.len()))
+        else {
+            return;
+        };
+        let binding = KeyBinding::from_event(&key);
+        if binding.is_reserved() {
+            self.show_message("Digits 1-9 always enter values");
+            return;
+        }
+        if let Some(other) = self.keymap.action(&key) {
+            if other != action {
+                self.show_message(&format!("{} is already used by {}", binding, other.label()));
+            }
+            return;
+        }
+        let mut keys = match capture {
+            KeyCapture::Replace => Vec::new(),
+            KeyCapture::Add => self.keymap.keys(action),
+        };
+        keys.push(binding);
+        self.config.bind(action, &keys);
+        self.rebuild_keymap();
+        self.show_message(&format!(
+            "{}: {}",
+            action.label(),
+            self.keymap.label(action)
+        ));
+    }
+
+    fn handle_settings_key(&mut self, key: KeyEvent) -> AppAction {
+        if let Some(capture) = self.settings_capture.take() {
+            if key.code != KeyCode::Esc {
+                self.capture_key(capture, key);
+            }
+            return AppAction::Continue;
+        }
+
+        let rows = SETTINGS.len() + Action::ALL.len();
+        let row = self.settings_selection;
+        let action = row.checked_sub(SETTINGS.len()).map(|i| Action::ALL[i]);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.screen_state = ScreenState::Playing;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.settings_selection = row.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.settings_selection = (row + 1).min(rows - 1);
+            }
+            KeyCode::Left | KeyCode::Char('h') if action.is_none() => {
+                self.change_setting(row, false);
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter | KeyCode::Char(' ')
+                if action.is_none() =>
+            {
+                self.change_setting(row, true);
+            }
+            KeyCode::Enter => self.settings_capture = Some(KeyCapture::Replace),
+            KeyCode::Char('a') if action.is_some() => {
+                self.settings_capture = Some(KeyCapture::Add);
+            }
+            KeyCode::Delete | KeyCode::Backspace => {
+                if let Some(action) = action {
+                    self.show_message(&format!("{}: default keys", action.label()));
+                    let defaults = action.default_keys().iter().map(|k| k.to_string());
+                    self.config
+                        .keys
+                        .insert(action.name().to_string(), defaults.collect());
+                    self.rebuild_keymap();
+                }
+            }
+            _ => {}
+        }
+        AppAction::Continue
     }
 
     fn handle_stats_key(&mut self, key: KeyEvent) -> AppAction {
@@ -705,12 +1724,51 @@ impl App {
                 self.screen_state = ScreenState::Playing;
             }
             KeyCode::Char('b') => {
-                self.screen_state = ScreenState::Leaderboard;
+                self.open_leaderboard();
+            }
+            KeyCode::Char('a') => {
+                self.achievement_selection = 0;
+                self.screen_state = ScreenState::Achievements;
             }
             KeyCode::Char('H') if key.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.history_scroll = 0;
                 self.screen_state = ScreenState::History;
             }
+            KeyCode::Char('P') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.open_profiles();
+            }
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.stats_view = self.stats_view.next();
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.stats_view = self.stats_view.prev();
+            }
+            KeyCode::Char(c @ '1'..='6') => {
+                self.stats_view = TrendView::ALL[c as usize - '1' as usize];
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.stats_view.uses_difficulty() => {
+                self.stats_difficulty = self.prev_difficulty(self.stats_difficulty);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.stats_view.uses_difficulty() => {
+                self.stats_difficulty = self.next_difficulty(self.stats_difficulty);
+            }
+            _ => {}
+        }
+        AppAction::Continue
+    }
+
+    fn handle_achievements_key(&mut self, key: KeyEvent) -> AppAction {
+        let count = sudoku_shared::achievements::ACHIEVEMENTS.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.screen_state = ScreenState::Stats;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.achievement_selection = self.achievement_selection.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.achievement_selection = (self.achievement_selection + 1).min(count - 1);
+            }
             _ => {}
         }
         AppAction::Continue
@@ -719,17 +1777,65 @@ impl App {
     fn handle_leaderboard_key(&mut self, key: KeyEvent) -> AppAction {
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => {
-                self.screen_state = ScreenState::Playing;
+                self.leaderboard.puzzle = None;
+                self.screen_state = self.leaderboard_back;
             }
             KeyCode::Char('i') => {
-                self.screen_state = ScreenState::Stats;
+                self.open_stats();
             }
             // Change difficulty filter
+            // Leaving a puzzle board goes back to its difficulty
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l')
+                if self.leaderboard.puzzle.is_some() =>
+            {
+                self.leaderboard.puzzle = None;
+                self.leaderboard.reset_paging();
+                self.fetch_leaderboard(false);
+            }
             KeyCode::Left | KeyCode::Char('h') => {
-                self.leaderboard_difficulty = self.prev_difficulty(self.leaderboard_difficulty);
+                self.leaderboard.difficulty = self.prev_difficulty(self.leaderboard.difficulty);
+                self.leaderboard.reset_paging();
+                self.fetch_leaderboard(false);
             }
             KeyCode::Right | KeyCode::Char('l') => {
-                self.leaderboard_difficulty = self.next_difficulty(self.leaderboard_difficulty);
+                self.leaderboard.difficulty = self.next_difficulty(self.leaderboard.difficulty);
+                self.leaderboard.reset_paging();
+                self.fetch_leaderboard(false);
+            }
+            // Time window: all time / this week / today
+            KeyCode::Char('t') => {
+                self.leaderboard.period = self.leaderboard.period.next();
+                self.leaderboard.reset_paging();
+                self.fetch_leaderboard(false);
+            }
+            KeyCode::Char('v') => {
+                self.leaderboard.verified_only = !self.leaderboard.verified_only;
+                self.leaderboard.reset_paging();
+                self.fetch_leaderboard(false);
+            }
+            // Paging
+            KeyCode::Down | KeyCode::PageDown | KeyCode::Char('n') | KeyCode::Char('j') => {
+                if self.leaderboard.next_page() {
+                    self.fetch_leaderboard(false);
+                }
+            }
+            KeyCode::Up | KeyCode::PageUp | KeyCode::Char('p') | KeyCode::Char('k') => {
+                if self.leaderboard.prev_page() {
+                    self.fetch_leaderboard(false);
+                }
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                if self.leaderboard.offset > 0 {
+                    self.leaderboard.reset_paging();
+                    self.fetch_leaderboard(false);
+                }
+            }
+            // Jump to the page with the player's own rank
+            KeyCode::Char('m') => {
+                self.fetch_leaderboard(true);
+            }
+            KeyCode::Char('r') => {
+                self.fetch_leaderboard(false);
             }
             _ => {}
         }
@@ -789,13 +1895,118 @@ impl App {
                 }
             }
             KeyCode::Char('i') => {
-                self.screen_state = ScreenState::Stats;
+                self.open_stats();
+            }
+            // Leaderboard for the selected game's puzzle
+            KeyCode::Char('p') => {
+                if let Some(record) = self.stats.history.get(self.history_scroll) {
+                    let puzzle = PuzzleFilter::from_record(record);
+                    self.open_puzzle_leaderboard(puzzle);
+                }
+            }
+            _ => {}
+        }
+        AppAction::Continue
+    }
+
+    fn handle_profiles_key(&mut self, key: KeyEvent) -> AppAction {
+        if let Some(edit) = self.profile_edit.take() {
+            self.handle_profile_edit_key(edit, key);
+            return AppAction::Continue;
+        }
+
+        let selected = self.profiles.get(self.profile_selection).cloned();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.screen_state = ScreenState::Playing;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.profile_selection = self.profile_selection.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let max = self.profiles.len().saturating_sub(1);
+                self.profile_selection = (self.profile_selection + 1).min(max);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(profile) = selected {
+                    self.switch_profile(&profile);
+                }
+            }
+            KeyCode::Char('n') => {
+                self.profile_edit = Some(ProfileEdit::Create(String::new()));
+            }
+            KeyCode::Char('r') => {
+                if let Some(profile) = selected {
+                    self.profile_edit = Some(ProfileEdit::Rename {
+                        id: profile.id,
+                        name: profile.name,
+                    });
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(profile) = selected {
+                    self.profile_edit = Some(ProfileEdit::ConfirmDelete(profile.id));
+                }
             }
             _ => {}
         }
         AppAction::Continue
     }
 
+    /// Keys while typing a profile name or confirming a deletion.
+    /// `edit` has been taken out of `self.profile_edit`; put it back to keep editing.
+    fn handle_profile_edit_key(&mut self, edit: ProfileEdit, key: KeyEvent) {
+        let result = match (edit, key.code) {
+            (_, KeyCode::Esc) => return,
+            (ProfileEdit::ConfirmDelete(id), KeyCode::Char('y')) => {
+                crate::profiles::delete(&id).map(|p| format!("Deleted profile {}", p.name))
+            }
+            (ProfileEdit::ConfirmDelete(_), _) => return,
+            (ProfileEdit::Create(name), KeyCode::Enter) => {
+                crate::profiles::create(&name).map(|p| format!("Created profile {}", p.name))
+            }
+            (ProfileEdit::Rename { id, name }, KeyCode::Enter) => {
+                crate::profiles::rename(&id, &name).map(|p| {
+                    if crate::profiles::active().id == p.id {
+                        self.stats.set_player_name(&p.name);
+                    }
+                    format!("Renamed to {}", p.name)
+                })
+            }
+            (mut edit, code) => {
+                let buffer = match edit {
+                    ProfileEdit::Create(ref mut name)
+                    | ProfileEdit::Rename { ref mut name, .. } => name,
+                    ProfileEdit::ConfirmDelete(_) => unreachable!("handled above"),
+                };
+                match code {
+                    KeyCode::Backspace => {
+                        buffer.pop();
+                    }
+                    KeyCode::Char(c)
+                        if !c.is_control()
+                            && buffer.chars().count() < crate::profiles::MAX_NAME_LEN =>
+                    {
+                        buffer.push(c);
+                    }
+                    _ => {}
+                }
+                self.profile_edit = Some(edit);
+                return;
+            }
+        };
+
+        match result {
+            Ok(msg) => {
+                self.show_message(&msg);
+                let selected = self.profile_selection;
+                self.open_profiles();
+                self.profile_selection = selected.min(self.profiles.len().saturating_sub(1));
+            }
+            Err(e) => self.show_message(&e.to_string()),
+        }
+    }
+
     fn move_cursor(&mut self, row_delta: i32, col_delta: i32) {
         let new_row = (self.cursor.row as i32 + row_delta).clamp(0, 8) as usize;
         let new_col = (self.cursor.col as i32 + col_delta).clamp(0, 8) as usize;
@@ -813,11 +2024,16 @@ impl App {
         self.cursor = Position::new(new_box_row * 3 + 1, new_box_col * 3 + 1);
     }
 
-    /// Get the save file path
-    fn save_path() -> PathBuf {
-        dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("sudoku_save.json")
+    /// Get the save file path for the active profile. A save left at the
+    /// default profile's original location is moved over on first use.
+    pub(crate) fn save_path() -> PathBuf {
+        let path = crate::persistence::profile_dir().join("sudoku_save.json");
+        if crate::profiles::active().is_default() && !path.exists() {
+            if let Some(old) = crate::persistence::legacy_save_path() {
+                let _ = fs::rename(old, &path);
+            }
+        }
+        path
     }
 
     /// Save the current game
@@ -837,11 +2053,7 @@ impl App {
             self.game.original_puzzle().to_string()
         };
 
-        let share_dir = dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("sudoku");
-        let _ = fs::create_dir_all(&share_dir);
-        let share_path = share_dir.join("share.txt");
+        let share_path = crate::persistence::app_data_dir().join("share.txt");
 
         match fs::write(&share_path, &code) {
             Ok(_) => self.show_message(&format!("Puzzle code copied: {}", code)),
@@ -882,3 +2094,26 @@ impl App {
         }
     }
 }
+
+/// The configured default difficulty, or Expert if it's a secret level the
+/// player hasn't unlocked yet (with a warning saying so)
+pub(crate) fn default_difficulty(
+    config: &Config,
+    stats: &StatsManager,
+) -> (Difficulty, Option<String>) {
+    let wanted = config.default_difficulty;
+    if stats.secrets_unlocked() || Difficulty::standard_levels().contains(&wanted) {
+        return (wanted, None);
+    }
+    let warning = format!(
+        "default_difficulty {:?} is locked; starting on {:?}",
+        wanted,
+        Difficulty::Expert
+    );
+    (Difficulty::Expert, Some(warning))
+}
+
+/// Modification time of a theme's file
+fn modified(entry: &ThemeEntry) -> Option<SystemTime> {
+    fs::metadata(entry.path.as_ref()?).ok()?.modified().ok()
+}
@@ -0,0 +1,725 @@
+//! Player settings and key bindings, kept in `config.toml` in the platform
+//! config directory (`--config` / `SUDOKU_CONFIG` to use another file).
+//! That file belongs to the default profile; other profiles keep theirs in
+//! `profiles/<id>/config.toml` beside it and start from a copy of it. A
+//! `--config` file is used by every profile.
+//!
+//! Every setting is optional in the file; missing ones take their defaults.
+//! Keys are bound to named actions under `[keys]`, e.g.
+//!
+//! ```toml
+//! [keys]
+//! up = ["k", "Up"]
+//! undo = ["Ctrl+z"]
+//! ```
+//!
+//! Actions left out keep their default keys. Digits 1-9 always enter values
+//! and can't be rebound.
+
+use crate::app::InputMode;
+use crate::profiles::Profile;
+use crate::render::Layout;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use sudoku_core::Difficulty;
+
+/// Set by `--config` / `SUDOKU_CONFIG`
+static OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `path` (or `SUDOKU_CONFIG`) instead of the default config file. Call
+/// once at startup.
+pub fn init(path: Option<PathBuf>) {
+    if let Some(path) = path.or_else(|| std::env::var_os("SUDOKU_CONFIG").map(PathBuf::from)) {
+        let _ = OVERRIDE.set(path);
+    }
+}
+
+/// The top-level config file: the default profile's, and where user themes
+/// are found
+pub fn shared_path() -> PathBuf {
+    OVERRIDE.get().cloned().unwrap_or_else(|| {
+        dirs::config_dir()
+            .unwrap_or_else(crate::persistence::app_data_dir)
+            .join("sudoku")
+            .join("config.toml")
+    })
+}
+
+/// The active profile's config file
+pub fn path() -> PathBuf {
+    match OVERRIDE.get() {
+        Some(path) => path.clone(),
+        None => profile_path(&crate::profiles::active(), &shared_path()),
+    }
+}
+
+/// Delete `profile`'s own config file, if it has one
+pub fn remove_profile(profile: &Profile) -> io::Result<()> {
+    if profile.is_default() || OVERRIDE.get().is_some() {
+        return Ok(());
+    }
+    let path = profile_path(profile, &shared_path());
+    match path.parent().map(std::fs::remove_dir_all) {
+        Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Where `profile` keeps its settings, given the top-level file `shared`
+fn profile_path(profile: &Profile, shared: &Path) -> PathBuf {
+    if profile.is_default() {
+        return shared.to_path_buf();
+    }
+    profile
+        .dir(shared.parent().unwrap_or(Path::new(".")))
+        .join("config.toml")
+}
+
+/// Everything the player can change on the settings screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Built-in theme name or the file stem of an installed theme
+    pub theme: String,
+    pub show_suggestions: bool,
+    pub show_naked_singles: bool,
+    /// Difficulty of the first game and first choice in the new game menu
+    pub default_difficulty: Difficulty,
+    /// Input mode a session starts in
+    pub input_mode: InputMode,
+    /// Print announcements line by line instead of drawing the board
+    pub screen_reader: bool,
+    /// No particles or flashing, and a steady low refresh rate
+    pub reduced_motion: bool,
+    /// Game screen arrangement; falls back to a smaller one that fits
+    pub layout: Layout,
+    /// Keys per action name; see `Keymap::new`
+    pub keys: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            show_suggestions: true,
+            show_naked_singles: false, // Off by default - it's basically cheating!
+            default_difficulty: Difficulty::Medium,
+            input_mode: InputMode::Normal,
+            screen_reader: false,
+            reduced_motion: false,
+            layout: Layout::Auto,
+            keys: Action::ALL
+                .iter()
+                .map(|a| {
+                    let keys = a.default_keys().iter().map(|k| k.to_string()).collect();
+                    (a.name().to_string(), keys)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the active profile's config file, or the top-level one if the
+    /// profile has none yet. Problems are returned as warnings alongside the
+    /// defaults; a file that can't be parsed is first copied to
+    /// `config.toml.bak` so saving settings doesn't lose it.
+    pub fn load() -> (Self, Vec<String>) {
+        Self::load_from(&path(), &shared_path())
+    }
+
+    fn load_from(path: &Path, shared: &Path) -> (Self, Vec<String>) {
+        let path = if path.exists() { path } else { shared };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (Self::default(), Vec::new()),
+            Err(e) => return (Self::default(), vec![format!("Cannot read config: {}", e)]),
+        };
+        match Self::parse(&text) {
+            Ok(config) => (config, Vec::new()),
+            Err(e) => {
+                let backup = path.with_extension("toml.bak");
+                let saved = match std::fs::copy(path, &backup) {
+                    Ok(_) => format!("; a copy was saved to {}", backup.display()),
+                    Err(_) => String::new(),
+                };
+                let warning = format!("Config file is invalid ({}){}", e.trim(), saved);
+                (Self::default(), vec![warning])
+            }
+        }
+    }
+
+    /// Parse a config file, filling in defaults for anything left out
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        for (name, keys) in Self::default().keys {
+            config.keys.entry(name).or_insert(keys);
+        }
+        if config.input_mode == InputMode::Menu {
+            config.input_mode = InputMode::Normal;
+        }
+        Ok(config)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&path())
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        crate::persistence::atomic_write(path, text.as_bytes())
+    }
+
+    /// Bind `action` to `keys` only
+    pub fn bind(&mut self, action: Action, keys: &[KeyBinding]) {
+        let keys = keys.iter().map(|k| k.to_string()).collect();
+        self.keys.insert(action.name().to_string(), keys);
+    }
+}
+
+/// Something a key can be bound to during play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    BoxUp,
+    BoxDown,
+    BoxLeft,
+    BoxRight,
+    Clear,
+    ClearNotes,
+    ClearAllNotes,
+    FillNotes,
+    FillAllNotes,
+    ToggleMode,
+    Undo,
+    Redo,
+    Hint,
+    ApplyHint,
+    NewGame,
+    Pause,
+    Theme,
+    ToggleSuggestions,
+    ToggleNakedSingles,
+    SaveShare,
+    Load,
+    Stats,
+    Leaderboard,
+    History,
+    Profiles,
+    Settings,
+    ReadCell,
+    ReadRow,
+    ReadColumn,
+    ReadBox,
+    ReadStatus,
+    Quit,
+}
+
+impl Action {
+    /// Every action, in the order the settings screen lists them
+    pub const ALL: [Action; 36] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::BoxUp,
+        Action::BoxDown,
+        Action::BoxLeft,
+        Action::BoxRight,
+        Action::Clear,
+        Action::ClearNotes,
+        Action::ClearAllNotes,
+        Action::FillNotes,
+        Action::FillAllNotes,
+        Action::ToggleMode,
+        Action::Undo,
+        Action::Redo,
+        Action::Hint,
+        Action::ApplyHint,
+        Action::NewGame,
+        Action::Pause,
+        Action::Theme,
+        Action::ToggleSuggestions,
+        Action::ToggleNakedSingles,
+        Action::SaveShare,
+        Action::Load,
+        Action::Stats,
+        Action::Leaderboard,
+        Action::History,
+        Action::Profiles,
+        Action::Settings,
+        Action::ReadCell,
+        Action::ReadRow,
+        Action::ReadColumn,
+        Action::ReadBox,
+        Action::ReadStatus,
+        Action::Quit,
+    ];
+
+    /// Name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::BoxUp => "box_up",
+            Action::BoxDown => "box_down",
+            Action::BoxLeft => "box_left",
+            Action::BoxRight => "box_right",
+            Action::Clear => "clear",
+            Action::ClearNotes => "clear_notes",
+            Action::ClearAllNotes => "clear_all_notes",
+            Action::FillNotes => "fill_notes",
+            Action::FillAllNotes => "fill_all_notes",
+            Action::ToggleMode => "toggle_mode",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Hint => "hint",
+            Action::ApplyHint => "apply_hint",
+            Action::NewGame => "new_game",
+            Action::Pause => "pause",
+            Action::Theme => "theme",
+            Action::ToggleSuggestions => "toggle_suggestions",
+            Action::ToggleNakedSingles => "toggle_naked_singles",
+            Action::SaveShare => "save_share",
+            Action::Load => "load",
+            Action::Stats => "stats",
+            Action::Leaderboard => "leaderboard",
+            Action::History => "history",
+            Action::Profiles => "profiles",
+            Action::Settings => "settings",
+            Action::ReadCell => "read_cell",
+            Action::ReadRow => "read_row",
+            Action::ReadColumn => "read_column",
+            Action::ReadBox => "read_box",
+            Action::ReadStatus => "read_status",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// Description for the settings screen
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Move left",
+            Action::Right => "Move right",
+            Action::BoxUp => "Jump a box up",
+            Action::BoxDown => "Jump a box down",
+            Action::BoxLeft => "Jump a box left",
+            Action::BoxRight => "Jump a box right",
+            Action::Clear => "Clear cell",
+            Action::ClearNotes => "Clear cell notes",
+            Action::ClearAllNotes => "Clear all notes",
+            Action::FillNotes => "Fill cell notes",
+            Action::FillAllNotes => "Fill all notes",
+            Action::ToggleMode => "Toggle notes mode",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Hint => "Show hint",
+            Action::ApplyHint => "Apply hint",
+            Action::NewGame => "New game",
+            Action::Pause => "Pause",
+            Action::Theme => "Theme menu",
+            Action::ToggleSuggestions => "Toggle valid hints",
+            Action::ToggleNakedSingles => "Toggle ghost hints",
+            Action::SaveShare => "Save and share",
+            Action::Load => "Load saved game",
+            Action::Stats => "Stats",
+            Action::Leaderboard => "Leaderboard",
+            Action::History => "History",
+            Action::Profiles => "Profiles",
+            Action::Settings => "Settings",
+            Action::ReadCell => "Read cell",
+            Action::ReadRow => "Read row",
+            Action::ReadColumn => "Read column",
+            Action::ReadBox => "Read box",
+            Action::ReadStatus => "Read game status",
+            Action::Quit => "Quit",
+        }
+    }
+
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Up => &["k", "Up"],
+            Action::Down => &["j", "Down"],
+            Action::Left => &["h", "Left"],
+            Action::Right => &["l", "Right"],
+            Action::BoxUp => &["w"],
+            Action::BoxDown => &["s"],
+            Action::BoxLeft => &["a"],
+            Action::BoxRight => &["d"],
+            Action::Clear => &["0", "Delete", "Backspace"],
+            Action::ClearNotes => &["x"],
+            Action::ClearAllNotes => &["X"],
+            Action::FillNotes => &["f"],
+            Action::FillAllNotes => &["F"],
+            Action::ToggleMode => &["c"],
+            Action::Undo => &["u"],
+            Action::Redo => &["Ctrl+r"],
+            Action::Hint => &["?"],
+            Action::ApplyHint => &["!"],
+            Action::NewGame => &["n"],
+            Action::Pause => &["p"],
+            Action::Theme => &["t"],
+            Action::ToggleSuggestions => &["v"],
+            Action::ToggleNakedSingles => &["g"],
+            Action::SaveShare => &["S"],
+            Action::Load => &["L"],
+            Action::Stats => &["i"],
+            Action::Leaderboard => &["b"],
+            Action::History => &["H"],
+            Action::Profiles => &["P"],
+            Action::Settings => &["o"],
+            Action::ReadCell => &["."],
+            Action::ReadRow => &["R"],
+            Action::ReadColumn => &["C"],
+            Action::ReadBox => &["B"],
+            Action::ReadStatus => &[","],
+            Action::Quit => &["q"],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// A key with Ctrl/Alt, written like `x`, `X`, `Ctrl+r`, `Alt+Left` or `F5`.
+/// Shift is part of the character (`X`), so it isn't tracked separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+/// Key names accepted besides single characters
+const NAMED_KEYS: [(&str, KeyCode); 12] = [
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+];
+
+impl KeyBinding {
+    /// The binding a key press matches
+    pub fn from_event(key: &KeyEvent) -> Self {
+        let code = match key.code {
+            KeyCode::BackTab => KeyCode::Tab,
+            code => code,
+        };
+        Self {
+            code,
+            modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut shift = false;
+        let mut rest = text;
+        // A lone "+" is the plus key, not a separator
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, k)| !k.is_empty()) {
+            match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => shift = true,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", prefix, text)),
+            }
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ if rest.eq_ignore_ascii_case("space") => KeyCode::Char(' '),
+            _ if rest.eq_ignore_ascii_case("esc") || rest.eq_ignore_ascii_case("escape") => {
+                KeyCode::Esc
+            }
+            _ => match NAMED_KEYS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+            {
+                Some(&(_, code)) => code,
+                None => match rest
+                    .strip_prefix(['F', 'f'])
+                    .and_then(|n| n.parse::<u8>().ok())
+                {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", text)),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Digits 1-9 always enter values
+    pub fn is_reserved(&self) -> bool {
+        matches!(self.code, KeyCode::Char('1'..='9'))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => match NAMED_KEYS.iter().find(|(_, c)| *c == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Which action each key triggers during play
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&Config::default().keys).0
+    }
+}
+
+impl Keymap {
+    /// Build the keymap from the config's `[keys]` table. Unknown actions,
+    /// unparseable or reserved keys and keys bound twice are skipped and
+    /// reported; actions not in the table keep their defaults.
+    pub fn new(keys: &BTreeMap<String, Vec<String>>) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        for name in keys.keys() {
+            if Action::from_name(name).is_none() {
+                warnings.push(format!("Unknown action '{}' in [keys]", name));
+            }
+        }
+
+        let mut bindings: Vec<(KeyBinding, Action)> = Vec::new();
+        for action in Action::ALL {
+            let names: Vec<&str> = match keys.get(action.name()) {
+                Some(names) => names.iter().map(String::as_str).collect(),
+                None => action.default_keys().to_vec(),
+            };
+            for name in names {
+                let key = match KeyBinding::parse(name) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        warnings.push(format!("{}: {}", action.name(), e));
+                        continue;
+                    }
+                };
+                if key.is_reserved() {
+                    warnings.push(format!(
+                        "{}: '{}' is reserved for digits",
+                        action.name(),
+                        key
+                    ));
+                } else if let Some((_, other)) = bindings.iter().find(|(k, _)| *k == key) {
+                    warnings.push(format!(
+                        "'{}' is bound to both {} and {}; using {}",
+                        key,
+                        other.name(),
+                        action.name(),
+                        other.name()
+                    ));
+                } else {
+                    bindings.push((key, action));
+                }
+            }
+        }
+        (Self { bindings }, warnings)
+    }
+
+    /// The action bound to a key press, if any
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        let pressed = KeyBinding::from_event(key);
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == pressed)
+            .map(|&(_, a)| a)
+    }
+
+    /// Keys bound to `action`, in config order
+    pub fn keys(&self, action: Action) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|&(k, _)| k)
+            .collect()
+    }
+
+    /// Keys bound to `action` joined with '/', or "-" if none
+    pub fn label(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(|k| k.to_string()).collect();
+        if keys.is_empty() {
+            "-".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_key_binding_parse_and_display() {
+        for text in [
+            "x", "X", "?", "+", "Ctrl+r", "Alt+Left", "F5", "Space", "Esc", "Delete",
+        ] {
+            let key = KeyBinding::parse(text).unwrap();
+            assert_eq!(key.to_string(), text);
+        }
+        assert_eq!(
+            KeyBinding::parse("shift+x").unwrap().code,
+            KeyCode::Char('X')
+        );
+        assert_eq!(
+            KeyBinding::parse("ctrl+R").unwrap(),
+            KeyBinding {
+                code: KeyCode::Char('R'),
+                modifiers: KeyModifiers::CONTROL
+            }
+        );
+        assert!(KeyBinding::parse("Hyper+x").is_err());
+        assert!(KeyBinding::parse("F13").is_err());
+        assert!(KeyBinding::parse("").is_err());
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let (keymap, warnings) = Keymap::new(&Config::default().keys);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('k'), KeyModifiers::NONE)),
+            Some(Action::Up)
+        );
+        // Shift is carried by the character
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('X'), KeyModifiers::SHIFT)),
+            Some(Action::ClearAllNotes)
+        );
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(Action::Redo)
+        );
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('r'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.label(Action::Clear), "0/Delete/Backspace");
+    }
+
+    #[test]
+    fn test_config_file() {
+        let config = Config::parse(
+            r#"
+theme = "light"
+default_difficulty = "Hard"
+input_mode = "candidate"
+
+[keys]
+undo = ["Ctrl+z", "u"]
+quit = ["Ctrl+q"]
+redo = ["Ctrl+z"]
+box_up = ["5"]
+teleport = ["T"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.theme, "light");
+        assert_eq!(config.default_difficulty, Difficulty::Hard);
+        assert_eq!(config.input_mode, InputMode::Candidate);
+        assert!(config.show_suggestions);
+        assert_eq!(config.keys["up"], ["k", "Up"]);
+
+        let (keymap, warnings) = Keymap::new(&config.keys);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            Some(Action::Undo)
+        );
+        assert_eq!(
+            keymap.action(&press(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert!(keymap.keys(Action::Redo).is_empty());
+        assert!(keymap.keys(Action::BoxUp).is_empty());
+
+        // Saving writes every setting and binding back out
+        let reparsed = Config::parse(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(reparsed, config);
+
+        assert!(Config::parse("theme = 3").is_err());
+    }
+
+    #[test]
+    fn test_profiles_keep_their_own_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("config.toml");
+        let profile = |id: &str| Profile {
+            id: id.to_string(),
+            name: id.to_string(),
+            created_at: 0,
+            last_used: 0,
+        };
+        let (default, ann, bob) = (profile("default"), profile("ann"), profile("bob"));
+        assert_eq!(profile_path(&default, &shared), shared);
+        let (ann, bob) = (profile_path(&ann, &shared), profile_path(&bob, &shared));
+        assert_eq!(ann, dir.path().join("profiles/ann/config.toml"));
+
+        let mut config = Config {
+            theme: "light".to_string(),
+            ..Config::default()
+        };
+        config.save_to(&shared).unwrap();
+        // A new profile starts from the default profile's settings
+        assert_eq!(Config::load_from(&ann, &shared).0.theme, "light");
+
+        config.theme = "high-contrast".to_string();
+        config.save_to(&ann).unwrap();
+        config.theme = "dark".to_string();
+        config.save_to(&bob).unwrap();
+        assert_eq!(Config::load_from(&ann, &shared).0.theme, "high-contrast");
+        assert_eq!(Config::load_from(&bob, &shared).0.theme, "dark");
+        assert_eq!(Config::load_from(&shared, &shared).0.theme, "light");
+    }
+}
@@ -0,0 +1,398 @@
+//! Off-screen frame buffer for the renderers.
+//!
+//! Screens are drawn into a [`Frame`] with [`draw!`], which takes the same
+//! crossterm commands as `execute!`. [`Screen`] then compares the new frame
+//! with the one on the terminal and sends only the cells that changed, in a
+//! single write. A frame can also be rendered headless and read back with
+//! [`Frame::text`] for snapshot tests.
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    queue,
+    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::fmt::Display;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// Marks the right half of a double-width character
+const WIDE_TAIL: char = '\0';
+
+/// Apply crossterm commands to a [`Frame`], in order, like `execute!`.
+/// Drawing can't fail; the `io::Result` lets call sites keep using `?`.
+macro_rules! draw {
+    ($frame:expr $(, $command:expr)* $(,)?) => {{
+        $( $crate::frame::Draw::draw($command, &mut *$frame); )*
+        std::io::Result::Ok(())
+    }};
+}
+pub(crate) use draw;
+
+/// One character cell of the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    const BLANK: Cell = Cell {
+        ch: ' ',
+        fg: Color::Reset,
+        bg: Color::Reset,
+    };
+}
+
+/// A picture of the whole terminal, with a pen that commands move and color
+#[derive(Debug, Clone)]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    x: u16,
+    y: u16,
+    fg: Color,
+    bg: Color,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::BLANK; width as usize * height as usize],
+            x: 0,
+            y: 0,
+            fg: Color::Reset,
+            bg: Color::Reset,
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    #[cfg(test)]
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    /// The characters on screen, one line per row with trailing spaces
+    /// removed
+    pub fn text(&self) -> String {
+        let mut lines: Vec<String> = self
+            .cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| {
+                let line: String = row
+                    .iter()
+                    .map(|c| c.ch)
+                    .filter(|&c| c != WIDE_TAIL)
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// Write `text` at the pen in the pen's colors; what runs off the right
+    /// edge is dropped
+    fn print(&mut self, text: &str) {
+        for ch in text.chars() {
+            let width = ch.width().unwrap_or(0) as u16;
+            if width == 0 {
+                continue;
+            }
+            if let Some(i) = self.index(self.x, self.y) {
+                self.unsplit(i);
+                self.cells[i] = Cell {
+                    ch,
+                    fg: self.fg,
+                    bg: self.bg,
+                };
+                if width == 2 {
+                    match self.index(self.x + 1, self.y) {
+                        Some(tail) => {
+                            self.unsplit(tail);
+                            self.cells[tail] = Cell {
+                                ch: WIDE_TAIL,
+                                ..self.cells[i]
+                            };
+                        }
+                        // No room for the right half
+                        None => self.cells[i].ch = ' ',
+                    }
+                }
+            }
+            self.x = self.x.saturating_add(width);
+        }
+    }
+
+    /// Blank the other half of a wide character about to be overwritten at
+    /// `i`, so none is left half drawn
+    fn unsplit(&mut self, i: usize) {
+        let x = i % self.width as usize;
+        if self.cells[i].ch == WIDE_TAIL && x > 0 {
+            self.cells[i - 1].ch = ' ';
+        } else if x + 1 < self.width as usize && self.cells[i + 1].ch == WIDE_TAIL {
+            self.cells[i + 1].ch = ' ';
+        }
+    }
+
+    /// Blank cells `range` in the current background, like a terminal does
+    fn clear(&mut self, range: std::ops::Range<usize>) {
+        let blank = Cell {
+            bg: self.bg,
+            ..Cell::BLANK
+        };
+        self.cells[range].fill(blank);
+    }
+
+    /// Queue the escape sequences that turn `before` into this frame
+    fn diff(&self, before: &Frame, out: &mut impl Write) -> io::Result<()> {
+        let width = self.width as usize;
+        let mut pen = None;
+        let (mut fg, mut bg) = (None, None);
+        for (i, cell) in self.cells.iter().enumerate() {
+            if cell.ch == WIDE_TAIL {
+                continue;
+            }
+            let wide = (i + 1) % width != 0 && self.cells[i + 1].ch == WIDE_TAIL;
+            let unchanged =
+                before.cells[i] == *cell && (!wide || before.cells[i + 1] == self.cells[i + 1]);
+            if unchanged {
+                continue;
+            }
+
+            let (x, y) = ((i % width) as u16, (i / width) as u16);
+            if pen != Some((x, y)) {
+                queue!(out, MoveTo(x, y))?;
+            }
+            if fg != Some(cell.fg) {
+                queue!(out, SetForegroundColor(cell.fg))?;
+                fg = Some(cell.fg);
+            }
+            if bg != Some(cell.bg) {
+                queue!(out, SetBackgroundColor(cell.bg))?;
+                bg = Some(cell.bg);
+            }
+            queue!(out, Print(cell.ch))?;
+            pen = Some((x + if wide { 2 } else { 1 }, y));
+        }
+        Ok(())
+    }
+}
+
+/// A crossterm command that can be drawn into a [`Frame`]
+pub trait Draw {
+    fn draw(self, frame: &mut Frame);
+}
+
+impl Draw for MoveTo {
+    fn draw(self, frame: &mut Frame) {
+        frame.x = self.0;
+        frame.y = self.1;
+    }
+}
+
+impl Draw for SetForegroundColor {
+    fn draw(self, frame: &mut Frame) {
+        frame.fg = self.0;
+    }
+}
+
+impl Draw for SetBackgroundColor {
+    fn draw(self, frame: &mut Frame) {
+        frame.bg = self.0;
+    }
+}
+
+impl<T: Display> Draw for Print<T> {
+    fn draw(self, frame: &mut Frame) {
+        frame.print(&self.0.to_string());
+    }
+}
+
+impl Draw for Clear {
+    fn draw(self, frame: &mut Frame) {
+        let end = frame.cells.len();
+        let width = frame.width as usize;
+        let row = frame.y.min(frame.height.saturating_sub(1)) as usize * width;
+        let at = frame.index(frame.x, frame.y).unwrap_or(end);
+        let range = match self.0 {
+            ClearType::All | ClearType::Purge => 0..end,
+            ClearType::FromCursorDown => at..end,
+            ClearType::FromCursorUp => 0..at.saturating_add(1).min(end),
+            ClearType::CurrentLine => row..(row + width).min(end),
+            ClearType::UntilNewLine => at.min(row + width)..(row + width).min(end),
+        };
+        frame.clear(range);
+    }
+}
+
+/// The frame on the terminal, so the next one can be sent as a diff
+#[derive(Debug, Default)]
+pub struct Screen {
+    shown: Option<Frame>,
+}
+
+impl Screen {
+    /// A frame to draw the next picture into: a copy of what's shown, so
+    /// screens that paint over themselves needn't clear, or a blank one after
+    /// a resize
+    pub fn next_frame(&self, width: u16, height: u16) -> Frame {
+        match &self.shown {
+            Some(frame) if frame.size() == (width, height) => {
+                let mut next = frame.clone();
+                next.x = 0;
+                next.y = 0;
+                next
+            }
+            _ => Frame::new(width, height),
+        }
+    }
+
+    /// Send `frame` to the terminal as the changes from the shown one, then
+    /// leave the cursor where drawing ended
+    pub fn show(&mut self, frame: Frame, out: &mut impl Write) -> io::Result<()> {
+        let mut buf = Vec::new();
+        queue!(buf, Hide)?;
+        let blank;
+        let before = match &self.shown {
+            Some(shown) if shown.size() == frame.size() => shown,
+            _ => {
+                queue!(buf, SetBackgroundColor(Color::Reset), Clear(ClearType::All))?;
+                blank = Frame::new(frame.width, frame.height);
+                &blank
+            }
+        };
+        frame.diff(before, &mut buf)?;
+        let x = frame.x.min(frame.width.saturating_sub(1));
+        let y = frame.y.min(frame.height.saturating_sub(1));
+        queue!(buf, MoveTo(x, y), Show)?;
+        out.write_all(&buf)?;
+        self.shown = Some(frame);
+        Ok(())
+    }
+
+    /// Forget what's on the terminal (it was cleared or replaced), so the
+    /// next frame is sent whole
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_text() {
+        let mut frame = Frame::new(12, 3);
+        draw!(
+            &mut frame,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Red),
+            Print("🏆 win"),
+            MoveTo(10, 0),
+            Print("cut")
+        )
+        .unwrap();
+        assert_eq!(frame.text(), "          cu\n 🏆 win");
+        assert_eq!(frame.cell(1, 1).unwrap().fg, Color::Red);
+
+        // Overwriting half of a wide character blanks the other half
+        draw!(&mut frame, MoveTo(2, 1), Print("x")).unwrap();
+        assert_eq!(frame.text(), "          cu\n  x win");
+
+        draw!(
+            &mut frame,
+            SetBackgroundColor(Color::Blue),
+            Clear(ClearType::All)
+        )
+        .unwrap();
+        assert_eq!(frame.text(), "");
+        assert_eq!(frame.cell(0, 0).unwrap().bg, Color::Blue);
+    }
+
+    /// Text of the output with the escape sequences left out
+    fn printed(out: &str) -> String {
+        let mut text = String::new();
+        let mut chars = out.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.find(|c| c.is_ascii_alphabetic());
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_screen_sends_changes() {
+        let mut screen = Screen::default();
+        let mut frame = screen.next_frame(20, 2);
+        draw!(&mut frame, MoveTo(0, 0), Print("Time 00:01")).unwrap();
+        let mut first = Vec::new();
+        screen.show(frame, &mut first).unwrap();
+        let first = String::from_utf8(first).unwrap();
+        assert!(first.contains("\x1b[2J"));
+        assert_eq!(printed(&first), "Time00:01");
+
+        let mut frame = screen.next_frame(20, 2);
+        draw!(&mut frame, MoveTo(0, 0), Print("Time 00:02")).unwrap();
+        let mut second = Vec::new();
+        screen.show(frame, &mut second).unwrap();
+        let second = String::from_utf8(second).unwrap();
+        assert!(!second.contains("\x1b[2J"));
+        assert!(!second.contains("Time"));
+        // Only the changed digit, at column 10 of row 1
+        assert!(second.contains("\x1b[1;10H"));
+        assert_eq!(printed(&second), "2");
+    }
+
+    /// `text` with the SE rating left out: it comes from sudoku-core's
+    /// solver, not from anything drawn here
+    fn without_rating(text: &str) -> String {
+        match (text.find("(SE "), text.find(")\n")) {
+            (Some(start), Some(end)) if start < end => {
+                format!("{}(SE -){}", &text[..start], &text[end + 1..])
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_full_screen_snapshot() {
+        crate::persistence::test_sandbox();
+        let mut app = crate::app::App::new_with_puzzle(Some(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ));
+        app.menu = crate::app::MenuState::NewGame;
+        app.menu_selection = 2;
+        let mut frame = Frame::new(100, 30);
+        crate::render::render(&mut frame, &mut app).unwrap();
+        let text = without_rating(&frame.text());
+
+        // UPDATE_SNAPSHOTS=1 rewrites the snapshot after an intended change
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/snapshots/new_game_menu.txt"
+        );
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(path, format!("{}\n", text)).unwrap();
+        }
+        let expected = std::fs::read_to_string(path).unwrap();
+        assert_eq!(text, expected.trim_end_matches('\n'), "\n{}", text);
+    }
+}
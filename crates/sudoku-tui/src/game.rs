@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use sudoku_core::{Difficulty, Grid, Hint, Position, PuzzleId, Solver};
+use sudoku_verify::{MoveAction, MoveLogEntry};
 
 /// A single move in the game (for undo/redo)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameMove {
     SetValue {
         pos: Position,
@@ -29,6 +30,71 @@ pub enum GameMove {
     },
 }
 
+impl GameMove {
+    /// The move that takes it back
+    pub fn inverse(&self) -> GameMove {
+        match *self {
+            GameMove::SetValue {
+                pos,
+                old_value,
+                new_value,
+            } => GameMove::SetValue {
+                pos,
+                old_value: new_value,
+                new_value: old_value,
+            },
+            GameMove::ToggleCandidate { pos, value } => GameMove::ToggleCandidate { pos, value },
+            GameMove::AddCandidate { pos, value } => GameMove::RemoveCandidate { pos, value },
+            GameMove::RemoveCandidate { pos, value } => GameMove::AddCandidate { pos, value },
+            GameMove::SetCandidates {
+                pos,
+                old_candidates,
+                new_candidates,
+            } => GameMove::SetCandidates {
+                pos,
+                old_candidates: new_candidates,
+                new_candidates: old_candidates,
+            },
+        }
+    }
+
+    pub fn pos(&self) -> Position {
+        match *self {
+            GameMove::SetValue { pos, .. }
+            | GameMove::ToggleCandidate { pos, .. }
+            | GameMove::AddCandidate { pos, .. }
+            | GameMove::RemoveCandidate { pos, .. }
+            | GameMove::SetCandidates { pos, .. } => pos,
+        }
+    }
+
+    /// Whether the cell and every digit and note are on the board, for
+    /// moves that arrive over the network
+    pub fn is_valid(&self) -> bool {
+        let digit = |value: &u8| (1..=9).contains(value);
+        let all_notes = (1..=9).fold(0, |raw, v| raw | sudoku_core::BitSet::single(v).as_raw());
+        let notes = |raw: u16| raw & !all_notes == 0;
+        let pos = self.pos();
+        pos.row < 9
+            && pos.col < 9
+            && match self {
+                GameMove::SetValue {
+                    old_value,
+                    new_value,
+                    ..
+                } => old_value.iter().all(digit) && new_value.iter().all(digit),
+                GameMove::ToggleCandidate { value, .. }
+                | GameMove::AddCandidate { value, .. }
+                | GameMove::RemoveCandidate { value, .. } => digit(value),
+                GameMove::SetCandidates {
+                    old_candidates,
+                    new_candidates,
+                    ..
+                } => notes(*old_candidates) && notes(*new_candidates),
+            }
+    }
+}
+
 /// The game state
 #[derive(Clone)]
 pub struct Game {
@@ -66,6 +132,10 @@ pub struct Game {
     seed: Option<u64>,
     /// Cached SE (Sudoku Explainer) rating
     se_rating: f32,
+    /// Timestamped move log for replay verification (same format as the web build)
+    move_log: Vec<MoveLogEntry>,
+    /// On a shared board, changes made here not yet sent to teammates
+    outgoing: Option<Vec<GameMove>>,
 }
 
 impl Game {
@@ -105,6 +175,8 @@ impl Game {
             notes_used: false,
             seed: Some(puzzle_id.seed),
             se_rating,
+            move_log: Vec::new(),
+            outgoing: None,
         }
     }
 
@@ -143,9 +215,23 @@ impl Game {
             notes_used: false,
             seed: Some(id.seed),
             se_rating,
+            move_log: Vec::new(),
+            outgoing: None,
         }
     }
 
+    /// Create a game from an 81-character puzzle string or an 8-character
+    /// short code
+    pub fn from_code(code: &str) -> Option<Self> {
+        if code.len() != 81 {
+            if let Some(id) = PuzzleId::from_short_code(code) {
+                return Some(Self::new_with_id(&id));
+            }
+        }
+        // Try as puzzle string anyway (fallback)
+        Self::from_string(code)
+    }
+
     /// Create a game from a puzzle string
     pub fn from_string(puzzle: &str) -> Option<Self> {
         let grid = Grid::from_string(puzzle)?;
@@ -173,6 +259,8 @@ impl Game {
             notes_used: false,
             seed: None,
             se_rating,
+            move_log: Vec::new(),
+            outgoing: None,
         })
     }
 
@@ -213,6 +301,15 @@ impl Game {
         self.completed
     }
 
+    /// Pick a completed game back up, e.g. when a race host didn't accept
+    /// the finished grid; the clock carries on from where it stopped
+    pub fn reopen(&mut self) {
+        if self.completed {
+            self.completed = false;
+            self.start_time = Instant::now();
+        }
+    }
+
     /// Get hints used count
     pub fn hints_used(&self) -> usize {
         self.hints_used
@@ -223,6 +320,22 @@ impl Game {
         self.mistakes
     }
 
+    /// Cells filled in by the player so far (givens don't count)
+    pub fn filled_count(&self) -> usize {
+        (0..81)
+            .map(|i| self.grid.cell(Position::new(i / 9, i % 9)))
+            .filter(|cell| cell.is_filled() && !cell.is_given())
+            .count()
+    }
+
+    /// Cells the puzzle leaves to fill
+    pub fn cells_to_fill(&self) -> usize {
+        self.original_puzzle
+            .chars()
+            .filter(|c| !('1'..='9').contains(c))
+            .count()
+    }
+
     /// Get the original puzzle string
     pub fn original_puzzle(&self) -> &str {
         &self.original_puzzle
@@ -238,6 +351,21 @@ impl Game {
         self.move_times_ms.len()
     }
 
+    /// Get the timestamped move log
+    pub fn move_log(&self) -> &[MoveLogEntry] {
+        &self.move_log
+    }
+
+    /// Append a move to the log, timestamped with the (pause-excluded) game clock
+    fn log_move(&mut self, pos: Position, action: MoveAction) {
+        self.move_log.push(MoveLogEntry {
+            seq: self.move_log.len() as u32,
+            ms: self.elapsed().as_millis() as i64,
+            cell: (pos.row * 9 + pos.col) as u8,
+            action,
+        });
+    }
+
     /// Check if notes (candidates) were used during this game
     pub fn notes_used(&self) -> bool {
         self.notes_used
@@ -315,9 +443,9 @@ impl Game {
 
         self.grid.set_cell_unchecked(pos, Some(value));
         self.grid.recalculate_candidates();
+        self.log_move(pos, MoveAction::Place(value));
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         // Check for completion
         if self.grid.is_complete() && self.grid.validate().is_valid {
@@ -339,10 +467,10 @@ impl Game {
             return false;
         }
 
-        let old_value = cell.value();
-        if old_value.is_none() {
+        let Some(old) = cell.value() else {
             return false;
-        }
+        };
+        let old_value = Some(old);
 
         let game_move = GameMove::SetValue {
             pos,
@@ -352,9 +480,9 @@ impl Game {
 
         self.grid.set_cell_unchecked(pos, None);
         self.grid.recalculate_candidates();
+        self.log_move(pos, MoveAction::Clear(old));
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         true
     }
@@ -374,8 +502,7 @@ impl Game {
 
         self.grid.cell_mut(pos).toggle_candidate(value);
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         // Mark that notes were used
         self.notes_used = true;
@@ -404,8 +531,7 @@ impl Game {
 
         self.grid.cell_mut(pos).add_candidate(value);
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         // Mark that notes were used
         self.notes_used = true;
@@ -434,8 +560,7 @@ impl Game {
 
         self.grid.cell_mut(pos).remove_candidate(value);
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         true
     }
@@ -466,8 +591,7 @@ impl Game {
             .cell_mut(pos)
             .set_candidates(sudoku_core::BitSet::empty());
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         true
     }
@@ -501,8 +625,7 @@ impl Game {
 
         self.grid.cell_mut(pos).set_candidates(valid);
 
-        self.undo_stack.push(game_move);
-        self.redo_stack.clear();
+        self.push_move(game_move);
 
         // Mark that notes were used
         self.notes_used = true;
@@ -517,7 +640,9 @@ impl Game {
         }
 
         // Recalculate all candidates based on constraints
+        let before = self.notes();
         self.grid.recalculate_candidates();
+        self.share_notes(&before);
         // Note: This is not undoable as a single action (would need complex undo)
         self.redo_stack.clear();
 
@@ -533,14 +658,15 @@ impl Game {
             return false;
         }
 
+        let before = self.notes();
         self.grid.clear_all_candidates();
+        self.share_notes(&before);
         self.redo_stack.clear();
 
         true
     }
 
     /// Get candidates for a cell (Read)
-    #[allow(dead_code)]
     pub fn get_cell_candidates(&self, pos: Position) -> Vec<u8> {
         self.grid.cell(pos).candidates().iter().collect()
     }
@@ -562,6 +688,7 @@ impl Game {
                 GameMove::SetValue { pos, old_value, .. } => {
                     self.grid.set_cell_unchecked(*pos, *old_value);
                     self.grid.recalculate_candidates();
+                    self.log_move(*pos, MoveAction::Undo(*old_value));
                 }
                 GameMove::ToggleCandidate { pos, value } => {
                     self.grid.cell_mut(*pos).toggle_candidate(*value);
@@ -584,6 +711,7 @@ impl Game {
                         .set_candidates(sudoku_core::BitSet::from_raw(*old_candidates));
                 }
             }
+            self.share(game_move.inverse());
             self.redo_stack.push(game_move);
             true
         } else {
@@ -602,6 +730,7 @@ impl Game {
                 GameMove::SetValue { pos, new_value, .. } => {
                     self.grid.set_cell_unchecked(*pos, *new_value);
                     self.grid.recalculate_candidates();
+                    self.log_move(*pos, MoveAction::Redo(*new_value));
                 }
                 GameMove::ToggleCandidate { pos, value } => {
                     self.grid.cell_mut(*pos).toggle_candidate(*value);
@@ -622,6 +751,7 @@ impl Game {
                         .set_candidates(sudoku_core::BitSet::from_raw(*new_candidates));
                 }
             }
+            self.share(game_move.clone());
             self.undo_stack.push(game_move);
             true
         } else {
@@ -659,6 +789,14 @@ impl Game {
                 // solution. Always trust self.solution to avoid false "mistake" counts.
                 let correct_value = self.solution.get(pos).unwrap_or(value);
                 self.set_value(pos, correct_value);
+
+                // Reclassify the Place entry that set_value just logged as Hint
+                if let Some(last) = self.move_log.last_mut() {
+                    if let MoveAction::Place(v) = last.action {
+                        last.action = MoveAction::Hint(v);
+                    }
+                }
+
                 Some(pos)
             }
             sudoku_core::HintType::EliminateCandidates { .. } => {
@@ -813,6 +951,174 @@ impl Game {
         false
     }
 
+    /// Push a move made here onto the undo stack
+    fn push_move(&mut self, game_move: GameMove) {
+        self.share(game_move.clone());
+        self.undo_stack.push(game_move);
+        self.redo_stack.clear();
+    }
+
+    /// Queue a change for teammates, on a shared board
+    fn share(&mut self, game_move: GameMove) {
+        if let Some(outgoing) = &mut self.outgoing {
+            outgoing.push(game_move);
+        }
+    }
+
+    /// Queue the notes that changed since `before` as one move per cell
+    fn share_notes(&mut self, before: &[u16]) {
+        let after = self.notes();
+        for (i, (&old_candidates, &new_candidates)) in before.iter().zip(&after).enumerate() {
+            if old_candidates != new_candidates {
+                let pos = Position::new(i / 9, i % 9);
+                self.share(GameMove::SetCandidates {
+                    pos,
+                    old_candidates,
+                    new_candidates,
+                });
+            }
+        }
+    }
+
+    /// Play on a board shared with teammates: from now on changes made here
+    /// are queued for [`Game::take_outgoing`]
+    pub fn share_board(&mut self) {
+        self.outgoing.get_or_insert_with(Vec::new);
+    }
+
+    /// Changes made here since the last call
+    pub fn take_outgoing(&mut self) -> Vec<GameMove> {
+        self.outgoing
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Whether `game_move` places a wrong digit
+    pub fn is_mistake(&self, game_move: &GameMove) -> bool {
+        match *game_move {
+            GameMove::SetValue {
+                pos,
+                new_value: Some(value),
+                ..
+            } => self.solution.get(pos) != Some(value),
+            _ => false,
+        }
+    }
+
+    /// Apply a move made elsewhere (by a teammate, or checked by the host)
+    /// without touching this player's undo history. Returns false, changing
+    /// nothing, if the board isn't in the state the move expects.
+    pub fn apply_move(&mut self, game_move: &GameMove) -> bool {
+        if self.completed {
+            return false;
+        }
+        let pos = game_move.pos();
+        let cell = self.grid.cell(pos);
+        if cell.is_given() {
+            return false;
+        }
+        match *game_move {
+            GameMove::SetValue {
+                old_value,
+                new_value,
+                ..
+            } => {
+                if cell.value() != old_value {
+                    return false;
+                }
+                self.grid.set_cell_unchecked(pos, new_value);
+                self.grid.recalculate_candidates();
+                if self.grid.is_complete() && self.grid.validate().is_valid {
+                    self.completed = true;
+                    self.elapsed += self.start_time.elapsed();
+                }
+            }
+            _ if cell.is_filled() => return false,
+            GameMove::ToggleCandidate { value, .. } => {
+                self.grid.cell_mut(pos).toggle_candidate(value);
+            }
+            GameMove::AddCandidate { value, .. } => {
+                self.grid.cell_mut(pos).add_candidate(value);
+            }
+            GameMove::RemoveCandidate { value, .. } => {
+                self.grid.cell_mut(pos).remove_candidate(value);
+            }
+            GameMove::SetCandidates {
+                old_candidates,
+                new_candidates,
+                ..
+            } => {
+                if cell.candidates().as_raw() != old_candidates {
+                    return false;
+                }
+                self.grid
+                    .cell_mut(pos)
+                    .set_candidates(sudoku_core::BitSet::from_raw(new_candidates));
+            }
+        }
+        true
+    }
+
+    /// Each cell's digit, or 0, as an 81-character string
+    pub fn values(&self) -> String {
+        (0..81)
+            .map(|i| {
+                let value = self.grid.cell(Position::new(i / 9, i % 9)).value();
+                char::from(b'0' + value.unwrap_or(0))
+            })
+            .collect()
+    }
+
+    /// Each cell's notes as a bit set
+    pub fn notes(&self) -> Vec<u16> {
+        (0..81)
+            .map(|i| {
+                self.grid
+                    .cell(Position::new(i / 9, i % 9))
+                    .candidates()
+                    .as_raw()
+            })
+            .collect()
+    }
+
+    /// Replace the board with a teammate's copy, from [`Game::values`] and
+    /// [`Game::notes`]. The undo history is kept; moves that no longer fit
+    /// are turned down by the host.
+    pub fn load_board(&mut self, values: &str, notes: &[u16]) {
+        for (i, ch) in values.chars().take(81).enumerate() {
+            let pos = Position::new(i / 9, i % 9);
+            if self.grid.cell(pos).is_given() {
+                continue;
+            }
+            let value = ch.to_digit(10).filter(|&d| d > 0).map(|d| d as u8);
+            self.grid.set_cell_unchecked(pos, value);
+        }
+        for (i, &raw) in notes.iter().take(81).enumerate() {
+            let pos = Position::new(i / 9, i % 9);
+            if !self.grid.cell(pos).is_given() && !self.grid.cell(pos).is_filled() {
+                self.grid
+                    .cell_mut(pos)
+                    .set_candidates(sudoku_core::BitSet::from_raw(raw));
+            }
+        }
+        if self.grid.is_complete() && self.grid.validate().is_valid && !self.completed {
+            self.completed = true;
+            self.elapsed += self.start_time.elapsed();
+        }
+    }
+
+    /// Use the team's mistake count
+    pub fn set_mistakes(&mut self, mistakes: usize) {
+        self.mistakes = mistakes;
+    }
+
+    /// Use the team's clock
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        self.start_time = Instant::now();
+    }
+
     /// Serialize the game state for saving
     pub fn serialize(&self) -> String {
         let state = SaveState {
@@ -852,6 +1158,8 @@ impl Game {
             notes_used: false,         // Reset for loaded game
             seed: None,                // Can't restore seed from save
             se_rating: 0.0,            // Can't restore SE rating from save
+            move_log: Vec::new(),      // Can't restore move log from save
+            outgoing: None,
         })
     }
 }
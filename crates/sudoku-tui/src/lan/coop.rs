@@ -0,0 +1,672 @@
+//! Cooperative play: several players fill in one board together. The host
+//! keeps the board of record and applies moves in the order they arrive. A
+//! move made against a cell that has changed since (a teammate got there
+//! first) is turned down and its sender gets the whole board again.
+//! Mistakes and the clock are shared; each player undoes only their own
+//! moves, and the host tells an undo or redo from a new move by keeping
+//! each player's history itself.
+
+use super::{Connection, LobbyView, Room, RoomEvent, Seats, Server, PROTOCOL_VERSION};
+use crate::app::MAX_MISTAKES;
+use crate::game::{Game, GameMove};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+use sudoku_core::Position;
+
+/// Messages from a player to the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToHost {
+    Hello {
+        version: u32,
+        code: String,
+        name: String,
+        /// Proves the sender is the hosting player, who may start the game
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Start,
+    Cursor {
+        pos: Position,
+    },
+    Move {
+        game_move: GameMove,
+    },
+    /// Send the whole board again
+    Resync,
+}
+
+/// Messages from the host to players
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToPlayer {
+    Welcome {
+        player: usize,
+    },
+    Rejected {
+        reason: String,
+    },
+    Lobby {
+        players: Vec<Teammate>,
+    },
+    /// The whole board: sent at the start, to late joiners and after a
+    /// conflict
+    Board {
+        /// Short code, or the puzzle string if it has none
+        puzzle: String,
+        /// Each cell's digit or 0, see [`Game::values`]
+        values: String,
+        notes: Vec<u16>,
+        mistakes: usize,
+        elapsed_secs: u64,
+    },
+    Moved {
+        player: usize,
+        game_move: GameMove,
+        /// The team's mistakes after this move
+        mistakes: usize,
+    },
+    /// The sender's last move was turned down; a board follows
+    Conflict,
+    Cursor {
+        player: usize,
+        pos: Position,
+    },
+    Over {
+        won: bool,
+        time_secs: u64,
+    },
+    Left {
+        player: usize,
+    },
+}
+
+/// One player as everyone sees them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Teammate {
+    pub id: usize,
+    pub name: String,
+    /// Index into the cursor colors, in order of joining
+    pub color: usize,
+    #[serde(default)]
+    pub cursor: Option<Position>,
+}
+
+/// What happened on the host since the last poll, for the UI to announce
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoopEvent {
+    /// The lobby changed
+    Lobby,
+    /// The game began (or was joined) and the board was replaced
+    Started,
+    Joined(String),
+    /// One of our moves lost to a teammate's
+    Conflict,
+    /// The host called the game
+    Over {
+        won: bool,
+    },
+    Left(String),
+    /// The host turned us away
+    Rejected(String),
+    Disconnected,
+}
+
+/// One player's moves as the host accepted them
+#[derive(Default)]
+struct History {
+    done: Vec<GameMove>,
+    undone: Vec<GameMove>,
+}
+
+impl History {
+    /// Note an accepted move. Returns false if it takes back the player's
+    /// last move or puts back the one they last took back, which never
+    /// counts as a mistake.
+    fn record(&mut self, game_move: &GameMove) -> bool {
+        if self.done.last().map(GameMove::inverse).as_ref() == Some(game_move) {
+            self.undone.extend(self.done.pop());
+            false
+        } else if self.undone.last() == Some(game_move) {
+            self.done.extend(self.undone.pop());
+            false
+        } else {
+            self.done.push(game_move.clone());
+            self.undone.clear();
+            true
+        }
+    }
+}
+
+/// Whether `pos` is a cell; positions from the network aren't trusted
+fn on_board(pos: Position) -> bool {
+    pos.row < 9 && pos.col < 9
+}
+
+/// The host's view of the game
+struct CoopRoom {
+    code: String,
+    token: String,
+    puzzle: String,
+    /// The board of record, once started
+    game: Option<Game>,
+    seats: Seats<Teammate>,
+    /// Each player's accepted moves, by player id
+    histories: HashMap<usize, History>,
+    mistakes: usize,
+    over: bool,
+    next_color: usize,
+}
+
+impl Room for CoopRoom {
+    type Message = ToHost;
+
+    fn handle(&mut self, event: RoomEvent<ToHost>) {
+        match event {
+            RoomEvent::Joined(id, stream) => self.seats.join(id, stream),
+            RoomEvent::Gone(id) => {
+                self.histories.remove(&id);
+                if self.seats.leave(id).is_some() {
+                    self.seats.broadcast(&ToPlayer::Left { player: id });
+                    let players = self.seats.players();
+                    self.seats.broadcast(&ToPlayer::Lobby { players });
+                }
+            }
+            RoomEvent::Message(id, message) => self.message(id, message),
+        }
+    }
+
+    fn close(&mut self) {
+        self.seats.close();
+    }
+}
+
+impl CoopRoom {
+    fn board(&self) -> Option<ToPlayer> {
+        let game = self.game.as_ref()?;
+        Some(ToPlayer::Board {
+            puzzle: self.puzzle.clone(),
+            values: game.values(),
+            notes: game.notes(),
+            mistakes: self.mistakes,
+            elapsed_secs: game.elapsed().as_secs(),
+        })
+    }
+
+    fn message(&mut self, id: usize, message: ToHost) {
+        let Some(seat) = self.seats.get_mut(id) else {
+            return;
+        };
+        if !matches!(message, ToHost::Hello { .. }) && seat.player.is_none() {
+            return;
+        }
+        match message {
+            ToHost::Hello {
+                version,
+                code,
+                name,
+                token,
+            } => {
+                let refusal = super::refusal(version, &code, &self.code)
+                    .or_else(|| self.over.then(|| "the game is over".to_string()));
+                if let Some(reason) = refusal {
+                    self.seats.reject(id, &ToPlayer::Rejected { reason });
+                    return;
+                }
+                if seat.player.is_some() {
+                    return;
+                }
+                seat.player = Some(Teammate {
+                    id,
+                    name: super::display_name(&name, id),
+                    color: self.next_color,
+                    cursor: None,
+                });
+                seat.host = token.as_deref() == Some(self.token.as_str());
+                self.next_color += 1;
+                self.seats.send(id, &ToPlayer::Welcome { player: id });
+                let players = self.seats.players();
+                self.seats.broadcast(&ToPlayer::Lobby { players });
+                // Late joiners start from the board as it is
+                if let Some(board) = self.board() {
+                    self.seats.send(id, &board);
+                }
+            }
+            ToHost::Start if seat.host && self.game.is_none() => {
+                self.game = Game::from_code(&self.puzzle);
+                if let Some(board) = self.board() {
+                    self.seats.broadcast(&board);
+                }
+            }
+            ToHost::Cursor { pos } if on_board(pos) => {
+                if let Some(player) = seat.player.as_mut() {
+                    player.cursor = Some(pos);
+                }
+                self.seats.broadcast(&ToPlayer::Cursor { player: id, pos });
+            }
+            ToHost::Move { game_move } if !self.over && game_move.is_valid() => {
+                let Some(game) = self.game.as_mut() else {
+                    return;
+                };
+                if !game.apply_move(&game_move) {
+                    // Someone got there first; put the sender straight
+                    self.seats.send(id, &ToPlayer::Conflict);
+                    if let Some(board) = self.board() {
+                        self.seats.send(id, &board);
+                    }
+                    return;
+                }
+                let new = self.histories.entry(id).or_default().record(&game_move);
+                if new && game.is_mistake(&game_move) {
+                    self.mistakes += 1;
+                    game.set_mistakes(self.mistakes);
+                }
+                let (won, time_secs) = (game.is_completed(), game.elapsed().as_secs());
+                self.seats.broadcast(&ToPlayer::Moved {
+                    player: id,
+                    game_move,
+                    mistakes: self.mistakes,
+                });
+                if won || self.mistakes >= MAX_MISTAKES {
+                    self.over = true;
+                    self.seats.broadcast(&ToPlayer::Over { won, time_secs });
+                }
+            }
+            ToHost::Resync => {
+                if let Some(board) = self.board() {
+                    self.seats.send(id, &board);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Listen on `port` (0 for any free one) and host a shared board of
+/// `puzzle`, a puzzle string or short code
+fn host(port: u16, puzzle: &str) -> io::Result<Server<ToHost>> {
+    let game = Game::from_code(puzzle)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid puzzle"))?;
+    let code = super::lobby_code();
+    let token = super::host_token();
+    let room = CoopRoom {
+        code: code.clone(),
+        token: token.clone(),
+        puzzle: game
+            .short_code()
+            .unwrap_or_else(|| game.original_puzzle().to_string()),
+        game: None,
+        seats: Seats::default(),
+        histories: HashMap::new(),
+        mistakes: 0,
+        over: false,
+        next_color: 0,
+    };
+    Server::start(port, code, token, room)
+}
+
+/// A player's side of a shared board
+pub struct Coop {
+    connection: Connection<ToPlayer>,
+    /// The server, when this player is hosting
+    host: Option<Server<ToHost>>,
+    pub code: String,
+    /// Our player id, once the host has let us in
+    pub me: Option<usize>,
+    pub team: Vec<Teammate>,
+    pub started: bool,
+    /// Whether the team won, once the host has called the game
+    pub over: Option<bool>,
+    /// Cursor last sent
+    cursor: Option<Position>,
+    /// The shared puzzle, once started
+    pub puzzle: Option<String>,
+}
+
+impl Coop {
+    /// Host a shared board of `puzzle` and join it
+    pub fn host(port: u16, puzzle: &str, name: &str) -> io::Result<Self> {
+        let host = host(port, puzzle)?;
+        let mut coop = Self::connect(
+            &format!("127.0.0.1:{}", host.port),
+            &host.code,
+            name,
+            Some(host.token.clone()),
+        )?;
+        coop.host = Some(host);
+        Ok(coop)
+    }
+
+    /// Join the game at `addr` with the lobby code the host gave out
+    pub fn join(addr: &str, code: &str, name: &str) -> io::Result<Self> {
+        Self::connect(addr, code, name, None)
+    }
+
+    fn connect(addr: &str, code: &str, name: &str, token: Option<String>) -> io::Result<Self> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&ToHost::Hello {
+            version: PROTOCOL_VERSION,
+            code: code.to_ascii_uppercase(),
+            name: name.to_string(),
+            token,
+        })?;
+        Ok(Self {
+            connection,
+            host: None,
+            code: code.to_ascii_uppercase(),
+            me: None,
+            team: Vec::new(),
+            started: false,
+            over: None,
+            cursor: None,
+            puzzle: None,
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.host.is_some()
+    }
+
+    pub fn lobby(&self) -> LobbyView {
+        LobbyView {
+            mode: "coop",
+            name: "Co-op",
+            code: self.code.clone(),
+            address: self.host.as_ref().map(|host| super::address(host.port)),
+            players: self
+                .team
+                .iter()
+                .map(|t| (t.name.clone(), Some(t.id) == self.me))
+                .collect(),
+            host: self.is_host(),
+        }
+    }
+
+    /// Begin the game (only the host's request is honored)
+    pub fn start(&mut self) {
+        let _ = self.connection.send(&ToHost::Start);
+    }
+
+    /// Send the moves made on `game` since the last call, and our cursor if
+    /// it moved
+    pub fn send(&mut self, game: &mut Game, cursor: Position) {
+        if !self.started || self.over.is_some() {
+            return;
+        }
+        for game_move in game.take_outgoing() {
+            let _ = self.connection.send(&ToHost::Move { game_move });
+        }
+        if self.cursor != Some(cursor) {
+            self.cursor = Some(cursor);
+            let _ = self.connection.send(&ToHost::Cursor { pos: cursor });
+        }
+    }
+
+    /// Teammates other than us
+    pub fn others(&self) -> impl Iterator<Item = &Teammate> {
+        self.team.iter().filter(move |t| Some(t.id) != self.me)
+    }
+
+    fn name(&self, id: usize) -> String {
+        self.team
+            .iter()
+            .find(|t| t.id == id)
+            .map_or_else(|| format!("Player {}", id + 1), |t| t.name.clone())
+    }
+
+    /// Apply what the host sent since the last call to `game`, which is
+    /// replaced by the shared board when the game starts
+    pub fn poll(&mut self, game: &mut Game) -> Vec<CoopEvent> {
+        let mut events = Vec::new();
+        loop {
+            let message = match self.connection.try_recv() {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(()) => {
+                    events.push(CoopEvent::Disconnected);
+                    break;
+                }
+            };
+            match message {
+                ToPlayer::Welcome { player } => self.me = Some(player),
+                ToPlayer::Rejected { reason } => events.push(CoopEvent::Rejected(reason)),
+                ToPlayer::Lobby { players } => {
+                    if self.started {
+                        for player in &players {
+                            let new = !self.team.iter().any(|t| t.id == player.id);
+                            if new && Some(player.id) != self.me {
+                                events.push(CoopEvent::Joined(player.name.clone()));
+                            }
+                        }
+                    }
+                    // Keep the cursors we know about
+                    let cursors: Vec<_> = self.team.iter().map(|t| (t.id, t.cursor)).collect();
+                    self.team = players;
+                    for teammate in &mut self.team {
+                        if let Some(&(_, Some(pos))) =
+                            cursors.iter().find(|(id, _)| *id == teammate.id)
+                        {
+                            teammate.cursor = Some(pos);
+                        }
+                    }
+                    events.push(CoopEvent::Lobby);
+                }
+                ToPlayer::Board {
+                    puzzle,
+                    values,
+                    notes,
+                    mistakes,
+                    elapsed_secs,
+                } => {
+                    if !self.started {
+                        let Some(shared) = Game::from_code(&puzzle) else {
+                            events
+                                .push(CoopEvent::Rejected("couldn't load the puzzle".to_string()));
+                            continue;
+                        };
+                        *game = shared;
+                        game.share_board();
+                        self.puzzle = Some(game.original_puzzle().to_string());
+                        self.started = true;
+                        events.push(CoopEvent::Started);
+                    }
+                    game.load_board(&values, &notes);
+                    game.set_mistakes(mistakes);
+                    game.set_elapsed(Duration::from_secs(elapsed_secs));
+                }
+                ToPlayer::Moved {
+                    player,
+                    game_move,
+                    mistakes,
+                } => {
+                    // Our own moves are already on the board
+                    if Some(player) != self.me && !game.apply_move(&game_move) {
+                        let _ = self.connection.send(&ToHost::Resync);
+                    }
+                    game.set_mistakes(mistakes);
+                }
+                ToPlayer::Conflict => events.push(CoopEvent::Conflict),
+                ToPlayer::Cursor { player, pos } => {
+                    if let Some(teammate) = self.team.iter_mut().find(|t| t.id == player) {
+                        teammate.cursor = Some(pos);
+                    }
+                }
+                ToPlayer::Over { won, time_secs } => {
+                    self.over = Some(won);
+                    game.set_elapsed(Duration::from_secs(time_secs));
+                    events.push(CoopEvent::Over { won });
+                }
+                ToPlayer::Left { player } => {
+                    events.push(CoopEvent::Left(self.name(player)));
+                    self.team.retain(|t| t.id != player);
+                }
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    /// Poll until `done` accepts the state, or give up after a while
+    fn wait(
+        coop: &mut Coop,
+        game: &mut Game,
+        mut done: impl FnMut(&Coop, &Game, &[CoopEvent]) -> bool,
+    ) -> bool {
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            events.extend(coop.poll(game));
+            if done(coop, game, &events) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    fn pos(row: usize, col: usize) -> Position {
+        Position::new(row, col)
+    }
+
+    #[test]
+    fn test_shared_board_over_localhost() {
+        let mut host = Coop::host(0, PUZZLE, "alice").unwrap();
+        let addr = format!("127.0.0.1:{}", host.host.as_ref().unwrap().port);
+        let mut guest = Coop::join(&addr, &host.code.clone(), "bob").unwrap();
+        let (mut a, mut b) = (
+            Game::from_code(PUZZLE).unwrap(),
+            Game::from_code(PUZZLE).unwrap(),
+        );
+        assert!(wait(&mut host, &mut a, |c, _, _| c.team.len() == 2));
+
+        host.start();
+        assert!(wait(&mut host, &mut a, |c, _, _| c.started));
+        assert!(wait(&mut guest, &mut b, |c, _, e| c.started
+            && e.contains(&CoopEvent::Started)));
+
+        // A right digit and a note reach the teammate, with the cursor
+        a.set_value(pos(0, 2), 4);
+        a.toggle_candidate(pos(0, 3), 6);
+        host.send(&mut a, pos(0, 3));
+        let (values, notes) = (a.values(), a.notes());
+        assert!(wait(&mut guest, &mut b, |c, g, _| {
+            g.values() == values
+                && g.notes() == notes
+                && c.others().any(|t| t.cursor == Some(pos(0, 3)))
+        }));
+
+        // Mistakes are pooled
+        b.set_value(pos(0, 5), 1);
+        guest.send(&mut b, pos(0, 5));
+        assert!(wait(&mut host, &mut a, |_, g, _| g.mistakes() == 1
+            && g.grid().cell(pos(0, 5)).value() == Some(1)));
+
+        // Both write the same cell: the host takes the first and resyncs
+        // the other
+        a.clear_cell(pos(0, 5));
+        host.send(&mut a, pos(0, 5));
+        std::thread::sleep(Duration::from_millis(50));
+        b.set_value(pos(0, 5), 8);
+        guest.send(&mut b, pos(0, 5));
+        assert!(wait(&mut guest, &mut b, |_, g, e| e
+            .contains(&CoopEvent::Conflict)
+            && g.grid().cell(pos(0, 5)).value().is_none()));
+
+        // Undo takes back our own last move (the clear), and restoring a
+        // wrong digit isn't a new mistake
+        assert!(a.undo());
+        host.send(&mut a, pos(0, 5));
+        assert!(wait(&mut guest, &mut b, |_, g, _| g
+            .grid()
+            .cell(pos(0, 5))
+            .value()
+            == Some(1)));
+        assert_eq!(b.mistakes(), 1);
+        assert_eq!(b.grid().cell(pos(0, 2)).value(), Some(4));
+    }
+
+    #[test]
+    fn test_host_keeps_each_players_history() {
+        let place = GameMove::SetValue {
+            pos: pos(0, 5),
+            old_value: None,
+            new_value: Some(1),
+        };
+        let mut history = History::default();
+        assert!(history.record(&place));
+        assert!(!history.record(&place.inverse()));
+        assert!(!history.record(&place));
+        assert!(!history.record(&place.inverse()));
+        // Another move in between makes the same digit count again
+        let note = GameMove::ToggleCandidate {
+            pos: pos(1, 1),
+            value: 2,
+        };
+        assert!(history.record(&note));
+        assert!(history.record(&place));
+    }
+
+    #[test]
+    fn test_moves_off_the_board_are_ignored() {
+        let mut host = Coop::host(0, PUZZLE, "alice").unwrap();
+        let addr = format!("127.0.0.1:{}", host.host.as_ref().unwrap().port);
+        let mut a = Game::from_code(PUZZLE).unwrap();
+        let mut guest = Connection::<ToPlayer>::connect(&addr).unwrap();
+        guest
+            .send(&ToHost::Hello {
+                version: PROTOCOL_VERSION,
+                code: host.code.clone(),
+                name: "mallory".to_string(),
+                token: None,
+            })
+            .unwrap();
+        assert!(wait(&mut host, &mut a, |c, _, _| c.team.len() == 2));
+        host.start();
+        assert!(wait(&mut host, &mut a, |c, _, _| c.started));
+
+        let bad = [
+            GameMove::SetValue {
+                pos: pos(9, 0),
+                old_value: None,
+                new_value: Some(1),
+            },
+            GameMove::SetValue {
+                pos: pos(0, 2),
+                old_value: None,
+                new_value: Some(12),
+            },
+            GameMove::AddCandidate {
+                pos: pos(0, 2),
+                value: 0,
+            },
+        ];
+        for game_move in bad {
+            assert!(!game_move.is_valid());
+            guest.send(&ToHost::Move { game_move }).unwrap();
+        }
+        guest.send(&ToHost::Cursor { pos: pos(4, 200) }).unwrap();
+
+        // The host is still up and applies the next real move
+        let good = GameMove::SetValue {
+            pos: pos(0, 2),
+            old_value: None,
+            new_value: Some(4),
+        };
+        guest.send(&ToHost::Move { game_move: good }).unwrap();
+        assert!(wait(&mut host, &mut a, |c, g, _| g
+            .grid()
+            .cell(pos(0, 2))
+            .value()
+            == Some(4)
+            && c.others().all(|t| t.cursor.is_none())));
+        assert_eq!(a.mistakes(), 0);
+    }
+}
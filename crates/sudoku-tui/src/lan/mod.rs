@@ -0,0 +1,362 @@
+//! Playing with others on the local network.
+//!
+//! Games are hosted by one player's TUI: it runs a small TCP server and then
+//! joins it over localhost like everyone else, so host and guests share one
+//! code path. Messages are JSON, one per line. A lobby is protected by a
+//! short code the host reads out; guests join with the host's address and
+//! that code.
+
+pub mod coop;
+pub mod race;
+
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::Duration;
+
+/// Bumped when messages change incompatibly
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Port a host listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Letters for lobby codes, without ones easily misheard or misread
+const CODE_ALPHABET: &[u8] = b"ACDEFHJKLMNPRTUVWXY";
+
+/// Longest player name shown to others
+const MAX_NAME: usize = 16;
+
+/// Longest message line accepted; a peer sending more is hung up on
+const MAX_LINE: usize = 64 * 1024;
+
+/// How long the host waits on one peer's socket before hanging up on it, so
+/// a player who stops reading can't stall the room
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A new four-letter lobby code
+pub fn lobby_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..4)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Why a player saying hello can't join the lobby `code`, if they can't
+pub fn refusal(version: u32, code: &str, lobby: &str) -> Option<String> {
+    if version != PROTOCOL_VERSION {
+        Some("different game version".to_string())
+    } else if !code.eq_ignore_ascii_case(lobby) {
+        Some("wrong lobby code".to_string())
+    } else {
+        None
+    }
+}
+
+/// A player's name as others see it: trimmed, shortened, never blank
+pub fn display_name(name: &str, id: usize) -> String {
+    let name: String = name.trim().chars().take(MAX_NAME).collect();
+    if name.is_empty() {
+        format!("Player {}", id + 1)
+    } else {
+        name
+    }
+}
+
+/// What the lobby screen shows, for any kind of LAN game
+pub struct LobbyView {
+    /// Subcommand others join with, e.g. "race"
+    pub mode: &'static str,
+    /// What the game is called on screen, e.g. "Race"
+    pub name: &'static str,
+    pub code: String,
+    /// Where others should connect, when hosting
+    pub address: Option<String>,
+    /// Names, with ours flagged
+    pub players: Vec<(String, bool)>,
+    pub host: bool,
+}
+
+/// This machine's address on the local network, for telling guests where to
+/// connect. Nothing is sent; connecting a UDP socket only picks a route.
+fn local_ip() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+/// `ip:port` others should connect to for a host listening on `port`
+pub fn address(port: u16) -> String {
+    let ip = local_ip().unwrap_or_else(|| "<this computer>".to_string());
+    format!("{}:{}", ip, port)
+}
+
+/// Write one message as a JSON line
+pub fn send_line<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Read JSON lines from `stream` on a background thread, handing each
+/// message to `deliver`, then `None` once the stream closes. Lines that
+/// don't parse are skipped; one longer than [`MAX_LINE`] closes the stream.
+/// Stops early if `deliver` returns false.
+pub fn spawn_reader<T, F>(stream: TcpStream, mut deliver: F)
+where
+    T: DeserializeOwned,
+    F: FnMut(Option<T>) -> bool + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let limit = MAX_LINE as u64 + 1;
+            match (&mut reader).take(limit).read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) if !line.ends_with('\n') && line.len() > MAX_LINE => {
+                    let _ = reader.get_ref().shutdown(Shutdown::Both);
+                    break;
+                }
+                Ok(_) => {}
+            }
+            if let Ok(message) = serde_json::from_str(&line) {
+                if !deliver(Some(message)) {
+                    return;
+                }
+            }
+        }
+        deliver(None);
+    });
+}
+
+/// Something for a host's room thread to deal with; connections are
+/// numbered in the order they arrive
+pub enum RoomEvent<T> {
+    Joined(usize, TcpStream),
+    Message(usize, T),
+    Gone(usize),
+}
+
+/// A game's rules on the host. It sees every connection and message, in
+/// order, on one thread.
+pub trait Room: Send + 'static {
+    type Message: DeserializeOwned + Send + 'static;
+
+    fn handle(&mut self, event: RoomEvent<Self::Message>);
+
+    /// The host is shutting down
+    fn close(&mut self);
+}
+
+/// A lobby server, listening until dropped
+pub struct Server<T> {
+    pub port: u16,
+    pub code: String,
+    /// Proves a player is the host's own, who may start the game
+    pub token: String,
+    events: Sender<Option<RoomEvent<T>>>,
+}
+
+impl<T: DeserializeOwned + Send + 'static> Server<T> {
+    /// Listen on `port` (0 for any free one) and run `room`, which checks
+    /// hellos against `code` and `token`
+    pub fn start<R>(port: u16, code: String, token: String, mut room: R) -> io::Result<Self>
+    where
+        R: Room<Message = T>,
+    {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let port = listener.local_addr()?.port();
+        let (events, rx) = mpsc::channel::<Option<RoomEvent<T>>>();
+
+        std::thread::spawn(move || {
+            while let Ok(Some(event)) = rx.recv() {
+                room.handle(event);
+            }
+            room.close();
+        });
+
+        let accept = events.clone();
+        std::thread::spawn(move || {
+            for (id, stream) in listener.incoming().enumerate() {
+                let Ok(stream) = stream else { continue };
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+                let _ = stream.set_nodelay(true);
+                if accept.send(Some(RoomEvent::Joined(id, stream))).is_err() {
+                    return;
+                }
+                let events = accept.clone();
+                spawn_reader(reader, move |message| {
+                    let event = match message {
+                        Some(message) => RoomEvent::Message(id, message),
+                        None => RoomEvent::Gone(id),
+                    };
+                    events.send(Some(event)).is_ok()
+                });
+            }
+        });
+
+        Ok(Self {
+            port,
+            code,
+            token,
+            events,
+        })
+    }
+}
+
+impl<T> Drop for Server<T> {
+    fn drop(&mut self) {
+        // Close every connection; the listener thread ends with the process
+        let _ = self.events.send(None);
+    }
+}
+
+/// A fresh host token
+pub fn host_token() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// A connection to the host; `player` is set once they've said hello
+pub struct Seat<P> {
+    stream: TcpStream,
+    pub player: Option<P>,
+    pub host: bool,
+}
+
+impl<P> Seat<P> {
+    /// Send `message`, hanging up if the peer is gone or too slow to read
+    /// it. Either way the reader then reports the seat `Gone`.
+    fn send<M: Serialize>(&mut self, message: &M) {
+        if send_line(&mut self.stream, message).is_err() {
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Everyone connected to a host
+pub struct Seats<P> {
+    seats: Vec<(usize, Seat<P>)>,
+}
+
+impl<P> Default for Seats<P> {
+    fn default() -> Self {
+        Self { seats: Vec::new() }
+    }
+}
+
+impl<P: Clone> Seats<P> {
+    pub fn join(&mut self, id: usize, stream: TcpStream) {
+        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+        let seat = Seat {
+            stream,
+            player: None,
+            host: false,
+        };
+        self.seats.push((id, seat));
+    }
+
+    /// Forget a closed connection, returning the player if they had joined
+    pub fn leave(&mut self, id: usize) -> Option<P> {
+        let index = self.seats.iter().position(|(seat_id, _)| *seat_id == id)?;
+        self.seats.remove(index).1.player
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Seat<P>> {
+        self.seats
+            .iter_mut()
+            .find(|(seat_id, _)| *seat_id == id)
+            .map(|(_, seat)| seat)
+    }
+
+    /// Everyone who has joined, in order of arrival
+    pub fn players(&self) -> Vec<P> {
+        self.seats
+            .iter()
+            .filter_map(|(_, seat)| seat.player.clone())
+            .collect()
+    }
+
+    pub fn send<M: Serialize>(&mut self, id: usize, message: &M) {
+        if let Some(seat) = self.get_mut(id) {
+            seat.send(message);
+        }
+    }
+
+    /// Send to everyone who has joined
+    pub fn broadcast<M: Serialize>(&mut self, message: &M) {
+        for (_, seat) in &mut self.seats {
+            if seat.player.is_some() {
+                seat.send(message);
+            }
+        }
+    }
+
+    /// Turn a connection away with `message`
+    pub fn reject<M: Serialize>(&mut self, id: usize, message: &M) {
+        if let Some(seat) = self.get_mut(id) {
+            let _ = send_line(&mut seat.stream, message);
+            let _ = seat.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Hang up on everyone
+    pub fn close(&mut self) {
+        for (_, seat) in &self.seats {
+            let _ = seat.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// A player's connection to a host, read without blocking the UI
+pub struct Connection<T> {
+    stream: TcpStream,
+    incoming: Receiver<T>,
+}
+
+impl<T: DeserializeOwned + Send + 'static> Connection<T> {
+    /// Connect to `addr` (`host:port`; the default port if none is given)
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let addr = if addr.contains(':') {
+            addr.to_string()
+        } else {
+            format!("{}:{}", addr, DEFAULT_PORT)
+        };
+        let target = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such host"))?;
+        let stream = TcpStream::connect_timeout(&target, Duration::from_secs(5))?;
+        stream.set_nodelay(true)?;
+        let (tx, incoming) = mpsc::channel();
+        spawn_reader(stream.try_clone()?, move |message| match message {
+            Some(message) => tx.send(message).is_ok(),
+            None => false,
+        });
+        Ok(Self { stream, incoming })
+    }
+
+    pub fn send<M: Serialize>(&mut self, message: &M) -> io::Result<()> {
+        send_line(&mut self.stream, message)
+    }
+
+    /// The next message if one has arrived; `Err` once the host is gone
+    pub fn try_recv(&self) -> Result<Option<T>, ()> {
+        match self.incoming.try_recv() {
+            Ok(message) => Ok(Some(message)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(()),
+        }
+    }
+}
+
+impl<T> Drop for Connection<T> {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+}
@@ -0,0 +1,579 @@
+//! Head-to-head races: everyone gets the same puzzle and sees how far the
+//! others are (cells filled, mistakes) but not their digits. The host checks
+//! each finished grid against the solution and hands out places, so the
+//! first valid completion wins.
+
+use super::{Connection, LobbyView, Room, RoomEvent, Seats, Server, PROTOCOL_VERSION};
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::time::Instant;
+use sudoku_core::{Grid, Solver};
+
+/// Messages from a player to the host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToHost {
+    Hello {
+        version: u32,
+        code: String,
+        name: String,
+        /// Proves the sender is the hosting player, who may start the race
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Start,
+    Progress {
+        filled: usize,
+        mistakes: usize,
+    },
+    /// The completed grid, 81 digits
+    Finished {
+        grid: String,
+    },
+    /// Out of mistakes
+    Out,
+}
+
+/// Messages from the host to players
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToPlayer {
+    Welcome {
+        player: usize,
+    },
+    Rejected {
+        reason: String,
+    },
+    Lobby {
+        players: Vec<Racer>,
+    },
+    Start {
+        /// Short code, or the puzzle string if it has none
+        puzzle: String,
+        players: Vec<Racer>,
+    },
+    Progress {
+        player: usize,
+        filled: usize,
+        mistakes: usize,
+    },
+    Placed {
+        player: usize,
+        place: usize,
+        time_secs: u64,
+    },
+    /// The grid sent as finished isn't the solution; keep racing
+    NotFinished {
+        reason: String,
+    },
+    Out {
+        player: usize,
+    },
+    Left {
+        player: usize,
+    },
+}
+
+/// One player as everyone sees them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Racer {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub filled: usize,
+    #[serde(default)]
+    pub mistakes: usize,
+    #[serde(default)]
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    #[default]
+    Racing,
+    Finished {
+        place: usize,
+        time_secs: u64,
+    },
+    Out,
+    Left,
+}
+
+/// What happened on the host since the last poll, for the UI to announce
+#[derive(Debug, Clone, PartialEq)]
+pub enum RaceEvent {
+    /// The lobby changed
+    Lobby,
+    /// The race began on this puzzle
+    Started(String),
+    Placed {
+        name: String,
+        place: usize,
+        me: bool,
+    },
+    /// The host didn't accept our finished grid
+    NotFinished(String),
+    Out(String),
+    Left(String),
+    /// The host turned us away
+    Rejected(String),
+    Disconnected,
+}
+
+/// The host's view of the race
+struct RaceRoom {
+    code: String,
+    token: String,
+    puzzle: String,
+    solution: String,
+    seats: Seats<Racer>,
+    started: Option<Instant>,
+    places: usize,
+}
+
+impl Room for RaceRoom {
+    type Message = ToHost;
+
+    fn handle(&mut self, event: RoomEvent<ToHost>) {
+        match event {
+            RoomEvent::Joined(id, stream) => self.seats.join(id, stream),
+            RoomEvent::Gone(id) => {
+                if self.seats.leave(id).is_some() {
+                    self.seats.broadcast(&ToPlayer::Left { player: id });
+                    if self.started.is_none() {
+                        let players = self.seats.players();
+                        self.seats.broadcast(&ToPlayer::Lobby { players });
+                    }
+                }
+            }
+            RoomEvent::Message(id, message) => self.message(id, message),
+        }
+    }
+
+    fn close(&mut self) {
+        self.seats.close();
+    }
+}
+
+impl RaceRoom {
+    fn message(&mut self, id: usize, message: ToHost) {
+        let started = self.started;
+        let Some(seat) = self.seats.get_mut(id) else {
+            return;
+        };
+        match message {
+            ToHost::Hello {
+                version,
+                code,
+                name,
+                token,
+            } => {
+                let refusal = super::refusal(version, &code, &self.code)
+                    .or_else(|| started.map(|_| "the race has already started".to_string()));
+                if let Some(reason) = refusal {
+                    self.seats.reject(id, &ToPlayer::Rejected { reason });
+                    return;
+                }
+                if seat.player.is_some() {
+                    return;
+                }
+                seat.player = Some(Racer {
+                    id,
+                    name: super::display_name(&name, id),
+                    filled: 0,
+                    mistakes: 0,
+                    status: Status::Racing,
+                });
+                seat.host = token.as_deref() == Some(self.token.as_str());
+                self.seats.send(id, &ToPlayer::Welcome { player: id });
+                let players = self.seats.players();
+                self.seats.broadcast(&ToPlayer::Lobby { players });
+            }
+            ToHost::Start if seat.host && started.is_none() => {
+                self.started = Some(Instant::now());
+                let message = ToPlayer::Start {
+                    puzzle: self.puzzle.clone(),
+                    players: self.seats.players(),
+                };
+                self.seats.broadcast(&message);
+            }
+            ToHost::Progress { filled, mistakes } if started.is_some() => {
+                let Some(racer) = seat.player.as_mut().filter(|r| r.status == Status::Racing)
+                else {
+                    return;
+                };
+                racer.filled = filled;
+                racer.mistakes = mistakes;
+                self.seats.broadcast(&ToPlayer::Progress {
+                    player: id,
+                    filled,
+                    mistakes,
+                });
+            }
+            ToHost::Finished { grid } => {
+                let Some(start) = started else { return };
+                let Some(racer) = seat.player.as_mut().filter(|r| r.status == Status::Racing)
+                else {
+                    return;
+                };
+                if grid != self.solution {
+                    let reason = "that grid isn't the solution".to_string();
+                    self.seats.send(id, &ToPlayer::NotFinished { reason });
+                    return;
+                }
+                let place = self.places + 1;
+                let time_secs = start.elapsed().as_secs();
+                racer.status = Status::Finished { place, time_secs };
+                self.places = place;
+                self.seats.broadcast(&ToPlayer::Placed {
+                    player: id,
+                    place,
+                    time_secs,
+                });
+            }
+            ToHost::Out if started.is_some() => {
+                let Some(racer) = seat.player.as_mut().filter(|r| r.status == Status::Racing)
+                else {
+                    return;
+                };
+                racer.status = Status::Out;
+                self.seats.broadcast(&ToPlayer::Out { player: id });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Listen on `port` (0 for any free one) and host a race on `puzzle`, a
+/// puzzle string or short code
+fn host(port: u16, puzzle: &str) -> io::Result<Server<ToHost>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid puzzle");
+    let game = Game::from_code(puzzle).ok_or_else(invalid)?;
+    let grid = Grid::from_string(game.original_puzzle()).ok_or_else(invalid)?;
+    let solution = Solver::new().solve(&grid).ok_or_else(invalid)?;
+
+    let code = super::lobby_code();
+    let token = super::host_token();
+    let room = RaceRoom {
+        code: code.clone(),
+        token: token.clone(),
+        puzzle: game
+            .short_code()
+            .unwrap_or_else(|| game.original_puzzle().to_string()),
+        solution: solution.to_string_compact(),
+        seats: Seats::default(),
+        started: None,
+        places: 0,
+    };
+    Server::start(port, code, token, room)
+}
+
+/// A player's side of a race
+pub struct Race {
+    connection: Connection<ToPlayer>,
+    /// The server, when this player is hosting
+    host: Option<Server<ToHost>>,
+    pub code: String,
+    /// Our player id, once the host has let us in
+    pub me: Option<usize>,
+    pub racers: Vec<Racer>,
+    pub started: bool,
+    /// The puzzle being raced, as loaded by the app
+    pub puzzle: Option<String>,
+    /// Progress last sent, so only changes go out
+    sent: (usize, usize),
+    /// Finished here; waiting for the host to confirm a place
+    pub awaiting_place: bool,
+}
+
+impl Race {
+    /// Host a race on `puzzle` and join it
+    pub fn host(port: u16, puzzle: &str, name: &str) -> io::Result<Self> {
+        let host = host(port, puzzle)?;
+        let mut race = Self::connect(
+            &format!("127.0.0.1:{}", host.port),
+            &host.code,
+            name,
+            Some(host.token.clone()),
+        )?;
+        race.host = Some(host);
+        Ok(race)
+    }
+
+    /// Join the race at `addr` with the lobby code the host gave out
+    pub fn join(addr: &str, code: &str, name: &str) -> io::Result<Self> {
+        Self::connect(addr, code, name, None)
+    }
+
+    fn connect(addr: &str, code: &str, name: &str, token: Option<String>) -> io::Result<Self> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&ToHost::Hello {
+            version: PROTOCOL_VERSION,
+            code: code.to_ascii_uppercase(),
+            name: name.to_string(),
+            token,
+        })?;
+        Ok(Self {
+            connection,
+            host: None,
+            code: code.to_ascii_uppercase(),
+            me: None,
+            racers: Vec::new(),
+            started: false,
+            puzzle: None,
+            sent: (0, 0),
+            awaiting_place: false,
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.host.is_some()
+    }
+
+    pub fn lobby(&self) -> LobbyView {
+        LobbyView {
+            mode: "race",
+            name: "Race",
+            code: self.code.clone(),
+            address: self.host.as_ref().map(|host| super::address(host.port)),
+            players: self
+                .racers
+                .iter()
+                .map(|r| (r.name.clone(), Some(r.id) == self.me))
+                .collect(),
+            host: self.is_host(),
+        }
+    }
+
+    /// Begin the race (only the host's request is honored)
+    pub fn start(&mut self) {
+        let _ = self.connection.send(&ToHost::Start);
+    }
+
+    /// Report progress if it changed since last time
+    pub fn progress(&mut self, filled: usize, mistakes: usize) {
+        if self.started && self.sent != (filled, mistakes) {
+            self.sent = (filled, mistakes);
+            let _ = self.connection.send(&ToHost::Progress { filled, mistakes });
+        }
+    }
+
+    /// Claim a finish with the completed grid
+    pub fn finished(&mut self, grid: &str) {
+        self.awaiting_place = true;
+        let _ = self.connection.send(&ToHost::Finished {
+            grid: grid.to_string(),
+        });
+    }
+
+    pub fn out(&mut self) {
+        let _ = self.connection.send(&ToHost::Out);
+    }
+
+    /// Our racer entry
+    pub fn mine(&self) -> Option<&Racer> {
+        self.racers.iter().find(|r| Some(r.id) == self.me)
+    }
+
+    pub fn my_place(&self) -> Option<usize> {
+        match self.mine()?.status {
+            Status::Finished { place, .. } => Some(place),
+            _ => None,
+        }
+    }
+
+    fn racer(&mut self, id: usize) -> Option<&mut Racer> {
+        self.racers.iter_mut().find(|r| r.id == id)
+    }
+
+    fn name(&self, id: usize) -> String {
+        self.racers
+            .iter()
+            .find(|r| r.id == id)
+            .map_or_else(|| format!("Player {}", id + 1), |r| r.name.clone())
+    }
+
+    /// Apply what the host sent since the last call
+    pub fn poll(&mut self) -> Vec<RaceEvent> {
+        let mut events = Vec::new();
+        loop {
+            let message = match self.connection.try_recv() {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(()) => {
+                    events.push(RaceEvent::Disconnected);
+                    break;
+                }
+            };
+            match message {
+                ToPlayer::Welcome { player } => self.me = Some(player),
+                ToPlayer::Rejected { reason } => events.push(RaceEvent::Rejected(reason)),
+                ToPlayer::Lobby { players } => {
+                    self.racers = players;
+                    events.push(RaceEvent::Lobby);
+                }
+                ToPlayer::Start { puzzle, players } => {
+                    self.racers = players;
+                    self.started = true;
+                    events.push(RaceEvent::Started(puzzle));
+                }
+                ToPlayer::Progress {
+                    player,
+                    filled,
+                    mistakes,
+                } => {
+                    if let Some(racer) = self.racer(player) {
+                        racer.filled = filled;
+                        racer.mistakes = mistakes;
+                    }
+                }
+                ToPlayer::Placed {
+                    player,
+                    place,
+                    time_secs,
+                } => {
+                    if let Some(racer) = self.racer(player) {
+                        racer.status = Status::Finished { place, time_secs };
+                    }
+                    let me = Some(player) == self.me;
+                    if me {
+                        self.awaiting_place = false;
+                    }
+                    events.push(RaceEvent::Placed {
+                        name: self.name(player),
+                        place,
+                        me,
+                    });
+                }
+                ToPlayer::NotFinished { reason } => {
+                    self.awaiting_place = false;
+                    events.push(RaceEvent::NotFinished(reason));
+                }
+                ToPlayer::Out { player } => {
+                    if let Some(racer) = self.racer(player) {
+                        racer.status = Status::Out;
+                    }
+                    events.push(RaceEvent::Out(self.name(player)));
+                }
+                ToPlayer::Left { player } => {
+                    let name = self.name(player);
+                    if self.started {
+                        if let Some(racer) = self.racer(player) {
+                            racer.status = Status::Left;
+                        }
+                    }
+                    events.push(RaceEvent::Left(name));
+                }
+            }
+        }
+        events
+    }
+}
+
+/// "1st", "2nd", ...
+pub fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    /// Poll until `done` accepts the race's state, or give up after a while
+    fn wait(race: &mut Race, mut done: impl FnMut(&Race, &[RaceEvent]) -> bool) -> bool {
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            events.extend(race.poll());
+            if done(race, &events) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn test_race_over_localhost() {
+        let mut host = Race::host(0, PUZZLE, "alice").unwrap();
+        let addr = format!("127.0.0.1:{}", host.host.as_ref().unwrap().port);
+        assert!(wait(&mut host, |r, _| r.me.is_some()));
+
+        let mut wrong = Race::join(&addr, "ZZZZ", "mallory").unwrap();
+        assert!(wait(&mut wrong, |_, events| events
+            .contains(&RaceEvent::Rejected("wrong lobby code".to_string()))));
+
+        let code = host.code.to_ascii_lowercase();
+        let mut guest = Race::join(&addr, &code, "bob").unwrap();
+        assert!(wait(&mut host, |r, _| r.racers.len() == 2));
+
+        // Guests can't start the race
+        guest.start();
+        std::thread::sleep(Duration::from_millis(50));
+        guest.poll();
+        assert!(!guest.started);
+
+        host.start();
+        assert!(wait(&mut guest, |r, events| r.started
+            && events.contains(&RaceEvent::Started(PUZZLE.to_string()))));
+        assert!(wait(&mut host, |r, _| r.started));
+
+        guest.progress(5, 1);
+        let bob = guest.me.unwrap();
+        assert!(wait(&mut host, |r, _| r.racers.iter().any(|racer| racer
+            .id
+            == bob
+            && racer.filled == 5
+            && racer.mistakes == 1)));
+
+        // A wrong grid doesn't count; the real solution wins
+        let grid = Grid::from_string(PUZZLE).unwrap();
+        let solution = Solver::new().solve(&grid).unwrap().to_string_compact();
+        host.finished(&PUZZLE.replace('0', "1"));
+        assert!(wait(&mut host, |r, events| !r.awaiting_place
+            && events.contains(&RaceEvent::NotFinished(
+                "that grid isn't the solution".to_string()
+            ))));
+        guest.finished(&solution);
+        assert!(wait(&mut host, |_, events| events.contains(
+            &RaceEvent::Placed {
+                name: "bob".to_string(),
+                place: 1,
+                me: false,
+            }
+        )));
+        assert!(wait(&mut guest, |r, _| r.my_place() == Some(1)));
+        assert!(!guest.awaiting_place);
+
+        // Late joiners are turned away
+        let mut late = Race::join(&addr, &code, "carol").unwrap();
+        assert!(wait(&mut late, |_, events| events.contains(
+            &RaceEvent::Rejected("the race has already started".to_string())
+        )));
+
+        drop(guest);
+        assert!(wait(&mut host, |_, events| events
+            .contains(&RaceEvent::Left("bob".to_string()))));
+    }
+
+    #[test]
+    fn test_ordinal() {
+        let places: Vec<String> = [1, 2, 3, 4, 11, 12, 21, 22].map(ordinal).to_vec();
+        assert_eq!(
+            places,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "21st", "22nd"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Leaderboard backend abstraction
 //!
-//! Supports different backends based on environment:
+//! Scores go to the configured server (see `crate::server`), with a local
+//! file as fallback. Other backends are chosen explicitly:
 //! - Local: File-based storage for development
 //! - Test: In-memory mock for testing
 //! - Production: Remote HTTP API
@@ -23,17 +24,6 @@ pub enum Environment {
     Production,
 }
 
-impl Environment {
-    /// Detect environment from SUDOKU_ENV variable
-    pub fn detect() -> Self {
-        match std::env::var("SUDOKU_ENV").as_deref() {
-            Ok("production") | Ok("prod") => Environment::Production,
-            Ok("test") | Ok("testing") => Environment::Test,
-            _ => Environment::Local,
-        }
-    }
-}
-
 /// Leaderboard entry for submission and retrieval
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
@@ -53,6 +43,97 @@ pub struct LeaderboardEntry {
     pub verified: bool,
 }
 
+/// Time window for leaderboard queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Period {
+    #[default]
+    AllTime,
+    /// Since Monday 00:00 UTC
+    Week,
+    /// Since 00:00 UTC
+    Today,
+}
+
+impl Period {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Period::AllTime => "All time",
+            Period::Week => "This week",
+            Period::Today => "Today",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Period::AllTime => Period::Week,
+            Period::Week => Period::Today,
+            Period::Today => Period::AllTime,
+        }
+    }
+
+    /// Earliest timestamp inside the window at time `now` (unix seconds)
+    pub fn since(&self, now: u64) -> Option<u64> {
+        const DAY: u64 = 86_400;
+        let today = now - now % DAY;
+        match self {
+            Period::AllTime => None,
+            Period::Today => Some(today),
+            // 1970-01-01 was a Thursday, so Monday-based weekday is (days + 3) % 7
+            Period::Week => Some(today - ((now / DAY + 3) % 7) * DAY),
+        }
+    }
+}
+
+/// Filter for a leaderboard listing
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LeaderboardQuery {
+    pub difficulty: Option<Difficulty>,
+    /// Only entries for this canonical puzzle hash
+    pub puzzle_hash: Option<String>,
+    /// Only entries with `timestamp >= since`
+    pub since: Option<u64>,
+    pub verified_only: bool,
+}
+
+impl LeaderboardQuery {
+    /// Everyone who solved one puzzle (see `canonical_puzzle_hash_str`)
+    pub fn for_puzzle(puzzle_hash: &str, difficulty: Difficulty) -> Self {
+        Self {
+            difficulty: Some(difficulty),
+            puzzle_hash: Some(puzzle_hash.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, entry: &LeaderboardEntry) -> bool {
+        self.difficulty.is_none_or(|d| entry.difficulty == d)
+            && self
+                .puzzle_hash
+                .as_ref()
+                .is_none_or(|h| entry.puzzle_hash == *h)
+            && self.since.is_none_or(|s| entry.timestamp >= s)
+            && (!self.verified_only || entry.verified)
+    }
+
+    /// Query string parameters understood by the server
+    fn to_params(&self) -> String {
+        let mut params = String::new();
+        if let Some(d) = self.difficulty {
+            params.push_str(&format!("&difficulty={:?}", d));
+        }
+        if let Some(ref hash) = self.puzzle_hash {
+            params.push_str(&format!("&puzzle={}", urlencoding::encode(hash)));
+        }
+        if let Some(since) = self.since {
+            params.push_str(&format!("&since={}", since));
+        }
+        if self.verified_only {
+            params.push_str("&verified=1");
+        }
+        params
+    }
+}
+
 /// Result type for leaderboard operations
 pub type LeaderboardResult<T> = Result<T, LeaderboardError>;
 
@@ -69,8 +150,8 @@ pub enum LeaderboardError {
     StorageError(String),
     /// Score was rejected (e.g., failed anti-bot)
     ScoreRejected(String),
-    /// Rate limited
-    RateLimited,
+    /// Rate limited (seconds from `Retry-After`, if the server sent it)
+    RateLimited(Option<u64>),
     /// Not authenticated
     NotAuthenticated,
 }
@@ -83,7 +164,7 @@ impl std::fmt::Display for LeaderboardError {
             Self::InvalidResponse(e) => write!(f, "Invalid response: {}", e),
             Self::StorageError(e) => write!(f, "Storage error: {}", e),
             Self::ScoreRejected(e) => write!(f, "Score rejected: {}", e),
-            Self::RateLimited => write!(f, "Rate limited, try again later"),
+            Self::RateLimited(_) => write!(f, "Rate limited, try again later"),
             Self::NotAuthenticated => write!(f, "Not authenticated"),
         }
     }
@@ -116,6 +197,53 @@ pub trait LeaderboardBackend: Send + Sync {
         limit: usize,
     ) -> LeaderboardResult<Vec<LeaderboardEntry>>;
 
+    /// One filtered page with ranks relative to the filter.
+    /// The default filters the full listing client-side.
+    fn query_page(
+        &self,
+        query: &LeaderboardQuery,
+        limit: usize,
+        offset: usize,
+    ) -> LeaderboardResult<LeaderboardPage> {
+        let matching: Vec<LeaderboardEntry> = self
+            .get_leaderboard(query.difficulty, usize::MAX, 0)?
+            .into_iter()
+            .filter(|e| query.matches(e))
+            .collect();
+        let total = matching.len();
+        let entries: Vec<LeaderboardEntry> = matching
+            .into_iter()
+            .enumerate()
+            .skip(offset)
+            .take(limit)
+            .map(|(i, mut e)| {
+                e.rank = Some(i + 1);
+                e
+            })
+            .collect();
+        let next_offset = offset + entries.len();
+
+        Ok(LeaderboardPage {
+            entries,
+            total: Some(total),
+            next_offset: (next_offset < total).then_some(next_offset),
+        })
+    }
+
+    /// A player's best rank within a filter
+    fn query_rank(
+        &self,
+        player_name: &str,
+        query: &LeaderboardQuery,
+    ) -> LeaderboardResult<Option<usize>> {
+        Ok(self
+            .get_leaderboard(query.difficulty, usize::MAX, 0)?
+            .iter()
+            .filter(|e| query.matches(e))
+            .position(|e| e.player_name == player_name)
+            .map(|pos| pos + 1))
+    }
+
     /// Check if backend is available/connected
     fn is_available(&self) -> bool;
 
@@ -391,18 +519,89 @@ impl LeaderboardBackend for MockLeaderboard {
 }
 
 // ==================== Remote HTTP Backend ====================
+//
+// Wire format (all bodies are JSON, entries use the `LeaderboardEntry` shape):
+//
+//   POST /api/v1/leaderboard/submit            {"entry": {...}}
+//        -> {"success": true, "message": null, "rank": 12}
+//   GET  /api/v1/leaderboard?difficulty=Hard&limit=50&offset=0
+//        -> {"entries": [...], "total": 1234, "next_offset": 50}
+//   GET  /api/v1/leaderboard/rank/{player}/{difficulty}
+//        -> {"rank": 12}
+//   GET  /api/v1/leaderboard/player/{player}?limit=10
+//        -> {"entries": [...]}
+//
+// Errors are returned as {"error": "message"} with a non-2xx status.
+
+/// Largest page requested from the server in one call
+const PAGE_SIZE: usize = 100;
+
+/// How long to prefer the fallback after the server failed
+const RETRY_AFTER_FAILURE_SECS: u64 = 30;
+
+/// Body of `POST /api/v1/leaderboard/submit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitRequest {
+    pub entry: LeaderboardEntry,
+}
+
+/// Response to a score submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitResponse {
+    pub success: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Rank of the submitted score, if the server computed one
+    #[serde(default)]
+    pub rank: Option<usize>,
+}
+
+/// One page of `GET /api/v1/leaderboard`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardPage {
+    pub entries: Vec<LeaderboardEntry>,
+    /// Total number of entries matching the filter
+    #[serde(default)]
+    pub total: Option<usize>,
+    /// Offset of the next page, or None when this is the last one
+    #[serde(default)]
+    pub next_offset: Option<usize>,
+}
+
+/// Response of `GET /api/v1/leaderboard/rank/{player}/{difficulty}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankResponse {
+    pub rank: Option<usize>,
+}
+
+/// Response of `GET /api/v1/leaderboard/player/{player}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoresResponse {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// Error body returned with non-2xx statuses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
 
 /// Remote HTTP API leaderboard for production
 pub struct RemoteLeaderboard {
-    base_url: String,
+    /// Root of the leaderboard API (`{base}/api/v1/leaderboard`)
+    leaderboard_url: String,
+    token_url: String,
     api_key: Option<String>,
-    client: Mutex<Option<()>>, // Placeholder for HTTP client
+    agent: ureq::Agent,
+    last_failure: Mutex<Option<std::time::Instant>>,
 }
 
 /// Configuration for remote backend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteConfig {
     pub base_url: String,
+    /// Static bearer token; when unset the player token from
+    /// `{base_url}/api/v1/token` is used
     pub api_key: Option<String>,
     pub timeout_secs: u64,
 }
@@ -410,7 +609,7 @@ pub struct RemoteConfig {
 impl Default for RemoteConfig {
     fn default() -> Self {
         Self {
-            base_url: "https://api.sudoku-game.example.com".to_string(),
+            base_url: crate::server::PRODUCTION_URL.to_string(),
             api_key: None,
             timeout_secs: 10,
         }
@@ -419,65 +618,143 @@ impl Default for RemoteConfig {
 
 impl RemoteLeaderboard {
     pub fn new(config: RemoteConfig) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+            .build();
+        let base_url = config.base_url.trim_end_matches('/');
         Self {
-            base_url: config.base_url,
+            leaderboard_url: format!("{}/api/v1/leaderboard", base_url),
+            token_url: format!("{}/api/v1/token", base_url),
             api_key: config.api_key,
-            client: Mutex::new(None),
+            agent,
+            last_failure: Mutex::new(None),
         }
     }
 
-    pub fn from_env() -> Self {
-        let base_url = std::env::var("SUDOKU_API_URL")
-            .unwrap_or_else(|_| "https://api.sudoku-game.example.com".to_string());
-        let api_key = std::env::var("SUDOKU_API_KEY").ok();
-
+    /// Backend for the configured server (see `crate::server`)
+    pub fn from_server(server: &crate::server::ServerConfig) -> Self {
         Self {
-            base_url,
-            api_key,
-            client: Mutex::new(None),
+            leaderboard_url: server.leaderboard_url().trim_end_matches('/').to_string(),
+            token_url: server.token_url(),
+            api_key: server.api_key.clone(),
+            agent: crate::server::agent().clone(),
+            last_failure: Mutex::new(None),
         }
     }
 
-    /// Make an HTTP request (placeholder - would use reqwest/ureq in real implementation)
+    /// Bearer token: the configured API key, else the shared player token
+    fn bearer_token(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| crate::telemetry::auth_token(&self.token_url))
+    }
+
+    fn mark_failed(&self) {
+        *self.last_failure.lock().unwrap() = Some(std::time::Instant::now());
+    }
+
+    fn mark_ok(&self) {
+        *self.last_failure.lock().unwrap() = None;
+    }
+
+    /// Make an HTTP request and decode the JSON response
     fn request<T: for<'de> Deserialize<'de>>(
         &self,
-        _method: &str,
-        _endpoint: &str,
-        _body: Option<&impl Serialize>,
+        method: &str,
+        endpoint: &str,
+        body: Option<&impl Serialize>,
     ) -> LeaderboardResult<T> {
-        // In a real implementation, this would:
-        // 1. Build the full URL
-        // 2. Add authentication headers
-        // 3. Make the HTTP request
-        // 4. Parse the response
-        //
-        // For now, return an error indicating remote is not implemented
-        Err(LeaderboardError::NetworkError(
-            "Remote backend not fully implemented - add HTTP client dependency".to_string(),
-        ))
+        let url = format!("{}{}", self.leaderboard_url, endpoint);
+        let mut req = self
+            .agent
+            .request(method, &url)
+            .set("Accept", "application/json");
+        if let Some(token) = self.bearer_token() {
+            req = req.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        let result = match body {
+            Some(body) => {
+                let json = serde_json::to_string(body)
+                    .map_err(|e| LeaderboardError::InvalidResponse(e.to_string()))?;
+                req.set("Content-Type", "application/json")
+                    .send_string(&json)
+            }
+            None => req.call(),
+        };
+
+        match result {
+            Ok(resp) => {
+                self.mark_ok();
+                let text = resp
+                    .into_string()
+                    .map_err(|e| LeaderboardError::InvalidResponse(e.to_string()))?;
+                serde_json::from_str(&text)
+                    .map_err(|e| LeaderboardError::InvalidResponse(e.to_string()))
+            }
+            Err(ureq::Error::Status(code, resp)) => Err(self.status_error(code, resp)),
+            Err(ureq::Error::Transport(e)) => {
+                self.mark_failed();
+                Err(LeaderboardError::NetworkError(e.to_string()))
+            }
+        }
+    }
+
+    /// Map a non-2xx response to a leaderboard error
+    fn status_error(&self, code: u16, resp: ureq::Response) -> LeaderboardError {
+        let retry_after = resp
+            .header("Retry-After")
+            .and_then(|v| v.trim().parse().ok());
+        let body = resp.into_string().unwrap_or_default();
+        let message = serde_json::from_str::<ErrorResponse>(&body)
+            .map(|e| e.error)
+            .unwrap_or(body);
+
+        match code {
+            401 | 403 => {
+                if self.api_key.is_none() {
+                    // Token expired or revoked; fetch a fresh one next time
+                    crate::telemetry::clear_token();
+                }
+                LeaderboardError::NotAuthenticated
+            }
+            429 => LeaderboardError::RateLimited(retry_after),
+            400 | 409 | 422 => LeaderboardError::ScoreRejected(message),
+            500..=599 => {
+                self.mark_failed();
+                LeaderboardError::ServerError(format!("HTTP {}: {}", code, message))
+            }
+            _ => LeaderboardError::ServerError(format!("HTTP {}: {}", code, message)),
+        }
+    }
+
+    /// Fetch a single page of the leaderboard
+    pub fn get_page(
+        &self,
+        difficulty: Option<Difficulty>,
+        limit: usize,
+        offset: usize,
+    ) -> LeaderboardResult<LeaderboardPage> {
+        let query = LeaderboardQuery {
+            difficulty,
+            ..Default::default()
+        };
+        self.query_page(&query, limit, offset)
     }
 }
 
 impl LeaderboardBackend for RemoteLeaderboard {
     fn submit_score(&self, entry: LeaderboardEntry) -> LeaderboardResult<()> {
-        #[derive(Serialize)]
-        struct SubmitRequest {
-            entry: LeaderboardEntry,
-        }
+        let response: SubmitResponse =
+            self.request("POST", "/submit", Some(&SubmitRequest { entry }))?;
 
-        #[derive(Deserialize)]
-        struct SubmitResponse {
-            success: bool,
-            message: Option<String>,
+        if response.success {
+            Ok(())
+        } else {
+            Err(LeaderboardError::ScoreRejected(
+                response.message.unwrap_or_default(),
+            ))
         }
-
-        let _response: SubmitResponse = self.request(
-            "POST",
-            "/api/v1/leaderboard/submit",
-            Some(&SubmitRequest { entry }),
-        )?;
-
-        Ok(())
     }
 
     fn get_leaderboard(
@@ -486,29 +763,21 @@ impl LeaderboardBackend for RemoteLeaderboard {
         limit: usize,
         offset: usize,
     ) -> LeaderboardResult<Vec<LeaderboardEntry>> {
-        #[derive(Serialize)]
-        struct ListRequest {
-            difficulty: Option<String>,
-            limit: usize,
-            offset: usize,
-        }
+        let mut entries = Vec::new();
+        let mut next = Some(offset);
 
-        #[derive(Deserialize)]
-        struct ListResponse {
-            entries: Vec<LeaderboardEntry>,
+        while let Some(page_offset) = next {
+            let wanted = limit - entries.len();
+            if wanted == 0 {
+                break;
+            }
+            let page = self.get_page(difficulty, wanted.min(PAGE_SIZE), page_offset)?;
+            let fetched = page.entries.len();
+            entries.extend(page.entries.into_iter().take(wanted));
+            next = page.next_offset.filter(|&n| fetched > 0 && n > page_offset);
         }
 
-        let response: ListResponse = self.request(
-            "GET",
-            "/api/v1/leaderboard",
-            Some(&ListRequest {
-                difficulty: difficulty.map(|d| format!("{:?}", d)),
-                limit,
-                offset,
-            }),
-        )?;
-
-        Ok(response.entries)
+        Ok(entries)
     }
 
     fn get_player_rank(
@@ -516,15 +785,10 @@ impl LeaderboardBackend for RemoteLeaderboard {
         player_name: &str,
         difficulty: Difficulty,
     ) -> LeaderboardResult<Option<usize>> {
-        #[derive(Deserialize)]
-        struct RankResponse {
-            rank: Option<usize>,
-        }
-
         let response: RankResponse = self.request(
             "GET",
             &format!(
-                "/api/v1/leaderboard/rank/{}/{:?}",
+                "/rank/{}/{:?}",
                 urlencoding::encode(player_name),
                 difficulty
             ),
@@ -539,15 +803,10 @@ impl LeaderboardBackend for RemoteLeaderboard {
         player_name: &str,
         limit: usize,
     ) -> LeaderboardResult<Vec<LeaderboardEntry>> {
-        #[derive(Deserialize)]
-        struct ScoresResponse {
-            entries: Vec<LeaderboardEntry>,
-        }
-
         let response: ScoresResponse = self.request(
             "GET",
             &format!(
-                "/api/v1/leaderboard/player/{}?limit={}",
+                "/player/{}?limit={}",
                 urlencoding::encode(player_name),
                 limit
             ),
@@ -557,10 +816,52 @@ impl LeaderboardBackend for RemoteLeaderboard {
         Ok(response.entries)
     }
 
+    fn query_page(
+        &self,
+        query: &LeaderboardQuery,
+        limit: usize,
+        offset: usize,
+    ) -> LeaderboardResult<LeaderboardPage> {
+        let endpoint = format!("?limit={}&offset={}{}", limit, offset, query.to_params());
+        self.request("GET", &endpoint, None::<&()>)
+    }
+
+    fn query_rank(
+        &self,
+        player_name: &str,
+        query: &LeaderboardQuery,
+    ) -> LeaderboardResult<Option<usize>> {
+        let Some(difficulty) = query.difficulty else {
+            return Err(LeaderboardError::InvalidResponse(
+                "rank needs a difficulty".into(),
+            ));
+        };
+        let filter = LeaderboardQuery {
+            difficulty: None,
+            ..query.clone()
+        };
+        let mut endpoint = format!(
+            "/rank/{}/{:?}",
+            urlencoding::encode(player_name),
+            difficulty
+        );
+        let params = filter.to_params();
+        if !params.is_empty() {
+            endpoint.push('?');
+            endpoint.push_str(params.trim_start_matches('&'));
+        }
+        let response: RankResponse = self.request("GET", &endpoint, None::<&()>)?;
+
+        Ok(response.rank)
+    }
+
     fn is_available(&self) -> bool {
-        // In real implementation, would ping the server
-        // For now, always return false since not implemented
-        false
+        // Optimistic until a request fails at the network/server level,
+        // then back off to the fallback for a while
+        self.last_failure
+            .lock()
+            .unwrap()
+            .is_none_or(|t| t.elapsed().as_secs() >= RETRY_AFTER_FAILURE_SECS)
     }
 
     fn backend_name(&self) -> &'static str {
@@ -575,13 +876,15 @@ pub fn create_backend(env: Environment) -> Arc<dyn LeaderboardBackend> {
     match env {
         Environment::Local => Arc::new(LocalLeaderboard::new()),
         Environment::Test => Arc::new(MockLeaderboard::new()),
-        Environment::Production => Arc::new(RemoteLeaderboard::from_env()),
+        Environment::Production => {
+            Arc::new(RemoteLeaderboard::from_server(crate::server::config()))
+        }
     }
 }
 
-/// Create backend with automatic environment detection
+/// Create the backend for the configured server
 pub fn create_backend_auto() -> Arc<dyn LeaderboardBackend> {
-    create_backend(Environment::detect())
+    create_backend(Environment::Production)
 }
 
 // ==================== Leaderboard Manager ====================
@@ -591,6 +894,8 @@ pub struct LeaderboardManager {
     primary: Arc<dyn LeaderboardBackend>,
     fallback: Option<Arc<dyn LeaderboardBackend>>,
     cache: Mutex<LeaderboardCache>,
+    /// Queue for submissions the primary backend failed to accept
+    outbox: Option<crate::outbox::Outbox>,
 }
 
 impl std::fmt::Debug for LeaderboardManager {
@@ -646,26 +951,16 @@ impl LeaderboardManager {
             primary,
             fallback: None,
             cache: Mutex::new(LeaderboardCache::new(60)), // 1 minute cache
+            outbox: None,
         }
     }
 
-    /// Create with automatic environment detection
+    /// Create for the configured server, falling back to the local file
+    /// and queueing failed submissions so they reach the server later
     pub fn auto() -> Self {
-        let env = Environment::detect();
-        let primary = create_backend(env);
-
-        // Use local as fallback for production
-        let fallback = if env == Environment::Production {
-            Some(Arc::new(LocalLeaderboard::new()) as Arc<dyn LeaderboardBackend>)
-        } else {
-            None
-        };
-
-        Self {
-            primary,
-            fallback,
-            cache: Mutex::new(LeaderboardCache::new(60)),
-        }
+        Self::new(create_backend_auto())
+            .with_fallback(Arc::new(LocalLeaderboard::new()))
+            .with_outbox(crate::outbox::Outbox::new())
     }
 
     /// Set a fallback backend
@@ -674,6 +969,12 @@ impl LeaderboardManager {
         self
     }
 
+    /// Queue submissions the primary rejects for transient reasons
+    pub fn with_outbox(mut self, outbox: crate::outbox::Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
     /// Get the active backend (primary if available, else fallback)
     fn active_backend(&self) -> &Arc<dyn LeaderboardBackend> {
         if self.primary.is_available() {
@@ -689,8 +990,19 @@ impl LeaderboardManager {
     pub fn submit_score(&self, entry: LeaderboardEntry) -> LeaderboardResult<()> {
         let result = self.primary.submit_score(entry.clone());
 
-        // If primary fails but we have fallback, try fallback
-        if result.is_err() {
+        if let Err(ref e) = result {
+            // Keep transient failures for a later retry against the primary
+            if let Some(ref outbox) = self.outbox {
+                if !matches!(e, LeaderboardError::ScoreRejected(_)) {
+                    let id = format!(
+                        "leaderboard:{}:{}:{}",
+                        entry.player_name, entry.puzzle_hash, entry.timestamp
+                    );
+                    outbox.enqueue(&id, crate::outbox::Payload::Leaderboard(entry.clone()));
+                }
+            }
+
+            // If primary fails but we have fallback, try fallback
             if let Some(ref fallback) = self.fallback {
                 let _ = fallback.submit_score(entry);
             }
@@ -740,6 +1052,46 @@ impl LeaderboardManager {
             .get_player_rank(player_name, difficulty)
     }
 
+    /// Run `f` on the active backend, retrying on the fallback if the
+    /// primary was active and failed
+    fn with_fallback_on_error<T>(
+        &self,
+        f: impl Fn(&dyn LeaderboardBackend) -> LeaderboardResult<T>,
+    ) -> LeaderboardResult<T> {
+        let active = self.active_backend();
+        match f(active.as_ref()) {
+            Err(e) if Arc::ptr_eq(active, &self.primary) => match self.fallback {
+                Some(ref fallback) => f(fallback.as_ref()).map_err(|_| e),
+                None => Err(e),
+            },
+            result => result,
+        }
+    }
+
+    /// One filtered page of the leaderboard (not cached)
+    pub fn query_page(
+        &self,
+        query: &LeaderboardQuery,
+        limit: usize,
+        offset: usize,
+    ) -> LeaderboardResult<LeaderboardPage> {
+        self.with_fallback_on_error(|b| b.query_page(query, limit, offset))
+    }
+
+    /// A player's rank within a filter
+    pub fn query_rank(
+        &self,
+        player_name: &str,
+        query: &LeaderboardQuery,
+    ) -> LeaderboardResult<Option<usize>> {
+        self.with_fallback_on_error(|b| b.query_rank(player_name, query))
+    }
+
+    /// Whether submissions leave this device
+    pub fn is_remote(&self) -> bool {
+        self.primary.backend_name() == "Remote"
+    }
+
     /// Get backend status info
     pub fn status(&self) -> LeaderboardStatus {
         LeaderboardStatus {
@@ -787,6 +1139,35 @@ mod tests {
         assert_eq!(leaderboard[0].rank, Some(1));
     }
 
+    #[test]
+    fn test_per_puzzle_board() {
+        let backend = MockLeaderboard::new();
+        for (name, score, hash) in [("a", 300, "p1"), ("b", 200, "p2"), ("c", 400, "p1")] {
+            backend
+                .submit_score(LeaderboardEntry {
+                    player_name: name.to_string(),
+                    score,
+                    time_secs: score,
+                    difficulty: Difficulty::Medium,
+                    hints_used: 0,
+                    mistakes: 0,
+                    timestamp: 0,
+                    puzzle_hash: hash.to_string(),
+                    rank: None,
+                    verified: true,
+                })
+                .unwrap();
+        }
+
+        let query = LeaderboardQuery::for_puzzle("p1", Difficulty::Medium);
+        let page = backend.query_page(&query, 10, 0).unwrap();
+        assert_eq!(page.total, Some(2));
+        assert_eq!(page.entries[1].player_name, "c");
+        assert_eq!(page.entries[1].rank, Some(2));
+        assert_eq!(backend.query_rank("a", &query).unwrap(), Some(1));
+        assert_eq!(backend.query_rank("b", &query).unwrap(), None);
+    }
+
     #[test]
     fn test_mock_unavailable() {
         let backend = MockLeaderboard::new();
@@ -799,10 +1180,60 @@ mod tests {
     }
 
     #[test]
-    fn test_environment_detection() {
-        // Default should be Local
-        let env = Environment::detect();
-        assert_eq!(env, Environment::Local);
+    fn test_auto_uses_configured_server() {
+        let manager = LeaderboardManager::auto();
+        assert!(manager.is_remote());
+        assert_eq!(manager.status().backend_name, "Remote");
+    }
+
+    #[test]
+    fn test_remote_urls_follow_server_config() {
+        let server = MockServer::start(vec![
+            (200, r#"{"entries":[],"total":0}"#.to_string()),
+            (200, r#"{"entries":[],"total":0}"#.to_string()),
+        ]);
+        let config = crate::server::ServerConfig {
+            base_url: format!("{}/", server.url),
+            api_key: Some("server-key".to_string()),
+            ..crate::server::ServerConfig::local()
+        };
+        RemoteLeaderboard::from_server(&config)
+            .get_leaderboard(None, 10, 0)
+            .unwrap();
+
+        let mut custom = config.clone();
+        custom.endpoints.leaderboard = Some(format!("{}/internal/scores/", server.url));
+        RemoteLeaderboard::from_server(&custom)
+            .get_leaderboard(None, 10, 0)
+            .unwrap();
+
+        let requests = server.finish();
+        assert!(requests[0].starts_with("GET /api/v1/leaderboard?limit=10&offset=0 "));
+        assert!(requests[0].contains("Bearer server-key"));
+        assert!(requests[1].starts_with("GET /internal/scores?limit=10&offset=0 "));
+    }
+
+    #[test]
+    fn test_rank_query_string_only_with_filters() {
+        let server = MockServer::start(vec![
+            (200, r#"{"rank":3}"#.to_string()),
+            (200, r#"{"rank":1}"#.to_string()),
+        ]);
+        let remote = server.backend();
+        let query = LeaderboardQuery {
+            difficulty: Some(Difficulty::Hard),
+            ..Default::default()
+        };
+        assert_eq!(remote.query_rank("ada", &query).unwrap(), Some(3));
+        let verified = LeaderboardQuery {
+            verified_only: true,
+            ..query
+        };
+        assert_eq!(remote.query_rank("ada", &verified).unwrap(), Some(1));
+
+        let requests = server.finish();
+        assert!(requests[0].starts_with("GET /api/v1/leaderboard/rank/ada/Hard "));
+        assert!(requests[1].starts_with("GET /api/v1/leaderboard/rank/ada/Hard?verified=1 "));
     }
 
     #[test]
@@ -811,4 +1242,254 @@ mod tests {
         assert!(backend.is_available());
         assert_eq!(backend.backend_name(), "Local");
     }
+
+    #[test]
+    fn test_manager_queues_failed_submissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = crate::outbox::Outbox::at(dir.path().join("outbox.json"));
+        let primary = Arc::new(MockLeaderboard::new());
+        primary.set_available(false);
+        let fallback = Arc::new(MockLeaderboard::new());
+        let manager = LeaderboardManager::new(primary)
+            .with_fallback(fallback.clone())
+            .with_outbox(outbox.clone());
+
+        let entry: LeaderboardEntry = serde_json::from_str(ENTRY_JSON).unwrap();
+        assert!(manager.submit_score(entry.clone()).is_err());
+        assert!(manager.submit_score(entry).is_err());
+
+        assert_eq!(fallback.count(), 2);
+        assert_eq!(outbox.pending_count(), 1);
+    }
+
+    /// Minimal HTTP server that answers each connection with the next canned
+    /// response and records the raw requests it received.
+    struct MockServer {
+        url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+        handle: std::thread::JoinHandle<()>,
+    }
+
+    impl MockServer {
+        fn start(responses: Vec<(u16, String)>) -> Self {
+            use std::io::{BufRead, BufReader, Read, Write};
+
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let log = Arc::clone(&requests);
+
+            let handle = std::thread::spawn(move || {
+                for (status, body) in responses {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request = String::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                            content_length = v.trim().parse().unwrap();
+                        }
+                        request.push_str(&line);
+                        if line == "\r\n" || line.is_empty() {
+                            break;
+                        }
+                    }
+                    let mut payload = vec![0; content_length];
+                    reader.read_exact(&mut payload).unwrap();
+                    request.push_str(&String::from_utf8_lossy(&payload));
+                    log.lock().unwrap().push(request);
+
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+
+            Self {
+                url,
+                requests,
+                handle,
+            }
+        }
+
+        fn backend(&self) -> RemoteLeaderboard {
+            RemoteLeaderboard::new(RemoteConfig {
+                base_url: self.url.clone(),
+                api_key: Some("test-key".to_string()),
+                timeout_secs: 5,
+            })
+        }
+
+        fn finish(self) -> Vec<String> {
+            self.handle.join().unwrap();
+            Arc::try_unwrap(self.requests)
+                .unwrap()
+                .into_inner()
+                .unwrap()
+        }
+    }
+
+    const ENTRY_JSON: &str = r#"{"player_name":"ada","score":420,"time_secs":400,"difficulty":"Hard","hints_used":0,"mistakes":1,"timestamp":0,"puzzle_hash":"abc","rank":1,"verified":true}"#;
+
+    #[test]
+    fn test_remote_get_leaderboard() {
+        let body = format!(
+            r#"{{"entries":[{}],"total":1,"next_offset":null}}"#,
+            ENTRY_JSON
+        );
+        let server = MockServer::start(vec![(200, body)]);
+        let backend = server.backend();
+
+        let entries = backend
+            .get_leaderboard(Some(Difficulty::Hard), 10, 0)
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].player_name, "ada");
+        assert_eq!(entries[0].rank, Some(1));
+
+        let requests = server.finish();
+        assert!(
+            requests[0].starts_with("GET /api/v1/leaderboard?limit=10&offset=0&difficulty=Hard ")
+        );
+        assert!(requests[0].contains("Bearer test-key"));
+    }
+
+    #[test]
+    fn test_remote_pagination() {
+        let first = format!(
+            r#"{{"entries":[{}],"total":2,"next_offset":100}}"#,
+            vec![ENTRY_JSON; 100].join(",")
+        );
+        let second = format!(
+            r#"{{"entries":[{}],"total":2,"next_offset":null}}"#,
+            ENTRY_JSON
+        );
+        let server = MockServer::start(vec![(200, first), (200, second)]);
+
+        let entries = server.backend().get_leaderboard(None, 150, 0).unwrap();
+        assert_eq!(entries.len(), 101);
+
+        let requests = server.finish();
+        assert!(requests[0].starts_with("GET /api/v1/leaderboard?limit=100&offset=0 "));
+        assert!(requests[1].starts_with("GET /api/v1/leaderboard?limit=50&offset=100 "));
+    }
+
+    #[test]
+    fn test_remote_filtered_query() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                format!(
+                    r#"{{"entries":[{}],"total":1,"next_offset":null}}"#,
+                    ENTRY_JSON
+                ),
+            ),
+            (200, r#"{"rank":1}"#.to_string()),
+        ]);
+        let backend = server.backend();
+        let query = LeaderboardQuery {
+            difficulty: Some(Difficulty::Hard),
+            since: Some(1_000),
+            verified_only: true,
+            ..Default::default()
+        };
+
+        let page = backend.query_page(&query, 10, 20).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(backend.query_rank("Ann", &query).unwrap(), Some(1));
+
+        let requests = server.finish();
+        assert!(requests[0].starts_with(
+            "GET /api/v1/leaderboard?limit=10&offset=20&difficulty=Hard&since=1000&verified=1 "
+        ));
+        assert!(
+            requests[1].starts_with("GET /api/v1/leaderboard/rank/Ann/Hard?since=1000&verified=1 ")
+        );
+    }
+
+    #[test]
+    fn test_period_since() {
+        // 2023-11-14 22:13:20 UTC, a Tuesday
+        let now = 1_700_000_000;
+        assert_eq!(Period::AllTime.since(now), None);
+        assert_eq!(Period::Today.since(now), Some(1_699_920_000));
+        assert_eq!(Period::Week.since(now), Some(1_699_833_600));
+    }
+
+    #[test]
+    fn test_remote_submit_and_rank() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"success":true,"message":null,"rank":3}"#.to_string(),
+            ),
+            (200, r#"{"rank":3}"#.to_string()),
+        ]);
+        let backend = server.backend();
+
+        let entry: LeaderboardEntry = serde_json::from_str(ENTRY_JSON).unwrap();
+        backend.submit_score(entry).unwrap();
+        assert_eq!(
+            backend
+                .get_player_rank("ada lovelace", Difficulty::Hard)
+                .unwrap(),
+            Some(3)
+        );
+
+        let requests = server.finish();
+        assert!(requests[0].starts_with("POST /api/v1/leaderboard/submit "));
+        assert!(requests[0].contains(r#"{"entry":{"player_name":"ada""#));
+        assert!(requests[1].starts_with("GET /api/v1/leaderboard/rank/ada%20lovelace/Hard "));
+    }
+
+    #[test]
+    fn test_remote_status_errors() {
+        let server = MockServer::start(vec![
+            (401, r#"{"error":"bad token"}"#.to_string()),
+            (429, r#"{"error":"slow down"}"#.to_string()),
+            (422, r#"{"error":"failed anti-bot"}"#.to_string()),
+            (503, r#"{"error":"maintenance"}"#.to_string()),
+        ]);
+        let backend = server.backend();
+
+        let get = || backend.get_leaderboard(None, 10, 0);
+        assert!(matches!(get(), Err(LeaderboardError::NotAuthenticated)));
+        assert!(matches!(get(), Err(LeaderboardError::RateLimited(None))));
+        assert!(matches!(
+            get(),
+            Err(LeaderboardError::ScoreRejected(m)) if m == "failed anti-bot"
+        ));
+        assert!(backend.is_available());
+        assert!(matches!(get(), Err(LeaderboardError::ServerError(_))));
+        assert!(!backend.is_available());
+
+        server.finish();
+    }
+
+    #[test]
+    fn test_remote_network_error() {
+        // Bind then drop to get a port nothing listens on
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let backend = RemoteLeaderboard::new(RemoteConfig {
+            base_url: format!("http://{}", addr),
+            api_key: Some("test-key".to_string()),
+            timeout_secs: 1,
+        });
+
+        assert!(matches!(
+            backend.get_leaderboard(None, 10, 0),
+            Err(LeaderboardError::NetworkError(_))
+        ));
+        assert!(!backend.is_available());
+    }
 }
@@ -0,0 +1,431 @@
+//! State for the interactive leaderboard screen.
+//!
+//! Pages are fetched on a background thread and delivered over a channel, so
+//! rendering never waits on the network. Each request bumps a generation
+//! counter and stale replies are dropped.
+
+use crate::leaderboard::{LeaderboardEntry, LeaderboardManager, LeaderboardQuery, Period};
+use crate::stats::GameRecord;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use sudoku_core::Difficulty;
+use sudoku_shared::dates::unix_now;
+
+/// Entries per page
+pub const PAGE_SIZE: usize = 10;
+
+/// A single puzzle's board, keyed by canonical puzzle hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleFilter {
+    pub hash: String,
+    /// Short code when known, else the start of the hash
+    pub label: String,
+    pub difficulty: Difficulty,
+}
+
+impl PuzzleFilter {
+    pub fn from_record(record: &GameRecord) -> Self {
+        Self {
+            hash: record.puzzle_hash.clone(),
+            label: record
+                .short_code
+                .clone()
+                .unwrap_or_else(|| record.puzzle_hash.chars().take(8).collect()),
+            difficulty: record.difficulty,
+        }
+    }
+
+    fn query(&self) -> LeaderboardQuery {
+        LeaderboardQuery::for_puzzle(&self.hash, self.difficulty)
+    }
+}
+
+/// Reply from a background fetch
+struct Fetched {
+    generation: u64,
+    offset: usize,
+    page: Result<(Vec<LeaderboardEntry>, Option<usize>, bool), String>,
+    my_rank: Option<usize>,
+    jumped: bool,
+}
+
+/// Filters, current page and fetch state of the leaderboard screen
+pub struct LeaderboardView {
+    pub difficulty: Difficulty,
+    /// Show one puzzle's board instead of the whole difficulty
+    pub puzzle: Option<PuzzleFilter>,
+    pub period: Period,
+    pub verified_only: bool,
+    /// Offset of the first entry on the current page
+    pub offset: usize,
+    pub entries: Vec<LeaderboardEntry>,
+    /// Total entries matching the filters, when the backend reports it
+    pub total: Option<usize>,
+    pub has_next: bool,
+    /// The player's best rank under the current filters
+    pub my_rank: Option<usize>,
+    /// A fetch is in flight
+    pub loading: bool,
+    /// Error from the last fetch
+    pub error: Option<String>,
+    /// The remote board isn't queried because score sharing is off
+    pub sharing_off: bool,
+    generation: u64,
+    rx: Option<Receiver<Fetched>>,
+}
+
+impl LeaderboardView {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            puzzle: None,
+            period: Period::AllTime,
+            verified_only: false,
+            offset: 0,
+            entries: Vec::new(),
+            total: None,
+            has_next: false,
+            my_rank: None,
+            loading: false,
+            error: None,
+            sharing_off: false,
+            generation: 0,
+            rx: None,
+        }
+    }
+
+    /// Query for the current filters
+    pub fn query(&self) -> LeaderboardQuery {
+        let now = unix_now();
+        let base = match self.puzzle {
+            Some(ref puzzle) => puzzle.query(),
+            None => LeaderboardQuery {
+                difficulty: Some(self.difficulty),
+                ..Default::default()
+            },
+        };
+        LeaderboardQuery {
+            since: self.period.since(now),
+            verified_only: self.verified_only,
+            ..base
+        }
+    }
+
+    /// 1-based page number and page count (if the total is known)
+    pub fn page_position(&self) -> (usize, Option<usize>) {
+        (
+            self.offset / PAGE_SIZE + 1,
+            self.total.map(|t| t.div_ceil(PAGE_SIZE).max(1)),
+        )
+    }
+
+    /// Start fetching the current page (or the page holding the player's
+    /// rank when `jump_to_me`) in the background
+    pub fn fetch(
+        &mut self,
+        manager: Option<Arc<LeaderboardManager>>,
+        player_name: &str,
+        jump_to_me: bool,
+    ) {
+        self.generation += 1;
+        self.loading = true;
+        self.error = None;
+
+        let Some(manager) = manager else {
+            self.loading = false;
+            self.error = Some("No leaderboard backend".to_string());
+            return;
+        };
+        // Browsing the remote board sends the player's name with the rank query
+        self.sharing_off = manager.is_remote() && !crate::privacy::telemetry_enabled();
+        if self.sharing_off {
+            self.loading = false;
+            self.rx = None;
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+
+        let generation = self.generation;
+        let query = self.query();
+        let offset = self.offset;
+        let player_name = player_name.to_string();
+        std::thread::spawn(move || {
+            let my_rank = manager.query_rank(&player_name, &query).ok().flatten();
+            let offset = match my_rank {
+                Some(rank) if jump_to_me => (rank - 1) / PAGE_SIZE * PAGE_SIZE,
+                _ => offset,
+            };
+            let page = manager
+                .query_page(&query, PAGE_SIZE, offset)
+                .map(|p| (p.entries, p.total, p.next_offset.is_some()))
+                .map_err(|e| e.to_string());
+            let _ = tx.send(Fetched {
+                generation,
+                offset,
+                page,
+                my_rank,
+                jumped: jump_to_me,
+            });
+        });
+    }
+
+    /// Apply a finished fetch, if any. Returns a message worth showing.
+    pub fn poll(&mut self) -> Option<String> {
+        let fetched = self.rx.as_ref()?.try_recv().ok()?;
+        self.rx = None;
+        if fetched.generation != self.generation {
+            return None;
+        }
+
+        self.loading = false;
+        self.my_rank = fetched.my_rank;
+        match fetched.page {
+            Ok((entries, total, has_next)) => {
+                self.offset = fetched.offset;
+                self.entries = entries;
+                self.total = total;
+                self.has_next = has_next;
+            }
+            Err(e) => self.error = Some(e),
+        }
+
+        (fetched.jumped && fetched.my_rank.is_none())
+            .then(|| "No ranked score for these filters yet".to_string())
+    }
+
+    /// Move to the next page. Returns false if already on the last one.
+    pub fn next_page(&mut self) -> bool {
+        if !self.has_next {
+            return false;
+        }
+        self.offset += PAGE_SIZE;
+        true
+    }
+
+    /// Move to the previous page. Returns false if already on the first one.
+    pub fn prev_page(&mut self) -> bool {
+        if self.offset == 0 {
+            return false;
+        }
+        self.offset = self.offset.saturating_sub(PAGE_SIZE);
+        true
+    }
+
+    /// Reset paging after a filter change
+    pub fn reset_paging(&mut self) {
+        self.offset = 0;
+        self.has_next = false;
+    }
+}
+
+/// The player's standing on one puzzle's board, looked up in the background
+/// for the end screen
+#[derive(Default)]
+pub struct PuzzleRank {
+    /// Puzzle being ranked
+    pub puzzle: Option<PuzzleFilter>,
+    pub rank: Option<usize>,
+    /// Entries on the puzzle's board
+    pub total: Option<usize>,
+    pub loading: bool,
+    rx: Option<Receiver<(Option<usize>, Option<usize>)>>,
+}
+
+impl PuzzleRank {
+    /// Look up the rank once `submitted` (the score submission) has finished
+    pub fn start(
+        &mut self,
+        manager: Option<Arc<LeaderboardManager>>,
+        player_name: &str,
+        puzzle: PuzzleFilter,
+        submitted: Option<JoinHandle<()>>,
+    ) {
+        *self = Self {
+            puzzle: Some(puzzle.clone()),
+            ..Default::default()
+        };
+        let Some(manager) = manager else {
+            return;
+        };
+        // Asking the server about a puzzle reveals it was played
+        if manager.is_remote() && !crate::privacy::telemetry_enabled() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        self.loading = true;
+
+        let player_name = player_name.to_string();
+        std::thread::spawn(move || {
+            if let Some(handle) = submitted {
+                let _ = handle.join();
+            }
+            let query = puzzle.query();
+            let rank = manager.query_rank(&player_name, &query).ok().flatten();
+            let total = manager
+                .query_page(&query, 1, 0)
+                .ok()
+                .and_then(|page| page.total);
+            let _ = tx.send((rank, total));
+        });
+    }
+
+    /// Apply a finished lookup, if any
+    pub fn poll(&mut self) {
+        let Some(Ok((rank, total))) = self.rx.as_ref().map(|rx| rx.try_recv()) else {
+            return;
+        };
+        self.rx = None;
+        self.loading = false;
+        self.rank = rank;
+        self.total = total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaderboard::{LeaderboardBackend, MockLeaderboard};
+    use std::time::Duration;
+
+    fn entry(name: &str, score: u64, verified: bool) -> LeaderboardEntry {
+        LeaderboardEntry {
+            player_name: name.to_string(),
+            score,
+            time_secs: score,
+            difficulty: Difficulty::Hard,
+            hints_used: 0,
+            mistakes: 0,
+            timestamp: 1,
+            puzzle_hash: format!("hash-{}", score),
+            rank: None,
+            verified,
+        }
+    }
+
+    fn wait(view: &mut LeaderboardView) -> Option<String> {
+        for _ in 0..200 {
+            let message = view.poll();
+            if !view.loading {
+                return message;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("fetch never finished");
+    }
+
+    fn manager() -> Arc<LeaderboardManager> {
+        let backend = Arc::new(MockLeaderboard::new());
+        for i in 0..25 {
+            let name = if i == 13 {
+                "me".to_string()
+            } else {
+                format!("p{}", i)
+            };
+            backend
+                .submit_score(entry(&name, 100 + i, i % 2 == 0))
+                .unwrap();
+        }
+        Arc::new(LeaderboardManager::new(backend))
+    }
+
+    #[test]
+    fn test_paging_and_jump_to_rank() {
+        let manager = manager();
+        let mut view = LeaderboardView::new(Difficulty::Hard);
+
+        view.fetch(Some(manager.clone()), "me", false);
+        assert!(view.loading);
+        wait(&mut view);
+        assert_eq!(view.entries.len(), PAGE_SIZE);
+        assert_eq!(view.total, Some(25));
+        assert_eq!(view.my_rank, Some(14));
+        assert_eq!(view.page_position(), (1, Some(3)));
+
+        assert!(view.next_page());
+        view.fetch(Some(manager.clone()), "me", false);
+        wait(&mut view);
+        assert_eq!(view.entries[0].rank, Some(11));
+
+        view.reset_paging();
+        view.fetch(Some(manager.clone()), "me", true);
+        assert_eq!(wait(&mut view), None);
+        assert_eq!(view.offset, 10);
+        assert!(view.entries.iter().any(|e| e.player_name == "me"));
+
+        view.verified_only = true;
+        view.reset_paging();
+        view.fetch(Some(manager), "me", true);
+        assert!(wait(&mut view).is_some());
+        assert_eq!(view.total, Some(13));
+        assert!(view.entries.iter().all(|e| e.verified));
+    }
+
+    #[test]
+    fn test_puzzle_board_and_rank() {
+        let manager = manager();
+        let puzzle = PuzzleFilter {
+            hash: "hash-113".to_string(),
+            label: "ABCD1234".to_string(),
+            difficulty: Difficulty::Hard,
+        };
+
+        let mut view = LeaderboardView::new(Difficulty::Medium);
+        view.puzzle = Some(puzzle.clone());
+        view.fetch(Some(manager.clone()), "me", false);
+        wait(&mut view);
+        assert_eq!(view.total, Some(1));
+        assert_eq!(view.my_rank, Some(1));
+
+        let mut lookup = PuzzleRank::default();
+        let submitted = std::thread::spawn(|| {});
+        lookup.start(Some(manager), "me", puzzle, Some(submitted));
+        for _ in 0..200 {
+            lookup.poll();
+            if !lookup.loading {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!((lookup.rank, lookup.total), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn test_remote_board_needs_consent() {
+        crate::privacy::disable_for_session();
+        let config = crate::server::ServerConfig {
+            base_url: "http://127.0.0.1:9".to_string(),
+            ..crate::server::ServerConfig::local()
+        };
+        let remote = crate::leaderboard::RemoteLeaderboard::from_server(&config);
+        let manager = Arc::new(LeaderboardManager::new(Arc::new(remote)));
+
+        let mut view = LeaderboardView::new(Difficulty::Hard);
+        view.fetch(Some(manager), "me", false);
+        assert!(view.sharing_off);
+        assert!(!view.loading);
+        assert!(view.poll().is_none());
+
+        // Local boards need no consent
+        view.fetch(Some(self::manager()), "me", false);
+        assert!(!view.sharing_off);
+        wait(&mut view);
+        assert_eq!(view.total, Some(25));
+    }
+
+    #[test]
+    fn test_stale_replies_are_dropped() {
+        let manager = manager();
+        let mut view = LeaderboardView::new(Difficulty::Hard);
+        view.fetch(Some(manager.clone()), "me", false);
+        view.difficulty = Difficulty::Easy;
+        view.fetch(Some(manager), "me", false);
+        wait(&mut view);
+        assert!(view.entries.is_empty());
+        assert_eq!(view.total, Some(0));
+    }
+}
@@ -1,22 +1,41 @@
+mod accessible;
 mod animations;
 mod app;
+mod config;
+mod frame;
 mod game;
+mod lan;
 mod leaderboard;
+mod leaderboard_view;
+mod outbox;
 mod persistence;
+mod privacy;
+mod profiles;
 mod render;
+mod script;
+mod server;
 mod stats;
+mod storage;
+mod sync;
 mod telemetry;
 mod theme;
+mod trends;
 
 use app::App;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
+use lan::coop::Coop;
+use lan::race::Race;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
+use sudoku_core::PuzzleId;
 
 #[derive(Parser)]
 #[command(name = "ukodus", about = "Terminal Sudoku game")]
@@ -24,18 +43,255 @@ struct Cli {
     /// Load a puzzle from an 81-character string or 8-character short code
     #[arg(long)]
     puzzle: Option<String>,
+
+    /// Don't upload any results this session (also: SUDOKU_TELEMETRY=off)
+    #[arg(long, global = true)]
+    no_telemetry: bool,
+
+    /// Player profile to use; created if it doesn't exist
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Server to use: "production", "local" or a base URL (also: SUDOKU_SERVER)
+    #[arg(long, global = true)]
+    server: Option<String>,
+
+    /// Server config file (default: sudoku_server.json in the data directory)
+    #[arg(long, global = true)]
+    server_config: Option<PathBuf>,
+
+    /// Extra PEM CA certificates to trust for the server (also: SUDOKU_CA_BUNDLE)
+    #[arg(long, global = true)]
+    ca_bundle: Option<PathBuf>,
+
+    /// Settings and key bindings file (default: config.toml in the config
+    /// directory; also: SUDOKU_CONFIG)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Announce the game as lines of text for screen readers instead of
+    /// drawing the board (also a setting)
+    #[arg(long)]
+    screen_reader: bool,
+
+    /// No particle animations or flashing, and a steady low refresh rate
+    /// (also a setting)
+    #[arg(long)]
+    reduced_motion: bool,
+
+    /// Write this session's input to a script file for `--replay`
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Run a recorded session or test script without a terminal, in a scratch
+    /// data directory, and print the final screen. Exits with status 1 if an
+    /// `expect` line fails.
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replay a submitted move log against its puzzle and print the verdict as JSON
+    Verify {
+        /// The puzzle that was played (81-character string or 8-character short code)
+        #[arg(long)]
+        puzzle: String,
+        /// Path to the move log JSON (as exported by the web build's `get_move_log`)
+        #[arg(long)]
+        log: PathBuf,
+        /// Claimed completion time in seconds
+        #[arg(long)]
+        time_secs: u64,
+        /// Claimed number of mistakes
+        #[arg(long, default_value_t = 0)]
+        mistakes: usize,
+        /// Claimed number of hints used
+        #[arg(long, default_value_t = 0)]
+        hints: usize,
+    },
+    /// Print the resolved server configuration and endpoints
+    Server,
+    /// Manage telemetry consent and the data stored about you
+    Privacy {
+        #[command(subcommand)]
+        action: PrivacyAction,
+    },
+    /// Export or import game history
+    Stats {
+        #[command(subcommand)]
+        action: StatsAction,
+    },
+    /// Sync stats between machines through a shared folder
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+    /// Race others on the local network to solve the same puzzle first
+    Race {
+        #[command(subcommand)]
+        action: LanAction,
+    },
+    /// Solve one puzzle together with others on the local network
+    Coop {
+        #[command(subcommand)]
+        action: LanAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum LanAction {
+    /// Open a lobby others can join, and start the game from it
+    Host {
+        /// Port to listen on
+        #[arg(long, default_value_t = lan::DEFAULT_PORT)]
+        port: u16,
+        /// Puzzle to play (81-character string or short code; default: a new
+        /// one at your default difficulty)
+        #[arg(long)]
+        puzzle: Option<String>,
+    },
+    /// Join a lobby with the address and code its host gave out
+    Join {
+        /// Host address, as `ip` or `ip:port`
+        addr: String,
+        /// Four-letter lobby code
+        code: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SyncAction {
+    /// Start syncing this profile's stats through `dir` (e.g. a Dropbox folder)
+    Enable {
+        /// Shared folder; created if missing
+        dir: PathBuf,
+    },
+    /// Stop syncing (the shared folder is left as is)
+    Disable,
+    /// Show the sync folder and the devices writing to it
+    Status,
+}
+
+#[derive(Subcommand)]
+enum StatsAction {
+    /// Write game history as CSV or JSON (schema "sudoku-game-records")
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge history exported from the TUI, web or iOS builds
+    Import {
+        /// Export file (CSV, records JSON, a TUI stats file or the iOS puzzle library)
+        file: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ExportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum PrivacyAction {
+    /// Show whether results are uploaded and why
+    Status,
+    /// Allow uploading game results
+    Enable,
+    /// Stop uploading game results and drop queued ones
+    Disable,
+    /// Write everything stored locally about you as JSON
+    Export {
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Ask the server to delete your results
+    Delete {
+        /// Also delete all local stats, saves and logs
+        #[arg(long)]
+        local: bool,
+    },
 }
 
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
 
+    if let Some(path) = &cli.replay {
+        let code = replay(path, (cli.screen_reader, cli.reduced_motion))?;
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+
+    if cli.no_telemetry {
+        privacy::disable_for_session();
+    }
+    config::init(cli.config);
+
+    let overrides = server::ServerOverrides {
+        server: cli.server,
+        config_path: cli.server_config,
+        ca_bundle: cli.ca_bundle,
+    };
+    if let Err(e) = server::init(&overrides) {
+        eprintln!("Server configuration error: {}", e);
+        std::process::exit(2);
+    }
+
+    if let Err(e) = profiles::init(cli.profile.as_deref()) {
+        eprintln!("Profile error: {}", e);
+        std::process::exit(2);
+    }
+
+    // Connect before taking over the terminal, so failures print plainly
+    let lan = match cli.command {
+        Some(Command::Race { action }) => {
+            Some(start_lan(action, Race::host, Race::join).map(Lan::Race))
+        }
+        Some(Command::Coop { action }) => {
+            Some(start_lan(action, Coop::host, Coop::join).map(Lan::Coop))
+        }
+        Some(command) => return run_command(command),
+        None => None,
+    };
+    let lan = match lan.transpose() {
+        Ok(lan) => lan,
+        Err(e) => {
+            eprintln!("LAN error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Retry any results that failed to upload last session
+    outbox::flush_in_background();
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
 
     // Run the app
-    let result = run_app(&mut stdout, cli.puzzle.as_deref());
+    // Ask who's playing when there is a choice and none was given
+    let pick_profile =
+        lan.is_none() && cli.profile.is_none() && profiles::registry().profiles.len() > 1;
+    let result = run_app(
+        &mut stdout,
+        cli.puzzle.as_deref(),
+        pick_profile,
+        (cli.screen_reader, cli.reduced_motion),
+        cli.record.as_deref(),
+        lan,
+    );
 
     // Restore terminal
     disable_raw_mode()?;
@@ -48,40 +304,374 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-fn run_app(stdout: &mut io::Stdout, puzzle: Option<&str>) -> io::Result<()> {
+/// `accessibility` is (screen reader, reduced motion) from the command
+/// line, added to the saved settings for this session
+fn run_app(
+    stdout: &mut io::Stdout,
+    puzzle: Option<&str>,
+    pick_profile: bool,
+    accessibility: (bool, bool),
+    record: Option<&Path>,
+    lan: Option<Lan>,
+) -> io::Result<()> {
     let mut app = App::new_with_puzzle(puzzle);
+    app.set_accessibility(
+        app.screen_reader || accessibility.0,
+        app.reduced_motion || accessibility.1,
+    );
+    if pick_profile {
+        app.open_profiles();
+    }
+    match lan {
+        Some(Lan::Race(race)) => app.enter_race(race),
+        Some(Lan::Coop(coop)) => app.enter_coop(coop),
+        None => {}
+    }
+    let mut recorder = match record {
+        Some(path) => Some(script::Recorder::create(path, &app, terminal::size()?)?),
+        None => None,
+    };
     let mut last_tick = Instant::now();
+    let mut narrator: Option<accessible::Narrator> = None;
+    let mut screen = frame::Screen::default();
 
     loop {
         // Determine tick rate based on screen mode
         let tick_rate = app.get_tick_rate();
 
+        // Screen reader mode prints to the normal screen so its lines stay
+        // in the scrollback
+        if app.screen_reader != narrator.is_some() {
+            if app.screen_reader {
+                execute!(stdout, LeaveAlternateScreen)?;
+                narrator = Some(accessible::Narrator::new());
+            } else {
+                execute!(stdout, EnterAlternateScreen)?;
+                narrator = None;
+                screen.invalidate();
+            }
+        }
+
         // Render
-        render::render(stdout, &mut app)?;
+        match &mut narrator {
+            Some(narrator) => narrator.render(stdout, &app)?,
+            None => {
+                let (width, height) = terminal::size()?;
+                let mut frame = screen.next_frame(width, height);
+                render::render(&mut frame, &mut app)?;
+                screen.show(frame, stdout)?;
+            }
+        }
         stdout.flush()?;
 
         // Handle input with timeout for animation updates
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if event::poll(timeout.min(Duration::from_millis(33)))? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            let action = match event {
                 // Handle Ctrl+C
-                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                Event::Key(key)
+                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c') =>
+                {
                     break;
                 }
-
-                match app.handle_key(key) {
-                    app::AppAction::Continue => {}
-                    app::AppAction::Quit => break,
-                }
+                Event::Key(key) => app.handle_key(key),
+                Event::Mouse(mouse) => app.handle_mouse(mouse),
+                _ => app::AppAction::Continue,
+            };
+            if let Some(recorder) = &mut recorder {
+                recorder.input(&event, &app)?;
+            }
+            if let app::AppAction::Quit = action {
+                break;
+            }
+            // Lay out again right away; a new size starts a blank frame
+            if let Event::Resize(_, _) = event {
+                continue;
             }
         }
 
         // Tick animations and timer
         if last_tick.elapsed() >= tick_rate {
             app.tick();
+            if let Some(recorder) = &mut recorder {
+                recorder.tick();
+            }
             last_tick = Instant::now();
         }
     }
 
     Ok(())
 }
+
+/// Run a script from `--replay` against a fresh app and print the final
+/// screen. Nothing touches the player's data: the replay gets its own data
+/// directory, the config the script carries, and no telemetry. Returns the
+/// exit code, so the scratch directory is gone before the process exits.
+fn replay(path: &Path, accessibility: (bool, bool)) -> io::Result<i32> {
+    let script = match script::Script::load(path) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Ok(2);
+        }
+    };
+
+    let scratch = tempfile::tempdir()?;
+    persistence::set_data_dir(scratch.path().join("data"));
+    let config_path = scratch.path().join("config.toml");
+    if let Some(config) = &script.config {
+        std::fs::write(&config_path, config)?;
+    }
+    config::init(Some(config_path));
+    privacy::disable_for_session();
+    if let Err(e) = profiles::init(None) {
+        eprintln!("Profile error: {}", e);
+        return Ok(2);
+    }
+
+    let mut app = App::new_with_puzzle(script.puzzle.as_deref());
+    app.set_accessibility(
+        app.screen_reader || accessibility.0,
+        app.reduced_motion || accessibility.1,
+    );
+    let mut driver = script::Driver::new(app, script.size());
+    let result = script.run(&mut driver);
+    println!("{}", driver.frame().text());
+    if let Err(e) = result {
+        eprintln!("{}: {}", path.display(), e);
+        return Ok(1);
+    }
+    Ok(0)
+}
+
+/// A race or shared board joined from the command line
+enum Lan {
+    Race(Race),
+    Coop(Coop),
+}
+
+type Host<T> = fn(u16, &str, &str) -> io::Result<T>;
+type Join<T> = fn(&str, &str, &str) -> io::Result<T>;
+
+/// Host or join a LAN game as the active profile
+fn start_lan<T>(action: LanAction, host: Host<T>, join: Join<T>) -> io::Result<T> {
+    let name = profiles::active().name;
+    match action {
+        LanAction::Host { port, puzzle } => {
+            let puzzle = puzzle.unwrap_or_else(|| {
+                let config = config::Config::load().0;
+                let (difficulty, locked) = app::default_difficulty(&config, &load_stats());
+                if let Some(warning) = locked {
+                    eprintln!("{}", warning);
+                }
+                let game = game::Game::new(difficulty);
+                game.short_code()
+                    .unwrap_or_else(|| game.original_puzzle().to_string())
+            });
+            host(port, &puzzle, &name)
+        }
+        LanAction::Join { addr, code } => join(&addr, &code, &name),
+    }
+}
+
+/// Run a non-interactive subcommand
+fn run_command(command: Command) -> io::Result<()> {
+    match command {
+        Command::Verify {
+            puzzle,
+            log,
+            time_secs,
+            mistakes,
+            hints,
+        } => {
+            let puzzle = if puzzle.len() == 81 {
+                puzzle
+            } else {
+                PuzzleId::from_short_code(&puzzle)
+                    .map(|id| id.generate().to_string_compact())
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "Invalid puzzle or short code")
+                    })?
+            };
+            let json = std::fs::read_to_string(&log)?;
+            let moves = sudoku_verify::parse_move_log(&json)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let claim = sudoku_verify::Claim {
+                time_secs,
+                mistakes,
+                hints_used: hints,
+            };
+
+            let verdict = sudoku_verify::verify(&puzzle, &moves, &claim);
+            let out = serde_json::to_string_pretty(&verdict).map_err(io::Error::other)?;
+            println!("{}", out);
+            for issue in &verdict.issues {
+                eprintln!("- {}", issue);
+            }
+
+            if !verdict.valid {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Server => {
+            let config = server::config();
+            let out = serde_json::json!({
+                "config": config,
+                "results": config.results_url(),
+                "token": config.token_url(),
+                "leaderboard": config.leaderboard_url(),
+                "puzzles": config.puzzles_url(),
+            });
+            let json = serde_json::to_string_pretty(&out).map_err(io::Error::other)?;
+            println!("{}", json);
+            Ok(())
+        }
+        Command::Privacy { action } => run_privacy(action),
+        Command::Stats { action } => run_stats(action),
+        Command::Sync { action } => run_sync(action),
+        // Played in the TUI; see `start_lan`
+        Command::Race { .. } | Command::Coop { .. } => unreachable!(),
+    }
+}
+
+/// Stats for a CLI command, reporting load problems on stderr
+fn load_stats() -> stats::StatsManager {
+    let mut stats = stats::StatsManager::load();
+    if let Some(warning) = stats.load_warning.take() {
+        eprintln!("{}", warning);
+    }
+    stats
+}
+
+fn run_sync(action: SyncAction) -> io::Result<()> {
+    match action {
+        SyncAction::Enable { dir } => {
+            let dir = std::path::absolute(&dir)?;
+            sync::SyncConfig { dir: dir.clone() }.save()?;
+            // Loading attaches the log, shares local history and merges the rest
+            let stats = load_stats();
+            if !stats.is_synced() {
+                sync::SyncConfig::remove()?;
+                std::process::exit(1);
+            }
+            println!(
+                "Syncing through {} ({} games)",
+                dir.display(),
+                stats.history.len()
+            );
+        }
+        SyncAction::Disable => {
+            sync::SyncConfig::remove()?;
+            println!("Sync disabled");
+        }
+        SyncAction::Status => match sync::SyncConfig::load() {
+            Some(config) => {
+                println!("Folder: {}", config.dir.display());
+                println!("This device: {}", sync::device_id());
+                for (device, events) in sync::devices(&config.dir) {
+                    println!("  {:<32} {} events", device, events);
+                }
+            }
+            None => println!("Sync is off; enable with `sudoku sync enable <dir>`"),
+        },
+    }
+    Ok(())
+}
+
+fn run_stats(action: StatsAction) -> io::Result<()> {
+    match action {
+        StatsAction::Export { format, output } => {
+            let records = load_stats().export_records();
+            let out = match format {
+                ExportFormat::Json => {
+                    let now = sudoku_shared::dates::unix_now();
+                    sudoku_shared::records::to_json(&records, "tui", now)
+                }
+                ExportFormat::Csv => sudoku_shared::records::to_csv(&records),
+            };
+            match output {
+                Some(path) => {
+                    persistence::atomic_write(&path, out.as_bytes())?;
+                    eprintln!("Wrote {} games to {}", records.len(), path.display());
+                }
+                None => print!("{}", out),
+            }
+        }
+        StatsAction::Import { file } => {
+            let input = std::fs::read_to_string(&file)?;
+            let records = match sudoku_shared::records::parse(&input) {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("Cannot import {}: {}", file.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            let mut stats = load_stats();
+            let summary = stats.import_records(records);
+            if let Some(warning) = stats.load_warning.take() {
+                eprintln!("{}", warning);
+            }
+            println!(
+                "Imported {} games ({} already present)",
+                summary.added, summary.duplicates
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_privacy(action: PrivacyAction) -> io::Result<()> {
+    match action {
+        PrivacyAction::Status => {
+            let state = match privacy::settings().telemetry {
+                Some(true) => "enabled",
+                Some(false) => "disabled",
+                None => "not yet chosen (off)",
+            };
+            println!("Telemetry: {}", state);
+            if let Some(reason) = privacy::override_reason() {
+                println!("Forced off by {}", reason);
+            }
+            println!("Pending uploads: {}", outbox::Outbox::new().pending_count());
+        }
+        PrivacyAction::Enable => {
+            privacy::set_consent(true);
+            println!("Telemetry enabled");
+        }
+        PrivacyAction::Disable => {
+            privacy::set_consent(false);
+            println!("Telemetry disabled");
+        }
+        PrivacyAction::Export { output } => {
+            let json =
+                serde_json::to_string_pretty(&privacy::export()).map_err(io::Error::other)?;
+            match output {
+                Some(path) => {
+                    persistence::atomic_write(&path, json.as_bytes())?;
+                    eprintln!("Wrote {}", path.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+        PrivacyAction::Delete { local } => {
+            match telemetry::request_deletion() {
+                Ok(pid) => println!("Server deletion requested for player {}", pid),
+                Err(e) => {
+                    eprintln!("Deletion request failed: {}", e);
+                    if !local {
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if local {
+                for path in privacy::delete_local()? {
+                    println!("Removed {}", path.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,440 @@
+//! Durable queue of results waiting to be uploaded.
+//!
+//! Game results and leaderboard submissions are written here before any
+//! network call, then removed once the server accepts them. Failed sends are
+//! retried with exponential backoff (or the server's `Retry-After`), and the
+//! queue is flushed again on the next startup.
+
+use crate::leaderboard::{LeaderboardBackend, LeaderboardEntry, LeaderboardError};
+use crate::stats::GameRecord;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use sudoku_shared::dates::unix_now;
+
+/// Delay before the first retry
+const BASE_BACKOFF_SECS: u64 = 30;
+/// Longest delay between retries
+const MAX_BACKOFF_SECS: u64 = 6 * 60 * 60;
+/// Give up on an item after this many failed attempts
+const MAX_ATTEMPTS: u32 = 20;
+
+/// Serializes read-modify-write cycles on the outbox file
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+/// Set while a background flush is running
+static FLUSHING: AtomicBool = AtomicBool::new(false);
+
+/// What an outbox item uploads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Payload {
+    /// Game result body for the results endpoint
+    Result(serde_json::Value),
+    /// Score for the remote leaderboard
+    Leaderboard(LeaderboardEntry),
+}
+
+/// A queued upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxItem {
+    /// Id used to dedupe (see [`result_id`])
+    pub id: String,
+    pub payload: Payload,
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Unix timestamp before which the item is not retried
+    pub next_attempt_at: u64,
+    /// Unix timestamp when queued
+    pub created_at: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Result of a single send attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// Accepted by the server
+    Sent,
+    /// Temporary failure; retry later (not before `retry_after_secs` if given)
+    Retry {
+        error: String,
+        retry_after_secs: Option<u64>,
+    },
+    /// Permanently refused; drop the item
+    Rejected(String),
+    /// Not attempted (e.g. telemetry is off); leave the item as it is
+    Deferred,
+}
+
+impl From<Result<(), LeaderboardError>> for SendOutcome {
+    fn from(result: Result<(), LeaderboardError>) -> Self {
+        match result {
+            Ok(()) => SendOutcome::Sent,
+            Err(LeaderboardError::ScoreRejected(e)) => SendOutcome::Rejected(e),
+            Err(LeaderboardError::RateLimited(retry_after_secs)) => SendOutcome::Retry {
+                error: "rate limited".to_string(),
+                retry_after_secs,
+            },
+            Err(e) => SendOutcome::Retry {
+                error: e.to_string(),
+                retry_after_secs: None,
+            },
+        }
+    }
+}
+
+/// Counts from one flush
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushSummary {
+    pub sent: usize,
+    pub retrying: usize,
+    pub dropped: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct OutboxData {
+    items: Vec<OutboxItem>,
+}
+
+/// On-disk outbox
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    path: PathBuf,
+}
+
+impl Default for Outbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Outbox {
+    /// Outbox stored in the app data directory
+    pub fn new() -> Self {
+        Self::at(crate::persistence::profile_dir().join("sudoku_outbox.json"))
+    }
+
+    /// Outbox stored at a specific path
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> OutboxData {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, data: &OutboxData) {
+        if let Ok(json) = serde_json::to_string_pretty(data) {
+            let _ = crate::persistence::atomic_write(&self.path, json.as_bytes());
+        }
+    }
+
+    /// Apply `f` to the stored items under the file lock
+    fn update<R>(&self, f: impl FnOnce(&mut Vec<OutboxItem>) -> R) -> R {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = self.load();
+        let result = f(&mut data.items);
+        self.save(&data);
+        result
+    }
+
+    /// Queue a payload. Returns false if an item with the same id is already queued.
+    pub fn enqueue(&self, id: &str, payload: Payload) -> bool {
+        let now = unix_now();
+        self.update(|items| {
+            if items.iter().any(|i| i.id == id) {
+                return false;
+            }
+            items.push(OutboxItem {
+                id: id.to_string(),
+                payload,
+                attempts: 0,
+                next_attempt_at: now,
+                created_at: now,
+                last_error: None,
+            });
+            true
+        })
+    }
+
+    /// Drop every queued item
+    pub fn clear(&self) {
+        self.update(|items| items.clear());
+    }
+
+    /// Number of items waiting to be sent
+    pub fn pending_count(&self) -> usize {
+        self.load().items.len()
+    }
+
+    /// Try every item that is due at `now` using `send`.
+    ///
+    /// The network calls happen without holding the file lock, so items can
+    /// be queued concurrently; results are merged back by id afterwards.
+    pub fn flush_with(
+        &self,
+        now: u64,
+        mut send: impl FnMut(&Payload) -> SendOutcome,
+    ) -> FlushSummary {
+        let due: Vec<OutboxItem> = self
+            .load()
+            .items
+            .into_iter()
+            .filter(|i| i.next_attempt_at <= now)
+            .collect();
+
+        let mut summary = FlushSummary::default();
+        let mut done: Vec<String> = Vec::new();
+        let mut retried: Vec<OutboxItem> = Vec::new();
+
+        for mut item in due {
+            match send(&item.payload) {
+                SendOutcome::Sent => {
+                    summary.sent += 1;
+                    done.push(item.id);
+                }
+                SendOutcome::Rejected(_) => {
+                    summary.dropped += 1;
+                    done.push(item.id);
+                }
+                SendOutcome::Deferred => {}
+                SendOutcome::Retry {
+                    error,
+                    retry_after_secs,
+                } => {
+                    item.attempts += 1;
+                    if item.attempts >= MAX_ATTEMPTS {
+                        summary.dropped += 1;
+                        done.push(item.id);
+                        continue;
+                    }
+                    let delay = backoff_secs(item.attempts).max(retry_after_secs.unwrap_or(0));
+                    item.next_attempt_at = now + delay;
+                    item.last_error = Some(error);
+                    summary.retrying += 1;
+                    retried.push(item);
+
+                    // The server asked us to slow down; leave the rest for later
+                    if retry_after_secs.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !done.is_empty() || !retried.is_empty() {
+            self.update(|items| {
+                items.retain(|i| !done.contains(&i.id));
+                for updated in retried {
+                    if let Some(item) = items.iter_mut().find(|i| i.id == updated.id) {
+                        *item = updated;
+                    }
+                }
+            });
+        }
+
+        summary
+    }
+
+    /// Send every due item to its real endpoint
+    pub fn flush(&self) -> FlushSummary {
+        self.flush_with(unix_now(), send_payload)
+    }
+}
+
+/// Retry delay after `attempts` failures: 30s, 60s, 120s, ... capped at 6h
+fn backoff_secs(attempts: u32) -> u64 {
+    BASE_BACKOFF_SECS
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(20))
+        .min(MAX_BACKOFF_SECS)
+}
+
+/// Everything queued here identifies the player (results carry their id,
+/// leaderboard scores their name), so nothing is sent without consent
+fn send_payload(payload: &Payload) -> SendOutcome {
+    if !crate::privacy::telemetry_enabled() {
+        return SendOutcome::Deferred;
+    }
+    match payload {
+        Payload::Result(body) => crate::telemetry::send_result(body),
+        Payload::Leaderboard(entry) => {
+            crate::leaderboard::RemoteLeaderboard::from_server(crate::server::config())
+                .submit_score(entry.clone())
+                .into()
+        }
+    }
+}
+
+/// Dedupe id for a game result. Record ids start over after a stats reset
+/// and repeat across profiles and devices, so those are part of it too.
+pub fn result_id(record: &GameRecord) -> String {
+    format!(
+        "result:{}:{}:{}:{}",
+        crate::sync::device_id(),
+        crate::profiles::active().id,
+        record.id,
+        record.timestamp
+    )
+}
+
+/// Clears `FLUSHING` when the flush thread ends, even if it panics
+struct FlushGuard;
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        FLUSHING.store(false, Ordering::Release);
+    }
+}
+
+/// Flush the default outbox on a background thread.
+/// Does nothing if a flush is already running.
+pub fn flush_in_background() {
+    if FLUSHING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let _guard = FlushGuard;
+        let _summary = Outbox::new().flush();
+        #[cfg(debug_assertions)]
+        if _summary != FlushSummary::default() {
+            eprintln!("Outbox: {:?}", _summary);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outbox() -> (tempfile::TempDir, Outbox) {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::at(dir.path().join("outbox.json"));
+        (dir, outbox)
+    }
+
+    fn result(n: u64) -> Payload {
+        Payload::Result(serde_json::json!({ "record_id": n }))
+    }
+
+    #[test]
+    fn test_enqueue_dedupes_and_persists() {
+        let (_dir, outbox) = outbox();
+        assert!(outbox.enqueue("result:1", result(1)));
+        assert!(!outbox.enqueue("result:1", result(1)));
+        assert!(outbox.enqueue("result:2", result(2)));
+
+        let reopened = Outbox::at(outbox.path.clone());
+        assert_eq!(reopened.pending_count(), 2);
+    }
+
+    #[test]
+    fn test_flush_removes_sent_and_rejected() {
+        let (_dir, outbox) = outbox();
+        outbox.enqueue("result:1", result(1));
+        outbox.enqueue("result:2", result(2));
+
+        let mut calls = 0;
+        let summary = outbox.flush_with(unix_now(), |_| {
+            calls += 1;
+            if calls == 1 {
+                SendOutcome::Sent
+            } else {
+                SendOutcome::Rejected("bad".into())
+            }
+        });
+
+        assert_eq!(summary.sent, 1);
+        assert_eq!(summary.dropped, 1);
+        assert_eq!(outbox.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_retry_backs_off_exponentially() {
+        let (_dir, outbox) = outbox();
+        outbox.enqueue("result:1", result(1));
+        let now = unix_now();
+        let fail = |_: &Payload| SendOutcome::Retry {
+            error: "offline".into(),
+            retry_after_secs: None,
+        };
+
+        assert_eq!(outbox.flush_with(now, fail).retrying, 1);
+        let item = &outbox.load().items[0];
+        assert_eq!(item.attempts, 1);
+        assert_eq!(item.next_attempt_at, now + 30);
+
+        // Not due yet: nothing is attempted
+        assert_eq!(outbox.flush_with(now + 10, fail), FlushSummary::default());
+
+        outbox.flush_with(now + 30, fail);
+        assert_eq!(outbox.load().items[0].next_attempt_at, now + 30 + 60);
+        assert_eq!(backoff_secs(30), MAX_BACKOFF_SECS);
+    }
+
+    fn score() -> Payload {
+        Payload::Leaderboard(LeaderboardEntry {
+            player_name: "Ada".to_string(),
+            score: 100,
+            time_secs: 300,
+            difficulty: sudoku_core::Difficulty::Medium,
+            hints_used: 0,
+            mistakes: 0,
+            timestamp: 0,
+            puzzle_hash: "abc123".to_string(),
+            rank: None,
+            verified: true,
+        })
+    }
+
+    #[test]
+    fn test_nothing_is_sent_without_consent() {
+        crate::privacy::disable_for_session();
+        assert_eq!(send_payload(&result(1)), SendOutcome::Deferred);
+        assert_eq!(send_payload(&score()), SendOutcome::Deferred);
+    }
+
+    #[test]
+    fn test_clear_drops_results_and_scores() {
+        let (_dir, outbox) = outbox();
+        outbox.enqueue("result:1", result(1));
+        outbox.enqueue("leaderboard:Ada", score());
+        outbox.clear();
+        assert_eq!(outbox.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_panicking_flush_allows_the_next_one() {
+        FLUSHING.store(true, Ordering::Release);
+        let flush = std::thread::spawn(|| {
+            let _guard = FlushGuard;
+            panic!("flush failed");
+        });
+        assert!(flush.join().is_err());
+        assert!(!FLUSHING.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_retry_after_defers_remaining_items() {
+        let (_dir, outbox) = outbox();
+        outbox.enqueue("result:1", result(1));
+        outbox.enqueue("result:2", result(2));
+        let now = unix_now();
+
+        let mut calls = 0;
+        outbox.flush_with(now, |_| {
+            calls += 1;
+            SendOutcome::Retry {
+                error: "rate limited".into(),
+                retry_after_secs: Some(600),
+            }
+        });
+
+        assert_eq!(calls, 1);
+        let items = outbox.load().items;
+        assert_eq!(items[0].next_attempt_at, now + 600);
+        assert_eq!(items[1].attempts, 0);
+    }
+}
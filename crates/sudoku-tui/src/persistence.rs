@@ -1,21 +1,74 @@
-//! Centralized persistence utilities: app data directory and atomic file writes.
+//! Centralized persistence utilities: app data directory, the active
+//! profile's directory, atomic file writes and line appends.
 
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Data directory of the active profile (see `crate::profiles`)
+static PROFILE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Replaces the platform data directory (see [`set_data_dir`])
+static DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Keep all data in `dir` instead of the platform data directory, e.g. so a
+/// replayed session can't touch the player's stats. Call before
+/// `profiles::init`.
+pub fn set_data_dir(dir: PathBuf) {
+    *DATA_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
 
 /// Return the application data directory, creating it if needed.
 /// Panics if the platform has no local data directory (should never happen on
 /// macOS/Linux/Windows where `dirs` is supported).
 pub fn app_data_dir() -> PathBuf {
-    let dir = dirs::data_local_dir()
-        .expect("Platform has no local data directory")
-        .join("sudoku");
+    let custom = DATA_DIR.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let dir = custom.unwrap_or_else(|| {
+        dirs::data_local_dir()
+            .expect("Platform has no local data directory")
+            .join("sudoku")
+    });
     if !dir.exists() {
         std::fs::create_dir_all(&dir).expect("Failed to create app data directory");
     }
     dir
 }
 
+/// Set the directory returned by [`profile_dir`]
+pub fn set_profile_dir(dir: PathBuf) {
+    *PROFILE_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
+
+/// Where the default profile's saved game lived before it moved into
+/// [`profile_dir`]. None once the data directory has been replaced, so a
+/// sandboxed run never picks up the player's save.
+pub fn legacy_save_path() -> Option<PathBuf> {
+    if DATA_DIR.read().unwrap_or_else(|e| e.into_inner()).is_some() {
+        return None;
+    }
+    dirs::data_local_dir()
+        .map(|dir| dir.join("sudoku_save.json"))
+        .filter(|path| path.exists())
+}
+
+/// Return the active profile's data directory, creating it if needed.
+/// Falls back to [`app_data_dir`] before a profile is selected.
+pub fn profile_dir() -> PathBuf {
+    let dir = PROFILE_DIR
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    match dir {
+        Some(dir) => {
+            if !dir.exists() {
+                std::fs::create_dir_all(&dir).expect("Failed to create profile directory");
+            }
+            dir
+        }
+        None => app_data_dir(),
+    }
+}
+
 /// Write data to a file atomically: write to a temporary file in the same
 /// directory, then rename into place. This prevents corruption if the process
 /// crashes mid-write.
@@ -26,3 +79,44 @@ pub fn atomic_write(path: &Path, data: &[u8]) -> io::Result<()> {
     tmp.persist(path).map_err(|e| e.error)?;
     Ok(())
 }
+
+/// Append `line` plus a newline to a file, creating it if needed, and flush it
+/// to disk. If a previous append was cut off mid-line, the new line starts on
+/// a fresh line so only the torn one is lost.
+pub fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let mut data = String::with_capacity(line.len() + 2);
+    if file.metadata()?.len() > 0 {
+        file.seek(SeekFrom::End(-1))?;
+        let mut last = [0u8];
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            data.push('\n');
+        }
+    }
+    data.push_str(line);
+    data.push('\n');
+    file.write_all(data.as_bytes())?;
+    file.sync_data()
+}
+
+/// Keep all data and the config in one scratch directory for the whole test
+/// run, with telemetry off, so tests never touch the player's files
+#[cfg(test)]
+pub(crate) fn test_sandbox() -> &'static Path {
+    static DIR: std::sync::OnceLock<tempfile::TempDir> = std::sync::OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = tempfile::tempdir().unwrap();
+        set_data_dir(dir.path().to_path_buf());
+        crate::config::init(Some(dir.path().join("config.toml")));
+        crate::privacy::disable_for_session();
+        dir
+    })
+    .path()
+}
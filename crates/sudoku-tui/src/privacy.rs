@@ -0,0 +1,190 @@
+//! Telemetry consent, opt-out overrides, and the local record of what was sent.
+//!
+//! Nothing is uploaded until the player has explicitly agreed. The choice is
+//! stored in `sudoku_privacy.json`; `--no-telemetry`, `SUDOKU_TELEMETRY=off`
+//! or `DO_NOT_TRACK=1` turn uploads off regardless of the stored choice.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use sudoku_shared::dates::unix_now;
+
+/// Set by `--no-telemetry` for the current process
+static DISABLED_FOR_SESSION: AtomicBool = AtomicBool::new(false);
+
+/// Persisted privacy choices
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacySettings {
+    /// None until the player has answered the consent prompt
+    #[serde(default)]
+    pub telemetry: Option<bool>,
+    /// Unix timestamp of the last change
+    #[serde(default)]
+    pub decided_at: Option<u64>,
+}
+
+fn settings_path() -> PathBuf {
+    crate::persistence::profile_dir().join("sudoku_privacy.json")
+}
+
+fn audit_path() -> PathBuf {
+    crate::persistence::profile_dir().join("sudoku_telemetry_audit.jsonl")
+}
+
+/// Load the stored settings
+pub fn settings() -> PrivacySettings {
+    std::fs::read_to_string(settings_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Store the player's telemetry choice. Opting out also drops any results
+/// and leaderboard scores still waiting in the outbox.
+pub fn set_consent(enabled: bool) {
+    let settings = PrivacySettings {
+        telemetry: Some(enabled),
+        decided_at: Some(unix_now()),
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&settings) {
+        let _ = crate::persistence::atomic_write(&settings_path(), json.as_bytes());
+    }
+    if !enabled {
+        crate::outbox::Outbox::new().clear();
+    }
+}
+
+/// Disable telemetry for this process only (`--no-telemetry`)
+pub fn disable_for_session() {
+    DISABLED_FOR_SESSION.store(true, Ordering::Relaxed);
+}
+
+/// Whether an environment value switches telemetry off
+fn env_value_disables(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "0" | "false" | "off" | "no" | "disabled"
+    )
+}
+
+/// Why telemetry is forced off, if it is
+pub fn override_reason() -> Option<&'static str> {
+    if DISABLED_FOR_SESSION.load(Ordering::Relaxed) {
+        Some("--no-telemetry")
+    } else if std::env::var("SUDOKU_TELEMETRY").is_ok_and(|v| env_value_disables(&v)) {
+        Some("SUDOKU_TELEMETRY")
+    } else if std::env::var("DO_NOT_TRACK").is_ok_and(|v| !v.is_empty() && v != "0") {
+        Some("DO_NOT_TRACK")
+    } else {
+        None
+    }
+}
+
+/// Whether results may be uploaded
+pub fn telemetry_enabled() -> bool {
+    override_reason().is_none() && settings().telemetry == Some(true)
+}
+
+/// Whether to ask for consent on startup
+pub fn needs_prompt() -> bool {
+    override_reason().is_none() && settings().telemetry.is_none()
+}
+
+/// Append an uploaded payload to the local audit log
+pub fn record_sent(endpoint: &str, status: u16, payload: &serde_json::Value) {
+    let line = serde_json::json!({
+        "sent_at": unix_now(),
+        "endpoint": endpoint,
+        "status": status,
+        "payload": payload,
+    });
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_path())
+    {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Every local file that holds player data, keyed by export section
+fn data_files() -> Vec<(&'static str, PathBuf)> {
+    let dir = crate::persistence::profile_dir();
+    vec![
+        ("player_id", dir.join("sudoku_player_id")),
+        ("player_secret", dir.join("sudoku_player_secret")),
+        ("privacy", settings_path()),
+        ("stats", dir.join("sudoku_stats.json")),
+        (
+            "leaderboard",
+            crate::persistence::app_data_dir().join("sudoku_leaderboard.json"),
+        ),
+        ("outbox", dir.join("sudoku_outbox.json")),
+        ("telemetry_audit", audit_path()),
+        ("saved_game", crate::app::App::save_path()),
+    ]
+}
+
+/// Parse a stored file for export: JSON, JSON lines, or plain text
+fn export_value(contents: &str) -> serde_json::Value {
+    if let Ok(value) = serde_json::from_str(contents) {
+        return value;
+    }
+    let lines: Option<Vec<serde_json::Value>> = contents
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).ok())
+        .collect();
+    match lines {
+        Some(lines) => serde_json::Value::Array(lines),
+        None => serde_json::Value::String(contents.trim().to_string()),
+    }
+}
+
+/// Everything stored locally about the player, as one JSON document
+pub fn export() -> serde_json::Value {
+    let mut out = serde_json::Map::new();
+    out.insert("exported_at".into(), unix_now().into());
+    for (key, path) in data_files() {
+        let value = std::fs::read_to_string(&path)
+            .map(|c| export_value(&c))
+            .unwrap_or(serde_json::Value::Null);
+        out.insert(key.into(), value);
+    }
+    serde_json::Value::Object(out)
+}
+
+/// Remove all local player data. Returns the files that were deleted.
+pub fn delete_local() -> std::io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for (_, path) in data_files() {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_values() {
+        for v in ["0", "false", "OFF", " no ", "disabled"] {
+            assert!(env_value_disables(v), "{v}");
+        }
+        for v in ["1", "on", "true", ""] {
+            assert!(!env_value_disables(v), "{v}");
+        }
+    }
+
+    #[test]
+    fn test_export_value_formats() {
+        assert_eq!(export_value(r#"{"a":1}"#)["a"], 1);
+        assert_eq!(export_value("{\"a\":1}\n{\"a\":2}\n")[1]["a"], 2);
+        assert_eq!(export_value("abc-123\n"), "abc-123");
+    }
+}
@@ -0,0 +1,343 @@
+//! Named local player profiles.
+//!
+//! Each profile has its own stats, history, privacy settings, upload outbox,
+//! saved game and telemetry player id, stored under `profiles/<id>/` in the
+//! app data directory. Its settings live under `profiles/<id>/` in the config
+//! directory (see `config`). The default profile keeps using the top-level files so
+//! existing installs carry over unchanged. The profile list lives in
+//! `sudoku_profiles.json`; the local leaderboard and server config are shared.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use sudoku_shared::dates::unix_now;
+
+/// Id of the profile that owns the top-level data files
+pub const DEFAULT_ID: &str = "default";
+
+/// Longest allowed profile name
+pub const MAX_NAME_LEN: usize = 24;
+
+/// The profile in use by this process
+static ACTIVE: RwLock<Option<Profile>> = RwLock::new(None);
+
+/// A local player profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Stable directory name, kept across renames
+    pub id: String,
+    /// Display name, also used on leaderboards
+    pub name: String,
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+impl Profile {
+    fn default_profile() -> Self {
+        Self {
+            id: DEFAULT_ID.to_string(),
+            name: "Player".to_string(),
+            created_at: 0,
+            last_used: 0,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_ID
+    }
+
+    /// Data directory of this profile under `root`
+    pub fn dir(&self, root: &Path) -> PathBuf {
+        if self.is_default() {
+            root.to_path_buf()
+        } else {
+            root.join("profiles").join(&self.id)
+        }
+    }
+}
+
+/// Errors from profile management
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    EmptyName,
+    NameTooLong,
+    NameTaken(String),
+    NotFound(String),
+    /// The active profile can't be deleted
+    Active,
+    /// The default profile can't be deleted
+    Default,
+    Io(String),
+}
+
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "Profile name can't be empty"),
+            Self::NameTooLong => write!(f, "Profile name is longer than {}", MAX_NAME_LEN),
+            Self::NameTaken(name) => write!(f, "A profile named '{}' already exists", name),
+            Self::NotFound(name) => write!(f, "No profile named '{}'", name),
+            Self::Active => write!(f, "Switch to another profile before deleting this one"),
+            Self::Default => write!(f, "The default profile can't be deleted"),
+            Self::Io(e) => write!(f, "Profile storage error: {}", e),
+        }
+    }
+}
+
+/// All profiles and the last one used
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Registry {
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub last_active: Option<String>,
+}
+
+impl Registry {
+    fn path(root: &Path) -> PathBuf {
+        root.join("sudoku_profiles.json")
+    }
+
+    /// Load the registry under `root`, always including the default profile
+    pub fn load_from(root: &Path) -> Self {
+        let mut registry: Self = std::fs::read_to_string(Self::path(root))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        if !registry.profiles.iter().any(Profile::is_default) {
+            registry.profiles.insert(0, Profile::default_profile());
+        }
+        registry
+    }
+
+    pub fn save_to(&self, root: &Path) -> Result<(), ProfileError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| ProfileError::Io(e.to_string()))?;
+        crate::persistence::atomic_write(&Self::path(root), json.as_bytes())
+            .map_err(|e| ProfileError::Io(e.to_string()))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Find a profile by name (case-insensitive) or id
+    pub fn find(&self, name_or_id: &str) -> Option<&Profile> {
+        let wanted = name_or_id.trim();
+        self.profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(wanted))
+            .or_else(|| self.get(wanted))
+    }
+
+    /// Validate a new name for profile `id` (None for a new profile)
+    fn check_name(&self, name: &str, id: Option<&str>) -> Result<String, ProfileError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        if name.chars().count() > MAX_NAME_LEN {
+            return Err(ProfileError::NameTooLong);
+        }
+        if self
+            .profiles
+            .iter()
+            .any(|p| Some(p.id.as_str()) != id && p.name.eq_ignore_ascii_case(name))
+        {
+            return Err(ProfileError::NameTaken(name.to_string()));
+        }
+        Ok(name.to_string())
+    }
+
+    /// Add a profile; its id is derived from the name
+    pub fn create(&mut self, name: &str, now: u64) -> Result<Profile, ProfileError> {
+        let name = self.check_name(name, None)?;
+        let slug: String = name
+            .to_ascii_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug.trim_matches('-');
+        let base = if slug.is_empty() { "profile" } else { slug };
+
+        let mut id = base.to_string();
+        let mut n = 2;
+        while id == DEFAULT_ID || self.get(&id).is_some() {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let profile = Profile {
+            id,
+            name,
+            created_at: now,
+            last_used: 0,
+        };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<Profile, ProfileError> {
+        let name = self.check_name(name, Some(id))?;
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| ProfileError::NotFound(id.to_string()))?;
+        profile.name = name;
+        Ok(profile.clone())
+    }
+
+    /// Remove a profile from the list (its files are left to the caller)
+    pub fn remove(&mut self, id: &str, active_id: &str) -> Result<Profile, ProfileError> {
+        if id == DEFAULT_ID {
+            return Err(ProfileError::Default);
+        }
+        if id == active_id {
+            return Err(ProfileError::Active);
+        }
+        let pos = self
+            .profiles
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| ProfileError::NotFound(id.to_string()))?;
+        if self.last_active.as_deref() == Some(id) {
+            self.last_active = None;
+        }
+        Ok(self.profiles.remove(pos))
+    }
+}
+
+/// The profile list
+pub fn registry() -> Registry {
+    Registry::load_from(&crate::persistence::app_data_dir())
+}
+
+/// The active profile
+pub fn active() -> Profile {
+    ACTIVE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(Profile::default_profile)
+}
+
+/// Make `profile` the active one: per-profile files now resolve to its directory
+pub fn activate(profile: &Profile) -> Result<(), ProfileError> {
+    let root = crate::persistence::app_data_dir();
+    let mut registry = Registry::load_from(&root);
+    registry.last_active = Some(profile.id.clone());
+    if let Some(p) = registry.profiles.iter_mut().find(|p| p.id == profile.id) {
+        p.last_used = unix_now();
+    }
+    registry.save_to(&root)?;
+
+    crate::persistence::set_profile_dir(profile.dir(&root));
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some(profile.clone());
+    // The cached auth token belongs to the previous profile's player id
+    crate::telemetry::clear_token();
+    Ok(())
+}
+
+/// Pick the startup profile: `name` (created if missing), else the last one used
+pub fn init(name: Option<&str>) -> Result<Profile, ProfileError> {
+    let profile = match name {
+        Some(name) => match registry().find(name) {
+            Some(profile) => profile.clone(),
+            None => create(name)?,
+        },
+        None => {
+            let registry = registry();
+            registry
+                .last_active
+                .as_deref()
+                .and_then(|id| registry.get(id))
+                .cloned()
+                .unwrap_or_else(Profile::default_profile)
+        }
+    };
+    activate(&profile)?;
+    Ok(profile)
+}
+
+pub fn create(name: &str) -> Result<Profile, ProfileError> {
+    let root = crate::persistence::app_data_dir();
+    let mut registry = Registry::load_from(&root);
+    let profile = registry.create(name, unix_now())?;
+    registry.save_to(&root)?;
+    Ok(profile)
+}
+
+pub fn rename(id: &str, name: &str) -> Result<Profile, ProfileError> {
+    let root = crate::persistence::app_data_dir();
+    let mut registry = Registry::load_from(&root);
+    let profile = registry.rename(id, name)?;
+    registry.save_to(&root)?;
+    if active().id == id {
+        *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some(profile.clone());
+    }
+    Ok(profile)
+}
+
+/// Delete a profile and all of its local data
+pub fn delete(id: &str) -> Result<Profile, ProfileError> {
+    let root = crate::persistence::app_data_dir();
+    let mut registry = Registry::load_from(&root);
+    let profile = registry.remove(id, &active().id)?;
+    registry.save_to(&root)?;
+
+    let dir = profile.dir(&root);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| ProfileError::Io(e.to_string()))?;
+    }
+    crate::config::remove_profile(&profile).map_err(|e| ProfileError::Io(e.to_string()))?;
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_create_rename_remove() {
+        let root = tempfile::tempdir().unwrap();
+        let mut registry = Registry::load_from(root.path());
+        assert_eq!(registry.profiles.len(), 1);
+        assert!(registry.profiles[0].is_default());
+
+        let ann = registry.create("Ann Lee", 1).unwrap();
+        assert_eq!(ann.id, "ann-lee");
+        assert_eq!(
+            ann.dir(root.path()),
+            root.path().join("profiles").join("ann-lee")
+        );
+        assert_eq!(
+            registry.create("ann lee", 2),
+            Err(ProfileError::NameTaken("ann lee".to_string()))
+        );
+        assert_eq!(registry.create("  ", 2), Err(ProfileError::EmptyName));
+        let other = registry.create("Ann-Lee!", 2).unwrap();
+        assert_eq!(other.id, "ann-lee-2");
+
+        registry.rename("ann-lee", "Annie").unwrap();
+        assert_eq!(registry.find("ANNIE").unwrap().id, "ann-lee");
+        // Renaming to your own name (case change) is fine
+        registry.rename("ann-lee", "annie").unwrap();
+
+        registry.save_to(root.path()).unwrap();
+        let mut registry = Registry::load_from(root.path());
+        assert_eq!(registry.profiles.len(), 3);
+
+        assert_eq!(
+            registry.remove(DEFAULT_ID, "ann-lee"),
+            Err(ProfileError::Default)
+        );
+        assert_eq!(
+            registry.remove("ann-lee", "ann-lee"),
+            Err(ProfileError::Active)
+        );
+        registry.remove("ann-lee", DEFAULT_ID).unwrap();
+        assert!(registry.find("annie").is_none());
+    }
+}
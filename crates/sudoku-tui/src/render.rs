@@ -1,158 +1,363 @@
 use crate::animations::particles::hue_to_rgb;
 use crate::animations::CelebrationManager;
-use crate::app::{App, InputMode, MenuState, ScreenState, MAX_MISTAKES};
+use crate::app::{App, InputMode, MenuState, ProfileEdit, ScreenState, MAX_MISTAKES, SETTINGS};
+use crate::config::Action;
+use crate::frame::{draw, Frame};
+use crate::lan::coop::Coop;
+use crate::lan::race::{self, Race, Status};
 use crate::stats::{format_time, GameResult};
+use crate::trends::{self, TrendView};
 use crossterm::{
-    cursor::{Hide, MoveTo, Show},
-    execute,
+    cursor::MoveTo,
     style::{Color, Print, SetBackgroundColor, SetForegroundColor},
-    terminal::{self, Clear, ClearType},
+    terminal::{Clear, ClearType},
 };
+use serde::{Deserialize, Serialize};
 use std::io;
 use sudoku_core::Position;
+use sudoku_shared::achievements::{Achievement, Condition};
+
+/// Something on screen that reacts to the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Cell(Position),
+    /// A digit on the number pad
+    Digit(u8),
+    /// An entry of the controls list or the mode indicator
+    Action(Action),
+    /// An option of the open menu
+    MenuItem(usize),
+    /// A row of the settings screen
+    SettingsRow(usize),
+}
+
+/// Where each clickable thing was drawn in the last frame. Regions are one
+/// row tall; later ones are on top (menus are drawn over the board).
+#[derive(Debug, Default)]
+pub struct HitMap {
+    regions: Vec<(u16, u16, u16, Target)>,
+}
+
+impl HitMap {
+    /// Make `width` columns starting at (`x`, `y`) hit `target`
+    fn add(&mut self, x: u16, y: u16, width: u16, target: Target) {
+        self.regions.push((x, y, width, target));
+    }
+
+    /// The topmost target at a screen position
+    pub fn at(&self, x: u16, y: u16) -> Option<Target> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|&&(rx, ry, width, _)| y == ry && x >= rx && x < rx + width)
+            .map(|&(_, _, _, target)| target)
+    }
+}
+
+/// How the game screen is arranged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Pick by terminal size
+    #[default]
+    Auto,
+    /// Info and controls stacked under the grid
+    Compact,
+    /// Info panel beside the grid, controls below
+    Standard,
+    /// Big digits and every note in its own spot of a 3x3 pencil grid
+    Large,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 4] = [Self::Auto, Self::Compact, Self::Standard, Self::Large];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Compact => "Compact",
+            Self::Standard => "Standard",
+            Self::Large => "Large",
+        }
+    }
+
+    /// Terminal size needed to draw the game screen without clipping
+    fn min_size(self) -> (u16, u16) {
+        match self {
+            Self::Auto | Self::Compact => (37, 24),
+            Self::Standard => (64, 25),
+            Self::Large => (100, 44),
+        }
+    }
+
+    /// The layout to draw in a `width` x `height` terminal: the chosen one
+    /// if it fits, otherwise the largest smaller one that does
+    pub fn resolve(self, width: u16, height: u16) -> Layout {
+        let fits = |layout: Layout| {
+            let (w, h) = layout.min_size();
+            width >= w && height >= h
+        };
+        match self {
+            Self::Auto => [Self::Large, Self::Standard]
+                .into_iter()
+                .find(|&l| fits(l))
+                .unwrap_or(Self::Compact),
+            Self::Large if fits(Self::Large) => Self::Large,
+            Self::Large | Self::Standard if fits(Self::Standard) => Self::Standard,
+            _ => Self::Compact,
+        }
+    }
 
-pub fn render(stdout: &mut io::Stdout, app: &mut App) -> io::Result<()> {
-    let (term_width, term_height) = terminal::size()?;
+    /// Width and height of one cell, not counting borders
+    fn cell_size(self) -> (u16, u16) {
+        match self {
+            Self::Large => (7, 3),
+            _ => (3, 1),
+        }
+    }
+}
 
-    execute!(stdout, Hide)?;
+/// Three-line glyphs for the digits 1-9 in the large layout
+const BIG_DIGITS: [[&str; 3]; 9] = [
+    [" ╶┐", "  │", " ╶┴"],
+    ["╶─┐", "┌─┘", "└─╴"],
+    ["╶─┐", " ─┤", "╶─┘"],
+    ["╷ ╷", "└─┤", "  ╵"],
+    ["┌─╴", "└─┐", "╶─┘"],
+    ["┌─╴", "├─┐", "└─┘"],
+    ["╶─┐", "  │", "  ╵"],
+    ["┌─┐", "├─┤", "└─┘"],
+    ["┌─┐", "└─┤", "╶─┘"],
+];
+
+/// Draw the current screen into `frame`, which is the size of the terminal
+pub fn render(frame: &mut Frame, app: &mut App) -> io::Result<()> {
+    let (term_width, term_height) = frame.size();
+    let mut hits = HitMap::default();
 
     match app.screen_state {
         // For animation screens, don't clear - we redraw everything anyway
-        ScreenState::Win => render_win_screen(stdout, app, term_width, term_height)?,
-        ScreenState::Lose => render_lose_screen(stdout, app, term_width, term_height)?,
+        ScreenState::Win => render_win_screen(frame, app, term_width, term_height)?,
+        ScreenState::Lose => render_lose_screen(frame, app, term_width, term_height)?,
         ScreenState::Stats => {
-            execute!(stdout, Clear(ClearType::All))?;
-            render_stats_screen(stdout, app, term_width, term_height)?;
+            draw!(frame, Clear(ClearType::All))?;
+            render_stats_screen(frame, app, term_width, term_height)?;
         }
         ScreenState::Leaderboard => {
-            execute!(stdout, Clear(ClearType::All))?;
-            render_leaderboard_screen(stdout, app, term_width, term_height)?;
+            draw!(frame, Clear(ClearType::All))?;
+            render_leaderboard_screen(frame, app, term_width, term_height)?;
         }
         ScreenState::History => {
-            execute!(stdout, Clear(ClearType::All))?;
-            render_history_screen(stdout, app, term_width, term_height)?;
+            draw!(frame, Clear(ClearType::All))?;
+            render_history_screen(frame, app, term_width, term_height)?;
+        }
+        ScreenState::Profiles => {
+            draw!(frame, Clear(ClearType::All))?;
+            render_profiles_screen(frame, app, term_width, term_height)?;
+        }
+        ScreenState::Achievements => {
+            draw!(frame, Clear(ClearType::All))?;
+            render_achievements_screen(frame, app, term_width, term_height)?;
+        }
+        ScreenState::Settings => {
+            draw!(frame, Clear(ClearType::All))?;
+            render_settings_screen(frame, app, &mut hits, term_width, term_height)?;
+        }
+        ScreenState::Lobby => {
+            draw!(frame, Clear(ClearType::All))?;
+            render_lobby_screen(frame, app, term_width, term_height)?;
         }
         ScreenState::Playing => {
             // Only clear for playing mode to avoid flicker during animations
-            execute!(stdout, Clear(ClearType::All))?;
-            render_game_screen(stdout, app, term_width, term_height)?;
+            draw!(frame, Clear(ClearType::All))?;
+            render_game_screen(frame, app, &mut hits, term_width, term_height)?;
         }
     }
+    app.hit_map = hits;
 
-    execute!(stdout, Show)?;
     Ok(())
 }
 
 fn render_game_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
+    hits: &mut HitMap,
     term_width: u16,
     term_height: u16,
 ) -> io::Result<()> {
-    // Grid dimensions: 37 chars wide x 19 tall
-    // Each cell is 3 chars wide, plus borders
-    // Row: | X | X | X || X | X | X || X | X | X |
-    //      1 + (3*3 + 2) * 3 + 2 = 1 + 11*3 + 2 = 36... let me recalc
-    // Actually: "|  X  X  X |  X  X  X |  X  X  X |"
-    // = 1 + 9 + 1 + 9 + 1 + 9 + 1 = 31 chars for cells + borders
-
-    let grid_width: u16 = 37;
-    let grid_height: u16 = 19;
-
-    // Center the grid horizontally, leave room for info panel
-    let total_width = grid_width + 25; // grid + gap + info panel
-    let start_x = if term_width > total_width {
-        (term_width - total_width) / 2
+    let layout = app.config.layout.resolve(term_width, term_height);
+
+    // Each cell is cell_w x cell_h inside single-character borders:
+    // 37x19 for the standard grid, 73x37 for the large one
+    let (cell_w, cell_h) = layout.cell_size();
+    let grid_width = 9 * (cell_w + 1) + 1;
+    let grid_height = 9 * (cell_h + 1) + 1;
+
+    if layout == Layout::Compact {
+        let (min_width, min_height) = layout.min_size();
+        if term_width < min_width || term_height < min_height {
+            let notice = format!("Enlarge the terminal to {}x{}", min_width, min_height);
+            draw!(
+                frame,
+                MoveTo(0, term_height / 2),
+                SetForegroundColor(app.theme.info),
+                Print(notice)
+            )?;
+            return Ok(());
+        }
+
+        let start_x = (term_width - grid_width) / 2;
+        render_grid(frame, app, hits, layout, start_x, 1)?;
+
+        let info_y = 1 + grid_height;
+        render_compact_info(frame, app, hits, start_x, info_y)?;
+
+        // As many control columns as fit across, and rows as fit below
+        let columns = (term_width / 17).clamp(1, 4);
+        let controls_x = (term_width - columns * 17) / 2;
+        let controls_y = info_y + 4;
+        let rows = term_height.saturating_sub(controls_y);
+        match (&app.race, &app.coop) {
+            (Some(race), _) if race.started => {
+                render_race_panel(frame, app, race, controls_x, controls_y, columns * 17, rows)?
+            }
+            (_, Some(coop)) if coop.started => {
+                render_team_panel(frame, app, coop, controls_x, controls_y, columns * 17, rows)?
+            }
+            _ => render_controls(frame, app, hits, controls_x, controls_y, columns, rows)?,
+        }
     } else {
-        1
-    };
+        // Center the grid horizontally, leave room for info panel
+        let total_width = grid_width + 25; // grid + gap + info panel
+        let start_x = if term_width > total_width {
+            (term_width - total_width) / 2
+        } else {
+            1
+        };
 
-    let start_y = if term_height > grid_height + 12 { 2 } else { 1 };
+        let start_y = if term_height > grid_height + 12 { 2 } else { 1 };
 
-    render_grid(stdout, app, start_x, start_y)?;
+        render_grid(frame, app, hits, layout, start_x, start_y)?;
 
-    let info_x = start_x + grid_width + 3;
-    render_info_panel(stdout, app, info_x, start_y)?;
+        let info_x = start_x + grid_width + 3;
+        render_info_panel(frame, app, hits, info_x, start_y)?;
 
-    let controls_y = start_y + grid_height + 1;
-    render_controls(stdout, app, start_x, controls_y)?;
+        let controls_y = start_y + grid_height + 1;
+        match (&app.race, &app.coop) {
+            (Some(race), _) if race.started => {
+                render_race_panel(frame, app, race, start_x, controls_y, 4 * 17, 4)?
+            }
+            (_, Some(coop)) if coop.started => {
+                render_team_panel(frame, app, coop, start_x, controls_y, 4 * 17, 4)?
+            }
+            _ => render_controls(frame, app, hits, start_x, controls_y, 4, 4)?,
+        }
+    }
 
     if let Some(ref msg) = app.message {
-        render_message(stdout, app, msg, term_width)?;
+        render_message(frame, app, msg, term_width)?;
     }
 
     if app.menu != MenuState::None {
-        render_menu(stdout, app, term_width, term_height)?;
+        render_menu(frame, app, hits, term_width, term_height)?;
     }
 
     if let Some(ref hint) = app.current_hint {
-        render_hint(stdout, app, &hint.explanation, term_width, term_height)?;
+        render_hint(frame, app, &hint.explanation, term_width, term_height)?;
     }
 
     Ok(())
 }
 
-fn render_grid(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::Result<()> {
+/// A horizontal grid line with `cell_w` fill characters per cell
+fn grid_separator(cell_w: u16, fill: char) -> String {
+    let segment = fill.to_string().repeat(cell_w as usize);
+    format!("+{}", format!("{}+", segment).repeat(9))
+}
+
+fn render_grid(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    layout: Layout,
+    x: u16,
+    y: u16,
+) -> io::Result<()> {
     let theme = &app.theme;
+    let (cell_w, cell_h) = layout.cell_size();
+    let thick = grid_separator(cell_w, '=');
+    let thin = grid_separator(cell_w, '-');
 
     // Grid design:
     // +---+---+---+---+---+---+---+---+---+
     // | 5 | 3 | . | . | 7 | . | . | . | . |
     // +---+---+---+---+---+---+---+---+---+
-    // Each cell is 3 chars: " X "
+    // Each cell is cell_w x cell_h: " X " in the standard layout
     // Border chars: + and - and |
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
     // Top border (thick - uses box_border for visibility)
     // Check if row 0 is celebrating
     let row0_intensity = app.celebrations.row_intensity(0);
     let top_border_color = CelebrationManager::throb_color(theme.box_border, row0_intensity);
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y),
         SetForegroundColor(top_border_color),
-        Print("+===+===+===+===+===+===+===+===+===+")
+        Print(&thick)
     )?;
 
     for row in 0..9 {
-        let cell_y = y + 1 + row as u16 * 2;
+        let row_y = y + 1 + row as u16 * (cell_h + 1);
 
         // Get row celebration intensity
         let row_intensity = app.celebrations.row_intensity(row);
 
-        // Cell row
-        execute!(stdout, MoveTo(x, cell_y))?;
+        for line in 0..cell_h {
+            let cell_y = row_y + line;
 
-        for col in 0..9 {
-            // Get column celebration intensity
-            let col_intensity = app.celebrations.column_intensity(col);
-            let border_intensity = row_intensity.max(col_intensity);
+            // Cell row
+            draw!(frame, MoveTo(x, cell_y))?;
 
-            // Left border - thick borders at 3x3 boundaries
-            let base_border_color = if col % 3 == 0 {
-                theme.box_border
-            } else {
-                theme.border
-            };
-            let border_color = CelebrationManager::throb_color(base_border_color, border_intensity);
+            for col in 0..9 {
+                // Get column celebration intensity
+                let col_intensity = app.celebrations.column_intensity(col);
+                let border_intensity = row_intensity.max(col_intensity);
 
-            if col % 3 == 0 {
-                execute!(stdout, SetForegroundColor(border_color), Print("║"))?;
-            } else {
-                execute!(stdout, SetForegroundColor(border_color), Print("│"))?;
-            }
+                // Left border - thick borders at 3x3 boundaries
+                let base_border_color = if col % 3 == 0 {
+                    theme.box_border
+                } else {
+                    theme.border
+                };
+                let border_color =
+                    CelebrationManager::throb_color(base_border_color, border_intensity);
+
+                if col % 3 == 0 {
+                    draw!(frame, SetForegroundColor(border_color), Print("║"))?;
+                } else {
+                    draw!(frame, SetForegroundColor(border_color), Print("│"))?;
+                }
 
-            let pos = Position::new(row, col);
-            render_cell(stdout, app, pos)?;
+                let pos = Position::new(row, col);
+                render_cell(frame, app, pos, layout, line)?;
+                hits.add(
+                    x + 1 + col as u16 * (cell_w + 1),
+                    cell_y,
+                    cell_w,
+                    Target::Cell(pos),
+                );
+            }
+            // Right border (thick) - use row intensity for right border
+            let right_border_color =
+                CelebrationManager::throb_color(theme.box_border, row_intensity);
+            draw!(frame, SetForegroundColor(right_border_color), Print("║"))?;
         }
-        // Right border (thick) - use row intensity for right border
-        let right_border_color = CelebrationManager::throb_color(theme.box_border, row_intensity);
-        execute!(stdout, SetForegroundColor(right_border_color), Print("║"))?;
 
         // Horizontal separator
-        let sep_y = cell_y + 1;
-        execute!(stdout, MoveTo(x, sep_y))?;
+        let sep_y = row_y + cell_h;
+        draw!(frame, MoveTo(x, sep_y))?;
 
         // Get intensities for the row below this separator
         let next_row = row + 1;
@@ -166,34 +371,29 @@ fn render_grid(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::Result
         if row == 8 {
             // Bottom border (thick - highlighted)
             let bottom_color = CelebrationManager::throb_color(theme.box_border, row_intensity);
-            execute!(
-                stdout,
-                SetForegroundColor(bottom_color),
-                Print("+===+===+===+===+===+===+===+===+===+")
-            )?;
+            draw!(frame, SetForegroundColor(bottom_color), Print(&thick))?;
         } else if (row + 1) % 3 == 0 {
             // Box separator (thick - highlighted)
             let box_sep_color = CelebrationManager::throb_color(theme.box_border, sep_intensity);
-            execute!(
-                stdout,
-                SetForegroundColor(box_sep_color),
-                Print("+===+===+===+===+===+===+===+===+===+")
-            )?;
+            draw!(frame, SetForegroundColor(box_sep_color), Print(&thick))?;
         } else {
             // Regular separator (thinner color)
             let sep_color = CelebrationManager::throb_color(theme.border, sep_intensity);
-            execute!(
-                stdout,
-                SetForegroundColor(sep_color),
-                Print("+---+---+---+---+---+---+---+---+---+")
-            )?;
+            draw!(frame, SetForegroundColor(sep_color), Print(&thin))?;
         }
     }
 
     Ok(())
 }
 
-fn render_cell(stdout: &mut io::Stdout, app: &App, pos: Position) -> io::Result<()> {
+/// Draw line `line` of a cell (there's only line 0 outside the large layout)
+fn render_cell(
+    frame: &mut Frame,
+    app: &App,
+    pos: Position,
+    layout: Layout,
+    line: u16,
+) -> io::Result<()> {
     let theme = &app.theme;
     let game = &app.game;
     let cell = game.grid().cell(pos);
@@ -210,9 +410,17 @@ fn render_cell(stdout: &mut io::Stdout, app: &App, pos: Position) -> io::Result<
     // Take the maximum intensity from any active celebration affecting this cell
     let celebration_intensity = row_intensity.max(col_intensity).max(box_intensity);
 
+    // A teammate's cursor, drawn in their color
+    let teammate = app
+        .coop
+        .as_ref()
+        .and_then(|coop| coop.others().find(|t| t.cursor == Some(pos)));
+
     // Background color
     let mut bg = if is_cursor {
         theme.selected_bg
+    } else if let Some(teammate) = teammate {
+        team_color(teammate.color)
     } else if has_same_value && !cell.is_empty() {
         Color::Rgb {
             r: 60,
@@ -246,11 +454,15 @@ fn render_cell(stdout: &mut io::Stdout, app: &App, pos: Position) -> io::Result<
         fg = CelebrationManager::throb_color(fg, celebration_intensity * 0.5);
     }
 
-    execute!(stdout, SetBackgroundColor(bg), SetForegroundColor(fg))?;
+    draw!(frame, SetBackgroundColor(bg), SetForegroundColor(fg))?;
+
+    if layout == Layout::Large {
+        return render_large_cell(frame, app, pos, line);
+    }
 
     // Cell content: 3 chars " X "
     if let Some(value) = cell.value() {
-        execute!(stdout, Print(format!(" {} ", value)))?;
+        draw!(frame, Print(format!(" {} ", value)))?;
     } else {
         let candidates = cell.candidates();
         let count = candidates.count();
@@ -260,55 +472,104 @@ fn render_cell(stdout: &mut io::Stdout, app: &App, pos: Position) -> io::Result<
                 let valid = app.game.grid().get_candidates(pos);
                 if valid.count() == 1 {
                     let val = valid.single_value().unwrap();
-                    execute!(
-                        stdout,
+                    draw!(
+                        frame,
                         SetForegroundColor(Color::DarkGrey),
                         Print(format!(" {} ", val))
                     )?;
                 } else {
-                    execute!(stdout, SetForegroundColor(Color::DarkGrey), Print(" · "))?;
+                    draw!(frame, SetForegroundColor(Color::DarkGrey), Print(" · "))?;
                 }
             } else {
-                execute!(stdout, SetForegroundColor(Color::DarkGrey), Print(" · "))?;
+                draw!(frame, SetForegroundColor(Color::DarkGrey), Print(" · "))?;
             }
         } else if count == 1 {
             // Single note - show it (this is user's own note, always show)
             let val = candidates.single_value().unwrap();
-            execute!(stdout, Print(format!(" {} ", val)))?;
+            draw!(frame, Print(format!(" {} ", val)))?;
         } else {
             // Multiple notes - show asterisk
-            execute!(stdout, Print(" * "))?;
+            draw!(frame, Print(" * "))?;
         }
     }
 
     Ok(())
 }
 
-fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::Result<()> {
+/// One line of a 7x3 cell: a big digit, a 3x3 grid of notes, or a dot
+fn render_large_cell(frame: &mut Frame, app: &App, pos: Position, line: u16) -> io::Result<()> {
+    let cell = app.game.grid().cell(pos);
+    if let Some(value) = cell.value() {
+        let glyph = BIG_DIGITS[value as usize - 1][line as usize];
+        return draw!(frame, Print(format!("  {}  ", glyph)));
+    }
+
+    let candidates = cell.candidates();
+    if candidates.count() > 0 {
+        // Notes sit where they are on a number pad: 1-3 top, 7-9 bottom
+        let marks: String = (1..=3)
+            .map(|i| {
+                let digit = line as u8 * 3 + i;
+                if candidates.contains(digit) {
+                    format!(" {}", digit)
+                } else {
+                    "  ".to_string()
+                }
+            })
+            .collect();
+        return draw!(frame, Print(format!("{} ", marks)));
+    }
+
+    let mark = if line != 1 {
+        ' '
+    } else if app.show_naked_singles {
+        // Same ghost hint as the standard grid
+        let valid = app.game.grid().get_candidates(pos);
+        match valid.single_value() {
+            Some(val) if valid.count() == 1 => (b'0' + val) as char,
+            _ => '·',
+        }
+    } else {
+        '·'
+    };
+    draw!(
+        frame,
+        SetForegroundColor(Color::DarkGrey),
+        Print(format!("   {}   ", mark))
+    )
+}
+
+fn render_info_panel(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    x: u16,
+    y: u16,
+) -> io::Result<()> {
     let theme = &app.theme;
     let game = &app.game;
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
     // Title
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y),
         SetForegroundColor(theme.key),
         Print("═══ SUDOKU ═══")
     )?;
 
     // Time
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 2),
         SetForegroundColor(theme.info),
         Print(format!("Time: {:>10}", game.elapsed_string()))
     )?;
 
     // Difficulty
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 4),
         SetForegroundColor(theme.info),
         Print(format!(
@@ -329,14 +590,15 @@ fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::
     } else {
         theme.fg
     };
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 6),
         SetForegroundColor(theme.info),
         Print("Mode: "),
         SetForegroundColor(mode_color),
         Print(format!("{:>10}", mode_str))
     )?;
+    hits.add(x, y + 6, 16, Target::Action(Action::ToggleMode));
 
     // Mistakes
     let mistakes_color = if game.mistakes() >= MAX_MISTAKES - 1 {
@@ -346,8 +608,8 @@ fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::
     } else {
         theme.info
     };
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 8),
         SetForegroundColor(mistakes_color),
         Print(format!(
@@ -357,51 +619,29 @@ fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::
     )?;
 
     // Hints
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 10),
         SetForegroundColor(theme.info),
         Print(format!("Hints used: {:>4}", game.hints_used()))
     )?;
 
     // Separator
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 12),
         SetForegroundColor(theme.border),
         Print("────────────────")
     )?;
 
-    // Number completion indicator
-    let completed = game.completed_numbers();
-    execute!(
-        stdout,
-        MoveTo(x, y + 14),
-        SetForegroundColor(theme.info),
-        Print("Numbers: ")
-    )?;
-    for (i, &is_completed) in completed.iter().enumerate() {
-        let num = (i + 1) as u8;
-        if is_completed {
-            execute!(
-                stdout,
-                SetForegroundColor(theme.success),
-                Print(format!("{}", num))
-            )?;
-        } else {
-            execute!(
-                stdout,
-                SetForegroundColor(theme.border),
-                Print(format!("{}", num))
-            )?;
-        }
-    }
+    // Number completion indicator, doubling as a number pad
+    render_number_pad(frame, app, hits, x, y + 14)?;
 
     // Current cell
     let pos = app.cursor;
     let cell = game.grid().cell(pos);
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + 16),
         SetForegroundColor(theme.info),
         Print(format!("Cell: Row {} Col {}", pos.row + 1, pos.col + 1))
@@ -415,15 +655,15 @@ fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::
                 .map(|v| v.to_string())
                 .collect::<Vec<_>>()
                 .join(" ");
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x, y + 17),
                 SetForegroundColor(Color::Green),
                 Print(format!("Valid: {:>9}", valid_str))
             )?;
         } else {
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x, y + 17),
                 SetForegroundColor(theme.border),
                 Print("Valid:   (v=show)")
@@ -436,58 +676,252 @@ fn render_info_panel(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::
             .map(|v| v.to_string())
             .collect::<Vec<_>>()
             .join(" ");
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(x, y + 18),
             SetForegroundColor(theme.candidate),
             Print(format!("Notes: {:>9}", notes_str))
         )?;
     } else {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(x, y + 17),
             SetForegroundColor(theme.bg),
             Print("                 ")
         )?;
-        execute!(stdout, MoveTo(x, y + 18), Print("                 "))?;
+        draw!(frame, MoveTo(x, y + 18), Print("                 "))?;
+    }
+
+    Ok(())
+}
+
+/// "Numbers: 123456789" with completed digits lit; each digit is clickable
+fn render_number_pad(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    x: u16,
+    y: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let completed = app.game.completed_numbers();
+    draw!(
+        frame,
+        MoveTo(x, y),
+        SetForegroundColor(theme.info),
+        Print("Numbers: ")
+    )?;
+    for (i, &is_completed) in completed.iter().enumerate() {
+        let num = (i + 1) as u8;
+        hits.add(x + 9 + i as u16, y, 1, Target::Digit(num));
+        if is_completed {
+            draw!(
+                frame,
+                SetForegroundColor(theme.success),
+                Print(format!("{}", num))
+            )?;
+        } else {
+            draw!(
+                frame,
+                SetForegroundColor(theme.border),
+                Print(format!("{}", num))
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The info panel squeezed into four lines under the grid
+fn render_compact_info(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    x: u16,
+    y: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let game = &app.game;
+
+    draw!(
+        frame,
+        SetBackgroundColor(theme.bg),
+        MoveTo(x, y),
+        SetForegroundColor(theme.info),
+        Print(format!(
+            "{} (SE {:.1})",
+            game.difficulty(),
+            game.se_rating()
+        )),
+        MoveTo(x + 27, y),
+        Print(format!("{:>10}", game.elapsed_string()))
+    )?;
+
+    let (mode_str, mode_color) = match app.mode {
+        InputMode::Candidate => ("Notes", Color::Cyan),
+        _ => ("Normal", theme.fg),
+    };
+    let mistakes_color = if game.mistakes() >= MAX_MISTAKES - 1 {
+        theme.error
+    } else if game.mistakes() > 0 {
+        Color::Yellow
+    } else {
+        theme.info
+    };
+    draw!(
+        frame,
+        MoveTo(x, y + 1),
+        SetForegroundColor(theme.info),
+        Print("Mode: "),
+        SetForegroundColor(mode_color),
+        Print(format!("{:<7}", mode_str)),
+        SetForegroundColor(mistakes_color),
+        Print(format!("Mistakes {}/{}", game.mistakes(), MAX_MISTAKES)),
+        SetForegroundColor(theme.info),
+        Print(format!("  Hints {}", game.hints_used()))
+    )?;
+    hits.add(x, y + 1, 13, Target::Action(Action::ToggleMode));
+
+    render_number_pad(frame, app, hits, x, y + 2)?;
+
+    let pos = app.cursor;
+    let cell = game.grid().cell(pos);
+    draw!(
+        frame,
+        MoveTo(x, y + 3),
+        SetForegroundColor(theme.info),
+        Print(format!("R{}C{}", pos.row + 1, pos.col + 1))
+    )?;
+    if cell.is_empty() {
+        let list = |digits: Vec<u8>| {
+            digits
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("")
+        };
+        draw!(
+            frame,
+            SetForegroundColor(theme.candidate),
+            Print(format!(
+                "  Notes: {:<9}",
+                list(cell.candidates().iter().collect())
+            ))
+        )?;
+        if app.show_suggestions {
+            let valid = game.grid().get_candidates(pos);
+            draw!(
+                frame,
+                SetForegroundColor(Color::Green),
+                Print(format!(" Valid: {}", list(valid.iter().collect())))
+            )?;
+        }
     }
 
     Ok(())
 }
 
-fn render_controls(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::Result<()> {
+fn render_controls(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    x: u16,
+    y: u16,
+    columns: u16,
+    rows: u16,
+) -> io::Result<()> {
     let theme = &app.theme;
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
+    // First key of each action, joined for pairs like fill/fill all
+    let keys = |actions: &[Action]| -> String {
+        let firsts: Vec<String> = actions
+            .iter()
+            .filter_map(|&a| app.keymap.keys(a).first().map(|k| k.to_string()))
+            .collect();
+        let joiner = if firsts.iter().all(|k| k.chars().count() == 1) && actions.len() == 4 {
+            ""
+        } else {
+            "/"
+        };
+        firsts.join(joiner)
+    };
+    // The action a click runs, if any; Quit isn't clickable so a stray
+    // click can't end the game
     let controls = [
-        ("hjkl/Arrows", "Move"),
-        ("1-9", "Set/Note"),
-        ("0/Del", "Clear"),
-        ("c", "Notes mode"),
-        ("f/F", "Fill notes"),
-        ("x/X", "Clear notes"),
-        ("v", "Valid hints"),
-        ("g", "Ghost hints"),
-        ("n", "New game"),
-        ("?/!", "Hint"),
-        ("u", "Undo"),
-        ("i", "Stats"),
-        ("b", "Leaderboard"),
-        ("H", "History"),
-        ("t", "Theme"),
-        ("q", "Quit"),
+        (
+            keys(&[Action::Left, Action::Down, Action::Up, Action::Right]),
+            "Move",
+            None,
+        ),
+        ("1-9".to_string(), "Set/Note", None),
+        (keys(&[Action::Clear]), "Clear", Some(Action::Clear)),
+        (
+            keys(&[Action::ToggleMode]),
+            "Notes mode",
+            Some(Action::ToggleMode),
+        ),
+        (
+            keys(&[Action::FillNotes, Action::FillAllNotes]),
+            "Fill notes",
+            Some(Action::FillNotes),
+        ),
+        (
+            keys(&[Action::ClearNotes, Action::ClearAllNotes]),
+            "Clear notes",
+            Some(Action::ClearNotes),
+        ),
+        (
+            keys(&[Action::ToggleSuggestions]),
+            "Valid hints",
+            Some(Action::ToggleSuggestions),
+        ),
+        (
+            keys(&[Action::ToggleNakedSingles]),
+            "Ghost hints",
+            Some(Action::ToggleNakedSingles),
+        ),
+        (keys(&[Action::NewGame]), "New game", Some(Action::NewGame)),
+        (
+            keys(&[Action::Hint, Action::ApplyHint]),
+            "Hint",
+            Some(Action::Hint),
+        ),
+        (keys(&[Action::Undo]), "Undo", Some(Action::Undo)),
+        (keys(&[Action::Stats]), "Stats", Some(Action::Stats)),
+        (
+            keys(&[Action::Leaderboard]),
+            "Leaderboard",
+            Some(Action::Leaderboard),
+        ),
+        (keys(&[Action::History]), "History", Some(Action::History)),
+        (
+            keys(&[Action::Settings]),
+            "Settings",
+            Some(Action::Settings),
+        ),
+        (keys(&[Action::Quit]), "Quit", None),
     ];
 
-    // Display in 4 columns (4 items each)
-    for (i, (key, desc)) in controls.iter().enumerate() {
-        let col = i / 4;
-        let row = i % 4;
-        let cx = x + (col as u16) * 17;
-        let cy = y + row as u16;
+    // Fill `columns` columns top to bottom; what doesn't fit is left out
+    let per_column = (controls.len() as u16).div_ceil(columns).min(rows);
+    if per_column == 0 {
+        return Ok(());
+    }
+    for (i, (key, desc, action)) in controls.iter().enumerate() {
+        let col = i as u16 / per_column;
+        let row = i as u16 % per_column;
+        if col >= columns {
+            break;
+        }
+        let cx = x + col * 17;
+        let cy = y + row;
+        if let Some(action) = action {
+            hits.add(cx, cy, 17, Target::Action(*action));
+        }
 
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(cx, cy),
             SetForegroundColor(theme.key),
             Print(format!("{:>8}", key)),
@@ -499,18 +933,148 @@ fn render_controls(stdout: &mut io::Stdout, app: &App, x: u16, y: u16) -> io::Re
     Ok(())
 }
 
-fn render_message(
-    stdout: &mut io::Stdout,
+/// Everyone's progress during a race, in place of the controls: cells filled
+/// and mistakes, never digits
+fn render_race_panel(
+    frame: &mut Frame,
     app: &App,
-    msg: &str,
-    term_width: u16,
+    race: &Race,
+    x: u16,
+    y: u16,
+    width: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let total = app.game.cells_to_fill().max(1);
+
+    draw!(
+        frame,
+        SetBackgroundColor(theme.bg),
+        MoveTo(x, y),
+        SetForegroundColor(theme.key),
+        Print(format!("Race {}", race.code)),
+        SetForegroundColor(theme.info),
+        Print(" · first to finish wins")
+    )?;
+
+    let columns = (width / 34).max(1);
+    let shown = (columns * rows.saturating_sub(1)) as usize;
+    for (i, racer) in race.racers.iter().take(shown).enumerate() {
+        let (column, row) = (i as u16 % columns, i as u16 / columns);
+        let me = Some(racer.id) == race.me;
+        // Our own count is current; others' are as last reported
+        let filled = if me {
+            app.game.filled_count()
+        } else {
+            racer.filled
+        };
+        let bar_len = filled.min(total) * 8 / total;
+        let bar = format!("{}{}", "█".repeat(bar_len), "░".repeat(8 - bar_len));
+        let (status, color) = match racer.status {
+            Status::Racing => (format!("{}/{}", filled, total), theme.fg),
+            Status::Finished { place, .. } => (race::ordinal(place), theme.success),
+            Status::Out => ("out".to_string(), theme.error),
+            Status::Left => ("left".to_string(), theme.border),
+        };
+        let name: String = racer.name.chars().take(10).collect();
+        draw!(
+            frame,
+            MoveTo(x + column * 34, y + 1 + row),
+            SetForegroundColor(if me { theme.key } else { theme.fg }),
+            Print(format!("{:<10} ", name)),
+            SetForegroundColor(theme.success),
+            Print(bar),
+            SetForegroundColor(color),
+            Print(format!(" {:<6}", status)),
+            SetForegroundColor(theme.error),
+            Print(format!("✗{}", racer.mistakes))
+        )?;
+    }
+    Ok(())
+}
+
+/// Cursor color for the `index`th player to join a shared board, dark enough
+/// to read digits over
+fn team_color(index: usize) -> Color {
+    const COLORS: [(u8, u8, u8); 6] = [
+        (40, 90, 140),
+        (130, 60, 40),
+        (40, 110, 60),
+        (110, 50, 120),
+        (120, 100, 30),
+        (30, 100, 110),
+    ];
+    let (r, g, b) = COLORS[index % COLORS.len()];
+    Color::Rgb { r, g, b }
+}
+
+/// The team on a shared board, in place of the controls: each player's
+/// cursor color and cell, and the mistakes everyone shares
+fn render_team_panel(
+    frame: &mut Frame,
+    app: &App,
+    coop: &Coop,
+    x: u16,
+    y: u16,
+    width: u16,
+    rows: u16,
 ) -> io::Result<()> {
     let theme = &app.theme;
+
+    draw!(
+        frame,
+        SetBackgroundColor(theme.bg),
+        MoveTo(x, y),
+        SetForegroundColor(theme.key),
+        Print(format!("Co-op {}", coop.code)),
+        SetForegroundColor(theme.info),
+        Print(" · team mistakes "),
+        SetForegroundColor(theme.error),
+        Print(format!("{}/{}", app.game.mistakes(), MAX_MISTAKES))
+    )?;
+
+    let columns = (width / 34).max(1);
+    let shown = (columns * rows.saturating_sub(1)) as usize;
+    for (i, teammate) in coop.team.iter().take(shown).enumerate() {
+        let (column, row) = (i as u16 % columns, i as u16 / columns);
+        let me = Some(teammate.id) == coop.me;
+        let cursor = if me {
+            Some(app.cursor)
+        } else {
+            teammate.cursor
+        };
+        let cell = cursor.map_or(String::new(), |pos| {
+            format!("r{}c{}", pos.row + 1, pos.col + 1)
+        });
+        let name: String = teammate.name.chars().take(10).collect();
+        draw!(
+            frame,
+            MoveTo(x + column * 34, y + 1 + row),
+            SetBackgroundColor(if me {
+                theme.selected_bg
+            } else {
+                team_color(teammate.color)
+            }),
+            Print("  "),
+            SetBackgroundColor(theme.bg),
+            SetForegroundColor(if me { theme.key } else { theme.fg }),
+            Print(format!(" {:<10} ", name)),
+            SetForegroundColor(theme.border),
+            Print(format!("{:<6}", if me { "(you)" } else { "" })),
+            SetForegroundColor(theme.info),
+            Print(cell)
+        )?;
+    }
+    Ok(())
+}
+
+fn render_message(frame: &mut Frame, app: &App, msg: &str, term_width: u16) -> io::Result<()> {
+    let theme = &app.theme;
     let padded = format!("  {}  ", msg);
     let x = term_width.saturating_sub(padded.len() as u16) / 2;
 
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, 0),
         SetForegroundColor(theme.fg),
         SetBackgroundColor(theme.selected_bg),
@@ -521,8 +1085,9 @@ fn render_message(
 }
 
 fn render_menu(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
+    hits: &mut HitMap,
     term_width: u16,
     term_height: u16,
 ) -> io::Result<()> {
@@ -538,8 +1103,8 @@ fn render_menu(
         }
     } else {
         match app.menu {
-            MenuState::Theme => 3,
-            MenuState::Confirm => 2,
+            MenuState::Theme => app.themes.len(),
+            MenuState::Confirm | MenuState::TelemetryConsent => 2,
             _ => 0,
         }
     };
@@ -557,8 +1122,8 @@ fn render_menu(
 
     // Background
     for row in 0..menu_height {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(x, y + row),
             SetBackgroundColor(bg),
             Print(" ".repeat(menu_width as usize))
@@ -566,24 +1131,24 @@ fn render_menu(
     }
 
     // Border
-    execute!(
-        stdout,
+    draw!(
+        frame,
         SetForegroundColor(theme.border),
         SetBackgroundColor(bg)
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y),
         Print("┌"),
         Print("─".repeat(menu_width as usize - 2)),
         Print("┐")
     )?;
     for row in 1..menu_height - 1 {
-        execute!(stdout, MoveTo(x, y + row), Print("│"))?;
-        execute!(stdout, MoveTo(x + menu_width - 1, y + row), Print("│"))?;
+        draw!(frame, MoveTo(x, y + row), Print("│"))?;
+        draw!(frame, MoveTo(x + menu_width - 1, y + row), Print("│"))?;
     }
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x, y + menu_height - 1),
         Print("└"),
         Print("─".repeat(menu_width as usize - 2)),
@@ -591,15 +1156,10 @@ fn render_menu(
     )?;
 
     // Title
-    let title = match app.menu {
-        MenuState::NewGame | MenuState::Difficulty => "Select Difficulty",
-        MenuState::Theme => "Select Theme",
-        MenuState::Confirm => "Confirm",
-        MenuState::None => "",
-    };
+    let title = app.menu_title();
     let title_x = x + (menu_width.saturating_sub(title.len() as u16)) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(title_x, y + 1),
         SetForegroundColor(theme.fg),
         SetBackgroundColor(bg),
@@ -678,21 +1238,22 @@ fn render_menu(
                 (*color, bg)
             };
 
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x + 2, y + 3 + i as u16),
                 SetForegroundColor(fg),
                 SetBackgroundColor(item_bg),
                 Print(format!(" {:^24} ", name))
             )?;
+            hits.add(x + 2, y + 3 + i as u16, 26, Target::MenuItem(i));
         }
 
         // Show unlock hint if not unlocked
         if !app.stats.secrets_unlocked() {
             let (wins, needed) = app.stats.expert_wins_progress();
             let hint = format!("🔒 {}/{} Expert wins", wins, needed);
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x + 2, y + 3 + difficulties.len() as u16),
                 SetForegroundColor(Color::DarkGrey),
                 SetBackgroundColor(bg),
@@ -700,11 +1261,7 @@ fn render_menu(
             )?;
         }
     } else {
-        let options: &[&str] = match app.menu {
-            MenuState::Theme => &["Dark", "Light", "High Contrast"],
-            MenuState::Confirm => &["Yes", "No"],
-            _ => &[],
-        };
+        let options = app.menu_options();
 
         for (i, option) in options.iter().enumerate() {
             let selected = i == app.menu_selection;
@@ -714,12 +1271,24 @@ fn render_menu(
                 (theme.fg, bg)
             };
 
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x + 2, y + 3 + i as u16),
                 SetForegroundColor(fg),
                 SetBackgroundColor(item_bg),
-                Print(format!(" {:^24} ", option))
+                Print(format!(" {:^24.24} ", option))
+            )?;
+            hits.add(x + 2, y + 3 + i as u16, 26, Target::MenuItem(i));
+        }
+
+        // Say what sharing means before the player picks
+        if app.menu == MenuState::TelemetryConsent {
+            draw!(
+                frame,
+                MoveTo(x + 2, y + 3 + options.len() as u16),
+                SetForegroundColor(Color::DarkGrey),
+                SetBackgroundColor(bg),
+                Print(format!(" {:^24} ", "puzzle, times, anon ID"))
             )?;
         }
     }
@@ -728,7 +1297,7 @@ fn render_menu(
 }
 
 fn render_hint(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
     hint: &str,
     term_width: u16,
@@ -752,8 +1321,8 @@ fn render_hint(
 
     // Background
     for row in 0..box_height {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(x, y + row),
             SetBackgroundColor(bg),
             Print(" ".repeat(box_width as usize))
@@ -761,8 +1330,8 @@ fn render_hint(
     }
 
     // Title
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(x + 2, y + 1),
         SetForegroundColor(theme.success),
         SetBackgroundColor(bg),
@@ -771,8 +1340,8 @@ fn render_hint(
 
     // Text
     for (i, line) in wrapped.iter().enumerate() {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(x + 2, y + 3 + i as u16),
             SetForegroundColor(theme.fg),
             SetBackgroundColor(bg),
@@ -786,7 +1355,7 @@ fn render_hint(
 // Win/Lose screens
 
 fn render_win_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &mut App,
     term_width: u16,
     term_height: u16,
@@ -806,8 +1375,8 @@ fn render_win_screen(
                 term_height as usize,
                 app.win_screen.frame_count() as f32,
             );
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x, y),
                 SetForegroundColor(color),
                 SetBackgroundColor(bg_base),
@@ -819,8 +1388,8 @@ fn render_win_screen(
     // Particles - draw on top of background
     for particle in app.win_screen.particles() {
         if particle.is_visible(term_width, term_height) {
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(particle.x as u16, particle.y as u16),
                 SetForegroundColor(particle.color),
                 SetBackgroundColor(bg_base),
@@ -838,8 +1407,8 @@ fn render_win_screen(
 
     for (i, line) in lines.iter().enumerate() {
         let hue = (app.win_screen.rainbow_offset() + i as f32 * 0.1) % 1.0;
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(banner_x, banner_y + i as u16),
             SetForegroundColor(hue_to_rgb(hue)),
             SetBackgroundColor(bg_base),
@@ -853,8 +1422,8 @@ fn render_win_screen(
     let msg_y = banner_y + lines.len() as u16 + 2;
     let hue = (app.win_screen.rainbow_offset() * 2.0) % 1.0;
 
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(msg_x, msg_y),
         SetForegroundColor(hue_to_rgb(hue)),
         SetBackgroundColor(bg_base),
@@ -869,8 +1438,8 @@ fn render_win_screen(
         app.game.difficulty()
     );
     let stats_x = term_width.saturating_sub(stats.len() as u16 + 2) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(stats_x, msg_y + 3),
         SetForegroundColor(Color::White),
         SetBackgroundColor(Color::Rgb {
@@ -881,12 +1450,34 @@ fn render_win_screen(
         Print(format!(" {} ", stats))
     )?;
 
+    // Standing among everyone who solved this puzzle
+    let rank = &app.puzzle_rank;
+    let rank_text = match (rank.rank, rank.total) {
+        _ if rank.loading => Some("Ranking on this puzzle...".to_string()),
+        (Some(r), Some(t)) => Some(format!("#{} of {} on this puzzle", r, t)),
+        (Some(r), None) => Some(format!("#{} on this puzzle", r)),
+        (None, Some(t)) if t > 0 => Some(format!("{} ranked on this puzzle", t)),
+        _ => None,
+    };
+    if let Some(text) = rank_text {
+        draw!(
+            frame,
+            MoveTo(
+                term_width.saturating_sub(text.chars().count() as u16) / 2,
+                msg_y + 4
+            ),
+            SetForegroundColor(Color::Cyan),
+            SetBackgroundColor(bg_base),
+            Print(text)
+        )?;
+    }
+
     // Instructions
-    let instr = "Press 'n' for new game or 'q' to quit";
+    let instr = "Press 'n' for new game, 'p' for this puzzle's leaderboard or 'q' to quit";
     let instr_x = term_width.saturating_sub(instr.len() as u16) / 2;
-    execute!(
-        stdout,
-        MoveTo(instr_x, msg_y + 5),
+    draw!(
+        frame,
+        MoveTo(instr_x, msg_y + 6),
         SetForegroundColor(Color::Yellow),
         SetBackgroundColor(bg_base),
         Print(instr)
@@ -896,7 +1487,7 @@ fn render_win_screen(
 }
 
 fn render_lose_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &mut App,
     term_width: u16,
     term_height: u16,
@@ -920,8 +1511,8 @@ fn render_lose_screen(
                 term_height as usize,
                 app.lose_screen.frame_count() as f32,
             );
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(x, y),
                 SetForegroundColor(color),
                 SetBackgroundColor(bg_base),
@@ -933,8 +1524,8 @@ fn render_lose_screen(
     // Particles - draw on top of background
     for particle in app.lose_screen.particles() {
         if particle.is_visible(term_width, term_height) {
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(particle.x as u16, particle.y as u16),
                 SetForegroundColor(particle.color),
                 SetBackgroundColor(bg_base),
@@ -952,8 +1543,8 @@ fn render_lose_screen(
 
     for (i, line) in lines.iter().enumerate() {
         let intensity = 150u8.saturating_sub((i * 10).min(100) as u8);
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(banner_x, banner_y + i as u16),
             SetForegroundColor(Color::Rgb {
                 r: intensity,
@@ -971,8 +1562,8 @@ fn render_lose_screen(
     let msg_y = banner_y + lines.len() as u16 + 2;
     let pulse = ((app.lose_screen.frame_count() as f32 * 0.1).sin() * 0.3 + 0.7) * 255.0;
 
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(msg_x, msg_y),
         SetForegroundColor(Color::Rgb {
             r: pulse as u8,
@@ -992,8 +1583,8 @@ fn render_lose_screen(
         app.game.difficulty()
     );
     let stats_x = term_width.saturating_sub(stats.len() as u16 + 2) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(stats_x, msg_y + 3),
         SetForegroundColor(Color::Grey),
         SetBackgroundColor(Color::Rgb {
@@ -1007,8 +1598,8 @@ fn render_lose_screen(
     // Instructions
     let instr = "Press 'n' for new game or 'q' to quit";
     let instr_x = term_width.saturating_sub(instr.len() as u16) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(instr_x, msg_y + 5),
         SetForegroundColor(Color::DarkYellow),
         SetBackgroundColor(bg_base),
@@ -1041,7 +1632,7 @@ fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
 // Stats, Leaderboard, History screens
 
 fn render_stats_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
     term_width: u16,
     term_height: u16,
@@ -1050,56 +1641,62 @@ fn render_stats_screen(
     let stats = &app.stats;
     let player = &stats.player;
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
     // Title
     let title = "═══ STATISTICS ═══";
     let title_x = term_width.saturating_sub(title.len() as u16) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(title_x, 1),
         SetForegroundColor(theme.key),
         Print(title)
     )?;
 
+    render_stats_tabs(frame, app, term_width)?;
+    if app.stats_view != TrendView::Overview {
+        render_trend_view(frame, app, term_width, term_height)?;
+        return render_stats_nav(frame, app, term_height);
+    }
+
     let start_y = 3;
     let col1_x = 4u16;
     let col2_x = term_width / 2;
 
     // Overall stats (left column)
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y),
         SetForegroundColor(theme.fg),
         Print(format!("Player: {}", player.player_name))
     )?;
 
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 2),
         SetForegroundColor(theme.info),
         Print(format!("Total Games: {}", player.total_games))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 3),
         SetForegroundColor(theme.success),
         Print(format!("Wins: {}", player.total_wins))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 4),
         SetForegroundColor(theme.error),
         Print(format!("Losses: {}", player.total_losses))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 5),
         SetForegroundColor(theme.border),
         Print(format!("Abandoned: {}", player.total_abandoned))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 7),
         SetForegroundColor(theme.fg),
         Print(format!("Win Rate: {:.1}%", player.overall_win_rate()))
@@ -1113,8 +1710,8 @@ fn render_stats_screen(
     } else {
         theme.info
     };
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 8),
         SetForegroundColor(streak_color),
         Print(format!("Current Streak: {}", player.current_streak.abs())),
@@ -1127,16 +1724,25 @@ fn render_stats_screen(
             ""
         })
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col1_x, start_y + 9),
         SetForegroundColor(theme.key),
         Print(format!("Best Streak: {} wins", player.best_streak))
     )?;
 
+    if app.pending_uploads > 0 {
+        draw!(
+            frame,
+            MoveTo(col1_x, start_y + 11),
+            SetForegroundColor(theme.border),
+            Print(format!("Pending uploads: {}", app.pending_uploads))
+        )?;
+    }
+
     // Per-difficulty stats (right column)
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(col2_x, start_y),
         SetForegroundColor(theme.fg),
         Print("By Difficulty:")
@@ -1176,14 +1782,14 @@ fn render_stats_screen(
             },
         };
 
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col2_x, y),
             SetForegroundColor(diff_color),
             Print(format!("{:?}", diff))
         )?;
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col2_x + 2, y + 1),
             SetForegroundColor(theme.info),
             Print(format!(
@@ -1202,8 +1808,8 @@ fn render_stats_screen(
             .avg_time_secs()
             .map(format_time)
             .unwrap_or_else(|| "--:--".to_string());
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col2_x + 2, y + 2),
             SetForegroundColor(theme.info),
             Print(format!("Best: {} | Avg: {}", best_str, avg_str))
@@ -1213,8 +1819,8 @@ fn render_stats_screen(
     // Puzzle Universe section (fun stats!)
     let universe_y = start_y + 18;
     if term_height > universe_y + 8 {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col1_x, universe_y),
             SetForegroundColor(theme.key),
             Print("═══ PUZZLE UNIVERSE ═══")
@@ -1236,8 +1842,8 @@ fn render_stats_screen(
             ("0 / 10³⁰ puzzles".to_string(), "(0%)".to_string())
         };
 
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col1_x, universe_y + 1),
             SetForegroundColor(Color::Blue),
             Print("◉ "),
@@ -1260,8 +1866,8 @@ fn render_stats_screen(
             _ => "A true warrior! The cosmos trembles (microscopically).",
         };
 
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(col1_x + 2, universe_y + 2),
             SetForegroundColor(theme.border),
             Print("└─ "),
@@ -1280,8 +1886,8 @@ fn render_stats_screen(
             let exponent = years.log10().floor() as i32;
             let mantissa = years / 10_f64.powi(exponent);
 
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(col1_x, universe_y + 4),
                 SetForegroundColor(Color::Rgb {
                     r: 255,
@@ -1306,8 +1912,8 @@ fn render_stats_screen(
                 _ => "Savoring it! New universes will form and die. Repeatedly.",
             };
 
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(col1_x + 2, universe_y + 5),
                 SetForegroundColor(theme.border),
                 Print("└─ "),
@@ -1317,36 +1923,369 @@ fn render_stats_screen(
         }
     }
 
-    // Navigation help
-    let nav_y = term_height.saturating_sub(3);
-    execute!(
-        stdout,
-        MoveTo(col1_x, nav_y),
-        SetForegroundColor(theme.border),
-        Print("────────────────────────────────────────────────────────────")
+    render_stats_nav(frame, app, term_height)
+}
+
+/// View selector under the stats title
+fn render_stats_tabs(frame: &mut Frame, app: &App, term_width: u16) -> io::Result<()> {
+    let theme = &app.theme;
+    let width: usize = TrendView::ALL
+        .iter()
+        .map(|v| v.label().chars().count() + 3)
+        .sum();
+    draw!(
+        frame,
+        MoveTo(term_width.saturating_sub(width as u16) / 2, 2)
     )?;
-    execute!(
-        stdout,
-        MoveTo(col1_x, nav_y + 1),
-        SetForegroundColor(theme.key),
-        Print("b"),
-        SetForegroundColor(theme.info),
-        Print(" Leaderboard  "),
-        SetForegroundColor(theme.key),
+    for view in TrendView::ALL {
+        let selected = view == app.stats_view;
+        draw!(
+            frame,
+            SetBackgroundColor(if selected {
+                theme.selected_bg
+            } else {
+                theme.bg
+            }),
+            SetForegroundColor(if selected { theme.key } else { theme.border }),
+            Print(format!(" {} ", view.label())),
+            SetBackgroundColor(theme.bg),
+            Print(" ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Chart for the selected trend view, below the tabs
+fn render_trend_view(
+    frame: &mut Frame,
+    app: &App,
+    term_width: u16,
+    term_height: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let history = &app.stats.history;
+    let x = 4u16;
+    let y = 4u16;
+    // Room for axis labels on the left and the nav help at the bottom
+    let chart_width = (term_width.saturating_sub(x + 12) as usize).clamp(10, 60);
+    let chart_height = (term_height.saturating_sub(y + 10) as usize).clamp(2, 8);
+    let difficulty = app.stats_difficulty;
+
+    let empty = |frame: &mut Frame, text: &str| {
+        draw!(
+            frame,
+            MoveTo(x, y + 2),
+            SetForegroundColor(theme.border),
+            Print(text)
+        )
+    };
+
+    match app.stats_view {
+        TrendView::Overview => {}
+        TrendView::SolveTime => {
+            let times = trends::solve_times(history, difficulty, chart_width * 2);
+            draw!(
+                frame,
+                MoveTo(x, y),
+                SetForegroundColor(theme.fg),
+                Print(format!(
+                    "Solve time, last {} {:?} wins (lower is better)",
+                    times.len(),
+                    difficulty
+                ))
+            )?;
+            if times.is_empty() {
+                return empty(frame, "No wins at this difficulty yet");
+            }
+            let values: Vec<f64> = times.iter().map(|&t| t as f64).collect();
+            let (min, max) = (
+                times.iter().copied().min().unwrap_or(0),
+                times.iter().copied().max().unwrap_or(0),
+            );
+            render_chart(
+                frame,
+                app,
+                x,
+                y + 2,
+                &trends::braille_chart(&values, chart_width, chart_height),
+                &format_time(max),
+                &format_time(min),
+            )?;
+            let avg = times.iter().sum::<u64>() / times.len() as u64;
+            draw!(
+                frame,
+                MoveTo(x, y + 3 + chart_height as u16),
+                SetForegroundColor(theme.info),
+                Print(format!(
+                    "Latest: {}  Best: {}  Average: {}",
+                    format_time(*times.last().unwrap_or(&0)),
+                    format_time(min),
+                    format_time(avg)
+                ))
+            )?;
+        }
+        TrendView::WinRate => {
+            const WINDOW: usize = 10;
+            let series = trends::win_rate_series(history, WINDOW);
+            draw!(
+                frame,
+                MoveTo(x, y),
+                SetForegroundColor(theme.fg),
+                Print(format!(
+                    "Win rate over the previous {} games, all difficulties",
+                    WINDOW
+                ))
+            )?;
+            if series.is_empty() {
+                return empty(frame, "No finished games yet");
+            }
+            let shown = &series[series.len().saturating_sub(chart_width * 2)..];
+            let min = shown.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = shown.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            render_chart(
+                frame,
+                app,
+                x,
+                y + 2,
+                &trends::braille_chart(shown, chart_width, chart_height),
+                &format!("{:.0}%", max),
+                &format!("{:.0}%", min),
+            )?;
+            draw!(
+                frame,
+                MoveTo(x, y + 3 + chart_height as u16),
+                SetForegroundColor(theme.info),
+                Print(format!(
+                    "Now: {:.0}%  Overall: {:.1}%",
+                    series.last().unwrap_or(&0.0),
+                    app.stats.player.overall_win_rate()
+                ))
+            )?;
+        }
+        TrendView::Accuracy => {
+            let games = trends::mistakes_hints(history, chart_width);
+            draw!(
+                frame,
+                MoveTo(x, y),
+                SetForegroundColor(theme.fg),
+                Print(format!("Mistakes and hints, last {} games", games.len()))
+            )?;
+            if games.is_empty() {
+                return empty(frame, "No finished games yet");
+            }
+            let mistakes: Vec<f64> = games.iter().map(|g| g.0 as f64).collect();
+            let hints: Vec<f64> = games.iter().map(|g| g.1 as f64).collect();
+            // Both series share one scale so their heights compare
+            let max = games.iter().map(|g| g.0.max(g.1)).max().unwrap_or(0);
+            let rows = [
+                ("Mistakes", theme.error, mistakes),
+                ("Hints", theme.info, hints),
+            ];
+            for (i, (label, color, values)) in rows.iter().enumerate() {
+                let avg = values.iter().sum::<f64>() / values.len() as f64;
+                let row_y = y + 2 + i as u16 * 2;
+                draw!(
+                    frame,
+                    MoveTo(x, row_y),
+                    SetForegroundColor(theme.fg),
+                    Print(format!("{:<9}", label)),
+                    SetForegroundColor(*color),
+                    Print(trends::sparkline(values, max as f64)),
+                    SetForegroundColor(theme.border),
+                    Print(format!("  avg {:.1}", avg))
+                )?;
+            }
+            draw!(
+                frame,
+                MoveTo(x, y + 6),
+                SetForegroundColor(theme.border),
+                Print(format!("Tallest bar = {} (oldest on the left)", max))
+            )?;
+        }
+        TrendView::Calendar => {
+            let weeks = (chart_width / 2).clamp(4, 26);
+            let grid = trends::calendar(history, sudoku_shared::dates::unix_now(), weeks);
+            let total: u32 = grid.iter().flatten().flatten().sum();
+            draw!(
+                frame,
+                MoveTo(x, y),
+                SetForegroundColor(theme.fg),
+                Print(format!(
+                    "Games played, last {} weeks: {} (UTC days)",
+                    weeks, total
+                ))
+            )?;
+            for (d, day) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+                draw!(
+                    frame,
+                    MoveTo(x, y + 2 + d as u16),
+                    SetForegroundColor(theme.border),
+                    Print(format!("{:<4}", day))
+                )?;
+                for week in &grid {
+                    let (ch, color) = match week[d] {
+                        None => (' ', theme.border),
+                        Some(0) => ('·', theme.border),
+                        Some(1) => ('░', theme.success),
+                        Some(2..=3) => ('▒', theme.success),
+                        Some(4..=5) => ('▓', theme.success),
+                        Some(_) => ('█', theme.success),
+                    };
+                    draw!(frame, SetForegroundColor(color), Print(format!("{} ", ch)))?;
+                }
+            }
+            draw!(
+                frame,
+                MoveTo(x, y + 10),
+                SetForegroundColor(theme.border),
+                Print("Less · "),
+                SetForegroundColor(theme.success),
+                Print("░ ▒ ▓ █"),
+                SetForegroundColor(theme.border),
+                Print(" More")
+            )?;
+        }
+        TrendView::PersonalBests => {
+            let bests = trends::personal_bests(history, difficulty);
+            draw!(
+                frame,
+                MoveTo(x, y),
+                SetForegroundColor(theme.fg),
+                Print(format!("Personal best progression, {:?}", difficulty))
+            )?;
+            if bests.is_empty() {
+                return empty(frame, "No wins at this difficulty yet");
+            }
+            let values: Vec<f64> = bests.iter().map(|&(_, t)| t as f64).collect();
+            draw!(
+                frame,
+                MoveTo(x, y + 1),
+                SetForegroundColor(theme.success),
+                Print(trends::sparkline(
+                    &values,
+                    values.iter().cloned().fold(0.0, f64::max)
+                ))
+            )?;
+            let rows = (term_height.saturating_sub(y + 8) as usize).max(1);
+            let mut prev: Option<u64> = None;
+            let lines: Vec<String> = bests
+                .iter()
+                .map(|&(timestamp, time)| {
+                    let gain = prev
+                        .map(|p| format!("  -{}", format_time(p - time)))
+                        .unwrap_or_default();
+                    prev = Some(time);
+                    format!(
+                        "{}  {:>8}{}",
+                        sudoku_shared::dates::format_date(timestamp),
+                        format_time(time),
+                        gain
+                    )
+                })
+                .collect();
+            // Most recent records are the interesting ones
+            for (i, line) in lines.iter().rev().take(rows).enumerate() {
+                draw!(
+                    frame,
+                    MoveTo(x, y + 3 + i as u16),
+                    SetForegroundColor(if i == 0 { theme.key } else { theme.info }),
+                    Print(line)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Braille chart rows with the top and bottom values labelled on the left
+fn render_chart(
+    frame: &mut Frame,
+    app: &App,
+    x: u16,
+    y: u16,
+    rows: &[String],
+    top: &str,
+    bottom: &str,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let last = rows.len().saturating_sub(1);
+    for (i, row) in rows.iter().enumerate() {
+        let label = match i {
+            0 => top,
+            i if i == last => bottom,
+            _ => "",
+        };
+        draw!(
+            frame,
+            MoveTo(x, y + i as u16),
+            SetForegroundColor(theme.border),
+            Print(format!("{:>7} ┤", label)),
+            SetForegroundColor(theme.success),
+            Print(row)
+        )?;
+    }
+    Ok(())
+}
+
+fn render_stats_nav(frame: &mut Frame, app: &App, term_height: u16) -> io::Result<()> {
+    let theme = &app.theme;
+    let col1_x = 4u16;
+
+    // Navigation help
+    let nav_y = term_height.saturating_sub(3);
+    draw!(
+        frame,
+        MoveTo(col1_x, nav_y),
+        SetForegroundColor(theme.border),
+        Print("────────────────────────────────────────────────────────────")
+    )?;
+    draw!(
+        frame,
+        MoveTo(col1_x, nav_y + 1),
+        SetForegroundColor(theme.key),
+        Print("b"),
+        SetForegroundColor(theme.info),
+        Print(" Leaderboard  "),
+        SetForegroundColor(theme.key),
+        Print("a"),
+        SetForegroundColor(theme.info),
+        Print(" Achievements  "),
+        SetForegroundColor(theme.key),
         Print("H"),
         SetForegroundColor(theme.info),
         Print(" History  "),
         SetForegroundColor(theme.key),
+        Print("P"),
+        SetForegroundColor(theme.info),
+        Print(" Profiles  "),
+        SetForegroundColor(theme.key),
         Print("Esc"),
         SetForegroundColor(theme.info),
         Print(" Back to game")
     )?;
+    draw!(
+        frame,
+        MoveTo(col1_x, nav_y + 2),
+        SetForegroundColor(theme.key),
+        Print("Tab/←→/1-6"),
+        SetForegroundColor(theme.info),
+        Print(" Switch view")
+    )?;
+    if app.stats_view.uses_difficulty() {
+        draw!(
+            frame,
+            SetForegroundColor(theme.key),
+            Print("  ↑/↓"),
+            SetForegroundColor(theme.info),
+            Print(" Difficulty")
+        )?;
+    }
 
     Ok(())
 }
 
 fn render_leaderboard_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
     term_width: u16,
     term_height: u16,
@@ -1354,13 +2293,13 @@ fn render_leaderboard_screen(
     let theme = &app.theme;
     let stats = &app.stats;
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
     // Title
     let title = "═══ LEADERBOARD ═══";
     let title_x = term_width.saturating_sub(title.len() as u16) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(title_x, 1),
         SetForegroundColor(theme.key),
         Print(title)
@@ -1375,7 +2314,8 @@ fn render_leaderboard_screen(
     } else {
         vec!["Beginner", "Easy", "Medium", "Inter", "Hard", "Expert"]
     };
-    let current_idx = match app.leaderboard_difficulty {
+    let view = &app.leaderboard;
+    let current_idx = match view.difficulty {
         sudoku_core::Difficulty::Beginner => 0,
         sudoku_core::Difficulty::Easy => 1,
         sudoku_core::Difficulty::Medium => 2,
@@ -1386,110 +2326,263 @@ fn render_leaderboard_screen(
         sudoku_core::Difficulty::Extreme => 7,
     };
 
-    execute!(
-        stdout,
-        MoveTo(4, diff_y),
-        SetForegroundColor(theme.info),
-        Print("◀ ")
-    )?;
-
-    for (i, name) in difficulties.iter().enumerate() {
-        let color = if i == current_idx {
-            theme.key
-        } else {
-            theme.border
-        };
-        execute!(
-            stdout,
-            SetForegroundColor(color),
-            Print(format!(" {} ", name))
+    if let Some(ref puzzle) = view.puzzle {
+        draw!(
+            frame,
+            MoveTo(4, diff_y),
+            SetForegroundColor(theme.info),
+            Print("Puzzle "),
+            SetForegroundColor(theme.key),
+            Print(&puzzle.label),
+            SetForegroundColor(theme.info),
+            Print(format!(" ({})  ", puzzle.difficulty)),
+            SetForegroundColor(theme.border),
+            Print("←/→ all puzzles")
+        )?;
+    } else {
+        draw!(
+            frame,
+            MoveTo(4, diff_y),
+            SetForegroundColor(theme.info),
+            Print("◀ ")
         )?;
+
+        for (i, name) in difficulties.iter().enumerate() {
+            let color = if i == current_idx {
+                theme.key
+            } else {
+                theme.border
+            };
+            draw!(
+                frame,
+                SetForegroundColor(color),
+                Print(format!(" {} ", name))
+            )?;
+        }
+        draw!(frame, SetForegroundColor(theme.info), Print(" ▶"))?;
     }
-    execute!(stdout, SetForegroundColor(theme.info), Print(" ▶"))?;
+
+    // Filters, own rank and backend status
+    let filter_y = diff_y + 1;
+    let status = stats.leaderboard_status();
+    let (status_text, status_color) = if status.using_fallback {
+        (
+            format!("{} (offline fallback)", status.backend_name),
+            theme.info,
+        )
+    } else if status.is_available {
+        (format!("{} ● online", status.backend_name), theme.success)
+    } else {
+        (
+            format!("{} ○ unavailable", status.backend_name),
+            theme.error,
+        )
+    };
+    let my_rank = match view.my_rank {
+        Some(rank) => format!("#{}", rank),
+        None => "-".to_string(),
+    };
+    draw!(
+        frame,
+        MoveTo(4, filter_y),
+        SetForegroundColor(theme.info),
+        Print(format!(
+            "{}{}  │  Your rank: ",
+            view.period.label(),
+            if view.verified_only {
+                " · verified only"
+            } else {
+                ""
+            }
+        )),
+        SetForegroundColor(theme.key),
+        Print(my_rank),
+        SetForegroundColor(theme.info),
+        Print("  │  "),
+        SetForegroundColor(status_color),
+        Print(status_text)
+    )?;
 
     // Header
-    let header_y = diff_y + 2;
-    execute!(
-        stdout,
+    let header_y = diff_y + 3;
+    draw!(
+        frame,
         MoveTo(4, header_y),
         SetForegroundColor(theme.fg),
         Print(format!(
-            "{:>4} {:>12} {:>8} {:>6} {:>8} {:>10}",
+            "{:>4} {:>12} {:>8} {:>8} {:>6} {:>10}",
             "Rank", "Player", "Score", "Time", "Hints", "Verified"
         ))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, header_y + 1),
         SetForegroundColor(theme.border),
         Print("─".repeat(60))
     )?;
 
-    // Leaderboard entries
-    let entries = stats.leaderboard_by_difficulty(app.leaderboard_difficulty);
-    let max_entries = (term_height.saturating_sub(header_y + 5)) as usize;
+    // Leaderboard entries. The scores kept on this device stand in when the
+    // remote board is off limits or unreachable.
+    let max_entries = (term_height.saturating_sub(header_y + 6)) as usize;
+    let player_name = &stats.player.player_name;
+    let local = view.sharing_off || view.error.is_some();
+    let rows: Vec<(usize, &str, u64, u64, usize, bool)> = if local {
+        let query = view.query();
+        stats
+            .leaderboard_by_difficulty(view.difficulty)
+            .into_iter()
+            .filter(|e| {
+                query
+                    .puzzle_hash
+                    .as_ref()
+                    .is_none_or(|h| e.puzzle_hash == *h)
+                    && query.since.is_none_or(|s| e.timestamp >= s)
+            })
+            .enumerate()
+            .map(|(i, e)| {
+                let name = e.player_name.as_str();
+                (i + 1, name, e.score, e.time_secs, e.hints_used, true)
+            })
+            .collect()
+    } else {
+        view.entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let rank = e.rank.unwrap_or(view.offset + i + 1);
+                let name = e.player_name.as_str();
+                (rank, name, e.score, e.time_secs, e.hints_used, e.verified)
+            })
+            .collect()
+    };
 
-    for (i, entry) in entries.iter().take(max_entries).enumerate() {
+    if local {
+        let (reason, color) = match view.error {
+            Some(ref error) => (error.as_str(), theme.error),
+            None => ("Score sharing is off", theme.border),
+        };
+        draw!(
+            frame,
+            MoveTo(4, diff_y + 2),
+            SetForegroundColor(color),
+            Print(format!("{} · showing scores from this device", reason))
+        )?;
+    }
+
+    for (i, &(rank, name, score, time_secs, hints_used, verified)) in
+        rows.iter().take(max_entries).enumerate()
+    {
         let y = header_y + 2 + i as u16;
-        let rank_color = match i {
-            0 => Color::Yellow, // Gold
-            1 => Color::Grey,   // Silver
-            2 => Color::Rgb {
+        let rank_color = match rank {
+            1 => Color::Yellow, // Gold
+            2 => Color::Grey,   // Silver
+            3 => Color::Rgb {
                 r: 205,
                 g: 127,
                 b: 50,
             }, // Bronze
             _ => theme.info,
         };
+        let (name_color, marker) = if name == player_name {
+            (theme.key, "▸")
+        } else {
+            (theme.fg, " ")
+        };
+        let (verified_mark, verified_color) = if verified {
+            ("✓", theme.success)
+        } else {
+            ("·", theme.border)
+        };
 
-        execute!(
-            stdout,
-            MoveTo(4, y),
+        draw!(
+            frame,
+            MoveTo(3, y),
+            SetForegroundColor(theme.key),
+            Print(marker),
             SetForegroundColor(rank_color),
-            Print(format!("{:>4}", i + 1)),
-            SetForegroundColor(theme.fg),
+            Print(format!("{:>4}", rank)),
+            SetForegroundColor(name_color),
             Print(format!(
                 " {:>12}",
-                &entry.player_name[..entry.player_name.len().min(12)]
+                name.chars().take(12).collect::<String>()
             )),
             SetForegroundColor(theme.key),
-            Print(format!(" {:>8}", entry.score)),
+            Print(format!(" {:>8}", score)),
             SetForegroundColor(theme.info),
-            Print(format!(" {:>8}", format_time(entry.time_secs))),
-            Print(format!(" {:>6}", entry.hints_used)),
-            SetForegroundColor(theme.success),
-            Print(format!(" {:>10}", "✓"))
+            Print(format!(" {:>8}", format_time(time_secs))),
+            Print(format!(" {:>6}", hints_used)),
+            SetForegroundColor(verified_color),
+            Print(format!(" {:>10}", verified_mark))
         )?;
     }
 
-    if entries.is_empty() {
-        execute!(
-            stdout,
+    let notice = if view.loading && rows.is_empty() {
+        Some(("Loading...", theme.border))
+    } else if rows.is_empty() {
+        Some(("No entries yet. Win some games!", theme.border))
+    } else {
+        None
+    };
+    if let Some((text, color)) = notice {
+        draw!(
+            frame,
             MoveTo(4, header_y + 3),
-            SetForegroundColor(theme.border),
-            Print("No entries yet. Win some games!")
+            SetForegroundColor(color),
+            Print(text)
         )?;
     }
 
+    // Page indicator
+    let (page, pages) = view.page_position();
+    let page_text = match (pages, view.total) {
+        _ if local => format!("{} entries on this device", rows.len()),
+        (Some(pages), Some(total)) => format!("Page {}/{} · {} entries", page, pages, total),
+        _ => format!("Page {}", page),
+    };
+    draw!(
+        frame,
+        MoveTo(4, term_height.saturating_sub(4)),
+        SetForegroundColor(theme.border),
+        Print(page_text),
+        SetForegroundColor(theme.info),
+        Print(if view.loading { "  (updating)" } else { "" })
+    )?;
+
     // Navigation help
     let nav_y = term_height.saturating_sub(3);
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, nav_y),
         SetForegroundColor(theme.border),
         Print("────────────────────────────────────────────────────────────")
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, nav_y + 1),
         SetForegroundColor(theme.key),
         Print("←/→"),
         SetForegroundColor(theme.info),
-        Print(" Change difficulty  "),
+        Print(" Difficulty  "),
         SetForegroundColor(theme.key),
-        Print("i"),
+        Print("↑/↓"),
         SetForegroundColor(theme.info),
-        Print(" Stats  "),
+        Print(" Page  "),
+        SetForegroundColor(theme.key),
+        Print("t"),
+        SetForegroundColor(theme.info),
+        Print(" Period  "),
+        SetForegroundColor(theme.key),
+        Print("v"),
+        SetForegroundColor(theme.info),
+        Print(" Verified  "),
+        SetForegroundColor(theme.key),
+        Print("m"),
+        SetForegroundColor(theme.info),
+        Print(" My rank  "),
+        SetForegroundColor(theme.key),
+        Print("r"),
+        SetForegroundColor(theme.info),
+        Print(" Refresh  "),
         SetForegroundColor(theme.key),
         Print("Esc"),
         SetForegroundColor(theme.info),
@@ -1500,7 +2593,7 @@ fn render_leaderboard_screen(
 }
 
 fn render_history_screen(
-    stdout: &mut io::Stdout,
+    frame: &mut Frame,
     app: &App,
     term_width: u16,
     term_height: u16,
@@ -1508,21 +2601,21 @@ fn render_history_screen(
     let theme = &app.theme;
     let stats = &app.stats;
 
-    execute!(stdout, SetBackgroundColor(theme.bg))?;
+    draw!(frame, SetBackgroundColor(theme.bg))?;
 
     // Title
     let title = "═══ GAME HISTORY ═══";
     let title_x = term_width.saturating_sub(title.len() as u16) / 2;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(title_x, 1),
         SetForegroundColor(theme.key),
         Print(title)
     )?;
 
     // Subtitle
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, 3),
         SetForegroundColor(theme.info),
         Print("Select a game and press Enter to replay")
@@ -1530,8 +2623,8 @@ fn render_history_screen(
 
     // Header
     let header_y = 5;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, header_y),
         SetForegroundColor(theme.fg),
         Print(format!(
@@ -1539,8 +2632,8 @@ fn render_history_screen(
             "#", "Result", "Diff", "Time", "Hints", "Errors", "Verified"
         ))
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, header_y + 1),
         SetForegroundColor(theme.border),
         Print("─".repeat(65))
@@ -1566,7 +2659,7 @@ fn render_history_screen(
             theme.bg
         };
 
-        execute!(stdout, SetBackgroundColor(bg))?;
+        draw!(frame, SetBackgroundColor(bg))?;
 
         let result_str = match record.result {
             GameResult::Win => "WIN",
@@ -1594,8 +2687,8 @@ fn render_history_screen(
             " "
         };
 
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(2, y),
             SetForegroundColor(theme.key),
             Print(prefix),
@@ -1610,15 +2703,21 @@ fn render_history_screen(
             Print(format!(" {:>6}", record.hints_used)),
             Print(format!(" {:>8}", record.mistakes)),
             SetForegroundColor(verified_color),
-            Print(format!(" {:>10}", verified_str))
+            Print(format!(" {:>10}", verified_str)),
+            SetForegroundColor(theme.key),
+            Print(
+                record
+                    .multiplayer
+                    .map_or(String::new(), |m| format!("  {}", m))
+            )
         )?;
 
-        execute!(stdout, SetBackgroundColor(theme.bg))?;
+        draw!(frame, SetBackgroundColor(theme.bg))?;
     }
 
     if history.is_empty() {
-        execute!(
-            stdout,
+        draw!(
+            frame,
             MoveTo(4, header_y + 3),
             SetForegroundColor(theme.border),
             Print("No games played yet!")
@@ -1634,8 +2733,8 @@ fn render_history_screen(
 
         for i in 0..scroll_height {
             let ch = if i == scroll_pos { '█' } else { '░' };
-            execute!(
-                stdout,
+            draw!(
+                frame,
                 MoveTo(term_width - 3, scroll_y + i),
                 SetForegroundColor(theme.border),
                 Print(ch)
@@ -1645,14 +2744,14 @@ fn render_history_screen(
 
     // Navigation help
     let nav_y = term_height.saturating_sub(3);
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, nav_y),
         SetForegroundColor(theme.border),
         Print("────────────────────────────────────────────────────────────────")
     )?;
-    execute!(
-        stdout,
+    draw!(
+        frame,
         MoveTo(4, nav_y + 1),
         SetForegroundColor(theme.key),
         Print("↑/↓"),
@@ -1663,6 +2762,10 @@ fn render_history_screen(
         SetForegroundColor(theme.info),
         Print(" Replay  "),
         SetForegroundColor(theme.key),
+        Print("p"),
+        SetForegroundColor(theme.info),
+        Print(" Puzzle board  "),
+        SetForegroundColor(theme.key),
         Print("i"),
         SetForegroundColor(theme.info),
         Print(" Stats  "),
@@ -1674,3 +2777,538 @@ fn render_history_screen(
 
     Ok(())
 }
+
+fn render_profiles_screen(
+    frame: &mut Frame,
+    app: &App,
+    term_width: u16,
+    term_height: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let active = crate::profiles::active();
+
+    draw!(frame, SetBackgroundColor(theme.bg))?;
+
+    let title = "═══ PROFILES ═══";
+    let title_x = term_width.saturating_sub(title.chars().count() as u16) / 2;
+    draw!(
+        frame,
+        MoveTo(title_x, 1),
+        SetForegroundColor(theme.key),
+        Print(title)
+    )?;
+
+    draw!(
+        frame,
+        MoveTo(4, 3),
+        SetForegroundColor(theme.info),
+        Print("Each profile keeps its own stats, history, settings and leaderboard name")
+    )?;
+
+    let list_y = 5;
+    for (i, profile) in app.profiles.iter().enumerate() {
+        let y = list_y + i as u16;
+        let selected = i == app.profile_selection;
+        let bg = if selected {
+            theme.selected_bg
+        } else {
+            theme.bg
+        };
+        let marker = if profile.id == active.id { "●" } else { " " };
+        let name = match app.profile_edit {
+            Some(ProfileEdit::Rename { ref id, ref name }) if *id == profile.id => {
+                format!("{}_", name)
+            }
+            _ => profile.name.clone(),
+        };
+
+        draw!(
+            frame,
+            MoveTo(2, y),
+            SetBackgroundColor(theme.bg),
+            SetForegroundColor(theme.key),
+            Print(if selected { "▶" } else { " " }),
+            MoveTo(4, y),
+            SetForegroundColor(theme.success),
+            Print(marker),
+            SetBackgroundColor(bg),
+            SetForegroundColor(theme.fg),
+            Print(format!(" {:<28}", name)),
+            SetForegroundColor(theme.border),
+            Print(if profile.is_default() {
+                "(default)"
+            } else {
+                ""
+            }),
+            SetBackgroundColor(theme.bg)
+        )?;
+    }
+
+    // Pending edit prompt
+    let prompt_y = list_y + app.profiles.len() as u16 + 1;
+    match app.profile_edit {
+        Some(ProfileEdit::Create(ref name)) => {
+            draw!(
+                frame,
+                MoveTo(4, prompt_y),
+                SetForegroundColor(theme.info),
+                Print("New profile name: "),
+                SetForegroundColor(theme.fg),
+                Print(format!("{}_", name))
+            )?;
+        }
+        Some(ProfileEdit::ConfirmDelete(ref id)) => {
+            let name = app
+                .profiles
+                .iter()
+                .find(|p| p.id == *id)
+                .map_or(id.as_str(), |p| p.name.as_str());
+            draw!(
+                frame,
+                MoveTo(4, prompt_y),
+                SetForegroundColor(theme.error),
+                Print(format!(
+                    "Delete '{}' and all of its stats and history? (y/n)",
+                    name
+                ))
+            )?;
+        }
+        _ => {}
+    }
+
+    // Navigation help
+    let nav_y = term_height.saturating_sub(3);
+    draw!(
+        frame,
+        MoveTo(4, nav_y),
+        SetForegroundColor(theme.border),
+        Print("────────────────────────────────────────────────────────────────")
+    )?;
+    draw!(frame, MoveTo(4, nav_y + 1))?;
+    let help: &[(&str, &str)] = if app.profile_edit.is_some() {
+        &[("Enter", "Confirm"), ("Esc", "Cancel")]
+    } else {
+        &[
+            ("↑/↓", "Select"),
+            ("Enter", "Play as"),
+            ("n", "New"),
+            ("r", "Rename"),
+            ("d", "Delete"),
+            ("Esc", "Back"),
+        ]
+    };
+    for (key, desc) in help {
+        draw!(
+            frame,
+            SetForegroundColor(theme.key),
+            Print(key),
+            SetForegroundColor(theme.info),
+            Print(format!(" {}  ", desc))
+        )?;
+    }
+
+    if let Some(ref msg) = app.message {
+        render_message(frame, app, msg, term_width)?;
+    }
+
+    Ok(())
+}
+
+fn render_lobby_screen(
+    frame: &mut Frame,
+    app: &App,
+    term_width: u16,
+    term_height: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+
+    draw!(frame, SetBackgroundColor(theme.bg))?;
+
+    let Some(lobby) = app.lobby() else {
+        return Ok(());
+    };
+
+    let title = format!("═══ {} LOBBY ═══", lobby.name.to_uppercase());
+    let title_x = term_width.saturating_sub(title.chars().count() as u16) / 2;
+    draw!(
+        frame,
+        MoveTo(title_x, 1),
+        SetForegroundColor(theme.key),
+        Print(title)
+    )?;
+
+    draw!(
+        frame,
+        MoveTo(4, 3),
+        SetForegroundColor(theme.info),
+        Print("Lobby code: "),
+        SetForegroundColor(theme.key),
+        Print(&lobby.code),
+        MoveTo(4, 4)
+    )?;
+    match &lobby.address {
+        Some(address) => draw!(
+            frame,
+            SetForegroundColor(theme.info),
+            Print("Others join with: "),
+            SetForegroundColor(theme.fg),
+            Print(format!(
+                "sudoku {} join {} {}",
+                lobby.mode, address, lobby.code
+            ))
+        )?,
+        None => draw!(
+            frame,
+            SetForegroundColor(theme.info),
+            Print("Waiting for the host to start")
+        )?,
+    }
+
+    draw!(
+        frame,
+        MoveTo(4, 6),
+        SetForegroundColor(theme.info),
+        Print("Players")
+    )?;
+    if lobby.players.is_empty() {
+        draw!(
+            frame,
+            MoveTo(6, 7),
+            SetForegroundColor(theme.border),
+            Print("Connecting…")
+        )?;
+    }
+    for (i, (name, me)) in lobby.players.iter().enumerate() {
+        draw!(
+            frame,
+            MoveTo(6, 7 + i as u16),
+            SetForegroundColor(theme.success),
+            Print("● "),
+            SetForegroundColor(theme.fg),
+            Print(name),
+            SetForegroundColor(theme.border),
+            Print(if *me { " (you)" } else { "" })
+        )?;
+    }
+
+    // Navigation help
+    let nav_y = term_height.saturating_sub(3);
+    draw!(
+        frame,
+        MoveTo(4, nav_y),
+        SetForegroundColor(theme.border),
+        Print("────────────────────────────────────────────────────────────────")
+    )?;
+    draw!(frame, MoveTo(4, nav_y + 1))?;
+    let help: &[(&str, &str)] = if lobby.host {
+        &[("Enter", "Start"), ("Esc", "Leave")]
+    } else {
+        &[("Esc", "Leave")]
+    };
+    for (key, desc) in help {
+        draw!(
+            frame,
+            SetForegroundColor(theme.key),
+            Print(key),
+            SetForegroundColor(theme.info),
+            Print(format!(" {}  ", desc))
+        )?;
+    }
+
+    if let Some(ref msg) = app.message {
+        render_message(frame, app, msg, term_width)?;
+    }
+
+    Ok(())
+}
+
+/// Progress value or target as shown on the achievements screen
+fn achievement_amount(achievement: &Achievement, value: u64) -> String {
+    match achievement.condition {
+        Condition::PlayTime => format!("{}h", value / 3600),
+        _ => value.to_string(),
+    }
+}
+
+fn render_achievements_screen(
+    frame: &mut Frame,
+    app: &App,
+    term_width: u16,
+    term_height: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+    let progress = app.stats.achievement_progress();
+
+    draw!(frame, SetBackgroundColor(theme.bg))?;
+
+    let title = "═══ ACHIEVEMENTS ═══";
+    let title_x = term_width.saturating_sub(title.chars().count() as u16) / 2;
+    draw!(
+        frame,
+        MoveTo(title_x, 1),
+        SetForegroundColor(theme.key),
+        Print(title)
+    )?;
+
+    let reached: usize = progress.iter().map(|p| p.tier).sum();
+    let total: usize = progress.iter().map(|p| p.achievement.tiers.len()).sum();
+    draw!(
+        frame,
+        MoveTo(4, 3),
+        SetForegroundColor(theme.info),
+        Print(format!("{} of {} tiers reached", reached, total))
+    )?;
+
+    // Two rows per achievement; scroll so the selection stays visible
+    let list_y = 5u16;
+    let rows = (term_height.saturating_sub(list_y + 4) / 2).max(1) as usize;
+    let first = app.achievement_selection.saturating_sub(rows - 1);
+
+    for (i, p) in progress.iter().enumerate().skip(first).take(rows) {
+        let y = list_y + ((i - first) * 2) as u16;
+        let selected = i == app.achievement_selection;
+        let a = p.achievement;
+
+        let (icon, name, description) = if p.is_secret() {
+            ("🔒", "???", "Hidden achievement")
+        } else if p.tier > 0 {
+            ("🏆", a.name, a.description)
+        } else {
+            ("  ", a.name, a.description)
+        };
+        let stars: String = (0..a.tiers.len())
+            .map(|t| if t < p.tier { '★' } else { '☆' })
+            .collect();
+
+        draw!(
+            frame,
+            MoveTo(2, y),
+            SetBackgroundColor(theme.bg),
+            SetForegroundColor(theme.key),
+            Print(if selected { "▶" } else { " " }),
+            MoveTo(4, y),
+            SetBackgroundColor(if selected {
+                theme.selected_bg
+            } else {
+                theme.bg
+            }),
+            SetForegroundColor(if p.tier > 0 { theme.success } else { theme.fg }),
+            Print(format!("{} {:<24}", icon, name)),
+            SetForegroundColor(theme.key),
+            Print(format!(" {:<4}", stars)),
+            SetBackgroundColor(theme.bg)
+        )?;
+
+        let detail = match p.next {
+            _ if p.is_secret() => String::new(),
+            None => "Complete".to_string(),
+            Some(target) => {
+                const WIDTH: u64 = 16;
+                let filled = (p.value.min(target) * WIDTH / target.max(1)) as usize;
+                format!(
+                    "[{}{}] {}/{}",
+                    "█".repeat(filled),
+                    "░".repeat(WIDTH as usize - filled),
+                    achievement_amount(a, p.value),
+                    achievement_amount(a, target)
+                )
+            }
+        };
+        let reward = match a.reward {
+            Some((tier, _)) if p.tier <= tier && !p.is_secret() => {
+                format!("  · tier {} unlocks secret levels", tier + 1)
+            }
+            _ => String::new(),
+        };
+        draw!(
+            frame,
+            MoveTo(7, y + 1),
+            SetForegroundColor(theme.info),
+            Print(format!("{:<44} ", description)),
+            SetForegroundColor(if p.next.is_none() {
+                theme.success
+            } else {
+                theme.fg
+            }),
+            Print(detail),
+            SetForegroundColor(theme.border),
+            Print(reward)
+        )?;
+    }
+
+    // Navigation help
+    let nav_y = term_height.saturating_sub(3);
+    draw!(
+        frame,
+        MoveTo(4, nav_y),
+        SetForegroundColor(theme.border),
+        Print("────────────────────────────────────────────────────────────────"),
+        MoveTo(4, nav_y + 1)
+    )?;
+    for (key, desc) in [("↑/↓", "Scroll"), ("Esc", "Back to stats")] {
+        draw!(
+            frame,
+            SetForegroundColor(theme.key),
+            Print(key),
+            SetForegroundColor(theme.info),
+            Print(format!(" {}  ", desc))
+        )?;
+    }
+
+    if let Some(ref msg) = app.message {
+        render_message(frame, app, msg, term_width)?;
+    }
+
+    Ok(())
+}
+
+fn render_settings_screen(
+    frame: &mut Frame,
+    app: &App,
+    hits: &mut HitMap,
+    term_width: u16,
+    term_height: u16,
+) -> io::Result<()> {
+    let theme = &app.theme;
+
+    draw!(frame, SetBackgroundColor(theme.bg))?;
+
+    let title = "═══ SETTINGS ═══";
+    let title_x = term_width.saturating_sub(title.chars().count() as u16) / 2;
+    draw!(
+        frame,
+        MoveTo(title_x, 1),
+        SetForegroundColor(theme.key),
+        Print(title),
+        MoveTo(4, 3),
+        SetForegroundColor(theme.info),
+        Print(format!("Saved to {}", crate::config::path().display()))
+    )?;
+
+    // Settings, then one row per action; scroll so the selection stays visible
+    let values = app.setting_values();
+    let mut rows: Vec<(String, String)> = SETTINGS
+        .iter()
+        .zip(values)
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    rows.extend(
+        Action::ALL
+            .iter()
+            .map(|a| (a.label().to_string(), app.keymap.label(*a))),
+    );
+
+    let list_y = 5u16;
+    let visible = term_height.saturating_sub(list_y + 5).max(1) as usize;
+    let first = app.settings_selection.saturating_sub(visible - 1);
+    let mut y = list_y;
+    for (i, (name, value)) in rows.iter().enumerate().skip(first).take(visible) {
+        if i == SETTINGS.len() {
+            draw!(
+                frame,
+                MoveTo(4, y),
+                SetForegroundColor(theme.border),
+                Print("── Keys ──────────────────────────────────")
+            )?;
+            y += 1;
+        }
+        let selected = i == app.settings_selection;
+        let value = if selected && app.settings_capture.is_some() {
+            "Press a key... (Esc cancels)".to_string()
+        } else if i < SETTINGS.len() {
+            format!("◀ {} ▶", value)
+        } else {
+            value.clone()
+        };
+        draw!(
+            frame,
+            MoveTo(2, y),
+            SetForegroundColor(theme.key),
+            Print(if selected { "▶" } else { " " }),
+            MoveTo(4, y),
+            SetBackgroundColor(if selected {
+                theme.selected_bg
+            } else {
+                theme.bg
+            }),
+            SetForegroundColor(theme.fg),
+            Print(format!("{:<22}", name)),
+            SetForegroundColor(if i < SETTINGS.len() {
+                theme.success
+            } else {
+                theme.key
+            }),
+            Print(format!(" {:<30}", value)),
+            SetBackgroundColor(theme.bg)
+        )?;
+        hits.add(2, y, 55, Target::SettingsRow(i));
+        y += 1;
+    }
+
+    // Navigation help
+    let nav_y = term_height.saturating_sub(3);
+    draw!(
+        frame,
+        MoveTo(4, nav_y),
+        SetForegroundColor(theme.border),
+        Print("────────────────────────────────────────────────────────────────"),
+        MoveTo(4, nav_y + 1)
+    )?;
+    let help: &[(&str, &str)] = if app.settings_selection < SETTINGS.len() {
+        &[("↑/↓", "Select"), ("←/→", "Change"), ("Esc", "Back")]
+    } else {
+        &[
+            ("↑/↓", "Select"),
+            ("Enter", "Rebind"),
+            ("a", "Add key"),
+            ("Del", "Default"),
+            ("Esc", "Back"),
+        ]
+    };
+    for (key, desc) in help {
+        draw!(
+            frame,
+            SetForegroundColor(theme.key),
+            Print(key),
+            SetForegroundColor(theme.info),
+            Print(format!(" {}  ", desc))
+        )?;
+    }
+
+    if let Some(ref msg) = app.message {
+        render_message(frame, app, msg, term_width)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_resolve() {
+        assert_eq!(Layout::Auto.resolve(80, 24), Layout::Compact);
+        assert_eq!(Layout::Auto.resolve(80, 30), Layout::Standard);
+        assert_eq!(Layout::Auto.resolve(120, 50), Layout::Large);
+        // A chosen layout is kept while it fits, then steps down
+        assert_eq!(Layout::Standard.resolve(120, 50), Layout::Standard);
+        assert_eq!(Layout::Compact.resolve(120, 50), Layout::Compact);
+        assert_eq!(Layout::Large.resolve(90, 40), Layout::Standard);
+        assert_eq!(Layout::Large.resolve(50, 40), Layout::Compact);
+        assert_eq!(grid_separator(3, '-'), "+---".repeat(9) + "+");
+    }
+
+    #[test]
+    fn test_hit_map() {
+        let mut hits = HitMap::default();
+        hits.add(10, 5, 3, Target::Cell(Position::new(0, 0)));
+        hits.add(0, 5, 40, Target::MenuItem(2));
+        hits.add(14, 5, 3, Target::Digit(4));
+        assert_eq!(hits.at(14, 5), Some(Target::Digit(4)));
+        // The menu drawn later covers the cell
+        assert_eq!(hits.at(11, 5), Some(Target::MenuItem(2)));
+        assert_eq!(hits.at(40, 5), None);
+        assert_eq!(hits.at(11, 6), None);
+    }
+}
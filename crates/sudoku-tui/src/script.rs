@@ -0,0 +1,635 @@
+//! Scripted input for the TUI: a headless driver, session recording and
+//! replay.
+//!
+//! A script is one command per line; `#` starts a comment:
+//!
+//! ```text
+//! size 100 40              # terminal size (a later one is a resize)
+//! config theme = "light"   # a config.toml line, before any input
+//! puzzle 5300700006...     # starting puzzle; later, the game a new game got
+//! key l l Ctrl+z Shift+5   # keys, written as in config.toml
+//! type 53                  # each character as a key
+//! click 12 4               # left click at column 12, row 4 (0-based)
+//! rclick 12 4              # right click
+//! scroll down
+//! tick 10                  # advance animations and timers 10 ticks
+//! expect cursor r5c3
+//! expect value r1c1 5      # or `.` for empty
+//! expect screen playing    # also: menu, mode, mistakes, message, render
+//! expect render SUDOKU     # text somewhere on the rendered screen
+//! ```
+//!
+//! `--record` writes the same format while playing, so a session can be
+//! replayed headless with `--replay` and extended with `expect` lines.
+
+use crate::app::{App, AppAction};
+use crate::config::KeyBinding;
+use crate::frame::Frame;
+use crate::game::Game;
+use crate::render;
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use sudoku_core::Position;
+
+/// Terminal size when a script doesn't give one
+const DEFAULT_SIZE: (u16, u16) = (100, 40);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// A key, click, scroll or resize
+    Input(Event),
+    Tick(u32),
+    /// Replace the game, as a new game did when the script was recorded
+    Puzzle(String),
+    Expect(Expect),
+}
+
+/// A check of the app's state
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expect {
+    /// `ScreenState`, compared by name
+    Screen(String),
+    /// `MenuState`, compared by name
+    Menu(String),
+    /// `InputMode`, compared by name
+    Mode(String),
+    Cursor(Position),
+    Value(Position, Option<u8>),
+    Mistakes(usize),
+    /// The message bar contains this text, or is empty for `none`
+    Message(Option<String>),
+    /// The rendered screen contains this text
+    Render(String),
+}
+
+/// A parsed script
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Script {
+    pub size: Option<(u16, u16)>,
+    /// Contents of the config file to run with
+    pub config: Option<String>,
+    pub puzzle: Option<String>,
+    /// Each step with its line number
+    pub steps: Vec<(usize, Step)>,
+}
+
+/// A line that couldn't be parsed or a failed expectation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut script = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let error = |message: String| ScriptError {
+                line: line_no,
+                message,
+            };
+            // `config` lines are TOML, which has its own comments
+            let toml = line.trim_start().strip_prefix("config");
+            if let Some(toml) = toml.filter(|t| t.is_empty() || t.starts_with(' ')) {
+                let toml = toml.strip_prefix(' ').unwrap_or(toml);
+                if !script.steps.is_empty() {
+                    return Err(error("config must come before any input".to_string()));
+                }
+                let config = script.config.get_or_insert_with(String::new);
+                config.push_str(toml);
+                config.push('\n');
+                continue;
+            }
+            let line = line.split_once(" #").map_or(line, |(code, _)| code).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            let before_input = script.steps.is_empty();
+            match command {
+                "size" => {
+                    let size = parse_pair(rest).map_err(error)?;
+                    if before_input && script.size.is_none() {
+                        script.size = Some(size);
+                    } else {
+                        script
+                            .steps
+                            .push((line_no, Step::Input(Event::Resize(size.0, size.1))));
+                    }
+                }
+                "puzzle" if rest.is_empty() => {
+                    return Err(error("puzzle needs a puzzle string or code".to_string()))
+                }
+                "puzzle" if before_input && script.puzzle.is_none() => {
+                    script.puzzle = Some(rest.to_string());
+                }
+                "puzzle" => script.steps.push((line_no, Step::Puzzle(rest.to_string()))),
+                "key" => {
+                    for name in rest.split_whitespace() {
+                        let key = parse_key(name).map_err(error)?;
+                        script.steps.push((line_no, Step::Input(Event::Key(key))));
+                    }
+                }
+                "type" => {
+                    for c in rest.chars() {
+                        let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+                        script.steps.push((line_no, Step::Input(Event::Key(key))));
+                    }
+                }
+                "click" | "rclick" => {
+                    let (column, row) = parse_pair(rest).map_err(error)?;
+                    let button = if command == "click" {
+                        MouseButton::Left
+                    } else {
+                        MouseButton::Right
+                    };
+                    let event = mouse(MouseEventKind::Down(button), column, row);
+                    script.steps.push((line_no, Step::Input(event)));
+                }
+                "scroll" => {
+                    let kind = match rest {
+                        "up" => MouseEventKind::ScrollUp,
+                        "down" => MouseEventKind::ScrollDown,
+                        _ => return Err(error(format!("scroll up or down, not '{}'", rest))),
+                    };
+                    script.steps.push((line_no, Step::Input(mouse(kind, 0, 0))));
+                }
+                "tick" => {
+                    let count = match rest {
+                        "" => 1,
+                        n => n
+                            .parse()
+                            .map_err(|_| error(format!("'{}' is not a tick count", n)))?,
+                    };
+                    script.steps.push((line_no, Step::Tick(count)));
+                }
+                "expect" => {
+                    let expect = parse_expect(rest).map_err(error)?;
+                    script.steps.push((line_no, Step::Expect(expect)));
+                }
+                _ => return Err(error(format!("unknown command '{}'", command))),
+            }
+        }
+        Ok(script)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.size.unwrap_or(DEFAULT_SIZE)
+    }
+
+    /// Run the steps, stopping at the first failed
+    /// expectation or when the app quits
+    pub fn run(&self, driver: &mut Driver) -> Result<(), ScriptError> {
+        for (line, step) in &self.steps {
+            if driver.quit {
+                return Err(ScriptError {
+                    line: *line,
+                    message: "the game has already quit".to_string(),
+                });
+            }
+            driver.step(step).map_err(|message| ScriptError {
+                line: *line,
+                message,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+fn parse_pair(text: &str) -> Result<(u16, u16), String> {
+    let numbers: Vec<u16> = text
+        .split_whitespace()
+        .map(|n| n.parse().map_err(|_| format!("'{}' is not a number", n)))
+        .collect::<Result<_, _>>()?;
+    match numbers[..] {
+        [a, b] => Ok((a, b)),
+        _ => Err(format!("expected two numbers, got '{}'", text)),
+    }
+}
+
+/// A key as in config.toml, plus `Shift+` (which turns a digit into a note)
+fn parse_key(name: &str) -> Result<KeyEvent, String> {
+    if name.eq_ignore_ascii_case("backtab") {
+        return Ok(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+    }
+    let (shift, rest) = match name.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("shift+") && name.len() > 6 => {
+            (true, &name[6..])
+        }
+        _ => (false, name),
+    };
+    let binding = KeyBinding::parse(rest)?;
+    let mut modifiers = binding.modifiers;
+    if shift {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    Ok(KeyEvent::new(binding.code, modifiers))
+}
+
+/// The script form of a key press; the inverse of `parse_key`
+fn key_name(key: &KeyEvent) -> String {
+    if key.code == KeyCode::BackTab {
+        return "BackTab".to_string();
+    }
+    let binding = KeyBinding {
+        code: key.code,
+        modifiers: key.modifiers,
+    };
+    let letter = matches!(key.code, KeyCode::Char(c) if c.is_alphabetic());
+    if key.modifiers.contains(KeyModifiers::SHIFT) && !letter {
+        format!("Shift+{}", binding)
+    } else {
+        binding.to_string()
+    }
+}
+
+/// A cell written `r5c3`
+fn parse_position(text: &str) -> Result<Position, String> {
+    let bad = || format!("'{}' is not a cell like r5c3", text);
+    let lower = text.to_ascii_lowercase();
+    let (row, col) = lower
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .ok_or_else(bad)?;
+    match (row.parse::<usize>(), col.parse::<usize>()) {
+        (Ok(row @ 1..=9), Ok(col @ 1..=9)) => Ok(Position::new(row - 1, col - 1)),
+        _ => Err(bad()),
+    }
+}
+
+fn parse_expect(text: &str) -> Result<Expect, String> {
+    let (what, value) = text.split_once(' ').unwrap_or((text, ""));
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("expect {} needs a value", what));
+    }
+    Ok(match what {
+        "screen" => Expect::Screen(value.to_string()),
+        "menu" => Expect::Menu(value.to_string()),
+        "mode" => Expect::Mode(value.to_string()),
+        "cursor" => Expect::Cursor(parse_position(value)?),
+        "value" => {
+            let (cell, digit) = value
+                .split_once(' ')
+                .ok_or_else(|| "expect value needs a cell and a digit".to_string())?;
+            let digit = match digit.trim() {
+                "." | "0" => None,
+                d => match d.parse::<u8>() {
+                    Ok(d @ 1..=9) => Some(d),
+                    _ => return Err(format!("'{}' is not a digit or '.'", d)),
+                },
+            };
+            Expect::Value(parse_position(cell)?, digit)
+        }
+        "mistakes" => Expect::Mistakes(
+            value
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", value))?,
+        ),
+        "message" if value == "none" => Expect::Message(None),
+        "message" => Expect::Message(Some(value.to_string())),
+        "render" => Expect::Render(value.to_string()),
+        _ => return Err(format!("unknown expectation '{}'", what)),
+    })
+}
+
+/// Runs an [`App`] without a terminal: input goes straight to the handlers
+/// and screens are drawn into a [`Frame`]
+pub struct Driver {
+    pub app: App,
+    frame: Frame,
+    /// The app asked to quit
+    pub quit: bool,
+}
+
+impl Driver {
+    pub fn new(app: App, (width, height): (u16, u16)) -> Self {
+        let mut driver = Self {
+            app,
+            frame: Frame::new(width, height),
+            quit: false,
+        };
+        driver.render();
+        driver
+    }
+
+    /// The last rendered screen
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+
+    /// Draw the screen again, as the terminal loop does before each input
+    /// (mouse clicks hit what was drawn last)
+    pub fn render(&mut self) {
+        let (width, height) = self.frame.size();
+        self.frame = Frame::new(width, height);
+        // Drawing into a frame can't fail
+        let _ = render::render(&mut self.frame, &mut self.app);
+    }
+
+    pub fn send(&mut self, event: &Event) {
+        let action = match event {
+            Event::Key(key) => self.app.handle_key(*key),
+            Event::Mouse(mouse) => self.app.handle_mouse(*mouse),
+            Event::Resize(width, height) => {
+                self.frame = Frame::new(*width, *height);
+                AppAction::Continue
+            }
+            _ => AppAction::Continue,
+        };
+        if let AppAction::Quit = action {
+            self.quit = true;
+        }
+        self.render();
+    }
+
+    pub fn tick(&mut self, count: u32) {
+        for _ in 0..count {
+            self.app.tick();
+        }
+        self.render();
+    }
+
+    fn step(&mut self, step: &Step) -> Result<(), String> {
+        match step {
+            Step::Input(event) => self.send(event),
+            Step::Tick(count) => self.tick(*count),
+            Step::Puzzle(code) => {
+                self.app.game =
+                    Game::from_code(code).ok_or_else(|| format!("invalid puzzle '{}'", code))?;
+                self.render();
+            }
+            Step::Expect(expect) => self.check(expect)?,
+        }
+        Ok(())
+    }
+
+    /// Compare the app with an expectation, describing any difference
+    pub fn check(&self, expect: &Expect) -> Result<(), String> {
+        let app = &self.app;
+        let named = |what: &str, want: &str, found: String| {
+            if found.eq_ignore_ascii_case(want) {
+                Ok(())
+            } else {
+                Err(format!("expected {} {}, found {}", what, want, found))
+            }
+        };
+        let cell = |pos: Position| format!("r{}c{}", pos.row + 1, pos.col + 1);
+        match expect {
+            Expect::Screen(want) => named("screen", want, format!("{:?}", app.screen_state)),
+            Expect::Menu(want) => named("menu", want, format!("{:?}", app.menu)),
+            Expect::Mode(want) => named("mode", want, format!("{:?}", app.mode)),
+            Expect::Cursor(pos) if app.cursor == *pos => Ok(()),
+            Expect::Cursor(pos) => Err(format!(
+                "expected cursor {}, found {}",
+                cell(*pos),
+                cell(app.cursor)
+            )),
+            Expect::Value(pos, want) => {
+                let found = app.game.grid().cell(*pos).value();
+                if found == *want {
+                    return Ok(());
+                }
+                let show = |v: Option<u8>| v.map_or(".".to_string(), |v| v.to_string());
+                Err(format!(
+                    "expected {} at {}, found {}",
+                    show(*want),
+                    cell(*pos),
+                    show(found)
+                ))
+            }
+            Expect::Mistakes(want) if app.game.mistakes() == *want => Ok(()),
+            Expect::Mistakes(want) => Err(format!(
+                "expected {} mistakes, found {}",
+                want,
+                app.game.mistakes()
+            )),
+            Expect::Message(want) => match (want, &app.message) {
+                (None, None) => Ok(()),
+                (Some(want), Some(found)) if found.contains(want.as_str()) => Ok(()),
+                (want, found) => Err(format!(
+                    "expected message {}, found {}",
+                    want.as_deref()
+                        .map_or("none".to_string(), |w| format!("'{}'", w)),
+                    found
+                        .as_deref()
+                        .map_or("none".to_string(), |f| format!("'{}'", f))
+                )),
+            },
+            Expect::Render(text) if self.frame.text().contains(text.as_str()) => Ok(()),
+            Expect::Render(text) => Err(format!(
+                "'{}' is not on screen:\n{}",
+                text,
+                self.frame.text()
+            )),
+        }
+    }
+}
+
+/// Writes a session as a script while it's played. Ticks are counted and
+/// written before the next input, so the replay sees the same timing.
+pub struct Recorder {
+    file: File,
+    ticks: u32,
+    /// Puzzle of the game last written, to spot new games
+    puzzle: String,
+}
+
+impl Recorder {
+    /// Start a recording with what a replay needs to start the same way
+    pub fn create(path: &Path, app: &App, (width, height): (u16, u16)) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(
+            file,
+            "# sudoku session; replay with: sudoku --replay <file>"
+        )?;
+        writeln!(file, "size {} {}", width, height)?;
+        let config = toml::to_string_pretty(&app.config).map_err(io::Error::other)?;
+        for line in config.lines() {
+            writeln!(file, "config {}", line)?;
+        }
+        let mut recorder = Self {
+            file,
+            ticks: 0,
+            puzzle: String::new(),
+        };
+        recorder.game_changed(app)?;
+        Ok(recorder)
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// Record an input, then the game it started if it started one
+    pub fn input(&mut self, event: &Event, app: &App) -> io::Result<()> {
+        let line = match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                format!("key {}", key_name(key))
+            }
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::Down(button) => {
+                    let alternate = button == MouseButton::Right || !mouse.modifiers.is_empty();
+                    let command = if alternate { "rclick" } else { "click" };
+                    format!("{} {} {}", command, mouse.column, mouse.row)
+                }
+                MouseEventKind::ScrollUp => "scroll up".to_string(),
+                MouseEventKind::ScrollDown => "scroll down".to_string(),
+                _ => return Ok(()),
+            },
+            Event::Resize(width, height) => format!("size {} {}", width, height),
+            _ => return Ok(()),
+        };
+        if self.ticks > 0 {
+            writeln!(self.file, "tick {}", self.ticks)?;
+            self.ticks = 0;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.game_changed(app)
+    }
+
+    fn game_changed(&mut self, app: &App) -> io::Result<()> {
+        if app.game.original_puzzle() == self.puzzle {
+            return Ok(());
+        }
+        self.puzzle = app.game.original_puzzle().to_string();
+        let code = app.game.short_code().unwrap_or_else(|| self.puzzle.clone());
+        writeln!(self.file, "puzzle {}", code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    /// An app whose config and data live in a scratch directory
+    fn sandbox_app(puzzle: &str) -> App {
+        assert!(crate::persistence::test_sandbox().exists());
+        App::new_with_puzzle(Some(puzzle))
+    }
+
+    #[test]
+    fn test_parse() {
+        let script = Script::parse(
+            "size 80 30\nconfig theme = \"light\" # a TOML comment\npuzzle abc\n\
+             key Shift+5 Ctrl+z # comment\ntype 4\ntick\nsize 60 20\n\
+             expect value r1c3 .\n",
+        )
+        .unwrap();
+        assert_eq!(script.size, Some((80, 30)));
+        assert_eq!(
+            script.config.as_deref(),
+            Some("theme = \"light\" # a TOML comment\n")
+        );
+        assert_eq!(script.puzzle.as_deref(), Some("abc"));
+        let steps: Vec<&Step> = script.steps.iter().map(|(_, s)| s).collect();
+        assert_eq!(
+            steps,
+            [
+                &Step::Input(Event::Key(KeyEvent::new(
+                    KeyCode::Char('5'),
+                    KeyModifiers::SHIFT
+                ))),
+                &Step::Input(Event::Key(KeyEvent::new(
+                    KeyCode::Char('z'),
+                    KeyModifiers::CONTROL
+                ))),
+                &Step::Input(Event::Key(KeyEvent::new(
+                    KeyCode::Char('4'),
+                    KeyModifiers::NONE
+                ))),
+                &Step::Tick(1),
+                &Step::Input(Event::Resize(60, 20)),
+                &Step::Expect(Expect::Value(Position::new(0, 2), None)),
+            ]
+        );
+        assert_eq!(script.steps[0].0, 4);
+
+        for key in ["Shift+5", "Ctrl+z", "X", "Esc", "Alt+Left", "BackTab"] {
+            assert_eq!(key_name(&parse_key(key).unwrap()), key);
+        }
+
+        let error = Script::parse("key l\nexpect cursor r0c1").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(Script::parse("key l\nconfig theme = \"dark\"").is_err());
+        assert!(Script::parse("jump 3").is_err());
+    }
+
+    #[test]
+    fn test_driver() {
+        let script = Script::parse(
+            "key l k\nexpect cursor r4c6\ntype 1\nexpect value r4c6 1\nexpect mistakes 0\n\
+             key Left Left\ntype 9\nexpect mistakes 1\nexpect message Incorrect\n\
+             expect render Mistakes:    1/3\nexpect screen playing\nexpect menu none\n",
+        )
+        .unwrap();
+        let mut driver = Driver::new(sandbox_app(PUZZLE), script.size());
+        script.run(&mut driver).unwrap();
+
+        // Clicking a cell selects what was drawn there
+        let cell = (0..100)
+            .flat_map(|x| (0..40).map(move |y| (x, y)))
+            .find(|&(x, y)| {
+                driver.app.hit_map.at(x, y) == Some(render::Target::Cell(Position::new(0, 2)))
+            })
+            .unwrap();
+        driver.send(&mouse(
+            MouseEventKind::Down(MouseButton::Left),
+            cell.0,
+            cell.1,
+        ));
+        assert_eq!(driver.check(&Expect::Cursor(Position::new(0, 2))), Ok(()));
+
+        let failed = Script::parse("key j\nexpect cursor r9c9")
+            .unwrap()
+            .run(&mut driver)
+            .unwrap_err();
+        assert_eq!(failed.line, 2);
+        assert_eq!(failed.message, "expected cursor r9c9, found r2c3");
+    }
+
+    #[test]
+    fn test_replay_saves_into_the_sandbox() {
+        let script = Script::parse("key S\nexpect message Puzzle code").unwrap();
+        let mut driver = Driver::new(sandbox_app(PUZZLE), script.size());
+        script.run(&mut driver).unwrap();
+
+        // The save and share code land in the sandbox, never the player's files
+        let sandbox = crate::persistence::test_sandbox();
+        let save = App::save_path();
+        assert!(save.starts_with(sandbox), "{}", save.display());
+        assert!(save.exists());
+        assert!(sandbox.join("share.txt").exists());
+        assert_eq!(crate::persistence::legacy_save_path(), None);
+    }
+}
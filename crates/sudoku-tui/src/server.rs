@@ -0,0 +1,399 @@
+//! Server configuration shared by every networked feature.
+//!
+//! Results, auth tokens, leaderboards and puzzle fetching all talk to one
+//! ukodus-compatible server. It is resolved once at startup from, in order of
+//! increasing precedence:
+//!
+//! 1. a named profile (`production` by default, or `local`)
+//! 2. the config file (`sudoku_server.json` in the app data dir, or
+//!    `--server-config` / `SUDOKU_SERVER_CONFIG`)
+//! 3. environment variables (`SUDOKU_SERVER`, `SUDOKU_API_URL`, `SUDOKU_API_KEY`,
+//!    `SUDOKU_API_TIMEOUT`, `SUDOKU_CA_BUNDLE`)
+//! 4. command line flags (`--server`, `--ca-bundle`)
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Public ukodus server
+pub const PRODUCTION_URL: &str = "https://ukodus.now";
+/// Self-hosted server / telemetry stub running on this machine
+pub const LOCAL_URL: &str = "http://127.0.0.1:8787";
+
+static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// Per-feature URL overrides; unset ones derive from `base_url`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoints {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Root of the leaderboard API (`.../api/v1/leaderboard`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puzzles: Option<String>,
+}
+
+/// Resolved server configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Profile the config started from ("production", "local" or "custom")
+    pub profile: String,
+    pub base_url: String,
+    #[serde(default)]
+    pub endpoints: Endpoints,
+    /// Static bearer token; when unset the per-player token is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    pub timeout_secs: u64,
+    /// Extra PEM root certificates to trust (e.g. an internal CA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+}
+
+/// Config file contents; every field is optional and layered over the profile
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerFile {
+    profile: Option<String>,
+    base_url: Option<String>,
+    endpoints: Endpoints,
+    api_key: Option<String>,
+    timeout_secs: Option<u64>,
+    ca_bundle: Option<PathBuf>,
+}
+
+/// Command line overrides
+#[derive(Debug, Clone, Default)]
+pub struct ServerOverrides {
+    /// Profile name or base URL
+    pub server: Option<String>,
+    pub config_path: Option<PathBuf>,
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self::production()
+    }
+}
+
+impl ServerConfig {
+    pub fn production() -> Self {
+        Self {
+            profile: "production".to_string(),
+            base_url: PRODUCTION_URL.to_string(),
+            endpoints: Endpoints::default(),
+            api_key: None,
+            timeout_secs: 10,
+            ca_bundle: None,
+        }
+    }
+
+    pub fn local() -> Self {
+        Self {
+            profile: "local".to_string(),
+            base_url: LOCAL_URL.to_string(),
+            ..Self::production()
+        }
+    }
+
+    /// Look up a named profile
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "production" | "prod" => Some(Self::production()),
+            "local" => Some(Self::local()),
+            _ => None,
+        }
+    }
+
+    /// Point at a profile's server or a bare base URL. Endpoint overrides
+    /// belong to the previous server and are cleared; credentials, timeout
+    /// and CA bundle are kept.
+    fn select(&mut self, server: &str) -> Result<(), String> {
+        let (profile, base_url) = if let Some(profile) = Self::profile(server) {
+            (profile.profile, profile.base_url)
+        } else if server.starts_with("http://") || server.starts_with("https://") {
+            ("custom".to_string(), server.to_string())
+        } else {
+            return Err(format!(
+                "unknown server '{}' (expected production, local or a URL)",
+                server
+            ));
+        };
+        self.profile = profile;
+        self.base_url = base_url;
+        self.endpoints = Endpoints::default();
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    pub fn results_url(&self) -> String {
+        self.endpoints
+            .results
+            .clone()
+            .unwrap_or_else(|| self.url("/api/v1/results"))
+    }
+
+    pub fn token_url(&self) -> String {
+        self.endpoints
+            .token
+            .clone()
+            .unwrap_or_else(|| self.url("/api/v1/token"))
+    }
+
+    pub fn leaderboard_url(&self) -> String {
+        self.endpoints
+            .leaderboard
+            .clone()
+            .unwrap_or_else(|| self.url("/api/v1/leaderboard"))
+    }
+
+    pub fn puzzles_url(&self) -> String {
+        self.endpoints
+            .puzzles
+            .clone()
+            .unwrap_or_else(|| self.url("/api/v1/puzzles"))
+    }
+
+    /// Base URL for player-scoped endpoints (`.../players/{id}`)
+    pub fn players_url(&self) -> String {
+        self.url("/api/v1/players")
+    }
+
+    /// HTTP agent with this config's timeout and CA bundle
+    pub fn build_agent(&self) -> Result<ureq::Agent, String> {
+        let mut builder = ureq::AgentBuilder::new().timeout(Duration::from_secs(self.timeout_secs));
+        if let Some(ref path) = self.ca_bundle {
+            builder = builder.tls_config(Arc::new(tls_config_with_ca(path)?));
+        }
+        Ok(builder.build())
+    }
+}
+
+/// rustls config trusting the usual web roots plus the certificates in `path`
+fn tls_config_with_ca(path: &Path) -> Result<rustls::ClientConfig, String> {
+    use rustls::pki_types::{pem::PemObject, CertificateDer};
+
+    let mut roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let certs =
+        CertificateDer::pem_file_iter(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut added = 0;
+    for cert in certs {
+        let cert = cert.map_err(|e| format!("{}: {}", path.display(), e))?;
+        roots
+            .add(cert)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        added += 1;
+    }
+    if added == 0 {
+        return Err(format!("{}: no certificates found", path.display()));
+    }
+
+    rustls::ClientConfig::builder_with_provider(rustls::crypto::ring::default_provider().into())
+        .with_protocol_versions(&[&rustls::version::TLS12, &rustls::version::TLS13])
+        .map_err(|e| e.to_string())
+        .map(|b| b.with_root_certificates(roots).with_no_client_auth())
+}
+
+fn default_config_path() -> PathBuf {
+    crate::persistence::app_data_dir().join("sudoku_server.json")
+}
+
+/// Resolve the configuration from file, environment (via `env`) and CLI
+pub fn resolve(
+    overrides: &ServerOverrides,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<ServerConfig, String> {
+    let mut config = ServerConfig::production();
+
+    // Config file: explicit paths must exist, the default one is optional
+    let explicit = overrides
+        .config_path
+        .clone()
+        .or_else(|| env("SUDOKU_SERVER_CONFIG").map(PathBuf::from));
+    let path = explicit.clone().unwrap_or_else(default_config_path);
+    match std::fs::read_to_string(&path) {
+        Ok(json) => {
+            let file: ServerFile =
+                serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+            if let Some(ref profile) = file.profile {
+                config.select(profile)?;
+            }
+            if let Some(url) = file.base_url {
+                config.base_url = url;
+                if file.profile.is_none() {
+                    config.profile = "custom".to_string();
+                }
+            }
+            config.endpoints = file.endpoints;
+            config.api_key = file.api_key.or(config.api_key);
+            config.timeout_secs = file.timeout_secs.unwrap_or(config.timeout_secs);
+            config.ca_bundle = file.ca_bundle;
+        }
+        Err(e) if explicit.is_some() => return Err(format!("{}: {}", path.display(), e)),
+        Err(_) => {}
+    }
+
+    // Environment
+    if let Some(server) = env("SUDOKU_SERVER") {
+        config.select(&server)?;
+    }
+    if let Some(url) = env("SUDOKU_API_URL") {
+        config.select(&url)?;
+    }
+    if let Some(key) = env("SUDOKU_API_KEY") {
+        config.api_key = Some(key);
+    }
+    if let Some(timeout) = env("SUDOKU_API_TIMEOUT") {
+        config.timeout_secs = timeout
+            .parse()
+            .map_err(|_| format!("SUDOKU_API_TIMEOUT: not a number: {}", timeout))?;
+    }
+    if let Some(ca) = env("SUDOKU_CA_BUNDLE") {
+        config.ca_bundle = Some(PathBuf::from(ca));
+    }
+
+    // Command line
+    if let Some(ref server) = overrides.server {
+        config.select(server)?;
+    }
+    if let Some(ref ca) = overrides.ca_bundle {
+        config.ca_bundle = Some(ca.clone());
+    }
+
+    Ok(config)
+}
+
+/// Resolve and install the process-wide configuration. Call once at startup.
+pub fn init(overrides: &ServerOverrides) -> Result<(), String> {
+    let config = resolve(overrides, |k| std::env::var(k).ok())?;
+    let agent = config.build_agent()?;
+    let _ = CONFIG.set(config);
+    let _ = AGENT.set(agent);
+    Ok(())
+}
+
+/// The active configuration (defaults if `init` was never called)
+pub fn config() -> &'static ServerConfig {
+    CONFIG.get_or_init(|| {
+        resolve(&ServerOverrides::default(), |k| std::env::var(k).ok()).unwrap_or_default()
+    })
+}
+
+/// Shared HTTP agent for the active configuration
+pub fn agent() -> &'static ureq::Agent {
+    AGENT.get_or_init(|| {
+        config()
+            .build_agent()
+            .unwrap_or_else(|_| ServerConfig::production().build_agent().unwrap())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve_with(
+        overrides: &ServerOverrides,
+        vars: &[(&str, &str)],
+    ) -> Result<ServerConfig, String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        resolve(overrides, |k| vars.get(k).cloned())
+    }
+
+    fn with_file(dir: &tempfile::TempDir, json: &str) -> ServerOverrides {
+        let path = dir.path().join("server.json");
+        std::fs::write(&path, json).unwrap();
+        ServerOverrides {
+            config_path: Some(path),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_profiles_and_derived_endpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let overrides = with_file(&dir, r#"{"profile":"local"}"#);
+        let config = resolve_with(&overrides, &[]).unwrap();
+        assert_eq!(config.profile, "local");
+        assert_eq!(config.results_url(), "http://127.0.0.1:8787/api/v1/results");
+        assert_eq!(config.token_url(), "http://127.0.0.1:8787/api/v1/token");
+        assert_eq!(
+            config.leaderboard_url(),
+            "http://127.0.0.1:8787/api/v1/leaderboard"
+        );
+    }
+
+    #[test]
+    fn test_file_endpoint_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let overrides = with_file(
+            &dir,
+            r#"{
+                "base_url": "https://sudoku.internal/",
+                "endpoints": { "results": "https://ingest.internal/results" },
+                "timeout_secs": 3
+            }"#,
+        );
+        let config = resolve_with(&overrides, &[]).unwrap();
+        assert_eq!(config.profile, "custom");
+        assert_eq!(config.results_url(), "https://ingest.internal/results");
+        assert_eq!(config.token_url(), "https://sudoku.internal/api/v1/token");
+        assert_eq!(config.timeout_secs, 3);
+    }
+
+    #[test]
+    fn test_precedence_cli_over_env_over_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut overrides = with_file(&dir, r#"{"base_url":"https://file.example"}"#);
+
+        let config =
+            resolve_with(&overrides, &[("SUDOKU_API_URL", "https://env.example")]).unwrap();
+        assert_eq!(config.base_url, "https://env.example");
+
+        overrides.server = Some("local".to_string());
+        let vars = [
+            ("SUDOKU_API_URL", "https://env.example"),
+            ("SUDOKU_API_KEY", "k"),
+        ];
+        let config = resolve_with(&overrides, &vars).unwrap();
+        assert_eq!(config.base_url, LOCAL_URL);
+        assert_eq!(config.api_key.as_deref(), Some("k"));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(resolve_with(&with_file(&dir, r#"{"bogus":1}"#), &[]).is_err());
+
+        let overrides = ServerOverrides {
+            server: Some("staging".to_string()),
+            config_path: Some(dir.path().join("missing.json")),
+            ..Default::default()
+        };
+        assert!(resolve_with(&overrides, &[]).is_err());
+
+        let overrides = ServerOverrides {
+            ca_bundle: Some(dir.path().join("missing.pem")),
+            ..with_file(&dir, "{}")
+        };
+        assert!(resolve_with(&overrides, &[])
+            .unwrap()
+            .build_agent()
+            .is_err());
+    }
+}
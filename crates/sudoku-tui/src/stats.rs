@@ -1,13 +1,19 @@
 #![allow(dead_code)]
 
 use crate::leaderboard::{self, LeaderboardManager};
+use crate::storage;
+use crate::sync;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 use sudoku_core::Difficulty;
+use sudoku_shared::achievements::{self, AchievementState, Reward, Unlock};
+use sudoku_shared::dates::unix_now;
+use sudoku_shared::records;
+use sudoku_verify::antibot;
+use sudoku_verify::plausibility::{self, GameContext, PlausibilityConfig, PlausibilityReport};
+use sudoku_verify::MoveLogEntry;
 
 /// Result of a completed game
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -17,8 +23,46 @@ pub enum GameResult {
     Abandoned,
 }
 
+/// How a game was played with others on the local network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Multiplayer {
+    /// Head-to-head on the same puzzle; `place` is None if we didn't finish
+    Race {
+        players: usize,
+        place: Option<usize>,
+    },
+    /// Everyone on one board, sharing the mistakes and the clock
+    Coop { players: usize },
+}
+
+impl Multiplayer {
+    /// Whether the game was solved together rather than against others
+    pub fn is_team(&self) -> bool {
+        matches!(self, Self::Coop { .. })
+    }
+}
+
+impl std::fmt::Display for Multiplayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Race {
+                players,
+                place: Some(place),
+            } => write!(
+                f,
+                "race, {} of {}",
+                crate::lan::race::ordinal(*place),
+                players
+            ),
+            Self::Race { players, .. } => write!(f, "race of {}", players),
+            Self::Coop { players } => write!(f, "co-op, team of {}", players),
+        }
+    }
+}
+
 /// Record of a single played game
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameRecord {
     /// Unique game ID
     pub id: u64,
@@ -54,17 +98,66 @@ pub struct GameRecord {
     /// Short code (if generated via PuzzleId)
     #[serde(default)]
     pub short_code: Option<String>,
+    /// Behavioral plausibility confidence (0.0 = bot-like, 1.0 = human-like),
+    /// when a move log was available to score
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Set for games played with others
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplayer: Option<Multiplayer>,
 }
 
 impl GameRecord {
+    /// This game in the portable format shared with the other frontends
+    pub fn to_portable(&self) -> records::GameRecord {
+        records::GameRecord {
+            timestamp: self.timestamp,
+            difficulty: self.difficulty,
+            result: match self.result {
+                GameResult::Win => records::Outcome::Win,
+                GameResult::Loss => records::Outcome::Loss,
+                GameResult::Abandoned => records::Outcome::Abandoned,
+            },
+            time_secs: self.time_secs,
+            hints_used: self.hints_used,
+            mistakes: self.mistakes,
+            puzzle_hash: self.puzzle_hash.clone(),
+            puzzle: self.puzzle.clone(),
+            short_code: self.short_code.clone(),
+            verified: self.verified,
+            source: Some("tui".to_string()),
+        }
+    }
+
     /// Calculate a score for this game (lower is better for time-based)
     /// Score formula: base_time + (hints * 30) + (mistakes * 15)
-    /// Only verified wins count
+    /// Only verified wins count, and only ones solved alone
     pub fn score(&self) -> Option<u64> {
         if self.result != GameResult::Win || !self.verified {
             return None;
         }
-        Some(self.time_secs + (self.hints_used as u64 * 30) + (self.mistakes as u64 * 15))
+        if self.multiplayer.is_some_and(|m| m.is_team()) {
+            return None;
+        }
+        Some(antibot::score(
+            self.time_secs,
+            self.hints_used,
+            self.mistakes,
+        ))
+    }
+
+    /// Timing summary used by the anti-bot checks
+    pub fn summary(&self) -> antibot::GameSummary {
+        antibot::GameSummary {
+            difficulty: self.difficulty,
+            time_secs: self.time_secs,
+            hints_used: self.hints_used,
+            mistakes: self.mistakes,
+            moves_count: self.moves_count,
+            avg_move_time_ms: self.avg_move_time_ms,
+            min_move_time_ms: self.min_move_time_ms,
+            move_time_std_dev: self.move_time_std_dev,
+        }
     }
 }
 
@@ -113,33 +206,18 @@ pub struct PlayerStats {
     pub by_difficulty: HashMap<String, DifficultyStats>,
     /// Whether secret difficulties are unlocked
     pub secret_unlocked: bool,
-    /// Number of expert wins (for unlock tracking)
+    /// Number of wins on Expert or above
     pub expert_wins: usize,
     /// Total play time in seconds
     pub total_play_time_secs: u64,
     /// Difficulties that have been won at least once
     pub difficulties_won: Vec<String>,
-    /// Whether a perfect game (Expert, 0 hints, 0 mistakes) was achieved
-    pub perfect_game_achieved: bool,
-    /// Whether speed demon (Hard < 5 min) was achieved
-    pub speed_demon_achieved: bool,
     /// Whether no-notes master (Hard+ without notes) was achieved
     pub no_notes_master_achieved: bool,
     /// Unlock reason (for display)
     pub unlock_reason: Option<String>,
 }
 
-/// Number of expert wins required to unlock secret difficulties
-pub const EXPERT_WINS_TO_UNLOCK: usize = 10;
-/// Win streak required to unlock
-pub const WIN_STREAK_TO_UNLOCK: usize = 5;
-/// Total wins required to unlock (Century)
-pub const CENTURY_WINS_TO_UNLOCK: usize = 100;
-/// Total play time required to unlock in seconds (10 hours)
-pub const MARATHON_TIME_TO_UNLOCK: u64 = 10 * 60 * 60;
-/// Speed demon time threshold in seconds (5 minutes)
-pub const SPEED_DEMON_TIME: u64 = 5 * 60;
-
 impl PlayerStats {
     pub fn new(name: &str) -> Self {
         Self {
@@ -177,94 +255,44 @@ pub struct LeaderboardEntry {
     pub puzzle_hash: String,
 }
 
-/// Anti-bot verification thresholds
+/// Anti-bot verification (thresholds live in `sudoku_verify::antibot`
+/// so the server applies the same checks)
 pub struct AntiBot;
 
 impl AntiBot {
-    /// Minimum realistic time to solve by difficulty (seconds)
-    const MIN_TIME_BEGINNER: u64 = 15;
-    const MIN_TIME_EASY: u64 = 30;
-    const MIN_TIME_MEDIUM: u64 = 60;
-    const MIN_TIME_INTERMEDIATE: u64 = 90;
-    const MIN_TIME_HARD: u64 = 120;
-    const MIN_TIME_EXPERT: u64 = 180;
-    const MIN_TIME_MASTER: u64 = 300;
-    const MIN_TIME_EXTREME: u64 = 600;
-
-    /// Minimum average move time (milliseconds) - humans can't click faster than ~100ms consistently
-    const MIN_AVG_MOVE_TIME_MS: u64 = 150;
-
-    /// Minimum move time (milliseconds) - single fastest move
-    const MIN_SINGLE_MOVE_TIME_MS: u64 = 50;
-
-    /// Minimum standard deviation (bots have very consistent timing)
-    const MIN_STD_DEV: f32 = 100.0;
-
     /// Verify a game record for bot-like behavior
     pub fn verify(record: &GameRecord) -> VerificationResult {
-        let mut issues = Vec::new();
-
-        // Check minimum time based on difficulty
-        let min_time = match record.difficulty {
-            Difficulty::Beginner => Self::MIN_TIME_BEGINNER,
-            Difficulty::Easy => Self::MIN_TIME_EASY,
-            Difficulty::Medium => Self::MIN_TIME_MEDIUM,
-            Difficulty::Intermediate => Self::MIN_TIME_INTERMEDIATE,
-            Difficulty::Hard => Self::MIN_TIME_HARD,
-            Difficulty::Expert => Self::MIN_TIME_EXPERT,
-            Difficulty::Master => Self::MIN_TIME_MASTER,
-            Difficulty::Extreme => Self::MIN_TIME_EXTREME,
-        };
-
-        if record.time_secs < min_time {
-            issues.push(format!(
-                "Time too fast: {}s (min {}s for {:?})",
-                record.time_secs, min_time, record.difficulty
-            ));
-        }
-
-        // Check average move time
-        if record.avg_move_time_ms < Self::MIN_AVG_MOVE_TIME_MS && record.moves_count > 10 {
-            issues.push(format!(
-                "Avg move time too fast: {}ms (min {}ms)",
-                record.avg_move_time_ms,
-                Self::MIN_AVG_MOVE_TIME_MS
-            ));
-        }
-
-        // Check minimum single move time
-        if record.min_move_time_ms < Self::MIN_SINGLE_MOVE_TIME_MS && record.moves_count > 5 {
-            issues.push(format!(
-                "Fastest move too quick: {}ms (min {}ms)",
-                record.min_move_time_ms,
-                Self::MIN_SINGLE_MOVE_TIME_MS
-            ));
-        }
-
-        // Check timing variance (bots are too consistent)
-        if record.move_time_std_dev < Self::MIN_STD_DEV && record.moves_count > 15 {
-            issues.push(format!(
-                "Move timing too consistent: std_dev={:.1}ms (min {:.1}ms)",
-                record.move_time_std_dev,
-                Self::MIN_STD_DEV
-            ));
-        }
-
-        // Check for suspicious hint/mistake patterns
-        // If someone wins expert with 0 hints, 0 mistakes in minimum time, suspicious
-        if record.difficulty == Difficulty::Expert
-            && record.hints_used == 0
-            && record.mistakes == 0
-            && record.time_secs < min_time * 2
-        {
-            issues.push("Suspiciously perfect expert game".to_string());
-        }
+        let issues = antibot::check(&record.summary());
 
         VerificationResult {
             verified: issues.is_empty(),
             issues,
         }
     }
+
+    /// Score the full move sequence of a game for human plausibility
+    pub fn score(
+        record: &GameRecord,
+        move_log: &[MoveLogEntry],
+        notes_used: bool,
+    ) -> PlausibilityReport {
+        Self::score_with(record, move_log, notes_used, &PlausibilityConfig::default())
+    }
+
+    /// Score with custom thresholds
+    pub fn score_with(
+        record: &GameRecord,
+        move_log: &[MoveLogEntry],
+        notes_used: bool,
+        config: &PlausibilityConfig,
+    ) -> PlausibilityReport {
+        let ctx = GameContext {
+            puzzle: &record.puzzle,
+            difficulty: record.difficulty,
+            notes_used,
+        };
+        plausibility::score_with(&ctx, move_log, config)
+    }
 }
 
 /// Result of anti-bot verification
@@ -274,20 +302,44 @@ pub struct VerificationResult {
     pub issues: Vec<String>,
 }
 
+/// Games kept in memory; older ones stay in the history log on disk
+const RECENT_GAMES: usize = 1000;
+
 /// The main statistics manager
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatsManager {
+    /// Layout version of the stats file (see `storage`)
+    #[serde(default)]
+    version: u32,
     /// Player stats
     pub player: PlayerStats,
-    /// All game records (most recent first)
+    /// The most recent game records (most recent first); the full history
+    /// is in the history log
+    #[serde(skip)]
     pub history: Vec<GameRecord>,
     /// Local leaderboard entries (best scores, limited to top 100)
     pub leaderboard: Vec<LeaderboardEntry>,
     /// Next game ID
     next_id: u64,
+    /// Progress toward achievements (empty in files from before they existed)
+    #[serde(default)]
+    achievements: AchievementState,
+    /// Achievement tiers reached since the UI last looked, oldest first
+    #[serde(skip)]
+    pub new_unlocks: Vec<Unlock>,
     /// Remote leaderboard manager (not serialized)
     #[serde(skip)]
     remote_leaderboard: Option<Arc<LeaderboardManager>>,
+    /// This device's log in the sync folder, when sync is enabled
+    #[serde(skip)]
+    sync: Option<sync::EventLog>,
+    /// Problem found while loading or saving, for the UI to report
+    #[serde(skip)]
+    pub load_warning: Option<String>,
+    /// The stats file is from a newer version: never overwrite it, and leave
+    /// the history log's existing lines alone (new games are still appended)
+    #[serde(skip)]
+    read_only: bool,
 }
 
 impl Default for StatsManager {
@@ -299,32 +351,293 @@ impl Default for StatsManager {
 impl StatsManager {
     pub fn new(player_name: &str) -> Self {
         Self {
+            version: storage::VERSION,
             player: PlayerStats::new(player_name),
             history: Vec::new(),
             leaderboard: Vec::new(),
             next_id: 1,
+            achievements: AchievementState::default(),
+            new_unlocks: Vec::new(),
             remote_leaderboard: Some(Arc::new(LeaderboardManager::auto())),
+            sync: None,
+            load_warning: None,
+            read_only: false,
         }
     }
 
     /// Get the save file path
     fn save_path() -> PathBuf {
-        crate::persistence::app_data_dir().join("sudoku_stats.json")
+        crate::persistence::profile_dir().join("sudoku_stats.json")
+    }
+
+    /// Get the history log
+    fn history_log() -> storage::HistoryLog {
+        storage::HistoryLog::new(crate::persistence::profile_dir().join("sudoku_history.jsonl"))
     }
 
-    /// Load stats from file
+    /// Load stats from file, upgrading older layouts. If the file can't be
+    /// used it is backed up, the totals are rebuilt from the history log and
+    /// `load_warning` says what happened. A damaged file is replaced; one from
+    /// a newer version is left alone and the stats are read-only.
     pub fn load() -> Self {
-        let mut stats: Self = match fs::read_to_string(Self::save_path()) {
-            Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
-            Err(_) => Self::default(),
+        Self::load_from(&Self::save_path(), &Self::history_log())
+    }
+
+    fn load_from(path: &Path, log: &storage::HistoryLog) -> Self {
+        let mut warnings = Vec::new();
+        let mut stats = match storage::load::<Self>(path, log) {
+            Ok(Some((mut stats, migrated))) => {
+                stats.version = storage::VERSION;
+                if migrated {
+                    stats.save();
+                }
+                stats
+            }
+            Ok(None) => Self::default(),
+            Err(e) => {
+                warnings.push(e.to_string());
+                let mut stats = Self::rebuilt_from(log);
+                match e {
+                    storage::LoadError::Corrupt { .. } => stats.save(),
+                    storage::LoadError::TooNew { .. } => {
+                        stats.read_only = true;
+                        warnings.push("stats won't be saved until you update".to_string());
+                    }
+                    storage::LoadError::Io(_) => {}
+                }
+                stats
+            }
         };
+        match log.recent(RECENT_GAMES) {
+            Ok((recent, 0)) => stats.history = recent,
+            Ok((recent, damaged)) => {
+                stats.history = recent;
+                warnings.push(format!("Skipped {} damaged games in the history", damaged));
+            }
+            Err(e) => warnings.push(format!("Cannot read game history: {}", e)),
+        }
+        if stats.achievements.is_empty() && stats.player.total_games > 0 {
+            stats.backfill_achievements(log);
+        }
+
         // Initialize remote leaderboard after deserialization
         stats.remote_leaderboard = Some(Arc::new(LeaderboardManager::auto()));
+        if let Some(config) = sync::SyncConfig::load() {
+            if let Err(e) = stats.attach_sync(&config.dir, &sync::device_id(), log) {
+                warnings.push(format!(
+                    "Stats sync unavailable ({}): {}",
+                    config.dir.display(),
+                    e
+                ));
+            }
+        }
+        if !warnings.is_empty() {
+            stats.load_warning = Some(warnings.join("; "));
+        }
+        stats
+    }
+
+    /// Work out achievement progress from the games played before
+    /// achievements existed
+    fn backfill_achievements(&mut self, log: &storage::HistoryLog) {
+        let mut state = AchievementState::from_games(log.iter().map(|r| r.to_portable()));
+        if self.player.no_notes_master_achieved {
+            state.trigger(achievements::events::NO_NOTES_WIN, unix_now());
+        }
+        self.achievements = state;
+        self.apply_rewards(None);
+        self.save();
+    }
+
+    /// Totals recomputed from every game in the history log
+    fn rebuilt_from(log: &storage::HistoryLog) -> Self {
+        let mut stats = Self::default();
+        for record in log.iter() {
+            stats.next_id = stats.next_id.max(record.id + 1);
+            stats.count_game(&record);
+        }
         stats
     }
 
-    /// Save stats to file
+    /// Start logging to the sync folder `dir` and merge in every device's log.
+    /// Local games and achievements no device has logged yet (played while
+    /// sync was off, imported, or whose log write failed) are logged first so
+    /// the merge keeps them and other devices get them too.
+    /// `device` is this device's id and `history` this profile's games.
+    fn attach_sync(
+        &mut self,
+        dir: &Path,
+        device: &str,
+        history: &storage::HistoryLog,
+    ) -> std::io::Result<()> {
+        let mut log = sync::EventLog::open(dir, device)?;
+        let first = log.is_empty();
+        let mut events = sync::read_all(dir);
+        let mut games = HashSet::new();
+        let mut logged_events = HashSet::new();
+        for event in &events {
+            match &event.kind {
+                sync::EventKind::Game { record } => {
+                    games.insert((record.puzzle_hash.clone(), record.timestamp));
+                }
+                sync::EventKind::Achievement { event } => {
+                    logged_events.insert(event.clone());
+                }
+                sync::EventKind::Unlock { .. } => {}
+            }
+        }
+
+        let mut added = false;
+        for record in history.iter() {
+            if !games.contains(&(record.puzzle_hash.clone(), record.timestamp)) {
+                log.append(record.timestamp, sync::EventKind::Game { record })?;
+                added = true;
+            }
+        }
+        if first {
+            if let Some(reason) = self.player.unlock_reason.clone() {
+                log.append(unix_now(), sync::EventKind::Unlock { reason })?;
+                added = true;
+            }
+        }
+        for event in self.achievements.events() {
+            if !logged_events.contains(event) {
+                let event = event.to_string();
+                log.append(unix_now(), sync::EventKind::Achievement { event })?;
+                added = true;
+            }
+        }
+        if added {
+            events = sync::read_all(dir);
+        }
+
+        self.sync = Some(log);
+        self.replay(events, history);
+        self.save();
+        Ok(())
+    }
+
+    /// Rebuild history and aggregates from sync events (already in replay
+    /// order). Games are counted once even if several devices logged them.
+    /// `history` is only rewritten if the merge changed it.
+    fn replay(&mut self, events: Vec<sync::Event>, history: &storage::HistoryLog) {
+        let mut rebuilt = Self {
+            version: storage::VERSION,
+            player: PlayerStats::new(&self.player.player_name),
+            history: Vec::new(),
+            leaderboard: Vec::new(),
+            next_id: 1,
+            achievements: AchievementState::default(),
+            new_unlocks: Vec::new(),
+            remote_leaderboard: None,
+            sync: None,
+            load_warning: None,
+            read_only: false,
+        };
+        let mut seen = HashSet::new();
+        for event in events {
+            match event.kind {
+                sync::EventKind::Game { record } => {
+                    if seen.insert((record.puzzle_hash.clone(), record.timestamp)) {
+                        rebuilt.apply_game(record);
+                    }
+                }
+                sync::EventKind::Unlock { reason } => {
+                    if !rebuilt.player.secret_unlocked {
+                        rebuilt.player.secret_unlocked = true;
+                        rebuilt.player.unlock_reason = Some(reason);
+                    }
+                }
+                sync::EventKind::Achievement { event: name } => {
+                    rebuilt.achievements.trigger(&name, event.timestamp);
+                    rebuilt.apply_rewards(None);
+                }
+            }
+        }
+        if !history.iter().eq(rebuilt.history.iter().cloned()) {
+            self.rewrite_history(history, &rebuilt.history);
+        }
+        rebuilt.history.reverse();
+        rebuilt.history.truncate(RECENT_GAMES);
+
+        // Not recorded per game, so keep what this device knows
+        rebuilt.player.no_notes_master_achieved |= self.player.no_notes_master_achieved;
+
+        self.player = rebuilt.player;
+        self.history = rebuilt.history;
+        self.leaderboard = rebuilt.leaderboard;
+        self.next_id = rebuilt.next_id;
+        self.achievements = rebuilt.achievements;
+    }
+
+    /// Count a logged game as if it had just been played. `history` is built
+    /// oldest first here and reversed by the caller.
+    fn apply_game(&mut self, mut record: GameRecord) {
+        record.id = self.next_id;
+        self.next_id += 1;
+        self.count_game(&record);
+        self.history.push(record);
+    }
+
+    /// Add a past game to the totals, streaks, achievements and local
+    /// leaderboard, without announcing anything
+    fn count_game(&mut self, record: &GameRecord) {
+        self.tally(record);
+        self.update_streak(record.result);
+        self.achievements.record_game(&record.to_portable());
+        self.apply_rewards(None);
+        if let Some(score) = record.score() {
+            self.add_to_leaderboard(LeaderboardEntry {
+                player_name: self.player.player_name.clone(),
+                score,
+                time_secs: record.time_secs,
+                difficulty: record.difficulty,
+                hints_used: record.hints_used,
+                mistakes: record.mistakes,
+                timestamp: record.timestamp,
+                puzzle_hash: record.puzzle_hash.clone(),
+            });
+        }
+    }
+
+    /// Append to this device's sync log, if sync is enabled
+    fn log_event(&mut self, timestamp: u64, kind: sync::EventKind) {
+        let Some(ref mut log) = self.sync else {
+            return;
+        };
+        if let Err(e) = log.append(timestamp, kind) {
+            self.warn(format!("Failed to write sync log: {}", e));
+        }
+    }
+
+    /// Queue a problem for the UI; stderr would garble the TUI
+    fn warn(&mut self, warning: String) {
+        self.load_warning = Some(match self.load_warning.take() {
+            Some(earlier) => format!("{}; {}", earlier, warning),
+            None => warning,
+        });
+    }
+
+    /// Whether stats are synced through a shared folder
+    pub fn is_synced(&self) -> bool {
+        self.sync.is_some()
+    }
+
+    /// Replace the history log with `history` (oldest first)
+    fn rewrite_history(&mut self, log: &storage::HistoryLog, history: &[GameRecord]) {
+        if self.read_only {
+            return;
+        }
+        if let Err(e) = log.rewrite(history) {
+            self.warn(format!("Failed to save game history: {}", e));
+        }
+    }
+
+    /// Save the totals (the history log is written as games are added)
     pub fn save(&self) {
+        if self.read_only {
+            return;
+        }
         if let Ok(json) = serde_json::to_string_pretty(self) {
             let _ = crate::persistence::atomic_write(&Self::save_path(), json.as_bytes());
         }
@@ -341,9 +654,11 @@ impl StatsManager {
         hints_used: usize,
         mistakes: usize,
         move_times_ms: &[u64],
+        move_log: &[MoveLogEntry],
         notes_used: bool,
         seed: Option<u64>,
         short_code: Option<String>,
+        multiplayer: Option<Multiplayer>,
     ) -> &GameRecord {
         let puzzle_hash = sudoku_core::canonical_puzzle_hash_str(puzzle);
 
@@ -370,10 +685,7 @@ impl StatsManager {
             (0, 0, 0.0)
         };
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        let timestamp = unix_now();
 
         let mut record = GameRecord {
             id: self.next_id,
@@ -392,59 +704,81 @@ impl StatsManager {
             verified: false,
             seed,
             short_code,
+            confidence: None,
+            multiplayer,
         };
 
-        // Run anti-bot verification
-        let verification = AntiBot::verify(&record);
-        record.verified = verification.verified;
+        // Run anti-bot verification. With a move log, the behavioral score
+        // replaces the fixed timing thresholds; without one, fall back to them.
+        if move_log.is_empty() {
+            record.verified = AntiBot::verify(&record).verified;
+        } else {
+            let report = AntiBot::score(&record, move_log, notes_used);
+            record.confidence = Some(report.confidence);
+            record.verified = report.plausible;
+        }
 
         self.next_id += 1;
+        self.log_event(
+            timestamp,
+            sync::EventKind::Game {
+                record: record.clone(),
+            },
+        );
+
+        self.tally(&record);
+        self.update_streak(result);
+        let mut unlocks = self.achievements.record_game(&record.to_portable());
+
+        // Check for No Notes Master (Hard+ without using notes); the record
+        // doesn't say whether notes were used, so it's reported as an event
+        if result == GameResult::Win && difficulty >= Difficulty::Hard && !notes_used {
+            self.player.no_notes_master_achieved = true;
+            unlocks.extend(self.trigger_event(achievements::events::NO_NOTES_WIN, timestamp));
+        }
+        self.apply_rewards(Some(unlocks));
 
-        // Update player stats
-        self.player.total_games += 1;
-        self.player.total_play_time_secs += time_secs;
+        if let Err(e) = Self::history_log().append(&record) {
+            self.warn(format!("Failed to save game history: {}", e));
+        }
 
+        // Add to history (most recent first)
+        self.history.insert(0, record);
+        self.history.truncate(RECENT_GAMES);
+
+        // Update leaderboard if it's a verified win
+        if let Some(score) = self.history[0].score() {
+            let entry = LeaderboardEntry {
+                player_name: self.player.player_name.clone(),
+                score,
+                time_secs,
+                difficulty,
+                hints_used,
+                mistakes,
+                timestamp,
+                puzzle_hash: self.history[0].puzzle_hash.clone(),
+            };
+            self.add_to_leaderboard(entry);
+        }
+
+        // Auto-save
+        self.save();
+
+        &self.history[0]
+    }
+
+    /// Extend or reset the current streak after a game
+    fn update_streak(&mut self, result: GameResult) {
         match result {
             GameResult::Win => {
-                self.player.total_wins += 1;
                 if self.player.current_streak >= 0 {
                     self.player.current_streak += 1;
                 } else {
                     self.player.current_streak = 1;
                 }
                 self.player.best_streak = self.player.best_streak.max(self.player.current_streak);
-
-                // Track difficulty won
-                let diff_key = format!("{:?}", difficulty);
-                if !self.player.difficulties_won.contains(&diff_key) {
-                    self.player.difficulties_won.push(diff_key);
-                }
-
-                // Track expert wins for unlock
-                if difficulty >= Difficulty::Expert {
-                    self.player.expert_wins += 1;
-                }
-
-                // Check for Perfect Game (Expert+, 0 hints, 0 mistakes)
-                if difficulty >= Difficulty::Expert && hints_used == 0 && mistakes == 0 {
-                    self.player.perfect_game_achieved = true;
-                }
-
-                // Check for Speed Demon (Hard+ under 5 minutes)
-                if difficulty >= Difficulty::Hard && time_secs < SPEED_DEMON_TIME {
-                    self.player.speed_demon_achieved = true;
-                }
-
-                // Check for No Notes Master (Hard+ without using notes)
-                if difficulty >= Difficulty::Hard && !notes_used {
-                    self.player.no_notes_master_achieved = true;
-                }
-
-                // Check all unlock conditions
-                self.check_unlocks();
             }
             GameResult::Loss => {
-                self.player.total_losses += 1;
                 if self.player.current_streak <= 0 {
                     self.player.current_streak -= 1;
                 } else {
@@ -452,19 +786,45 @@ impl StatsManager {
                 }
             }
             GameResult::Abandoned => {
-                self.player.total_abandoned += 1;
                 self.player.current_streak = 0;
             }
         }
+    }
+
+    /// Add a finished game to the totals, per-difficulty stats and
+    /// achievement flags (everything but streaks)
+    fn tally(&mut self, record: &GameRecord) {
+        let difficulty = record.difficulty;
+        let time_secs = record.time_secs;
+        self.player.total_games += 1;
+        self.player.total_play_time_secs += time_secs;
+
+        match record.result {
+            GameResult::Win => {
+                self.player.total_wins += 1;
+
+                // Track difficulty won
+                let diff_key = format!("{:?}", difficulty);
+                if !self.player.difficulties_won.contains(&diff_key) {
+                    self.player.difficulties_won.push(diff_key);
+                }
+
+                if difficulty >= Difficulty::Expert {
+                    self.player.expert_wins += 1;
+                }
+            }
+            GameResult::Loss => self.player.total_losses += 1,
+            GameResult::Abandoned => self.player.total_abandoned += 1,
+        }
 
         // Update difficulty stats
         let diff_key = format!("{:?}", difficulty);
         let diff_stats = self.player.by_difficulty.entry(diff_key).or_default();
         diff_stats.total_games += 1;
-        diff_stats.total_hints += hints_used;
-        diff_stats.total_mistakes += mistakes;
+        diff_stats.total_hints += record.hints_used;
+        diff_stats.total_mistakes += record.mistakes;
 
-        match result {
+        match record.result {
             GameResult::Win => {
                 diff_stats.wins += 1;
                 diff_stats.total_time_secs += time_secs;
@@ -482,34 +842,116 @@ impl StatsManager {
             GameResult::Loss => diff_stats.losses += 1,
             GameResult::Abandoned => diff_stats.abandoned += 1,
         }
+    }
 
-        // Add to history (most recent first)
-        self.history.insert(0, record);
+    /// Full history in the portable export format, oldest first
+    pub fn export_records(&self) -> Vec<records::GameRecord> {
+        Self::history_log()
+            .iter()
+            .map(|r| r.to_portable())
+            .collect()
+    }
 
-        // Limit history to last 1000 games
-        if self.history.len() > 1000 {
-            self.history.truncate(1000);
+    /// Merge records exported from any platform into the history, skipping
+    /// games already present, and update the aggregates to match
+    pub fn import_records(&mut self, incoming: Vec<records::GameRecord>) -> records::MergeSummary {
+        let (added, summary) = records::merge(&self.export_records(), incoming);
+        if added.is_empty() {
+            return summary;
         }
-
-        // Update leaderboard if it's a verified win
-        if let Some(score) = self.history[0].score() {
-            let entry = LeaderboardEntry {
-                player_name: self.player.player_name.clone(),
-                score,
-                time_secs,
-                difficulty,
-                hints_used,
-                mistakes,
-                timestamp,
-                puzzle_hash: self.history[0].puzzle_hash.clone(),
+        let mut history: Vec<GameRecord> = Self::history_log().iter().collect();
+
+        for r in added {
+            let record = GameRecord {
+                id: self.next_id,
+                puzzle_hash: r.puzzle_hash,
+                puzzle: r.puzzle,
+                difficulty: r.difficulty,
+                result: match r.result {
+                    records::Outcome::Win => GameResult::Win,
+                    records::Outcome::Loss => GameResult::Loss,
+                    records::Outcome::Abandoned => GameResult::Abandoned,
+                },
+                time_secs: r.time_secs,
+                hints_used: r.hints_used,
+                mistakes: r.mistakes,
+                moves_count: 0,
+                timestamp: r.timestamp,
+                avg_move_time_ms: 0,
+                min_move_time_ms: 0,
+                move_time_std_dev: 0.0,
+                // The file's word isn't proof the game passed anti-bot checks
+                verified: false,
+                seed: None,
+                short_code: r.short_code,
+                confidence: None,
+                multiplayer: None,
             };
-            self.add_to_leaderboard(entry);
+            self.next_id += 1;
+            self.log_event(
+                record.timestamp,
+                sync::EventKind::Game {
+                    record: record.clone(),
+                },
+            );
+            self.tally(&record);
+            let unlocks = self.achievements.record_game(&record.to_portable());
+            self.apply_rewards(Some(unlocks));
+            if let Some(score) = record.score() {
+                self.add_to_leaderboard(LeaderboardEntry {
+                    player_name: self.player.player_name.clone(),
+                    score,
+                    time_secs: record.time_secs,
+                    difficulty: record.difficulty,
+                    hints_used: record.hints_used,
+                    mistakes: record.mistakes,
+                    timestamp: record.timestamp,
+                    puzzle_hash: record.puzzle_hash.clone(),
+                });
+            }
+            history.push(record);
         }
 
-        // Auto-save
+        // Imported games may be older than local ones; keep the log in play order
+        history.sort_by_key(|r| r.timestamp);
+        self.rewrite_history(&Self::history_log(), &history);
+        history.reverse();
+        history.truncate(RECENT_GAMES);
+        self.history = history;
+        self.recompute_streaks();
         self.save();
+        summary
+    }
 
-        &self.history[0]
+    /// Rebuild the current streak from history and raise the best streak if
+    /// the history contains a longer run
+    fn recompute_streaks(&mut self) {
+        let streak_of = |r: &GameRecord| match r.result {
+            GameResult::Win => 1,
+            GameResult::Loss => -1,
+            GameResult::Abandoned => 0,
+        };
+
+        self.player.current_streak = match self.history.first().map(streak_of) {
+            Some(sign) if sign != 0 => {
+                sign * self
+                    .history
+                    .iter()
+                    .take_while(|r| streak_of(r) == sign)
+                    .count() as i32
+            }
+            _ => 0,
+        };
+
+        let mut run = 0;
+        for r in self.history.iter().rev() {
+            run = if r.result == GameResult::Win {
+                run + 1
+            } else {
+                0
+            };
+            self.player.best_streak = self.player.best_streak.max(run);
+        }
     }
 
     /// Add entry to leaderboard (maintains sorted order, top 100)
@@ -554,196 +996,98 @@ impl StatsManager {
         self.save();
     }
 
-    /// Unlock secret difficulties (via Konami code)
-    pub fn unlock_secrets(&mut self) {
-        self.player.secret_unlocked = true;
-        self.save();
-    }
-
     /// Check if secret difficulties are unlocked
     pub fn secrets_unlocked(&self) -> bool {
         self.player.secret_unlocked
     }
 
-    /// Get expert wins progress toward unlock
-    pub fn expert_wins_progress(&self) -> (usize, usize) {
-        (self.player.expert_wins, EXPERT_WINS_TO_UNLOCK)
-    }
-
-    /// Check all unlock conditions and unlock secrets if any are met
-    fn check_unlocks(&mut self) {
-        if self.player.secret_unlocked {
-            return; // Already unlocked
-        }
-
-        // Method 1: Perfect Game (Expert+, 0 hints, 0 mistakes)
-        if self.player.perfect_game_achieved {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason =
-                Some("Perfect Game: Won Expert+ with no hints or mistakes!".to_string());
-            return;
-        }
-
-        // Method 2: Speed Demon (Hard+ under 5 minutes)
-        if self.player.speed_demon_achieved {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason =
-                Some("Speed Demon: Won Hard+ in under 5 minutes!".to_string());
-            return;
-        }
-
-        // Method 3: Win Streak (5 consecutive wins)
-        if self.player.best_streak >= WIN_STREAK_TO_UNLOCK as i32 {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some(format!(
-                "Win Streak: {} consecutive wins!",
-                WIN_STREAK_TO_UNLOCK
-            ));
-            return;
-        }
-
-        // Method 4: Completionist (win on all 4 standard difficulties)
-        let standard_diffs = [
-            "Beginner",
-            "Easy",
-            "Medium",
-            "Intermediate",
-            "Hard",
-            "Expert",
-        ];
-        let all_standard_won = standard_diffs
+    /// Progress toward the Expert win count that unlocks secret difficulties
+    pub fn expert_wins_progress(&self) -> (u64, u64) {
+        let progress = self.achievement_progress();
+        let expert = progress
             .iter()
-            .all(|d| self.player.difficulties_won.contains(&d.to_string()));
-        if all_standard_won {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason =
-                Some("Completionist: Won on all standard difficulties!".to_string());
-            return;
-        }
-
-        // Method 5: Marathon (10+ hours total play time)
-        if self.player.total_play_time_secs >= MARATHON_TIME_TO_UNLOCK {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some("Marathon: 10+ hours of total play time!".to_string());
-            return;
-        }
-
-        // Method 6: Century (100 total wins)
-        if self.player.total_wins >= CENTURY_WINS_TO_UNLOCK {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason =
-                Some(format!("Century: {} total wins!", CENTURY_WINS_TO_UNLOCK));
-            return;
-        }
-
-        // Method 7: No Notes Master (Hard+ without using notes)
-        if self.player.no_notes_master_achieved {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason =
-                Some("No Notes Master: Won Hard+ without using notes!".to_string());
-            return;
-        }
+            .find(|p| p.achievement.id == "expert_master")
+            .expect("expert_master achievement");
+        let target = match expert.achievement.reward {
+            Some((tier, _)) => expert.achievement.tiers[tier],
+            None => expert.achievement.tiers[0],
+        };
+        (expert.value, target)
+    }
 
-        // Method 8: Expert Wins (10 wins on Expert)
-        if self.player.expert_wins >= EXPERT_WINS_TO_UNLOCK {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some(format!(
-                "Expert Master: {} wins on Expert difficulty!",
-                EXPERT_WINS_TO_UNLOCK
-            ));
-            return;
-        }
+    /// Progress toward every achievement, in display order
+    pub fn achievement_progress(&self) -> Vec<achievements::Progress> {
+        self.achievements.progress()
+    }
 
-        // Method 9: Secret Date (September 9th - 9/9)
-        if Self::check_secret_date() {
+    /// Grant the rewards of reached tiers. `unlocks` were just reached in
+    /// play and are queued for the UI to announce.
+    fn apply_rewards(&mut self, unlocks: Option<Vec<Unlock>>) {
+        let unlocks = unlocks.unwrap_or_default();
+        if !self.player.secret_unlocked && self.achievements.has_reward(Reward::SecretDifficulties)
+        {
             self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some("Secret Date: Playing on 9/9!".to_string());
+            self.player.unlock_reason = Some(
+                match unlocks
+                    .iter()
+                    .find(|u| u.reward == Some(Reward::SecretDifficulties))
+                {
+                    Some(u) => format!("{}: {}", u, u.description),
+                    None => "Achievement reward".to_string(),
+                },
+            );
         }
+        self.new_unlocks.extend(unlocks);
     }
 
-    /// Check if today is September 9th (9/9)
-    fn check_secret_date() -> bool {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        if let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) {
-            // Get the current day/month (rough calculation)
-            let secs = duration.as_secs();
-            let days_since_epoch = secs / 86400;
-
-            // Calculate year, month, day from days since epoch
-            // This is a simplified calculation - good enough for 9/9 check
-            let mut days = days_since_epoch as i64;
-            let mut year = 1970i64;
-
-            loop {
-                let days_in_year = if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 {
-                    366
-                } else {
-                    365
-                };
-                if days < days_in_year {
-                    break;
-                }
-                days -= days_in_year;
-                year += 1;
-            }
-
-            // Days of month for this year
-            let is_leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
-            let months = if is_leap {
-                [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-            } else {
-                [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-            };
-
-            let mut month = 0;
-            for (i, &days_in_month) in months.iter().enumerate() {
-                if days < days_in_month as i64 {
-                    month = i + 1;
-                    break;
-                }
-                days -= days_in_month as i64;
-            }
-            let day = days + 1;
+    /// Report an achievement event and log it for sync
+    fn trigger_event(&mut self, event: &str, timestamp: u64) -> Vec<Unlock> {
+        self.log_event(
+            timestamp,
+            sync::EventKind::Achievement {
+                event: event.to_string(),
+            },
+        );
+        self.achievements.trigger(event, timestamp)
+    }
 
-            // September 9th = month 9, day 9
-            return month == 9 && day == 9;
-        }
-        false
+    /// Report an easter egg found outside of play
+    fn easter_egg(&mut self, event: &str) {
+        let unlocks = self.trigger_event(event, unix_now());
+        self.apply_rewards(Some(unlocks));
+        self.save();
     }
 
-    /// Unlock via Konami code (called from app)
+    /// Konami code on the new game menu
     pub fn unlock_via_konami(&mut self) {
-        if !self.player.secret_unlocked {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some("Konami Code: ↑↑↓↓←→←→BA".to_string());
-            self.save();
-        }
+        self.easter_egg(achievements::events::KONAMI);
     }
 
-    /// Unlock via reverse Konami (lose screen easter egg)
+    /// Reverse Konami on the lose screen
     pub fn unlock_via_reverse_konami(&mut self) {
-        if !self.player.secret_unlocked {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some("Reverse Konami: AB→←→←↓↓↑↑".to_string());
-            self.save();
-        }
+        self.easter_egg(achievements::events::REVERSE_KONAMI);
     }
 
-    /// Unlock via "42" pattern (The Answer)
+    /// "42" on an end screen (The Answer)
     pub fn unlock_via_the_answer(&mut self) {
-        if !self.player.secret_unlocked {
-            self.player.secret_unlocked = true;
-            self.player.unlock_reason = Some("The Answer: 42!".to_string());
-            self.save();
-        }
+        self.easter_egg(achievements::events::THE_ANSWER);
     }
 
     // ==================== Remote Leaderboard Methods ====================
 
-    /// Submit a score to the remote leaderboard
-    pub fn submit_to_remote(&self, record: &GameRecord) {
+    /// Shared handle to the leaderboard manager, for background queries
+    pub fn leaderboard_manager(&self) -> Option<Arc<LeaderboardManager>> {
+        self.remote_leaderboard.clone()
+    }
+
+    /// Submit a score to the remote leaderboard in a background thread.
+    /// Returns the thread handle when a submission was started.
+    pub fn submit_to_remote(&self, record: &GameRecord) -> Option<std::thread::JoinHandle<()>> {
         if let Some(ref manager) = self.remote_leaderboard {
+            // The player name is public, so only share it with consent
+            if manager.is_remote() && !crate::privacy::telemetry_enabled() {
+                return None;
+            }
             if let Some(score) = record.score() {
                 let entry = leaderboard::LeaderboardEntry {
                     player_name: self.player.player_name.clone(),
@@ -758,10 +1102,14 @@ impl StatsManager {
                     verified: record.verified,
                 };
 
-                // Submit in background (ignore errors for now)
-                let _ = manager.submit_score(entry);
+                // Failures are queued in the outbox by the manager
+                let manager = Arc::clone(manager);
+                return Some(std::thread::spawn(move || {
+                    let _ = manager.submit_score(entry);
+                }));
             }
         }
+        None
     }
 
     /// Get leaderboard from remote (with fallback to local)
@@ -843,3 +1191,146 @@ pub fn format_time(secs: u64) -> String {
         format!("{:02}:{:02}", mins, secs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(timestamp: u64, result: GameResult) -> sync::EventKind {
+        sync::EventKind::Game {
+            record: GameRecord {
+                id: 0,
+                puzzle_hash: format!("h{}", timestamp),
+                puzzle: String::new(),
+                difficulty: Difficulty::Medium,
+                result,
+                time_secs: 100,
+                hints_used: 0,
+                mistakes: 0,
+                moves_count: 0,
+                timestamp,
+                avg_move_time_ms: 0,
+                min_move_time_ms: 0,
+                move_time_std_dev: 0.0,
+                verified: true,
+                seed: None,
+                short_code: None,
+                confidence: None,
+                multiplayer: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_replay_two_devices() {
+        crate::persistence::test_sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let mut laptop = sync::EventLog::open(dir.path(), "laptop").unwrap();
+        let mut desktop = sync::EventLog::open(dir.path(), "desktop").unwrap();
+        laptop.append(10, game(10, GameResult::Win)).unwrap();
+        desktop.append(20, game(20, GameResult::Win)).unwrap();
+        // The desktop's first sync shared a game the laptop already logged
+        desktop.append(10, game(10, GameResult::Win)).unwrap();
+        desktop.append(30, game(30, GameResult::Loss)).unwrap();
+        laptop.append(40, game(40, GameResult::Win)).unwrap();
+        laptop.append(50, game(50, GameResult::Win)).unwrap();
+
+        let log = storage::HistoryLog::new(dir.path().join("sudoku_history.jsonl"));
+        let mut stats = StatsManager::new("Ann");
+        stats.replay(sync::read_all(dir.path()), &log);
+        let player = &stats.player;
+        assert_eq!(
+            (player.total_games, player.total_wins, player.total_losses),
+            (5, 4, 1)
+        );
+        assert_eq!(player.total_play_time_secs, 500);
+        // Win, win, loss, win, win in timestamp order, whichever device
+        assert_eq!((player.current_streak, player.best_streak), (2, 2));
+        let timestamps: Vec<u64> = stats.history.iter().map(|r| r.timestamp).collect();
+        assert_eq!(timestamps, [50, 40, 30, 20, 10]);
+        assert_eq!(stats.next_id, 6);
+        let medium = stats.player.get_difficulty_stats(Difficulty::Medium);
+        assert_eq!((medium.total_games, medium.wins), (5, 4));
+        assert_eq!(log.iter().count(), 5);
+    }
+
+    #[test]
+    fn test_games_played_while_detached_survive_reattaching() {
+        crate::persistence::test_sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let sync_dir = dir.path().join("sync");
+        let log = storage::HistoryLog::new(dir.path().join("sudoku_history.jsonl"));
+        let record = |timestamp| match game(timestamp, GameResult::Win) {
+            sync::EventKind::Game { record } => record,
+            _ => unreachable!(),
+        };
+
+        let mut stats = StatsManager::new("Ann");
+        log.append(&record(10)).unwrap();
+        stats.attach_sync(&sync_dir, "laptop", &log).unwrap();
+        // Sync turned off, then two games played
+        stats.sync = None;
+        log.append(&record(20)).unwrap();
+        log.append(&record(30)).unwrap();
+
+        let mut stats = StatsManager::new("Ann");
+        stats.attach_sync(&sync_dir, "laptop", &log).unwrap();
+        let timestamps: Vec<u64> = log.iter().map(|r| r.timestamp).collect();
+        assert_eq!(timestamps, [10, 20, 30]);
+        assert_eq!(stats.player.total_games, 3);
+        assert_eq!(stats.player.current_streak, 3);
+        assert_eq!(sync::devices(&sync_dir), [("laptop".to_string(), 3)]);
+
+        // Nothing new: the history file is left as it is
+        let before = std::fs::metadata(dir.path().join("sudoku_history.jsonl"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        StatsManager::new("Ann")
+            .attach_sync(&sync_dir, "laptop", &log)
+            .unwrap();
+        let after = std::fs::metadata(dir.path().join("sudoku_history.jsonl"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_newer_stats_file_is_left_alone() {
+        crate::persistence::test_sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sudoku_stats.json");
+        let newer = r#"{"version": 99, "player": {"player_name": "Ann"}}"#;
+        std::fs::write(&path, newer).unwrap();
+        let log = storage::HistoryLog::new(dir.path().join("sudoku_history.jsonl"));
+
+        let mut stats = StatsManager::load_from(&path, &log);
+        let warning = stats.load_warning.take().unwrap();
+        assert!(warning.contains("newer version (v99)"), "{}", warning);
+        assert!(warning.contains("won't be saved"), "{}", warning);
+
+        stats.save();
+        stats.rewrite_history(&log, &[]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+        assert!(stats.load_warning.is_none());
+    }
+
+    #[test]
+    fn test_sync_write_failures_become_warnings() {
+        crate::persistence::test_sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let mut stats = StatsManager::new("Ann");
+        stats.sync = Some(sync::EventLog::open(&dir.path().join("sync"), "laptop").unwrap());
+        std::fs::remove_dir_all(dir.path().join("sync")).unwrap();
+
+        stats.log_event(1, game(1, GameResult::Win));
+        let warning = stats.load_warning.take().unwrap();
+        assert!(
+            warning.starts_with("Failed to write sync log"),
+            "{}",
+            warning
+        );
+    }
+}
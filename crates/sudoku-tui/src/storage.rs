@@ -0,0 +1,333 @@
+//! On-disk layout of the stats: a small versioned `sudoku_stats.json` with
+//! the aggregates, and an append-only `sudoku_history.jsonl` with one
+//! `GameRecord` per line, oldest first.
+//!
+//! Finishing a game appends one line to the history and rewrites only the
+//! aggregates. The history is read from the end, so loading the recent games
+//! doesn't depend on how many were ever played.
+//!
+//! Older layouts are upgraded by the steps in `MIGRATIONS`, after keeping a
+//! copy of the original file. A file that can't be read is backed up and
+//! reported instead of being overwritten with empty stats.
+
+use crate::stats::GameRecord;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use sudoku_shared::dates::unix_now;
+
+/// Layout version written to `sudoku_stats.json`
+pub const VERSION: u32 = 1;
+
+/// A step from one layout version to the next. Games the step moves out of
+/// the document go to `history`, oldest first.
+type Migration = fn(doc: &mut Value, history: &mut Vec<Value>);
+
+/// `MIGRATIONS[v]` upgrades a version `v` document to `v + 1`
+const MIGRATIONS: [Migration; VERSION as usize] = [v0_split_history];
+
+/// Version 0 (no `version` field) kept every game inline in `history`, most
+/// recent first
+fn v0_split_history(doc: &mut Value, history: &mut Vec<Value>) {
+    if let Some(Value::Array(games)) = doc.as_object_mut().and_then(|o| o.remove("history")) {
+        history.extend(games.into_iter().rev());
+    }
+}
+
+/// Why the stats file couldn't be used
+#[derive(Debug)]
+pub enum LoadError {
+    /// Not valid JSON or not the expected shape
+    Corrupt { error: String, backup: PathBuf },
+    /// Written by a newer version of the app
+    TooNew { version: u64, backup: PathBuf },
+    /// Reading, backing up or migrating failed
+    Io(io::Error),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Corrupt { error, backup } => write!(
+                f,
+                "Stats file is damaged ({}); a copy was saved to {}",
+                error,
+                backup.display()
+            ),
+            Self::TooNew { version, backup } => write!(
+                f,
+                "Stats file is from a newer version (v{}); a copy was saved to {}",
+                version,
+                backup.display()
+            ),
+            Self::Io(e) => write!(f, "Cannot read stats: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Copy `path` next to itself as `<name>.<tag>.bak`
+fn backup(path: &Path, tag: &str) -> io::Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", tag));
+    let backup = path.with_file_name(name);
+    std::fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+/// Load the document at `path` in the current layout. Returns `None` if there
+/// is no file, and whether a migration ran (the caller should save). Games
+/// moved out by a migration are written to `history` before returning.
+pub fn load<T: DeserializeOwned>(
+    path: &Path,
+    history: &HistoryLog,
+) -> Result<Option<(T, bool)>, LoadError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let corrupt = |error: String| -> LoadError {
+        match backup(path, &format!("corrupt-{}", unix_now())) {
+            Ok(backup) => LoadError::Corrupt { error, backup },
+            Err(e) => LoadError::Io(e),
+        }
+    };
+
+    let mut doc: Value = serde_json::from_str(&text).map_err(|e| corrupt(e.to_string()))?;
+    let version = doc.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > VERSION as u64 {
+        let backup = backup(path, &format!("v{}", version))?;
+        return Err(LoadError::TooNew { version, backup });
+    }
+
+    let migrated = version < VERSION as u64;
+    if migrated {
+        backup(path, &format!("v{}", version))?;
+        let mut moved = Vec::new();
+        for step in &MIGRATIONS[version as usize..] {
+            step(&mut doc, &mut moved);
+        }
+        if let Some(o) = doc.as_object_mut() {
+            o.insert("version".to_string(), VERSION.into());
+        }
+        if !moved.is_empty() {
+            // Safe to redo: the old file stays in place until the caller saves
+            let records: Vec<GameRecord> = moved
+                .into_iter()
+                .filter_map(|r| serde_json::from_value(r).ok())
+                .collect();
+            history.rewrite(&records)?;
+        }
+    }
+
+    let value = serde_json::from_value(doc).map_err(|e| corrupt(e.to_string()))?;
+    Ok(Some((value, migrated)))
+}
+
+/// The append-only game history
+#[derive(Debug, Clone)]
+pub struct HistoryLog {
+    path: PathBuf,
+}
+
+/// How far back `HistoryLog::recent` reads per step
+const CHUNK: u64 = 64 * 1024;
+
+impl HistoryLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Add a finished game
+    pub fn append(&self, record: &GameRecord) -> io::Result<()> {
+        let line = serde_json::to_string(record).map_err(io::Error::other)?;
+        crate::persistence::append_line(&self.path, &line)
+    }
+
+    /// Replace the whole history (oldest first)
+    pub fn rewrite(&self, records: &[GameRecord]) -> io::Result<()> {
+        let mut data = String::new();
+        for record in records {
+            data.push_str(&serde_json::to_string(record).map_err(io::Error::other)?);
+            data.push('\n');
+        }
+        crate::persistence::atomic_write(&self.path, data.as_bytes())
+    }
+
+    /// The last `n` games, most recent first, reading only the end of the
+    /// file. Also returns how many damaged lines were skipped, not counting a
+    /// torn final line.
+    pub fn recent(&self, n: usize) -> io::Result<(Vec<GameRecord>, usize)> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(e),
+        };
+
+        // Read backwards until the buffer holds n + 1 line breaks (so the
+        // first, possibly partial, line can be dropped) or the whole file
+        let len = file.metadata()?.len();
+        let mut start = len;
+        let mut tail: Vec<u8> = Vec::new();
+        while start > 0 && tail.iter().filter(|&&b| b == b'\n').count() <= n {
+            let step = CHUNK.min(start);
+            start -= step;
+            let mut chunk = vec![0u8; step as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&tail);
+            tail = chunk;
+        }
+
+        let text = String::from_utf8_lossy(&tail);
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        if start > 0 {
+            lines.remove(0);
+        }
+        // A complete file ends with '\n'; anything after it is a torn write
+        lines.pop();
+
+        let mut records = Vec::new();
+        let mut damaged = 0;
+        for line in lines.iter().rev().filter(|l| !l.trim().is_empty()) {
+            if records.len() == n {
+                break;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(_) => damaged += 1,
+            }
+        }
+        Ok((records, damaged))
+    }
+
+    /// Every game, oldest first, read one line at a time
+    pub fn iter(&self) -> impl Iterator<Item = GameRecord> {
+        File::open(&self.path)
+            .ok()
+            .map(BufReader::new)
+            .into_iter()
+            .flat_map(|reader| reader.lines().map_while(Result::ok))
+            .filter_map(|line| serde_json::from_str(&line).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::GameResult;
+    use serde::Deserialize;
+    use std::io::Write;
+    use sudoku_core::Difficulty;
+
+    fn record(id: u64) -> GameRecord {
+        GameRecord {
+            id,
+            puzzle_hash: format!("h{}", id),
+            puzzle: String::new(),
+            difficulty: Difficulty::Easy,
+            result: GameResult::Win,
+            time_secs: 100,
+            hints_used: 0,
+            mistakes: 0,
+            moves_count: 0,
+            timestamp: id,
+            avg_move_time_ms: 0,
+            min_move_time_ms: 0,
+            move_time_std_dev: 0.0,
+            verified: true,
+            seed: None,
+            short_code: None,
+            confidence: None,
+            multiplayer: None,
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Doc {
+        version: u32,
+        next_id: u64,
+    }
+
+    #[test]
+    fn test_history_tail_and_torn_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = HistoryLog::new(dir.path().join("history.jsonl"));
+        assert!(log.recent(5).unwrap().0.is_empty());
+
+        for id in 1..=2000 {
+            log.append(&record(id)).unwrap();
+        }
+        let (recent, damaged) = log.recent(3).unwrap();
+        assert_eq!(
+            recent.iter().map(|r| r.id).collect::<Vec<_>>(),
+            [2000, 1999, 1998]
+        );
+        assert_eq!(damaged, 0);
+        assert_eq!(log.recent(5000).unwrap().0.len(), 2000);
+
+        // A torn append is dropped, and the next append still lands intact
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("history.jsonl"))
+            .unwrap();
+        file.write_all(b"{\"id\":20").unwrap();
+        assert_eq!(log.recent(1).unwrap().0[0].id, 2000);
+        log.append(&record(2001)).unwrap();
+        let (recent, damaged) = log.recent(2).unwrap();
+        assert_eq!(
+            recent.iter().map(|r| r.id).collect::<Vec<_>>(),
+            [2001, 2000]
+        );
+        assert_eq!(damaged, 1);
+        assert_eq!(log.iter().count(), 2001);
+    }
+
+    #[test]
+    fn test_migration_and_corrupt_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.json");
+        let log = HistoryLog::new(dir.path().join("history.jsonl"));
+        assert!(load::<Doc>(&path, &log).unwrap().is_none());
+
+        // Version 0: history inline, most recent first
+        let v0 = serde_json::json!({
+            "next_id": 3,
+            "history": [record(2), record(1)],
+        });
+        std::fs::write(&path, v0.to_string()).unwrap();
+        let (doc, migrated) = load::<Doc>(&path, &log).unwrap().unwrap();
+        assert!(migrated);
+        assert_eq!((doc.version, doc.next_id), (VERSION, 3));
+        assert_eq!(log.iter().map(|r| r.id).collect::<Vec<_>>(), [1, 2]);
+        assert!(dir.path().join("stats.json.v0.bak").exists());
+
+        std::fs::write(&path, r#"{"version":1,"next_id":3}"#).unwrap();
+        assert!(!load::<Doc>(&path, &log).unwrap().unwrap().1);
+
+        std::fs::write(&path, r#"{"version":1,"next_id":"#).unwrap();
+        match load::<Doc>(&path, &log) {
+            Err(LoadError::Corrupt { backup, .. }) => {
+                assert_eq!(
+                    std::fs::read_to_string(backup).unwrap(),
+                    r#"{"version":1,"next_id":"#
+                );
+            }
+            other => panic!("expected a corrupt-file error, got {:?}", other.map(|_| ())),
+        }
+
+        std::fs::write(&path, r#"{"version":99,"next_id":3}"#).unwrap();
+        assert!(matches!(
+            load::<Doc>(&path, &log),
+            Err(LoadError::TooNew { version: 99, .. })
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+//! Stats sync between machines through a shared folder (network drive,
+//! Dropbox, a git checkout, ...), with no server.
+//!
+//! Each device appends events to its own `events-<device>.jsonl` in the sync
+//! folder and never touches another device's file, so folder sync tools never
+//! see two writers on one file. On load every device's log is read, events are
+//! put in one deterministic order and the stats are rebuilt from them (see
+//! `StatsManager::replay`). A torn last line from an interrupted append is
+//! skipped rather than failing the whole log.
+
+use crate::stats::GameRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Per-profile sync settings, `sudoku_sync.json` in the profile directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Shared folder holding every device's event log
+    pub dir: PathBuf,
+}
+
+impl SyncConfig {
+    fn path() -> PathBuf {
+        crate::persistence::profile_dir().join("sudoku_sync.json")
+    }
+
+    /// The active profile's sync settings, if sync is enabled
+    pub fn load() -> Option<Self> {
+        let json = std::fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        crate::persistence::atomic_write(&Self::path(), json.as_bytes())
+    }
+
+    pub fn remove() -> io::Result<()> {
+        match std::fs::remove_file(Self::path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Something that changed the stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+    /// A finished (or imported) game
+    Game { record: GameRecord },
+    /// Secret difficulties unlocked in a way the games alone don't show
+    /// (written by older versions; see `Achievement`)
+    Unlock { reason: String },
+    /// An achievement event the games alone don't show (easter eggs,
+    /// no-notes wins)
+    Achievement { event: String },
+}
+
+/// One line of an event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub device: String,
+    /// Position in the device's own log, from 0
+    pub seq: u64,
+    /// Unix seconds; the game's own timestamp for game events
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// Stable id of this machine, shared by all profiles
+pub fn device_id() -> String {
+    let path = crate::persistence::app_data_dir().join("sudoku_device_id");
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        let id = id.trim().to_string();
+        if !id.is_empty() {
+            return id;
+        }
+    }
+    // Hostname keeps the file names readable; the suffix keeps them unique
+    let host: String = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .take(24)
+        .collect();
+    let host = if host.is_empty() { "device" } else { &host };
+    let id = format!(
+        "{}-{:08x}",
+        host.to_ascii_lowercase(),
+        rand::random::<u32>()
+    );
+    let _ = crate::persistence::atomic_write(&path, id.as_bytes());
+    id
+}
+
+/// This device's log in a sync folder
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+    device: String,
+    next_seq: u64,
+}
+
+impl EventLog {
+    /// Open (creating the folder if needed) the log of `device` under `dir`
+    pub fn open(dir: &Path, device: &str) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("events-{}.jsonl", device));
+        let next_seq = read_log(&path).iter().map(|e| e.seq + 1).max().unwrap_or(0);
+        Ok(Self {
+            path,
+            device: device.to_string(),
+            next_seq,
+        })
+    }
+
+    /// Whether this device has logged anything yet
+    pub fn is_empty(&self) -> bool {
+        self.next_seq == 0
+    }
+
+    /// Append an event as one line, flushed to disk before returning
+    pub fn append(&mut self, timestamp: u64, kind: EventKind) -> io::Result<()> {
+        let event = Event {
+            device: self.device.clone(),
+            seq: self.next_seq,
+            timestamp,
+            kind,
+        };
+        let line = serde_json::to_string(&event).map_err(io::Error::other)?;
+        crate::persistence::append_line(&self.path, &line)?;
+        self.next_seq += 1;
+        Ok(())
+    }
+}
+
+/// Parse one log, skipping lines that don't parse (e.g. a torn last write)
+fn read_log(path: &Path) -> Vec<Event> {
+    std::fs::read_to_string(path)
+        .map(|text| {
+            text.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Every device's events under `dir`, in replay order: by timestamp, then
+/// device, then sequence. Events seen twice (same device and seq) are dropped.
+pub fn read_all(dir: &Path) -> Vec<Event> {
+    let mut events: Vec<Event> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("events-") && n.ends_with(".jsonl"))
+                })
+                .flat_map(|p| read_log(&p))
+                .collect()
+        })
+        .unwrap_or_default();
+    events.sort_by(|a, b| (a.timestamp, &a.device, a.seq).cmp(&(b.timestamp, &b.device, b.seq)));
+    let mut seen = HashSet::new();
+    events.retain(|e| seen.insert((e.device.clone(), e.seq)));
+    events
+}
+
+/// Devices with a log under `dir` and how many events each has written
+pub fn devices(dir: &Path) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for event in read_all(dir) {
+        match counts.iter_mut().find(|(d, _)| *d == event.device) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((event.device, 1)),
+        }
+    }
+    counts.sort();
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{GameRecord, GameResult};
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use sudoku_core::Difficulty;
+
+    fn game(timestamp: u64) -> EventKind {
+        EventKind::Game {
+            record: GameRecord {
+                id: 0,
+                puzzle_hash: format!("h{}", timestamp),
+                puzzle: String::new(),
+                difficulty: Difficulty::Easy,
+                result: GameResult::Win,
+                time_secs: 100,
+                hints_used: 0,
+                mistakes: 0,
+                moves_count: 0,
+                timestamp,
+                avg_move_time_ms: 0,
+                min_move_time_ms: 0,
+                move_time_std_dev: 0.0,
+                verified: true,
+                seed: None,
+                short_code: None,
+                confidence: None,
+                multiplayer: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_logs_merge_in_order_and_survive_torn_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut laptop = EventLog::open(dir.path(), "laptop").unwrap();
+        let mut desktop = EventLog::open(dir.path(), "desktop").unwrap();
+        assert!(laptop.is_empty());
+
+        laptop.append(30, game(30)).unwrap();
+        desktop.append(20, game(20)).unwrap();
+        laptop.append(10, game(10)).unwrap();
+        desktop
+            .append(
+                30,
+                EventKind::Unlock {
+                    reason: "42".to_string(),
+                },
+            )
+            .unwrap();
+
+        // Interrupted append on the laptop
+        let laptop_path = dir.path().join("events-laptop.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&laptop_path).unwrap();
+        file.write_all(b"{\"device\":\"laptop\",\"seq\":2,")
+            .unwrap();
+
+        let order: Vec<(u64, String)> = read_all(dir.path())
+            .into_iter()
+            .map(|e| (e.timestamp, e.device))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (10, "laptop".to_string()),
+                (20, "desktop".to_string()),
+                (30, "desktop".to_string()),
+                (30, "laptop".to_string()),
+            ]
+        );
+
+        // Reopening continues the sequence and the next line is intact
+        let mut laptop = EventLog::open(dir.path(), "laptop").unwrap();
+        laptop.append(40, game(40)).unwrap();
+        let events = read_all(dir.path());
+        assert_eq!(events.len(), 5);
+        assert_eq!(events.last().unwrap().seq, 2);
+        assert_eq!(
+            devices(dir.path()),
+            vec![("desktop".to_string(), 2), ("laptop".to_string(), 3)]
+        );
+    }
+}
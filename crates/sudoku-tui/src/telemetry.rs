@@ -1,16 +1,17 @@
 //! Fire-and-forget telemetry that submits game results to the ukodus API.
 //! Results populate the Galaxy visualization and leaderboards alongside web/iOS games.
 
+use crate::outbox::{Outbox, Payload, SendOutcome};
 use crate::stats::GameRecord;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use sudoku_core::canonical_puzzle_hash_str;
 
-const API_ENDPOINT: &str = "https://ukodus.now/api/v1/results";
-const TOKEN_ENDPOINT: &str = "https://ukodus.now/api/v1/token";
-
 struct CachedToken {
+    endpoint: String,
+    /// Tokens are per player, and the player changes with the profile
+    player_id: String,
     token: String,
     expires_at: u64, // Unix timestamp
 }
@@ -19,11 +20,16 @@ static TOKEN_CACHE: Mutex<Option<CachedToken>> = Mutex::new(None);
 
 /// Fetch an auth token from the server. Returns None if the server is unavailable
 /// or doesn't support the token endpoint yet (migration period).
-fn fetch_token(player_id: &str) -> Option<CachedToken> {
-    let body = serde_json::json!({ "player_id": player_id });
-    let resp = ureq::post(TOKEN_ENDPOINT)
+fn fetch_token(endpoint: &str, player_id: &str) -> Option<CachedToken> {
+    let secret_path = player_secret_path();
+    let secret = std::fs::read_to_string(&secret_path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let body = serde_json::json!({ "player_id": player_id, "player_secret": secret });
+    let resp = crate::server::agent()
+        .post(endpoint)
         .set("Content-Type", "application/json")
-        .timeout(std::time::Duration::from_secs(5))
         .send_string(&body.to_string())
         .ok()?;
 
@@ -31,17 +37,30 @@ fn fetch_token(player_id: &str) -> Option<CachedToken> {
     let json: serde_json::Value = serde_json::from_str(&body).ok()?;
     let token = json["token"].as_str()?.to_string();
     let expires_at = json["expires_at"].as_u64()?;
+    // The first token for a player id comes with the secret that proves
+    // ownership of it from then on
+    if let Some(secret) = json["player_secret"].as_str() {
+        let _ = crate::persistence::atomic_write(&secret_path, secret.as_bytes());
+    }
 
-    Some(CachedToken { token, expires_at })
+    Some(CachedToken {
+        endpoint: endpoint.to_string(),
+        player_id: player_id.to_string(),
+        token,
+        expires_at,
+    })
 }
 
 /// Get a valid auth token, fetching a new one if needed.
 /// Returns None during migration (server doesn't support tokens yet).
-fn get_token(player_id: &str) -> Option<String> {
+fn get_token(endpoint: &str, player_id: &str) -> Option<String> {
     let mut cache = TOKEN_CACHE.lock().unwrap();
 
     // Check if cached token is still valid (with 60s buffer)
-    if let Some(ref cached) = *cache {
+    if let Some(cached) = cache
+        .as_ref()
+        .filter(|c| c.endpoint == endpoint && c.player_id == player_id)
+    {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
@@ -52,12 +71,24 @@ fn get_token(player_id: &str) -> Option<String> {
     }
 
     // Fetch new token
-    let new_token = fetch_token(player_id)?;
+    let new_token = fetch_token(endpoint, player_id)?;
     let token_str = new_token.token.clone();
     *cache = Some(new_token);
     Some(token_str)
 }
 
+/// Bearer token for this player from `endpoint`, shared with other API
+/// clients (e.g. the remote leaderboard) so they reuse the cached token.
+pub(crate) fn auth_token(endpoint: &str) -> Option<String> {
+    get_token(endpoint, &player_id())
+}
+
+/// Drop the cached token after the server rejected it, so the next request
+/// fetches a fresh one.
+pub(crate) fn clear_token() {
+    *TOKEN_CACHE.lock().unwrap() = None;
+}
+
 /// Get or create a persistent player UUID stored alongside stats.
 fn player_id() -> String {
     let path = player_id_path();
@@ -81,92 +112,131 @@ fn player_id() -> String {
 }
 
 fn player_id_path() -> PathBuf {
-    crate::persistence::app_data_dir().join("sudoku_player_id")
+    crate::persistence::profile_dir().join("sudoku_player_id")
+}
+
+fn player_secret_path() -> PathBuf {
+    crate::persistence::profile_dir().join("sudoku_player_secret")
 }
 
-/// Submit a game result to the ukodus API. Spawns a background thread
-/// so it never blocks the TUI. Failures are silently ignored.
+/// Queue a game result for the ukodus API and flush the outbox on a
+/// background thread so it never blocks the TUI. Results that fail to send
+/// stay in the outbox and are retried later.
 pub fn submit_result(record: &GameRecord, se_rating: f32) {
-    let puzzle_string = record.puzzle.clone();
-    let puzzle_hash = canonical_puzzle_hash_str(&record.puzzle);
-    let difficulty = format!("{:?}", record.difficulty);
+    if !crate::privacy::telemetry_enabled() {
+        return;
+    }
+
     let result_str = match record.result {
         crate::stats::GameResult::Win => "Win",
         crate::stats::GameResult::Loss => "Loss",
         _ => return, // Don't submit abandoned games
     };
-    let time_secs = record.time_secs;
-    let hints_used = record.hints_used;
-    let mistakes = record.mistakes;
-    let moves_count = record.moves_count;
-    let avg_move_time_ms = record.avg_move_time_ms;
-    let min_move_time_ms = record.min_move_time_ms;
-    let move_time_std_dev = record.move_time_std_dev;
-    let short_code = record.short_code.clone();
-    let pid = player_id();
-    let version = env!("CARGO_PKG_VERSION");
-
-    std::thread::spawn(move || {
-        let mut body = serde_json::json!({
-            "puzzle_hash": puzzle_hash,
-            "puzzle_string": puzzle_string,
-            "difficulty": difficulty,
-            "se_rating": se_rating,
-            "result": result_str,
-            "time_secs": time_secs,
-            "hints_used": hints_used,
-            "mistakes": mistakes,
-            "moves_count": moves_count,
-            "avg_move_time_ms": avg_move_time_ms,
-            "min_move_time_ms": min_move_time_ms,
-            "move_time_std_dev": move_time_std_dev,
-            "player_id": pid,
-            "platform": "tui",
-            "app_version": version,
-        });
-
-        if let Some(code) = short_code {
-            body["short_code"] = serde_json::Value::String(code);
-        }
 
-        // Try to get auth token (None during migration = unauthenticated)
-        let token = get_token(&pid);
+    let mut body = serde_json::json!({
+        "record_id": record.id,
+        "puzzle_hash": canonical_puzzle_hash_str(&record.puzzle),
+        "puzzle_string": record.puzzle,
+        "difficulty": format!("{:?}", record.difficulty),
+        "se_rating": se_rating,
+        "result": result_str,
+        "time_secs": record.time_secs,
+        "hints_used": record.hints_used,
+        "mistakes": record.mistakes,
+        "moves_count": record.moves_count,
+        "avg_move_time_ms": record.avg_move_time_ms,
+        "min_move_time_ms": record.min_move_time_ms,
+        "move_time_std_dev": record.move_time_std_dev,
+        "player_id": player_id(),
+        "platform": "tui",
+        "app_version": env!("CARGO_PKG_VERSION"),
+    });
 
-        let mut req = ureq::post(API_ENDPOINT)
-            .set("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(10));
+    if let Some(ref code) = record.short_code {
+        body["short_code"] = serde_json::Value::String(code.clone());
+    }
 
-        if let Some(ref t) = token {
-            req = req.set("Authorization", &format!("Bearer {}", t));
-        }
+    let outbox = Outbox::new();
+    outbox.enqueue(&crate::outbox::result_id(record), Payload::Result(body));
+    crate::outbox::flush_in_background();
+}
 
-        let resp = req.send_string(&body.to_string());
+/// POST a queued result body to the results endpoint
+pub(crate) fn send_result(body: &serde_json::Value) -> SendOutcome {
+    let pid = body["player_id"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(player_id);
 
-        match resp {
-            Ok(_r) => {
-                #[cfg(debug_assertions)]
-                eprintln!("Telemetry: {} for {}", _r.status(), puzzle_hash);
-            }
-            Err(ureq::Error::Status(401, _)) => {
-                // Token expired or invalid — clear cache so next submission refreshes
-                *TOKEN_CACHE.lock().unwrap() = None;
-                #[cfg(debug_assertions)]
-                eprintln!("Telemetry: 401 — token expired, cleared cache");
-            }
-            Err(ureq::Error::Status(429, _resp)) => {
-                #[cfg(debug_assertions)]
-                {
-                    let retry_after = _resp.header("Retry-After").unwrap_or("?");
-                    eprintln!(
-                        "Telemetry: 429 — rate limited, retry after {}s",
-                        retry_after
-                    );
-                }
-            }
-            Err(_e) => {
-                #[cfg(debug_assertions)]
-                eprintln!("Telemetry error: {}", _e);
+    // Try to get auth token (None during migration = unauthenticated)
+    let server = crate::server::config();
+    let token = get_token(&server.token_url(), &pid);
+
+    let endpoint = server.results_url();
+    let mut req = crate::server::agent()
+        .post(&endpoint)
+        .set("Content-Type", "application/json");
+
+    if let Some(ref t) = token {
+        req = req.set("Authorization", &format!("Bearer {}", t));
+    }
+
+    match req.send_string(&body.to_string()) {
+        Ok(r) => {
+            #[cfg(debug_assertions)]
+            eprintln!("Telemetry: {} for {}", r.status(), body["puzzle_hash"]);
+            crate::privacy::record_sent(&endpoint, r.status(), body);
+            SendOutcome::Sent
+        }
+        Err(ureq::Error::Status(401, _)) => {
+            // Token expired or invalid — clear cache so the retry refreshes
+            clear_token();
+            SendOutcome::Retry {
+                error: "401 — token expired".to_string(),
+                retry_after_secs: None,
             }
         }
-    });
+        Err(ureq::Error::Status(429, resp)) => SendOutcome::Retry {
+            error: "429 — rate limited".to_string(),
+            retry_after_secs: resp
+                .header("Retry-After")
+                .and_then(|v| v.trim().parse().ok()),
+        },
+        Err(ureq::Error::Status(code, resp)) if (400..500).contains(&code) => {
+            SendOutcome::Rejected(format!(
+                "{} — {}",
+                code,
+                resp.into_string().unwrap_or_default()
+            ))
+        }
+        Err(e) => SendOutcome::Retry {
+            error: e.to_string(),
+            retry_after_secs: None,
+        },
+    }
+}
+
+/// Ask the server to delete everything it holds for this player.
+/// Returns the player id the request was made for.
+pub fn request_deletion() -> Result<String, String> {
+    let pid = player_id();
+    let server = crate::server::config();
+    let token = get_token(&server.token_url(), &pid);
+
+    let url = format!("{}/{}", server.players_url(), urlencoding::encode(&pid));
+    let mut req = crate::server::agent().delete(&url);
+
+    if let Some(ref t) = token {
+        req = req.set("Authorization", &format!("Bearer {}", t));
+    }
+
+    match req.call() {
+        Ok(_) => Ok(pid),
+        Err(ureq::Error::Status(code, resp)) => Err(format!(
+            "server returned {}: {}",
+            code,
+            resp.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(e.to_string()),
+    }
 }
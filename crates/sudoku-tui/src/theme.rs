@@ -1,4 +1,6 @@
 use crossterm::style::Color;
+use std::path::{Path, PathBuf};
+use sudoku_shared::themes::{Rgb, ThemeError, ThemeFile};
 
 /// Color theme for the TUI
 #[derive(Debug, Clone)]
@@ -37,7 +39,139 @@ impl Default for Theme {
     }
 }
 
+/// A theme the player can pick: built-in or a file in [`themes_dir`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeEntry {
+    /// Name written in the config file: the built-in name or the file stem
+    pub id: String,
+    /// Name shown in menus
+    pub label: String,
+    /// File the theme is read from, `None` for built-ins
+    pub path: Option<PathBuf>,
+}
+
+impl ThemeEntry {
+    /// Build the theme, reading its file again if it has one
+    pub fn load(&self) -> Result<Theme, String> {
+        match &self.path {
+            Some(path) => read_theme_file(path)
+                .and_then(|file| Theme::from_file(&file))
+                .map_err(|e| format!("{}: {}", self.id, e)),
+            None => Theme::named(&self.id).ok_or_else(|| format!("Unknown theme '{}'", self.id)),
+        }
+    }
+}
+
+/// Directory of user themes (`*.toml` or `*.json`), next to the config file
+pub fn themes_dir() -> PathBuf {
+    let config = crate::config::shared_path();
+    config
+        .parent()
+        .map_or_else(|| PathBuf::from("themes"), |dir| dir.join("themes"))
+}
+
+/// Parse a theme file, as TOML unless it ends in `.json`
+fn read_theme_file(path: &Path) -> Result<ThemeFile, ThemeError> {
+    let text = std::fs::read_to_string(path).map_err(|e| ThemeError::Parse(e.to_string()))?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return ThemeFile::from_json(&text);
+    }
+    let file: ThemeFile = toml::from_str(&text).map_err(|e| ThemeError::Parse(e.to_string()))?;
+    file.base_name()?;
+    Ok(file)
+}
+
 impl Theme {
+    /// Names of the built-in themes, as written in the config file
+    pub const BUILTIN: [&'static str; 5] = sudoku_shared::themes::BUILTIN;
+
+    /// Built-in themes followed by the files in [`themes_dir`], sorted by
+    /// name. Files that fail to parse are still listed so picking one shows
+    /// the error.
+    pub fn installed() -> Vec<ThemeEntry> {
+        let mut entries: Vec<ThemeEntry> = Self::BUILTIN
+            .iter()
+            .zip([
+                "Dark",
+                "Light",
+                "High Contrast",
+                "Colorblind",
+                "Colorblind Light",
+            ])
+            .map(|(id, label)| ThemeEntry {
+                id: id.to_string(),
+                label: label.to_string(),
+                path: None,
+            })
+            .collect();
+
+        let mut files: Vec<ThemeEntry> = std::fs::read_dir(themes_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let ext = path.extension()?.to_str()?;
+                if ext != "toml" && ext != "json" {
+                    return None;
+                }
+                let id = path.file_stem()?.to_str()?.to_string();
+                if Self::BUILTIN.contains(&id.as_str()) {
+                    return None;
+                }
+                let label = read_theme_file(&path)
+                    .ok()
+                    .and_then(|file| file.name)
+                    .unwrap_or_else(|| id.clone());
+                Some(ThemeEntry {
+                    id,
+                    label,
+                    path: Some(path),
+                })
+            })
+            .collect();
+        files.sort_by_key(|t| t.label.to_lowercase());
+        files.dedup_by(|a, b| a.id == b.id);
+        entries.extend(files);
+        entries
+    }
+
+    /// Build a theme from a file: its base with the file's colors on top
+    pub fn from_file(file: &ThemeFile) -> Result<Self, ThemeError> {
+        let mut theme = Self::named(file.base_name()?).unwrap_or_default();
+        let colors = &file.colors;
+        let set = |slot: &mut Color, color: Option<Rgb>| {
+            if let Some(Rgb { r, g, b }) = color {
+                *slot = Color::Rgb { r, g, b };
+            }
+        };
+        set(&mut theme.bg, colors.background);
+        set(&mut theme.fg, colors.text);
+        set(&mut theme.border, colors.grid_lines);
+        set(&mut theme.box_border, colors.box_border);
+        set(&mut theme.given, colors.given);
+        set(&mut theme.filled, colors.player);
+        set(&mut theme.candidate, colors.candidate);
+        set(&mut theme.selected_bg, colors.cursor_bg);
+        set(&mut theme.highlight_bg, colors.highlight_bg);
+        set(&mut theme.error, colors.error);
+        set(&mut theme.success, colors.success);
+        set(&mut theme.info, colors.info);
+        set(&mut theme.key, colors.accent);
+        Ok(theme)
+    }
+
+    /// Look up a built-in theme by name
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "colorblind" => Some(Self::colorblind()),
+            "colorblind-light" => Some(Self::colorblind_light()),
+            _ => None,
+        }
+    }
+
     /// Dark theme (default) - improved contrast
     pub fn dark() -> Self {
         Self {
@@ -202,4 +336,109 @@ impl Theme {
             key: Color::Yellow,
         }
     }
+
+    /// Dark theme using the Okabe-Ito colors, which stay apart under the
+    /// common kinds of color blindness: blue entries, vermillion errors,
+    /// bluish-green completions
+    pub fn colorblind() -> Self {
+        Self {
+            filled: Color::Rgb {
+                r: 86,
+                g: 180,
+                b: 233,
+            },
+            selected_bg: Color::Rgb {
+                r: 0,
+                g: 75,
+                b: 120,
+            },
+            error: Color::Rgb {
+                r: 230,
+                g: 110,
+                b: 0,
+            },
+            success: Color::Rgb {
+                r: 0,
+                g: 170,
+                b: 125,
+            },
+            key: Color::Rgb {
+                r: 240,
+                g: 228,
+                b: 66,
+            },
+            ..Self::dark()
+        }
+    }
+
+    /// Light counterpart of [`Theme::colorblind`]
+    pub fn colorblind_light() -> Self {
+        Self {
+            filled: Color::Rgb {
+                r: 0,
+                g: 114,
+                b: 178,
+            },
+            selected_bg: Color::Rgb {
+                r: 200,
+                g: 225,
+                b: 245,
+            },
+            error: Color::Rgb {
+                r: 213,
+                g: 94,
+                b: 0,
+            },
+            success: Color::Rgb {
+                r: 0,
+                g: 140,
+                b: 100,
+            },
+            key: Color::Rgb {
+                r: 204,
+                g: 121,
+                b: 167,
+            },
+            ..Self::light()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.toml");
+        std::fs::write(
+            &path,
+            "name = \"Paper\"\nbase = \"light\"\n\n[colors]\nbackground = \"#fdf6e3\"\n",
+        )
+        .unwrap();
+        let entry = ThemeEntry {
+            id: "paper".into(),
+            label: "Paper".into(),
+            path: Some(path.clone()),
+        };
+        let theme = entry.load().unwrap();
+        assert_eq!(
+            theme.bg,
+            Color::Rgb {
+                r: 0xfd,
+                g: 0xf6,
+                b: 0xe3
+            }
+        );
+        assert_eq!(theme.fg, Theme::light().fg);
+
+        std::fs::write(&path, "[colors]\nbackground = \"blue\"\n").unwrap();
+        let err = entry.load().unwrap_err();
+        assert!(err.starts_with("paper: invalid theme"), "{}", err);
+
+        let json = dir.path().join("mono.json");
+        std::fs::write(&json, r#"{"base": "high-contrast"}"#).unwrap();
+        assert!(read_theme_file(&json).is_ok());
+    }
 }
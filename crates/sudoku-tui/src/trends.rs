@@ -0,0 +1,295 @@
+//! Trend series and text charts for the stats screen.
+//!
+//! Everything here is computed from `StatsManager::history` (most recent
+//! first) and returns oldest-first series ready to plot.
+
+use crate::stats::{GameRecord, GameResult};
+use sudoku_core::Difficulty;
+use sudoku_shared::dates::DAY;
+
+/// Views on the stats screen, cycled with Tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrendView {
+    #[default]
+    Overview,
+    SolveTime,
+    WinRate,
+    Accuracy,
+    Calendar,
+    PersonalBests,
+}
+
+impl TrendView {
+    pub const ALL: [TrendView; 6] = [
+        TrendView::Overview,
+        TrendView::SolveTime,
+        TrendView::WinRate,
+        TrendView::Accuracy,
+        TrendView::Calendar,
+        TrendView::PersonalBests,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrendView::Overview => "Overview",
+            TrendView::SolveTime => "Solve time",
+            TrendView::WinRate => "Win rate",
+            TrendView::Accuracy => "Mistakes & hints",
+            TrendView::Calendar => "Calendar",
+            TrendView::PersonalBests => "Personal bests",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|v| v == self).unwrap_or(0)
+    }
+
+    pub fn next(&self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the view is per difficulty (↑/↓ changes it)
+    pub fn uses_difficulty(&self) -> bool {
+        matches!(self, TrendView::SolveTime | TrendView::PersonalBests)
+    }
+}
+
+/// Games that ended in a win or loss, oldest first
+fn finished(history: &[GameRecord]) -> impl Iterator<Item = &GameRecord> {
+    history
+        .iter()
+        .rev()
+        .filter(|r| r.result != GameResult::Abandoned)
+}
+
+/// Solve times of the last `n` wins at `difficulty`, oldest first
+pub fn solve_times(history: &[GameRecord], difficulty: Difficulty, n: usize) -> Vec<u64> {
+    let wins: Vec<u64> = history
+        .iter()
+        .filter(|r| r.result == GameResult::Win && r.difficulty == difficulty)
+        .take(n)
+        .map(|r| r.time_secs)
+        .collect();
+    wins.into_iter().rev().collect()
+}
+
+/// Win rate (0-100) over a rolling window of `window` games, one point per game
+pub fn win_rate_series(history: &[GameRecord], window: usize) -> Vec<f64> {
+    let wins: Vec<bool> = finished(history)
+        .map(|r| r.result == GameResult::Win)
+        .collect();
+    let window = window.max(1);
+    (0..wins.len())
+        .map(|i| {
+            let slice = &wins[(i + 1).saturating_sub(window)..=i];
+            slice.iter().filter(|&&w| w).count() as f64 / slice.len() as f64 * 100.0
+        })
+        .collect()
+}
+
+/// `(mistakes, hints)` of the last `n` finished games, oldest first
+pub fn mistakes_hints(history: &[GameRecord], n: usize) -> Vec<(usize, usize)> {
+    let recent: Vec<(usize, usize)> = history
+        .iter()
+        .filter(|r| r.result != GameResult::Abandoned)
+        .take(n)
+        .map(|r| (r.mistakes, r.hints_used))
+        .collect();
+    recent.into_iter().rev().collect()
+}
+
+/// Games played per day for the `weeks` weeks ending with the week of `now`,
+/// one column per Monday-based week. Days after today are `None`.
+pub fn calendar(history: &[GameRecord], now: u64, weeks: usize) -> Vec<[Option<u32>; 7]> {
+    let today = now / DAY;
+    // 1970-01-01 was a Thursday, so Monday-based weekday is (days + 3) % 7
+    let first_day = (today - (today + 3) % 7).saturating_sub((weeks.max(1) as u64 - 1) * 7);
+
+    let mut grid = vec![[None; 7]; weeks.max(1)];
+    for (w, column) in grid.iter_mut().enumerate() {
+        for (d, cell) in column.iter_mut().enumerate() {
+            if first_day + (w * 7 + d) as u64 <= today {
+                *cell = Some(0);
+            }
+        }
+    }
+    for record in history {
+        let day = record.timestamp / DAY;
+        if day < first_day || day > today {
+            continue;
+        }
+        let offset = (day - first_day) as usize;
+        if let Some(count) = grid[offset / 7][offset % 7].as_mut() {
+            *count += 1;
+        }
+    }
+    grid
+}
+
+/// Wins that set a new best time at `difficulty`, oldest first, as
+/// `(timestamp, time_secs)`
+pub fn personal_bests(history: &[GameRecord], difficulty: Difficulty) -> Vec<(u64, u64)> {
+    let mut best: Option<u64> = None;
+    let mut progression = Vec::new();
+    for record in history
+        .iter()
+        .rev()
+        .filter(|r| r.result == GameResult::Win && r.difficulty == difficulty)
+    {
+        if best.is_none_or(|b| record.time_secs < b) {
+            best = Some(record.time_secs);
+            progression.push((record.timestamp, record.time_secs));
+        }
+    }
+    progression
+}
+
+/// One-line bar chart, one character per value, with `max` as a full bar
+pub fn sparkline(values: &[f64], max: f64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    values
+        .iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                BARS[0]
+            } else {
+                BARS[((v / max) * 7.0).round().clamp(0.0, 7.0) as usize]
+            }
+        })
+        .collect()
+}
+
+/// Line chart drawn with braille dots (2×4 dots per character), scaled to
+/// `min..=max` of the data. Returns `height` rows, top first.
+pub fn braille_chart(values: &[f64], width: usize, height: usize) -> Vec<String> {
+    let (dots_x, dots_y) = (width * 2, height * 4);
+    let mut cells = vec![vec![0u8; width]; height];
+    if values.is_empty() || width == 0 || height == 0 {
+        return cells.iter().map(|_| " ".repeat(width)).collect();
+    }
+
+    // Keep the most recent values that fit
+    let values = &values[values.len().saturating_sub(dots_x)..];
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let span = if max > min { max - min } else { 1.0 };
+
+    // Bit for dot (column, row) inside a braille cell
+    const BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    let to_y = |v: f64| ((max - v) / span * (dots_y - 1) as f64).round() as usize;
+
+    let mut prev: Option<usize> = None;
+    for (x, &v) in values.iter().enumerate() {
+        let y = to_y(v);
+        // Fill the vertical gap from the previous point so the line is continuous
+        let (lo, hi) = match prev {
+            Some(p) => (p.min(y), p.max(y)),
+            None => (y, y),
+        };
+        for dy in lo..=hi {
+            cells[dy / 4][x / 2] |= BITS[x % 2][dy % 4];
+        }
+        prev = Some(y);
+    }
+
+    cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&bits| char::from_u32(0x2800 + bits as u32).unwrap_or(' '))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(result: GameResult, difficulty: Difficulty, time_secs: u64, day: u64) -> GameRecord {
+        GameRecord {
+            id: day,
+            puzzle_hash: String::new(),
+            puzzle: String::new(),
+            difficulty,
+            result,
+            time_secs,
+            hints_used: 1,
+            mistakes: 2,
+            moves_count: 0,
+            timestamp: day * DAY + 60,
+            avg_move_time_ms: 0,
+            min_move_time_ms: 0,
+            move_time_std_dev: 0.0,
+            verified: true,
+            seed: None,
+            short_code: None,
+            confidence: None,
+            multiplayer: None,
+        }
+    }
+
+    /// Most recent first, like `StatsManager::history`
+    fn history() -> Vec<GameRecord> {
+        let mut h = vec![
+            record(GameResult::Win, Difficulty::Easy, 300, 100),
+            record(GameResult::Loss, Difficulty::Easy, 200, 101),
+            record(GameResult::Win, Difficulty::Easy, 400, 102),
+            record(GameResult::Abandoned, Difficulty::Easy, 10, 102),
+            record(GameResult::Win, Difficulty::Easy, 250, 103),
+            record(GameResult::Win, Difficulty::Hard, 900, 104),
+        ];
+        h.reverse();
+        h
+    }
+
+    #[test]
+    fn test_series() {
+        let h = history();
+        assert_eq!(solve_times(&h, Difficulty::Easy, 10), vec![300, 400, 250]);
+        assert_eq!(solve_times(&h, Difficulty::Easy, 2), vec![400, 250]);
+        assert_eq!(
+            win_rate_series(&h, 2),
+            vec![100.0, 50.0, 50.0, 100.0, 100.0]
+        );
+        assert_eq!(mistakes_hints(&h, 10).len(), 5);
+        assert_eq!(
+            personal_bests(&h, Difficulty::Easy),
+            vec![(100 * DAY + 60, 300), (103 * DAY + 60, 250)]
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        // Day 104 (1970-04-15) is a Wednesday
+        let h = history();
+        let grid = calendar(&h, 104 * DAY + 100, 2);
+        assert_eq!(grid.len(), 2);
+        let total: u32 = grid.iter().flatten().flatten().sum();
+        assert_eq!(total, 6);
+        // Days after today are blank
+        let today = ((104 + 3) % 7) as usize;
+        assert_eq!(grid[1][today], Some(1));
+        assert!(grid[1][today + 1..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_charts() {
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0], 2.0), "▁▅█");
+        assert_eq!(sparkline(&[1.0, 2.0], 4.0), "▃▅");
+        assert_eq!(sparkline(&[0.0, 0.0], 0.0), "▁▁");
+
+        let rows = braille_chart(&[0.0, 1.0, 2.0, 3.0], 2, 1);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].chars().count(), 2);
+        // Rising line: bottom-left dot set, top-right dot set
+        let first = rows[0].chars().next().unwrap() as u32 - 0x2800;
+        assert_ne!(first & 0x40, 0);
+        let last = rows[0].chars().last().unwrap() as u32 - 0x2800;
+        assert_ne!(last & 0x08, 0);
+    }
+}
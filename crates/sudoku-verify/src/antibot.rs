@@ -0,0 +1,146 @@
+//! Aggregate timing checks and leaderboard scoring shared by clients and server.
+//!
+//! These work from the summary numbers every client already submits, so they
+//! apply even when no move log is available.
+
+use serde::{Deserialize, Serialize};
+use sudoku_core::Difficulty;
+
+/// Minimum average move time (milliseconds) - humans can't click faster than ~100ms consistently
+pub const MIN_AVG_MOVE_TIME_MS: u64 = 150;
+
+/// Minimum move time (milliseconds) - single fastest move
+pub const MIN_SINGLE_MOVE_TIME_MS: u64 = 50;
+
+/// Minimum standard deviation (bots have very consistent timing)
+pub const MIN_STD_DEV: f32 = 100.0;
+
+/// Seconds added to the score per hint
+pub const HINT_PENALTY_SECS: u64 = 30;
+
+/// Seconds added to the score per mistake
+pub const MISTAKE_PENALTY_SECS: u64 = 15;
+
+/// Minimum realistic time to solve by difficulty (seconds)
+pub fn min_time_secs(difficulty: Difficulty) -> u64 {
+    match difficulty {
+        Difficulty::Beginner => 15,
+        Difficulty::Easy => 30,
+        Difficulty::Medium => 60,
+        Difficulty::Intermediate => 90,
+        Difficulty::Hard => 120,
+        Difficulty::Expert => 180,
+        Difficulty::Master => 300,
+        Difficulty::Extreme => 600,
+    }
+}
+
+/// Timing summary of a finished game, as submitted by the clients
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub difficulty: Difficulty,
+    pub time_secs: u64,
+    pub hints_used: usize,
+    pub mistakes: usize,
+    pub moves_count: usize,
+    pub avg_move_time_ms: u64,
+    pub min_move_time_ms: u64,
+    pub move_time_std_dev: f32,
+}
+
+/// Check a game summary for bot-like behavior. Returns the issues found;
+/// an empty list means the game passes.
+pub fn check(game: &GameSummary) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    // Check minimum time based on difficulty
+    let min_time = min_time_secs(game.difficulty);
+    if game.time_secs < min_time {
+        issues.push(format!(
+            "Time too fast: {}s (min {}s for {:?})",
+            game.time_secs, min_time, game.difficulty
+        ));
+    }
+
+    // Check average move time
+    if game.avg_move_time_ms < MIN_AVG_MOVE_TIME_MS && game.moves_count > 10 {
+        issues.push(format!(
+            "Avg move time too fast: {}ms (min {}ms)",
+            game.avg_move_time_ms, MIN_AVG_MOVE_TIME_MS
+        ));
+    }
+
+    // Check minimum single move time
+    if game.min_move_time_ms < MIN_SINGLE_MOVE_TIME_MS && game.moves_count > 5 {
+        issues.push(format!(
+            "Fastest move too quick: {}ms (min {}ms)",
+            game.min_move_time_ms, MIN_SINGLE_MOVE_TIME_MS
+        ));
+    }
+
+    // Check timing variance (bots are too consistent)
+    if game.move_time_std_dev < MIN_STD_DEV && game.moves_count > 15 {
+        issues.push(format!(
+            "Move timing too consistent: std_dev={:.1}ms (min {:.1}ms)",
+            game.move_time_std_dev, MIN_STD_DEV
+        ));
+    }
+
+    // Check for suspicious hint/mistake patterns
+    // If someone wins expert with 0 hints, 0 mistakes in minimum time, suspicious
+    if game.difficulty == Difficulty::Expert
+        && game.hints_used == 0
+        && game.mistakes == 0
+        && game.time_secs < min_time * 2
+    {
+        issues.push("Suspiciously perfect expert game".to_string());
+    }
+
+    issues
+}
+
+/// Leaderboard score for a win (lower is better):
+/// time + hints * 30 + mistakes * 15
+pub fn score(time_secs: u64, hints_used: usize, mistakes: usize) -> u64 {
+    time_secs + hints_used as u64 * HINT_PENALTY_SECS + mistakes as u64 * MISTAKE_PENALTY_SECS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn human_game() -> GameSummary {
+        GameSummary {
+            difficulty: Difficulty::Hard,
+            time_secs: 600,
+            hints_used: 1,
+            mistakes: 2,
+            moves_count: 55,
+            avg_move_time_ms: 10_000,
+            min_move_time_ms: 800,
+            move_time_std_dev: 4_000.0,
+        }
+    }
+
+    #[test]
+    fn test_human_game_passes() {
+        assert!(check(&human_game()).is_empty());
+    }
+
+    #[test]
+    fn test_bot_game_flagged() {
+        let bot = GameSummary {
+            time_secs: 20,
+            avg_move_time_ms: 100,
+            min_move_time_ms: 10,
+            move_time_std_dev: 5.0,
+            ..human_game()
+        };
+        assert_eq!(check(&bot).len(), 4);
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score(600, 1, 2), 600 + 30 + 30);
+    }
+}
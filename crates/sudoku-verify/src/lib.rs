@@ -0,0 +1,575 @@
+//! Deterministic replay verification for submitted game results.
+//!
+//! A client submits the puzzle it played, the timestamped move log recorded
+//! during play and the result it claims. [`verify`] replays the log against the
+//! puzzle from scratch and produces a [`Verdict`] that a server can store next
+//! to the result. Unlike the aggregate timing checks done by the clients, the
+//! replay proves the claimed board was actually reached move by move.
+//!
+//! [`plausibility`] complements the replay with a behavioral confidence score
+//! over the same move sequence, and [`antibot`] holds the aggregate timing
+//! checks and leaderboard score formula shared by clients and server.
+
+pub mod antibot;
+mod log;
+pub mod plausibility;
+
+pub use log::{parse_move_log, MoveAction, MoveLogEntry};
+pub use plausibility::{PlausibilityConfig, PlausibilityReport};
+
+use serde::{Deserialize, Serialize};
+use sudoku_core::{canonical_puzzle_hash_str, Grid, Position, Solver};
+
+/// What the client claims about the game it is submitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+    /// Time to complete in seconds
+    pub time_secs: u64,
+    /// Number of mistakes made
+    pub mistakes: usize,
+    /// Number of hints used
+    pub hints_used: usize,
+}
+
+/// Tunables for the replay checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Allowed difference between the last logged timestamp and the claimed
+    /// time, in seconds. Covers rounding and the tick between the final move
+    /// and the win being recorded.
+    pub time_tolerance_secs: u64,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            time_tolerance_secs: 2,
+        }
+    }
+}
+
+/// A problem found while replaying a submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// The puzzle string could not be parsed
+    InvalidPuzzle,
+    /// The puzzle has no solution
+    Unsolvable,
+    /// Sequence numbers are not contiguous from 0
+    SequenceGap { expected: u32, found: u32 },
+    /// A move is timestamped before the game started
+    PlacedBeforeStart { seq: u32, ms: i64 },
+    /// A move is timestamped earlier than the move before it
+    TimestampDecreased { seq: u32, ms: i64, previous_ms: i64 },
+    /// Cell index outside the 81-cell grid
+    InvalidCell { seq: u32, cell: u8 },
+    /// Digit outside 1-9
+    InvalidDigit { seq: u32, digit: u8 },
+    /// A given (clue) cell was changed
+    GivenModified { seq: u32, cell: u8 },
+    /// A clear did not match the value that was in the cell
+    InconsistentClear {
+        seq: u32,
+        cell: u8,
+        expected: Option<u8>,
+        found: u8,
+    },
+    /// An undo or redo restored a value the cell's history doesn't hold
+    InconsistentHistory {
+        seq: u32,
+        cell: u8,
+        expected: Option<Option<u8>>,
+        found: Option<u8>,
+    },
+    /// A hint placed a digit that is not the solution
+    IncorrectHint { seq: u32, cell: u8, digit: u8 },
+    /// Moves were logged after the board was already solved
+    MovesAfterCompletion { seq: u32 },
+    /// The final board is not the solution
+    NotSolved {
+        empty_cells: usize,
+        wrong_cells: usize,
+    },
+    /// Replayed mistakes differ from the claim
+    MistakeCountMismatch { claimed: usize, replayed: usize },
+    /// Replayed hints differ from the claim
+    HintCountMismatch { claimed: usize, replayed: usize },
+    /// The log's duration does not match the claimed time
+    TimeMismatch { claimed_secs: u64, logged_ms: u64 },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPuzzle => write!(f, "Puzzle string is not a valid grid"),
+            Self::Unsolvable => write!(f, "Puzzle has no solution"),
+            Self::SequenceGap { expected, found } => {
+                write!(f, "Sequence gap: expected #{}, found #{}", expected, found)
+            }
+            Self::PlacedBeforeStart { seq, ms } => {
+                write!(f, "Move #{} at {}ms is before the game started", seq, ms)
+            }
+            Self::TimestampDecreased {
+                seq,
+                ms,
+                previous_ms,
+            } => write!(
+                f,
+                "Move #{} at {}ms is earlier than the previous move ({}ms)",
+                seq, ms, previous_ms
+            ),
+            Self::InvalidCell { seq, cell } => write!(f, "Move #{} targets cell {}", seq, cell),
+            Self::InvalidDigit { seq, digit } => write!(f, "Move #{} uses digit {}", seq, digit),
+            Self::GivenModified { seq, cell } => {
+                write!(f, "Move #{} modifies given cell {}", seq, cell)
+            }
+            Self::InconsistentClear {
+                seq,
+                cell,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Move #{} clears {} from cell {} which holds {:?}",
+                seq, found, cell, expected
+            ),
+            Self::InconsistentHistory {
+                seq,
+                cell,
+                expected: Some(expected),
+                found,
+            } => write!(
+                f,
+                "Move #{} restores {:?} to cell {} instead of {:?}",
+                seq, found, cell, expected
+            ),
+            Self::InconsistentHistory { seq, cell, .. } => {
+                write!(
+                    f,
+                    "Move #{} restores cell {} with nothing to restore",
+                    seq, cell
+                )
+            }
+            Self::IncorrectHint { seq, cell, digit } => {
+                write!(
+                    f,
+                    "Hint #{} placed wrong digit {} in cell {}",
+                    seq, digit, cell
+                )
+            }
+            Self::MovesAfterCompletion { seq } => {
+                write!(f, "Move #{} was made after the puzzle was solved", seq)
+            }
+            Self::NotSolved {
+                empty_cells,
+                wrong_cells,
+            } => write!(
+                f,
+                "Final grid not solved: {} empty, {} wrong",
+                empty_cells, wrong_cells
+            ),
+            Self::MistakeCountMismatch { claimed, replayed } => {
+                write!(f, "Claimed {} mistakes, replay shows {}", claimed, replayed)
+            }
+            Self::HintCountMismatch { claimed, replayed } => {
+                write!(f, "Claimed {} hints, replay shows {}", claimed, replayed)
+            }
+            Self::TimeMismatch {
+                claimed_secs,
+                logged_ms,
+            } => write!(f, "Claimed {}s, log spans {}ms", claimed_secs, logged_ms),
+        }
+    }
+}
+
+/// Structured outcome of replaying a submission, suitable for storage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    /// True when no issues were found
+    pub valid: bool,
+    /// Canonical hash of the submitted puzzle
+    pub puzzle_hash: String,
+    /// Whether the replayed board ended up solved
+    pub solved: bool,
+    /// Number of log entries replayed
+    pub moves: usize,
+    /// Mistakes counted during replay
+    pub mistakes: usize,
+    /// Hints counted during replay
+    pub hints_used: usize,
+    /// Timestamp of the last log entry
+    pub logged_time_ms: u64,
+    /// Everything that failed verification
+    pub issues: Vec<Issue>,
+}
+
+/// Replay `log` against `puzzle` and compare the outcome to `claim`
+pub fn verify(puzzle: &str, log: &[MoveLogEntry], claim: &Claim) -> Verdict {
+    verify_with(puzzle, log, claim, &VerifyOptions::default())
+}
+
+/// Same as [`verify`] with explicit tolerances
+pub fn verify_with(
+    puzzle: &str,
+    log: &[MoveLogEntry],
+    claim: &Claim,
+    options: &VerifyOptions,
+) -> Verdict {
+    let mut verdict = Verdict {
+        valid: false,
+        puzzle_hash: canonical_puzzle_hash_str(puzzle),
+        solved: false,
+        moves: log.len(),
+        mistakes: 0,
+        hints_used: 0,
+        logged_time_ms: 0,
+        issues: Vec::new(),
+    };
+
+    let Some(grid) = Grid::from_string(puzzle) else {
+        verdict.issues.push(Issue::InvalidPuzzle);
+        return verdict;
+    };
+    let Some(solution) = Solver::new().solve(&grid) else {
+        verdict.issues.push(Issue::Unsolvable);
+        return verdict;
+    };
+
+    let mut board = [None; 81];
+    let mut givens = [false; 81];
+    let mut answer = [0u8; 81];
+    for (idx, slot) in board.iter_mut().enumerate() {
+        let pos = Position::new(idx / 9, idx % 9);
+        *slot = grid.get(pos);
+        givens[idx] = slot.is_some();
+        answer[idx] = solution.get(pos).unwrap_or(0);
+    }
+
+    // Each cell's earlier values, so undo and redo can only restore what the
+    // cell actually held
+    let mut undo: Vec<Vec<Option<u8>>> = vec![Vec::new(); 81];
+    let mut redo: Vec<Vec<Option<u8>>> = vec![Vec::new(); 81];
+
+    let mut previous_ms = 0i64;
+    let mut completed = false;
+
+    for (i, entry) in log.iter().enumerate() {
+        let seq = entry.seq;
+        if seq != i as u32 {
+            verdict.issues.push(Issue::SequenceGap {
+                expected: i as u32,
+                found: seq,
+            });
+        }
+
+        if entry.ms < 0 {
+            verdict
+                .issues
+                .push(Issue::PlacedBeforeStart { seq, ms: entry.ms });
+        } else if entry.ms < previous_ms {
+            verdict.issues.push(Issue::TimestampDecreased {
+                seq,
+                ms: entry.ms,
+                previous_ms,
+            });
+        }
+        previous_ms = previous_ms.max(entry.ms);
+
+        if completed {
+            verdict.issues.push(Issue::MovesAfterCompletion { seq });
+        }
+
+        let cell = entry.cell;
+        let idx = cell as usize;
+        if idx >= 81 {
+            verdict.issues.push(Issue::InvalidCell { seq, cell });
+            continue;
+        }
+        if let Some(digit) = entry.action.digit() {
+            if !(1..=9).contains(&digit) {
+                verdict.issues.push(Issue::InvalidDigit { seq, digit });
+                continue;
+            }
+        }
+        if givens[idx] {
+            verdict.issues.push(Issue::GivenModified { seq, cell });
+            continue;
+        }
+
+        let before = board[idx];
+        match entry.action {
+            MoveAction::Place(v) => {
+                if v != answer[idx] {
+                    verdict.mistakes += 1;
+                }
+                board[idx] = Some(v);
+            }
+            MoveAction::Hint(v) => {
+                verdict.hints_used += 1;
+                if v != answer[idx] {
+                    verdict.issues.push(Issue::IncorrectHint {
+                        seq,
+                        cell,
+                        digit: v,
+                    });
+                }
+                board[idx] = Some(v);
+            }
+            MoveAction::Clear(v) => {
+                if board[idx] != Some(v) {
+                    verdict.issues.push(Issue::InconsistentClear {
+                        seq,
+                        cell,
+                        expected: board[idx],
+                        found: v,
+                    });
+                }
+                board[idx] = None;
+            }
+            MoveAction::Undo(v) | MoveAction::Redo(v) => {
+                let (from, to) = match entry.action {
+                    MoveAction::Undo(_) => (&mut undo[idx], &mut redo[idx]),
+                    _ => (&mut redo[idx], &mut undo[idx]),
+                };
+                let expected = from.pop();
+                if expected != Some(v) {
+                    verdict.issues.push(Issue::InconsistentHistory {
+                        seq,
+                        cell,
+                        expected,
+                        found: v,
+                    });
+                }
+                to.push(before);
+                board[idx] = v;
+            }
+        }
+        if matches!(
+            entry.action,
+            MoveAction::Place(_) | MoveAction::Hint(_) | MoveAction::Clear(_)
+        ) {
+            undo[idx].push(before);
+            redo[idx].clear();
+        }
+
+        if !completed && board.iter().zip(answer.iter()).all(|(b, a)| *b == Some(*a)) {
+            completed = true;
+        }
+    }
+
+    verdict.logged_time_ms = previous_ms.max(0) as u64;
+
+    let empty_cells = board.iter().filter(|v| v.is_none()).count();
+    let wrong_cells = board
+        .iter()
+        .zip(answer.iter())
+        .filter(|(b, a)| matches!(b, Some(v) if v != *a))
+        .count();
+    verdict.solved = empty_cells == 0 && wrong_cells == 0;
+    if !verdict.solved {
+        verdict.issues.push(Issue::NotSolved {
+            empty_cells,
+            wrong_cells,
+        });
+    }
+
+    if verdict.mistakes != claim.mistakes {
+        verdict.issues.push(Issue::MistakeCountMismatch {
+            claimed: claim.mistakes,
+            replayed: verdict.mistakes,
+        });
+    }
+    if verdict.hints_used != claim.hints_used {
+        verdict.issues.push(Issue::HintCountMismatch {
+            claimed: claim.hints_used,
+            replayed: verdict.hints_used,
+        });
+    }
+
+    let logged_secs = verdict.logged_time_ms / 1000;
+    if logged_secs.abs_diff(claim.time_secs) > options.time_tolerance_secs {
+        verdict.issues.push(Issue::TimeMismatch {
+            claimed_secs: claim.time_secs,
+            logged_ms: verdict.logged_time_ms,
+        });
+    }
+
+    verdict.valid = verdict.issues.is_empty();
+    verdict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    /// Build a clean log that fills every empty cell correctly, 2s apart
+    fn solving_log() -> Vec<MoveLogEntry> {
+        PUZZLE
+            .bytes()
+            .zip(SOLUTION.bytes())
+            .enumerate()
+            .filter(|(_, (p, _))| *p == b'.')
+            .enumerate()
+            .map(|(seq, (cell, (_, s)))| MoveLogEntry {
+                seq: seq as u32,
+                ms: (seq as i64 + 1) * 2000,
+                cell: cell as u8,
+                action: MoveAction::Place(s - b'0'),
+            })
+            .collect()
+    }
+
+    fn claim_for(log: &[MoveLogEntry]) -> Claim {
+        Claim {
+            time_secs: log.last().map(|e| e.ms as u64 / 1000).unwrap_or(0),
+            mistakes: 0,
+            hints_used: 0,
+        }
+    }
+
+    #[test]
+    fn test_clean_log_is_valid() {
+        let log = solving_log();
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(verdict.valid, "{:?}", verdict.issues);
+        assert!(verdict.solved);
+        assert_eq!(verdict.moves, log.len());
+    }
+
+    #[test]
+    fn test_understated_mistakes_rejected() {
+        let mut log = solving_log();
+        let first = log[0].clone();
+        let wrong = if first.action == MoveAction::Place(1) {
+            2
+        } else {
+            1
+        };
+        log.insert(
+            0,
+            MoveLogEntry {
+                action: MoveAction::Place(wrong),
+                ms: 1000,
+                ..first
+            },
+        );
+        for (i, e) in log.iter_mut().enumerate() {
+            e.seq = i as u32;
+        }
+
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(!verdict.valid);
+        assert_eq!(verdict.mistakes, 1);
+        assert!(verdict.issues.contains(&Issue::MistakeCountMismatch {
+            claimed: 0,
+            replayed: 1
+        }));
+    }
+
+    #[test]
+    fn test_decreasing_and_negative_timestamps_rejected() {
+        let mut log = solving_log();
+        log[0].ms = -5;
+        log[3].ms = 1;
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(verdict
+            .issues
+            .iter()
+            .any(|i| matches!(i, Issue::PlacedBeforeStart { seq: 0, .. })));
+        assert!(verdict
+            .issues
+            .iter()
+            .any(|i| matches!(i, Issue::TimestampDecreased { seq: 3, .. })));
+    }
+
+    #[test]
+    fn test_given_cell_and_time_mismatch_rejected() {
+        let mut log = solving_log();
+        log[0].cell = 0; // a given
+        let mut claim = claim_for(&log);
+        claim.time_secs += 60;
+        let verdict = verify(PUZZLE, &log, &claim);
+        assert!(verdict
+            .issues
+            .contains(&Issue::GivenModified { seq: 0, cell: 0 }));
+        assert!(verdict
+            .issues
+            .iter()
+            .any(|i| matches!(i, Issue::TimeMismatch { .. })));
+        assert!(!verdict.solved);
+    }
+
+    #[test]
+    fn test_forged_redo_rejected() {
+        let mut log = solving_log();
+        // Claim the last cell was filled by redoing a move that never happened
+        let last = log.last_mut().unwrap();
+        let MoveAction::Place(v) = last.action else {
+            unreachable!()
+        };
+        last.action = MoveAction::Redo(Some(v));
+        let cell = last.cell;
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(!verdict.valid);
+        assert!(verdict.issues.contains(&Issue::InconsistentHistory {
+            seq: log.len() as u32 - 1,
+            cell,
+            expected: None,
+            found: Some(v),
+        }));
+    }
+
+    #[test]
+    fn test_undo_and_redo_follow_cell_history() {
+        let mut log = solving_log();
+        let first = log[0].clone();
+        let MoveAction::Place(v) = first.action else {
+            unreachable!()
+        };
+        let extra = [
+            MoveAction::Undo(None),
+            MoveAction::Redo(Some(v)),
+            MoveAction::Undo(None),
+            MoveAction::Redo(Some(v)),
+        ];
+        for (i, action) in extra.into_iter().enumerate() {
+            log.insert(
+                1 + i,
+                MoveLogEntry {
+                    action,
+                    ms: first.ms,
+                    ..first.clone()
+                },
+            );
+        }
+        for (i, e) in log.iter_mut().enumerate() {
+            e.seq = i as u32;
+        }
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(verdict.valid, "{:?}", verdict.issues);
+
+        // Undoing to a value the cell never held is caught
+        log[1].action = MoveAction::Undo(Some(v));
+        let verdict = verify(PUZZLE, &log, &claim_for(&log));
+        assert!(verdict.issues.contains(&Issue::InconsistentHistory {
+            seq: 1,
+            cell: first.cell,
+            expected: Some(None),
+            found: Some(v),
+        }));
+    }
+
+    #[test]
+    fn test_move_log_json_roundtrip() {
+        let json = r#"[{"seq":0,"ms":1200,"cell":2,"action":{"Place":4}},{"seq":1,"ms":1800,"cell":2,"action":{"Undo":null}}]"#;
+        let log = parse_move_log(json).unwrap();
+        assert_eq!(log[0].action, MoveAction::Place(4));
+        assert_eq!(log[1].action, MoveAction::Undo(None));
+    }
+}
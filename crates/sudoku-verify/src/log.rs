@@ -0,0 +1,51 @@
+//! Move log format shared with the clients.
+//!
+//! This mirrors the anti-cheat log the WASM build records (`get_move_log`), so
+//! a log exported by any frontend can be fed straight into the verifier.
+
+use serde::{Deserialize, Serialize};
+
+/// A single timestamped move from a submitted game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveLogEntry {
+    /// 0-indexed sequence number
+    pub seq: u32,
+    /// Milliseconds since game start (pauses excluded).
+    /// Signed so that forged logs with negative offsets can be reported
+    /// instead of failing to parse.
+    pub ms: i64,
+    /// Cell index: row*9 + col (0..80)
+    pub cell: u8,
+    /// What the player did
+    pub action: MoveAction,
+}
+
+/// The action taken on a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveAction {
+    /// Player placed digit 1-9
+    Place(u8),
+    /// Player erased cell (stores old value)
+    Clear(u8),
+    /// Hint system placed digit
+    Hint(u8),
+    /// Undo restored cell to this value (None = cleared)
+    Undo(Option<u8>),
+    /// Redo restored cell to this value (None = cleared)
+    Redo(Option<u8>),
+}
+
+impl MoveAction {
+    /// The digit carried by this action, if any
+    pub fn digit(&self) -> Option<u8> {
+        match *self {
+            MoveAction::Place(v) | MoveAction::Clear(v) | MoveAction::Hint(v) => Some(v),
+            MoveAction::Undo(v) | MoveAction::Redo(v) => v,
+        }
+    }
+}
+
+/// Parse a move log from its JSON array representation
+pub fn parse_move_log(json: &str) -> Result<Vec<MoveLogEntry>, serde_json::Error> {
+    serde_json::from_str(json)
+}
@@ -0,0 +1,451 @@
+//! Behavioral plausibility scoring over a full move sequence.
+//!
+//! Where the replay in [`crate::verify`] proves a result is *possible*, this
+//! module estimates how *human* the sequence looks. Each signal yields a
+//! suspicion in `0.0..=1.0` together with an explanation, and the weighted
+//! signals are folded into a single confidence score.
+
+use crate::log::{MoveAction, MoveLogEntry};
+use serde::{Deserialize, Serialize};
+use sudoku_core::{Difficulty, Grid, Position, Solver};
+
+/// A linear ramp between a value that is fine and a value that is fully
+/// suspicious. `ok` may be above or below `suspicious`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ramp {
+    pub ok: f32,
+    pub suspicious: f32,
+}
+
+impl Ramp {
+    pub const fn new(ok: f32, suspicious: f32) -> Self {
+        Self { ok, suspicious }
+    }
+
+    /// Map a measured value onto `0.0` (fine) ..= `1.0` (suspicious)
+    pub fn suspicion(&self, value: f32) -> f32 {
+        let span = self.suspicious - self.ok;
+        if span == 0.0 {
+            return if value == self.ok { 1.0 } else { 0.0 };
+        }
+        ((value - self.ok) / span).clamp(0.0, 1.0)
+    }
+}
+
+/// Thresholds and weights for every signal.
+///
+/// The defaults, which the clients use as-is:
+///
+/// | signal                 | fine at  | fully suspicious at | weight |
+/// |------------------------|----------|---------------------|--------|
+/// | unreachable placements | 35%      | 80%                 | 1.0    |
+/// | solver order           | 60%      | 95%                 | 1.0    |
+/// | timing spread (CV)     | 0.5      | 0.15                | 1.0    |
+/// | timing skew            | 0.5      | 0.0                 | (same) |
+/// | no notes from Hard up  | -        | -                   | 0.5    |
+///
+/// Signals need at least 10 placements, and a sequence passes from a
+/// confidence of 0.5. A server can load its own thresholds from JSON or TOML;
+/// fields left out keep these defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlausibilityConfig {
+    /// Placements needed before any signal is evaluated
+    pub min_placements: usize,
+    /// Share of correct placements that were not a naked or hidden single
+    pub unreachable_ratio: Ramp,
+    pub unreachable_weight: f32,
+    /// Share of correct placements made on the first single a row-major scan finds
+    pub solver_order_ratio: Ramp,
+    pub solver_order_weight: f32,
+    /// Coefficient of variation of the time between moves
+    pub timing_cv: Ramp,
+    /// Skewness of the time between moves (humans are right-skewed)
+    pub timing_skew: Ramp,
+    pub timing_weight: f32,
+    /// Difficulty from which solving without notes is unusual
+    pub notes_expected_from: Difficulty,
+    pub no_notes_weight: f32,
+    /// Minimum confidence for a sequence to be considered plausible
+    pub pass_confidence: f32,
+}
+
+impl Default for PlausibilityConfig {
+    fn default() -> Self {
+        Self {
+            min_placements: 10,
+            unreachable_ratio: Ramp::new(0.35, 0.8),
+            unreachable_weight: 1.0,
+            solver_order_ratio: Ramp::new(0.6, 0.95),
+            solver_order_weight: 1.0,
+            timing_cv: Ramp::new(0.5, 0.15),
+            timing_skew: Ramp::new(0.5, 0.0),
+            timing_weight: 1.0,
+            notes_expected_from: Difficulty::Hard,
+            no_notes_weight: 0.5,
+            pass_confidence: 0.5,
+        }
+    }
+}
+
+/// Which behavior a signal measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    UnreachablePlacements,
+    SolverOrder,
+    TimingShape,
+    NoNotes,
+}
+
+/// One evaluated signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signal {
+    pub kind: SignalKind,
+    /// The raw measurement
+    pub value: f32,
+    /// `0.0` (human-like) ..= `1.0` (bot-like)
+    pub suspicion: f32,
+    pub weight: f32,
+    /// Human-readable reason for the suspicion
+    pub explanation: String,
+}
+
+/// Outcome of scoring a move sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlausibilityReport {
+    /// `1.0` = clearly human, `0.0` = clearly automated
+    pub confidence: f32,
+    /// Whether `confidence` reaches the configured pass mark
+    pub plausible: bool,
+    /// Signals that were evaluated (empty when there was too little data)
+    pub signals: Vec<Signal>,
+}
+
+/// Context about the game the log belongs to
+#[derive(Debug, Clone, Copy)]
+pub struct GameContext<'a> {
+    pub puzzle: &'a str,
+    pub difficulty: Difficulty,
+    pub notes_used: bool,
+}
+
+/// Score `log` with the default thresholds
+pub fn score(ctx: &GameContext, log: &[MoveLogEntry]) -> PlausibilityReport {
+    score_with(ctx, log, &PlausibilityConfig::default())
+}
+
+/// Score `log` with explicit thresholds
+pub fn score_with(
+    ctx: &GameContext,
+    log: &[MoveLogEntry],
+    config: &PlausibilityConfig,
+) -> PlausibilityReport {
+    let mut signals = Vec::new();
+
+    if let Some((placements, unreachable, in_order)) = replay_placements(ctx.puzzle, log) {
+        if placements >= config.min_placements {
+            let ratio = unreachable as f32 / placements as f32;
+            signals.push(Signal {
+                kind: SignalKind::UnreachablePlacements,
+                value: ratio,
+                suspicion: config.unreachable_ratio.suspicion(ratio),
+                weight: config.unreachable_weight,
+                explanation: format!(
+                    "{} of {} placements were not reachable with singles at the time",
+                    unreachable, placements
+                ),
+            });
+
+            let ratio = in_order as f32 / placements as f32;
+            signals.push(Signal {
+                kind: SignalKind::SolverOrder,
+                value: ratio,
+                suspicion: config.solver_order_ratio.suspicion(ratio),
+                weight: config.solver_order_weight,
+                explanation: format!(
+                    "{} of {} placements followed the solver's scan order",
+                    in_order, placements
+                ),
+            });
+
+            if let Some((cv, skew)) = timing_shape(log) {
+                let suspicion = config
+                    .timing_cv
+                    .suspicion(cv)
+                    .max(config.timing_skew.suspicion(skew));
+                signals.push(Signal {
+                    kind: SignalKind::TimingShape,
+                    value: cv,
+                    suspicion,
+                    weight: config.timing_weight,
+                    explanation: format!(
+                        "Move intervals have variation {:.2} and skew {:.2}",
+                        cv, skew
+                    ),
+                });
+            }
+        }
+    }
+
+    if ctx.difficulty >= config.notes_expected_from {
+        let suspicion = if ctx.notes_used { 0.0 } else { 1.0 };
+        signals.push(Signal {
+            kind: SignalKind::NoNotes,
+            value: suspicion,
+            suspicion,
+            weight: config.no_notes_weight,
+            explanation: if ctx.notes_used {
+                format!("Notes were used on {}", ctx.difficulty)
+            } else {
+                format!("No notes were taken on {}", ctx.difficulty)
+            },
+        });
+    }
+
+    let total_weight: f32 = signals.iter().map(|s| s.weight).sum();
+    let confidence = if total_weight > 0.0 {
+        1.0 - signals.iter().map(|s| s.suspicion * s.weight).sum::<f32>() / total_weight
+    } else {
+        1.0
+    };
+
+    PlausibilityReport {
+        confidence,
+        plausible: confidence >= config.pass_confidence,
+        signals,
+    }
+}
+
+/// Replay the log and count, over correct player placements:
+/// the total, those that were not a single, and those made on the first
+/// single a row-major scan would find.
+fn replay_placements(puzzle: &str, log: &[MoveLogEntry]) -> Option<(usize, usize, usize)> {
+    let grid = Grid::from_string(puzzle)?;
+    let solution = Solver::new().solve(&grid)?;
+
+    let mut board = [0u8; 81];
+    let mut answer = [0u8; 81];
+    for idx in 0..81 {
+        let pos = Position::new(idx / 9, idx % 9);
+        board[idx] = grid.get(pos).unwrap_or(0);
+        answer[idx] = solution.get(pos).unwrap_or(0);
+    }
+
+    let (mut placements, mut unreachable, mut in_order) = (0, 0, 0);
+    for entry in log {
+        let idx = entry.cell as usize;
+        if idx >= 81 {
+            continue;
+        }
+        match entry.action {
+            MoveAction::Place(v) => {
+                if v == answer[idx] && board[idx] == 0 {
+                    placements += 1;
+                    if !is_single(&board, idx, v) {
+                        unreachable += 1;
+                    }
+                    if first_single(&board) == Some(idx) {
+                        in_order += 1;
+                    }
+                }
+                board[idx] = v;
+            }
+            MoveAction::Hint(v) => board[idx] = v,
+            MoveAction::Clear(_) => board[idx] = 0,
+            MoveAction::Undo(v) | MoveAction::Redo(v) => board[idx] = v.unwrap_or(0),
+        }
+    }
+
+    Some((placements, unreachable, in_order))
+}
+
+/// Indices of the row, column and box containing `idx`
+fn units(idx: usize) -> [[usize; 9]; 3] {
+    let (row, col) = (idx / 9, idx % 9);
+    let (box_row, box_col) = (row / 3 * 3, col / 3 * 3);
+    [
+        std::array::from_fn(|i| row * 9 + i),
+        std::array::from_fn(|i| i * 9 + col),
+        std::array::from_fn(|i| (box_row + i / 3) * 9 + box_col + i % 3),
+    ]
+}
+
+/// Bitmask of digits that can still go in `idx`
+fn candidates(board: &[u8; 81], idx: usize) -> u16 {
+    let mut mask = 0b11_1111_1110u16;
+    for unit in units(idx) {
+        for peer in unit {
+            mask &= !(1 << board[peer]);
+        }
+    }
+    mask
+}
+
+/// Whether `v` at `idx` is a naked or hidden single on `board`
+fn is_single(board: &[u8; 81], idx: usize, v: u8) -> bool {
+    let cands = candidates(board, idx);
+    if cands == 1 << v {
+        return true;
+    }
+    units(idx).iter().any(|unit| {
+        unit.iter()
+            .filter(|&&peer| board[peer] == 0 && candidates(board, peer) & (1 << v) != 0)
+            .count()
+            == 1
+    })
+}
+
+/// The first empty cell in row-major order that holds a single
+fn first_single(board: &[u8; 81]) -> Option<usize> {
+    (0..81).find(|&idx| {
+        board[idx] == 0 && {
+            let cands = candidates(board, idx);
+            (1..=9).any(|v| cands & (1 << v) != 0 && is_single(board, idx, v))
+        }
+    })
+}
+
+/// Coefficient of variation and skewness of the gaps between moves
+fn timing_shape(log: &[MoveLogEntry]) -> Option<(f32, f32)> {
+    let mut previous = 0i64;
+    let gaps: Vec<f32> = log
+        .iter()
+        .map(|e| {
+            let gap = (e.ms - previous).max(0) as f32;
+            previous = e.ms;
+            gap
+        })
+        .collect();
+    if gaps.len() < 3 {
+        return None;
+    }
+
+    let n = gaps.len() as f32;
+    let mean = gaps.iter().sum::<f32>() / n;
+    if mean <= 0.0 {
+        return Some((0.0, 0.0));
+    }
+    let variance = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f32>() / n;
+    let std_dev = variance.sqrt();
+    if std_dev == 0.0 {
+        return Some((0.0, 0.0));
+    }
+    let skew = gaps
+        .iter()
+        .map(|g| ((g - mean) / std_dev).powi(3))
+        .sum::<f32>()
+        / n;
+
+    Some((std_dev / mean, skew))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    /// Fill every empty cell in row-major order, with `gap(i)` ms between moves
+    fn scan_order_log(gap: impl Fn(usize) -> i64) -> Vec<MoveLogEntry> {
+        let mut ms = 0;
+        PUZZLE
+            .bytes()
+            .zip(SOLUTION.bytes())
+            .enumerate()
+            .filter(|(_, (p, _))| *p == b'.')
+            .enumerate()
+            .map(|(seq, (cell, (_, s)))| {
+                ms += gap(seq);
+                MoveLogEntry {
+                    seq: seq as u32,
+                    ms,
+                    cell: cell as u8,
+                    action: MoveAction::Place(s - b'0'),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ramp_direction() {
+        let up = Ramp::new(0.2, 0.8);
+        assert_eq!(up.suspicion(0.1), 0.0);
+        assert_eq!(up.suspicion(0.9), 1.0);
+        let down = Ramp::new(0.5, 0.1);
+        assert_eq!(down.suspicion(0.6), 0.0);
+        assert_eq!(down.suspicion(0.0), 1.0);
+    }
+
+    #[test]
+    fn test_config_fields_default_when_left_out() {
+        let config: PlausibilityConfig =
+            serde_json::from_str(r#"{"pass_confidence": 0.8, "timing_weight": 2.0}"#).unwrap();
+        assert_eq!(config.pass_confidence, 0.8);
+        assert_eq!(config.timing_weight, 2.0);
+        let defaults = PlausibilityConfig::default();
+        assert_eq!(config.min_placements, defaults.min_placements);
+        assert_eq!(config.unreachable_ratio, defaults.unreachable_ratio);
+    }
+
+    #[test]
+    fn test_metronome_bot_scores_low() {
+        let log = scan_order_log(|_| 200);
+        let ctx = GameContext {
+            puzzle: PUZZLE,
+            difficulty: Difficulty::Hard,
+            notes_used: false,
+        };
+        let report = score(&ctx, &log);
+        assert!(!report.plausible, "confidence {}", report.confidence);
+        let timing = report
+            .signals
+            .iter()
+            .find(|s| s.kind == SignalKind::TimingShape)
+            .unwrap();
+        assert_eq!(timing.suspicion, 1.0);
+    }
+
+    #[test]
+    fn test_irregular_timing_with_notes_scores_higher() {
+        let bot = score(
+            &GameContext {
+                puzzle: PUZZLE,
+                difficulty: Difficulty::Hard,
+                notes_used: false,
+            },
+            &scan_order_log(|_| 200),
+        );
+        let human = score(
+            &GameContext {
+                puzzle: PUZZLE,
+                difficulty: Difficulty::Hard,
+                notes_used: true,
+            },
+            &scan_order_log(|i| {
+                if i % 7 == 0 {
+                    25_000
+                } else {
+                    2_000 + (i as i64 % 5) * 900
+                }
+            }),
+        );
+        assert!(human.confidence > bot.confidence);
+    }
+
+    #[test]
+    fn test_short_logs_skip_sequence_signals() {
+        let log = scan_order_log(|_| 1000);
+        let ctx = GameContext {
+            puzzle: PUZZLE,
+            difficulty: Difficulty::Easy,
+            notes_used: false,
+        };
+        let report = score(&ctx, &log[..3]);
+        assert!(report.signals.is_empty());
+        assert_eq!(report.confidence, 1.0);
+    }
+}
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use sudoku_core::{
     BitSet, Difficulty, Generator, Grid, Hint, HintType, Position, PuzzleId, Solver,
 };
+use sudoku_shared::achievements::{self, AchievementState, Reward, Unlock};
+use sudoku_shared::records::{self, GameRecord, MergeSummary, Outcome};
 
 /// Maximum mistakes before game over
 pub const MAX_MISTAKES: usize = 3;
@@ -12,6 +14,12 @@ pub const MAX_MISTAKES: usize = 3;
 /// Estimated total puzzles in the puzzle universe (~10^30)
 pub const TOTAL_PUZZLE_UNIVERSE: f64 = 1e30;
 
+/// Finished games kept in `PlayerStats::history`
+pub const MAX_HISTORY: usize = 1000;
+
+/// Layout version of the stats JSON (see `PlayerStats::migrate`)
+pub const STATS_VERSION: u32 = 1;
+
 /// Level of hint detail shown to the player
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HintDetailLevel {
@@ -68,13 +76,83 @@ pub enum ScreenState {
     Loading,
 }
 
-/// Player statistics for lifetime tracking
+/// Statistics for one difficulty
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DifficultyStats {
+    pub total_games: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub abandoned: usize,
+    pub best_time_secs: Option<u64>,
+    pub worst_time_secs: Option<u64>,
+    /// Time spent on won games
+    pub total_time_secs: u64,
+    pub total_hints: usize,
+    pub total_mistakes: usize,
+}
+
+impl DifficultyStats {
+    pub fn avg_time_secs(&self) -> Option<u64> {
+        if self.wins > 0 {
+            Some(self.total_time_secs / self.wins as u64)
+        } else {
+            None
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.total_games > 0 {
+            self.wins as f64 / self.total_games as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    fn add(&mut self, record: &GameRecord) {
+        self.total_games += 1;
+        self.total_hints += record.hints_used;
+        self.total_mistakes += record.mistakes;
+        match record.result {
+            Outcome::Win => {
+                self.wins += 1;
+                self.total_time_secs += record.time_secs;
+                self.best_time_secs = Some(
+                    self.best_time_secs
+                        .map_or(record.time_secs, |b| b.min(record.time_secs)),
+                );
+                self.worst_time_secs = Some(
+                    self.worst_time_secs
+                        .map_or(record.time_secs, |w| w.max(record.time_secs)),
+                );
+            }
+            Outcome::Loss => self.losses += 1,
+            Outcome::Abandoned => self.abandoned += 1,
+        }
+    }
+}
+
+/// Player statistics for lifetime tracking
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerStats {
-    /// Total games played
+    /// Layout version; 0 for stats saved before it existed
+    #[serde(default)]
+    pub version: u32,
+    /// Total games played, including lost and abandoned ones
     pub games_played: u32,
     /// Total games won
     pub games_won: u32,
+    /// Total games lost (too many mistakes)
+    #[serde(default)]
+    pub games_lost: u32,
+    /// Games left unfinished for a new one
+    #[serde(default)]
+    pub games_abandoned: u32,
+    /// Hints used over all games
+    #[serde(default)]
+    pub total_hints: u64,
+    /// Mistakes made over all games
+    #[serde(default)]
+    pub total_mistakes: u64,
     /// Total play time in seconds
     pub total_play_time_secs: u64,
     /// Current win streak
@@ -83,30 +161,175 @@ pub struct PlayerStats {
     pub best_streak: u32,
     /// Best times by difficulty (in seconds)
     pub best_times: std::collections::HashMap<String, u32>,
+    /// Per-difficulty stats, keyed like `best_times`
+    #[serde(default)]
+    pub by_difficulty: std::collections::HashMap<String, DifficultyStats>,
+    /// Finished games, oldest first, in the portable export format
+    #[serde(default)]
+    pub history: Vec<GameRecord>,
+    /// Progress toward achievements (empty in stats saved before they existed)
+    #[serde(default)]
+    pub achievements: AchievementState,
 }
 
-impl PlayerStats {
-    /// Record a game completion
-    pub fn record_game(&mut self, won: bool, difficulty: Difficulty, time_secs: u32) {
-        self.games_played += 1;
-        self.total_play_time_secs += time_secs as u64;
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            version: STATS_VERSION,
+            games_played: 0,
+            games_won: 0,
+            games_lost: 0,
+            games_abandoned: 0,
+            total_hints: 0,
+            total_mistakes: 0,
+            total_play_time_secs: 0,
+            current_streak: 0,
+            best_streak: 0,
+            best_times: Default::default(),
+            by_difficulty: Default::default(),
+            history: Vec::new(),
+            achievements: AchievementState::default(),
+        }
+    }
+}
 
-        if won {
-            self.games_won += 1;
+impl PlayerStats {
+    /// Record a game completion. Returns the achievement tiers it reached.
+    pub fn record_game(&mut self, record: GameRecord) -> Vec<Unlock> {
+        self.tally(&record);
+        let unlocks = self.achievements.record_game(&record);
+        if record.result == Outcome::Win {
             self.current_streak += 1;
             if self.current_streak > self.best_streak {
                 self.best_streak = self.current_streak;
             }
-
-            // Update best time for difficulty
-            let diff_key = format!("{:?}", difficulty);
-            let entry = self.best_times.entry(diff_key).or_insert(u32::MAX);
-            if time_secs < *entry {
-                *entry = time_secs;
-            }
         } else {
             self.current_streak = 0;
         }
+        self.push_history(vec![record]);
+        unlocks
+    }
+
+    /// Add a game to the totals, best times and per-difficulty stats
+    /// (everything but streaks)
+    fn tally(&mut self, record: &GameRecord) {
+        self.games_played += 1;
+        self.total_play_time_secs += record.time_secs;
+        self.total_hints += record.hints_used as u64;
+        self.total_mistakes += record.mistakes as u64;
+        self.by_difficulty
+            .entry(format!("{:?}", record.difficulty))
+            .or_default()
+            .add(record);
+
+        match record.result {
+            Outcome::Win => {
+                self.games_won += 1;
+
+                // Update best time for difficulty
+                let time_secs = record.time_secs.min(u32::MAX as u64) as u32;
+                let diff_key = format!("{:?}", record.difficulty);
+                let entry = self.best_times.entry(diff_key).or_insert(u32::MAX);
+                if time_secs < *entry {
+                    *entry = time_secs;
+                }
+            }
+            Outcome::Loss => self.games_lost += 1,
+            Outcome::Abandoned => self.games_abandoned += 1,
+        }
+    }
+
+    fn push_history(&mut self, records: Vec<GameRecord>) {
+        self.history.extend(records);
+        self.history.sort_by_key(|r| r.timestamp);
+        let excess = self.history.len().saturating_sub(MAX_HISTORY);
+        self.history.drain(..excess);
+    }
+
+    /// Merge games exported from any platform (see `sudoku_shared::records`),
+    /// skipping ones already in the history, and update the aggregates
+    pub fn import(&mut self, incoming: Vec<GameRecord>) -> MergeSummary {
+        let (added, summary) = records::merge(&self.history, incoming);
+        for record in &added {
+            self.tally(record);
+            self.achievements.record_game(record);
+        }
+        self.push_history(added);
+
+        // Streaks follow the merged timeline
+        let mut run = 0;
+        for record in &self.history {
+            run = if record.result == Outcome::Win {
+                run + 1
+            } else {
+                0
+            };
+            self.best_streak = self.best_streak.max(run);
+        }
+        self.current_streak = run;
+        summary
+    }
+
+    /// Upgrade stats saved by an older version. Version 0 only counted games
+    /// played and won; the rest is rebuilt from the history, which misses
+    /// games older than the last `MAX_HISTORY` (or from before it was kept).
+    fn migrate(&mut self) {
+        if self.version >= STATS_VERSION {
+            return;
+        }
+        let mut by_difficulty = std::collections::HashMap::<String, DifficultyStats>::new();
+        for record in &self.history {
+            by_difficulty
+                .entry(format!("{:?}", record.difficulty))
+                .or_default()
+                .add(record);
+        }
+        // Best times predate the history and may be better than any kept game
+        for (key, &best) in &self.best_times {
+            let entry = by_difficulty.entry(key.clone()).or_default();
+            entry.best_time_secs = Some(
+                entry
+                    .best_time_secs
+                    .map_or(best as u64, |b| b.min(best as u64)),
+            );
+        }
+        self.games_abandoned = self.count(Outcome::Abandoned);
+        self.games_lost = self
+            .games_played
+            .saturating_sub(self.games_won + self.games_abandoned);
+        self.total_hints = self.history.iter().map(|r| r.hints_used as u64).sum();
+        self.total_mistakes = self.history.iter().map(|r| r.mistakes as u64).sum();
+        self.by_difficulty = by_difficulty;
+        self.version = STATS_VERSION;
+    }
+
+    fn count(&self, result: Outcome) -> u32 {
+        self.history.iter().filter(|r| r.result == result).count() as u32
+    }
+
+    /// Work out achievement progress for stats saved before achievements
+    /// existed
+    fn backfill_achievements(&mut self) {
+        if self.achievements.is_empty() && !self.history.is_empty() {
+            self.achievements = AchievementState::from_games(&self.history);
+        }
+    }
+
+    /// Per-difficulty stats for the difficulties played, easiest first
+    pub fn difficulty_breakdown(&self) -> Vec<(Difficulty, &DifficultyStats)> {
+        [
+            Difficulty::Beginner,
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Intermediate,
+            Difficulty::Hard,
+            Difficulty::Expert,
+            Difficulty::Master,
+            Difficulty::Extreme,
+        ]
+        .into_iter()
+        .filter_map(|d| Some((d, self.by_difficulty.get(&format!("{:?}", d))?)))
+        .collect()
     }
 
     /// Calculate average solve time (for wins only)
@@ -282,6 +505,10 @@ pub struct GameState {
     move_seq: u32,
     /// Deferred new-game request (difficulty the host should generate asynchronously)
     pending_new_game: Option<Difficulty>,
+    /// Achievement tiers reached that JS hasn't collected yet
+    achievement_unlocks: Vec<Unlock>,
+    /// Achievement messages waiting to be shown on the canvas
+    announcements: Vec<String>,
 }
 
 /// Konami code sequence: Up Up Down Down Left Right Left Right B A
@@ -344,6 +571,8 @@ impl GameState {
             move_log: Vec::new(),
             move_seq: 0,
             pending_new_game: None,
+            achievement_unlocks: Vec::new(),
+            announcements: Vec::new(),
         }
     }
 
@@ -404,6 +633,8 @@ impl GameState {
             move_log: Vec::new(),
             move_seq: 0,
             pending_new_game: None,
+            achievement_unlocks: Vec::new(),
+            announcements: Vec::new(),
         })
     }
 
@@ -453,6 +684,8 @@ impl GameState {
             move_log: Vec::new(),
             move_seq: 0,
             pending_new_game: None,
+            achievement_unlocks: Vec::new(),
+            announcements: Vec::new(),
         })
     }
 
@@ -502,6 +735,8 @@ impl GameState {
             move_log: Vec::new(),
             move_seq: 0,
             pending_new_game: None,
+            achievement_unlocks: Vec::new(),
+            announcements: Vec::new(),
         })
     }
 
@@ -580,6 +815,10 @@ impl GameState {
                 self.message = None;
             }
         }
+        if self.message.is_none() && !self.announcements.is_empty() {
+            let announcement = self.announcements.remove(0);
+            self.show_message(&announcement);
+        }
 
         // Check win/lose conditions
         if self.screen == ScreenState::Playing {
@@ -588,10 +827,11 @@ impl GameState {
                 self.screen = ScreenState::Win;
                 // Record the win
                 if !self.game_recorded {
-                    self.player_stats
-                        .record_game(true, self.difficulty, self.elapsed_secs());
+                    let unlocks = self
+                        .player_stats
+                        .record_game(self.finished_record(Outcome::Win));
                     self.game_recorded = true;
-                    self.check_gameplay_unlock();
+                    self.announce(unlocks);
                 }
                 // Create win screen animation
                 let seed = (Self::now() * 1000.0) as u64;
@@ -601,9 +841,11 @@ impl GameState {
                 self.screen = ScreenState::Lose;
                 // Record the loss
                 if !self.game_recorded {
-                    self.player_stats
-                        .record_game(false, self.difficulty, self.elapsed_secs());
+                    let unlocks = self
+                        .player_stats
+                        .record_game(self.finished_record(Outcome::Loss));
                     self.game_recorded = true;
+                    self.announce(unlocks);
                 }
                 // Create lose screen animation
                 let seed = (Self::now() * 1000.0) as u64;
@@ -707,14 +949,16 @@ impl GameState {
 
     /// Check Konami code progress. Returns true if the key was consumed.
     fn check_konami(&mut self, key: &str) -> bool {
-        if self.secrets_unlocked {
-            return false;
-        }
         if key == KONAMI_SEQUENCE[self.konami_progress] {
             self.konami_progress += 1;
             if self.konami_progress >= KONAMI_SEQUENCE.len() {
                 self.konami_progress = 0;
-                self.secrets_unlocked = true;
+                let now = (js_sys::Date::now() / 1000.0) as u64;
+                let unlocks = self
+                    .player_stats
+                    .achievements
+                    .trigger(achievements::events::KONAMI, now);
+                self.announce(unlocks);
                 self.show_message("Secrets unlocked! 7: Master  8: Extreme");
                 return true;
             }
@@ -1084,10 +1328,24 @@ impl GameState {
     /// Set a deferred new-game request and show the loading screen.
     /// The host (JS/Swift) should poll `take_pending_new_game()` and provide puzzle data.
     fn request_new_game(&mut self, difficulty: Difficulty) {
+        self.abandon();
         self.pending_new_game = Some(difficulty);
         self.screen = ScreenState::Loading;
     }
 
+    /// Count the current game as abandoned if the player made a move and it
+    /// hasn't ended yet. Call before replacing it with another game.
+    pub fn abandon(&mut self) {
+        if self.game_recorded || self.move_log.is_empty() {
+            return;
+        }
+        let unlocks = self
+            .player_stats
+            .record_game(self.finished_record(Outcome::Abandoned));
+        self.game_recorded = true;
+        self.announce(unlocks);
+    }
+
     /// Take (and clear) the pending new-game difficulty, if any.
     pub fn take_pending_new_game(&mut self) -> Option<Difficulty> {
         self.pending_new_game.take()
@@ -1144,15 +1402,59 @@ impl GameState {
         })
     }
 
+    /// Record of the game that just ended, for the stats history
+    fn finished_record(&self, result: Outcome) -> GameRecord {
+        let puzzle = self.puzzle_string();
+        GameRecord {
+            timestamp: (js_sys::Date::now() / 1000.0) as u64,
+            difficulty: self.difficulty,
+            result,
+            time_secs: self.elapsed_secs() as u64,
+            hints_used: self.hints_used,
+            mistakes: self.mistakes,
+            puzzle_hash: sudoku_core::canonical_puzzle_hash_str(&puzzle),
+            puzzle,
+            short_code: self.short_code(),
+            verified: false,
+            source: Some("web".to_string()),
+        }
+    }
+
     /// Get player stats as JSON for persistence
     pub fn stats_json(&self) -> String {
         serde_json::to_string(&self.player_stats).unwrap_or_default()
     }
 
+    /// Merge imported game records into the player stats
+    pub fn import_stats(&mut self, records: Vec<GameRecord>) -> MergeSummary {
+        let summary = self.player_stats.import(records);
+        self.check_gameplay_unlock();
+        summary
+    }
+
+    /// Queue newly reached achievement tiers for JS and the canvas, and
+    /// grant their rewards
+    fn announce(&mut self, unlocks: Vec<Unlock>) {
+        self.announcements.extend(
+            unlocks
+                .iter()
+                .map(|u| format!("Achievement unlocked: {}", u)),
+        );
+        self.achievement_unlocks.extend(unlocks);
+        self.check_gameplay_unlock();
+    }
+
+    /// Take the achievement tiers reached since the last call
+    pub fn take_achievement_unlocks(&mut self) -> Vec<Unlock> {
+        std::mem::take(&mut self.achievement_unlocks)
+    }
+
     /// Load player stats from JSON
     pub fn load_stats_json(&mut self, json: &str) -> bool {
         if let Ok(stats) = serde_json::from_str(json) {
             self.player_stats = stats;
+            self.player_stats.migrate();
+            self.player_stats.backfill_achievements();
             self.check_gameplay_unlock();
             true
         } else {
@@ -1347,18 +1649,17 @@ impl GameState {
             move_log: Vec::new(),
             move_seq: 0,
             pending_new_game: None,
+            achievement_unlocks: Vec::new(),
+            announcements: Vec::new(),
         }
     }
 
-    /// Auto-unlock secret difficulties based on gameplay achievements.
-    /// Beating Expert (or higher) unlocks Master and Extreme.
+    /// Unlock secret difficulties once an achievement grants them
     fn check_gameplay_unlock(&mut self) {
-        if self.secrets_unlocked {
-            return;
-        }
-        if self.player_stats.best_times.contains_key("Expert")
-            || self.player_stats.best_times.contains_key("Master")
-            || self.player_stats.best_times.contains_key("Extreme")
+        if self
+            .player_stats
+            .achievements
+            .has_reward(Reward::SecretDifficulties)
         {
             self.secrets_unlocked = true;
         }
@@ -4,6 +4,7 @@
 //! like the terminal UI version.
 
 use sudoku_core::{canonical_puzzle_hash_str, Difficulty, PuzzleId, Solver};
+use sudoku_shared::themes::ThemeFile;
 use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, KeyboardEvent};
 
@@ -124,7 +125,13 @@ impl SudokuGame {
     /// Start a new game with specified difficulty
     #[wasm_bindgen]
     pub fn new_game(&mut self, difficulty: &str) {
-        self.state = GameState::new(parse_difficulty(difficulty));
+        self.state.abandon();
+        let stats = self.state.player_stats().clone();
+        self.state = GameState::new_preserving(
+            parse_difficulty(difficulty),
+            stats,
+            self.state.secrets_unlocked(),
+        );
         self.render();
     }
 
@@ -132,7 +139,8 @@ impl SudokuGame {
     #[wasm_bindgen]
     pub fn load_puzzle_string(&mut self, puzzle: &str) -> bool {
         if let Some(mut new_state) = GameState::from_puzzle_string(puzzle) {
-            // Preserve player stats
+            // Preserve player stats, counting the replaced game as abandoned
+            self.state.abandon();
             new_state.load_stats_json(&self.state.stats_json());
             self.state = new_state;
             self.render();
@@ -152,6 +160,7 @@ impl SudokuGame {
     #[wasm_bindgen]
     pub fn load_short_code(&mut self, code: &str) -> bool {
         if let Some(mut new_state) = GameState::from_short_code(code) {
+            self.state.abandon();
             new_state.load_stats_json(&self.state.stats_json());
             self.state = new_state;
             self.render();
@@ -170,14 +179,29 @@ impl SudokuGame {
     /// Set the color theme
     #[wasm_bindgen]
     pub fn set_theme(&mut self, theme_name: &str) {
-        self.theme = match theme_name {
-            "light" | "ukodus" => Theme::light(),
-            "high_contrast" => Theme::high_contrast(),
-            _ => Theme::dark(),
-        };
+        self.theme = Theme::named(theme_name).unwrap_or_else(Theme::dark);
         self.render();
     }
 
+    /// Apply a theme file in the shared JSON format. Returns
+    /// `{"name": ...}` on success or `{"error": ...}`, keeping the current
+    /// theme.
+    #[wasm_bindgen]
+    pub fn set_theme_json(&mut self, json: &str) -> String {
+        let result = match ThemeFile::from_json(json).and_then(|file| {
+            let theme = Theme::from_file(&file)?;
+            Ok((file, theme))
+        }) {
+            Ok((file, theme)) => {
+                self.theme = theme;
+                self.render();
+                serde_json::json!({ "name": file.name.unwrap_or_else(|| "Custom".into()) })
+            }
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        result.to_string()
+    }
+
     /// Get current game state as JSON
     #[wasm_bindgen]
     pub fn get_state_json(&self) -> String {
@@ -208,6 +232,34 @@ impl SudokuGame {
         self.state.load_stats_json(json)
     }
 
+    /// Export game history as CSV
+    #[wasm_bindgen]
+    pub fn export_stats_csv(&self) -> String {
+        sudoku_shared::records::to_csv(&self.state.player_stats().history)
+    }
+
+    /// Export game history as JSON (schema "sudoku-game-records")
+    #[wasm_bindgen]
+    pub fn export_stats_json(&self) -> String {
+        let now = (js_sys::Date::now() / 1000.0) as u64;
+        sudoku_shared::records::to_json(&self.state.player_stats().history, "web", now)
+    }
+
+    /// Merge game history exported from the TUI, web or iOS builds (CSV or
+    /// JSON). Returns `{"added": n, "duplicates": n}` or `{"error": "..."}`.
+    #[wasm_bindgen]
+    pub fn import_stats(&mut self, data: &str) -> String {
+        let result = match sudoku_shared::records::parse(data) {
+            Ok(records) => {
+                let summary = self.state.import_stats(records);
+                serde_json::to_value(summary).unwrap_or_default()
+            }
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        self.render();
+        result.to_string()
+    }
+
     /// Get games won count
     #[wasm_bindgen]
     pub fn games_won(&self) -> u32 {
@@ -274,6 +326,28 @@ impl SudokuGame {
         self.state.move_log_json()
     }
 
+    /// Progress toward every achievement as a JSON array (hidden ones have
+    /// no name or description until reached)
+    #[wasm_bindgen]
+    pub fn achievements_json(&self) -> String {
+        let progress: Vec<_> = self
+            .state
+            .player_stats()
+            .achievements
+            .progress()
+            .iter()
+            .map(|p| p.summary())
+            .collect();
+        serde_json::to_string(&progress).unwrap_or_default()
+    }
+
+    /// Achievement tiers reached since the last call, as a JSON array of
+    /// `{id, name, description, tier, tiers, reward}`
+    #[wasm_bindgen]
+    pub fn take_achievement_unlocks(&mut self) -> String {
+        serde_json::to_string(&self.state.take_achievement_unlocks()).unwrap_or_default()
+    }
+
     /// Check if secret difficulties (Master/Extreme) are unlocked
     #[wasm_bindgen]
     pub fn is_secrets_unlocked(&self) -> bool {
@@ -325,7 +399,9 @@ impl SudokuGame {
         if let Some(mut new_state) =
             GameState::from_pregenerated(puzzle_str, solution_str, diff, se_rating)
         {
-            // Preserve player stats and secrets
+            // Preserve player stats and secrets, counting the replaced game
+            // as abandoned
+            self.state.abandon();
             new_state.load_stats_json(&self.state.stats_json());
             if self.state.secrets_unlocked() {
                 new_state.set_secrets_unlocked(true);
@@ -1012,6 +1012,26 @@ fn render_stats_screen(
     ctx.set_text_align("left");
     cy += line_height * 0.8;
 
+    let _ = ctx.fill_text("Lost / Abandoned:", left_x, cy);
+    ctx.set_text_align("right");
+    let _ = ctx.fill_text(
+        &format!("{} / {}", stats.games_lost, stats.games_abandoned),
+        right_x,
+        cy,
+    );
+    ctx.set_text_align("left");
+    cy += line_height * 0.8;
+
+    let _ = ctx.fill_text("Hints / Mistakes:", left_x, cy);
+    ctx.set_text_align("right");
+    let _ = ctx.fill_text(
+        &format!("{} / {}", stats.total_hints, stats.total_mistakes),
+        right_x,
+        cy,
+    );
+    ctx.set_text_align("left");
+    cy += line_height * 0.8;
+
     let _ = ctx.fill_text("Total Play Time:", left_x, cy);
     ctx.set_text_align("right");
     let _ = ctx.fill_text(&stats.total_time_formatted(), right_x, cy);
@@ -1042,6 +1062,49 @@ fn render_stats_screen(
     ctx.set_text_align("left");
     cy += line_height * 1.2;
 
+    // By difficulty section
+    let breakdown = stats.difficulty_breakdown();
+    if !breakdown.is_empty() {
+        ctx.set_font(&format!(
+            "bold {}px 'JetBrains Mono', monospace",
+            font_size * 0.9
+        ));
+        ctx.set_fill_style_str(&theme.win_color.as_css());
+        let _ = ctx.fill_text("By Difficulty", left_x, cy);
+        cy += line_height;
+
+        ctx.set_font(&format!(
+            "{}px 'JetBrains Mono', monospace",
+            small_font * 0.85
+        ));
+        ctx.set_fill_style_str(&theme.candidate_text.as_css());
+        let _ = ctx.fill_text(
+            &format!(
+                "{:<12} {:>9} {:>5} {:>7} {:>7} {:>5}",
+                "", "W/L/A", "Win%", "Best", "Avg", "H/M"
+            ),
+            left_x,
+            cy,
+        );
+        cy += line_height * 0.7;
+
+        ctx.set_fill_style_str(&theme.info_text.as_css());
+        for (difficulty, d) in breakdown {
+            let row = format!(
+                "{:<12} {:>9} {:>4.0}% {:>7} {:>7} {:>5}",
+                format!("{:?}", difficulty),
+                format!("{}/{}/{}", d.wins, d.losses, d.abandoned),
+                d.win_rate(),
+                d.best_time_secs.map_or("-".to_string(), format_duration),
+                d.avg_time_secs().map_or("-".to_string(), format_duration),
+                format!("{}/{}", d.total_hints, d.total_mistakes),
+            );
+            let _ = ctx.fill_text(&row, left_x, cy);
+            cy += line_height * 0.7;
+        }
+        cy += line_height * 0.5;
+    }
+
     // Puzzle Universe section
     ctx.set_font(&format!(
         "bold {}px 'JetBrains Mono', monospace",
@@ -1123,6 +1186,15 @@ fn render_stats_screen(
     let _ = ctx.fill_text("Press Escape or S to return", w / 2.0, h - 40.0);
 }
 
+/// `m:ss`, or `h:mm:ss` from an hour up
+fn format_duration(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
 /// Render hint info panel below the grid
 fn render_hint_panel(
     ctx: &CanvasRenderingContext2d,
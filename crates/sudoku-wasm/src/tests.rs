@@ -337,4 +337,89 @@ mod tests {
             assert!(cell.candidates().count() > 0);
         }
     }
+
+    #[test]
+    fn test_stats_migration_and_abandon() {
+        let mut state = GameState::new(Difficulty::Easy);
+
+        // Stats saved before per-difficulty tracking: one win in the history,
+        // and a best time from before the history was kept
+        let v0 = serde_json::json!({
+            "games_played": 2,
+            "games_won": 1,
+            "total_play_time_secs": 500,
+            "current_streak": 1,
+            "best_streak": 1,
+            "best_times": {"Easy": 150, "Hard": 900},
+            "history": [{
+                "timestamp": 1000,
+                "difficulty": "Easy",
+                "result": "Win",
+                "time_secs": 200,
+                "hints_used": 2,
+                "mistakes": 1,
+                "puzzle_hash": "h1",
+            }],
+        });
+        assert!(state.load_stats_json(&v0.to_string()));
+        let stats = state.player_stats();
+        assert_eq!(stats.version, crate::game::STATS_VERSION);
+        assert_eq!((stats.games_lost, stats.games_abandoned), (1, 0));
+        assert_eq!((stats.total_hints, stats.total_mistakes), (2, 1));
+        let easy = &stats.by_difficulty["Easy"];
+        assert_eq!((easy.wins, easy.best_time_secs), (1, Some(150)));
+        assert_eq!(stats.by_difficulty["Hard"].best_time_secs, Some(900));
+
+        // Migrated stats round-trip unchanged
+        let json = state.stats_json();
+        assert!(state.load_stats_json(&json));
+        let parse = |s: &str| serde_json::from_str::<serde_json::Value>(s).unwrap();
+        assert_eq!(parse(&state.stats_json()), parse(&json));
+
+        // Leaving a game after a move counts it once as abandoned
+        state.abandon();
+        assert_eq!(state.player_stats().games_abandoned, 0);
+        let pos = (0..81)
+            .map(|i| Position::new(i / 9, i % 9))
+            .find(|&p| state.grid().get(p).is_none())
+            .unwrap();
+        while state.cursor() != pos {
+            let key = if state.cursor().row < pos.row {
+                "j"
+            } else if state.cursor().row > pos.row {
+                "k"
+            } else if state.cursor().col < pos.col {
+                "l"
+            } else {
+                "h"
+            };
+            state.handle_key(key, false, false);
+        }
+        state.handle_key("1", false, false);
+        state.abandon();
+        state.abandon();
+        let stats = state.player_stats();
+        assert_eq!((stats.games_played, stats.games_abandoned), (3, 1));
+        assert_eq!(stats.by_difficulty["Easy"].abandoned, 1);
+        assert_eq!(stats.current_streak, 0);
+    }
+
+    #[test]
+    fn test_theme_from_file() {
+        use crate::Theme;
+        use sudoku_shared::themes::ThemeFile;
+
+        let file = ThemeFile::from_json(
+            r##"{"base": "light", "colors": {"background": "#102030", "hint_panel": "#000"}}"##,
+        )
+        .unwrap();
+        let theme = Theme::from_file(&file).unwrap();
+        assert_eq!(theme.background.as_css(), "rgb(16, 32, 48)");
+        assert_eq!(theme.hint_panel_bg.as_css(), "rgb(0, 0, 0)");
+        assert_eq!(
+            theme.given_text.as_css(),
+            Theme::light().given_text.as_css()
+        );
+        assert!(Theme::named("high-contrast").is_some());
+    }
 }
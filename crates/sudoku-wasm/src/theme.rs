@@ -1,6 +1,7 @@
 //! Color themes for the WASM Sudoku UI
 
 use serde::{Deserialize, Serialize};
+use sudoku_shared::themes::{Rgb, ThemeError, ThemeFile};
 
 /// RGB color
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -89,6 +90,60 @@ pub struct Theme {
 }
 
 impl Theme {
+    /// Look up a built-in theme by name ("ukodus" is the old name of light)
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" | "ukodus" => Some(Self::light()),
+            "high-contrast" | "high_contrast" => Some(Self::high_contrast()),
+            "colorblind" => Some(Self::colorblind()),
+            "colorblind-light" | "colorblind_light" => Some(Self::colorblind_light()),
+            _ => None,
+        }
+    }
+
+    /// Build a theme from a theme file: its base with the file's colors on
+    /// top. The terminal-only `text` role is ignored.
+    pub fn from_file(file: &ThemeFile) -> Result<Self, ThemeError> {
+        let mut theme = Self::named(file.base_name()?).unwrap_or_else(Self::dark);
+        let c = &file.colors;
+        let set = |slot: &mut Color, color: Option<Rgb>| {
+            if let Some(Rgb { r, g, b }) = color {
+                *slot = Color::new(r, g, b);
+            }
+        };
+        set(&mut theme.background, c.background);
+        set(&mut theme.grid_lines, c.grid_lines);
+        set(&mut theme.box_border, c.box_border);
+        set(&mut theme.cell_bg, c.cell_bg);
+        set(&mut theme.highlight_bg, c.highlight_bg);
+        set(&mut theme.cursor_bg, c.cursor_bg);
+        set(&mut theme.same_value_bg, c.same_value_bg);
+        set(&mut theme.given_text, c.given);
+        set(&mut theme.player_text, c.player);
+        set(&mut theme.candidate_text, c.candidate);
+        set(&mut theme.error_text, c.error);
+        set(&mut theme.lose_color, c.error);
+        set(&mut theme.win_color, c.success);
+        set(&mut theme.completed_bg, c.completed_bg);
+        set(&mut theme.info_text, c.info);
+        set(&mut theme.message_text, c.accent);
+        set(&mut theme.hint_involved_bg, c.hint_involved);
+        set(&mut theme.hint_target_bg, c.hint_target);
+        set(&mut theme.hint_chain_on, c.hint_chain_on);
+        set(&mut theme.hint_chain_off, c.hint_chain_off);
+        set(&mut theme.hint_fish_base, c.hint_fish_base);
+        set(&mut theme.hint_fish_cover, c.hint_fish_cover);
+        set(&mut theme.hint_fish_fin, c.hint_fish_fin);
+        set(&mut theme.hint_ur_floor, c.hint_ur_floor);
+        set(&mut theme.hint_ur_roof, c.hint_ur_roof);
+        set(&mut theme.hint_als_group, c.hint_als_group);
+        set(&mut theme.hint_panel_bg, c.hint_panel);
+        set(&mut theme.hint_technique_text, c.hint_technique);
+        set(&mut theme.hint_explain_text, c.hint_explain);
+        Ok(theme)
+    }
+
     /// Dark theme (default)
     pub fn dark() -> Self {
         Self {
@@ -193,4 +248,40 @@ impl Theme {
             hint_explain_text: Color::new(255, 255, 255),
         }
     }
+
+    /// Dark theme using the Okabe-Ito colors, which stay apart under the
+    /// common kinds of color blindness. Red/green pairs (errors, chain
+    /// polarity) become vermillion/blue.
+    pub fn colorblind() -> Self {
+        Self {
+            player_text: Color::new(86, 180, 233),
+            error_text: Color::new(230, 110, 0),
+            message_text: Color::new(240, 228, 66),
+            win_color: Color::new(0, 170, 125),
+            lose_color: Color::new(230, 110, 0),
+            completed_bg: Color::new(0, 70, 60),
+            hint_target_bg: Color::new(110, 60, 0),
+            hint_chain_on: Color::new(0, 90, 140),
+            hint_chain_off: Color::new(140, 70, 0),
+            hint_technique_text: Color::new(86, 180, 233),
+            ..Self::dark()
+        }
+    }
+
+    /// Light counterpart of [`Theme::colorblind`]
+    pub fn colorblind_light() -> Self {
+        Self {
+            player_text: Color::new(0, 114, 178),
+            error_text: Color::new(213, 94, 0),
+            message_text: Color::new(204, 121, 167),
+            win_color: Color::new(0, 140, 100),
+            lose_color: Color::new(213, 94, 0),
+            completed_bg: Color::new(200, 232, 222),
+            hint_target_bg: Color::new(250, 215, 170),
+            hint_chain_on: Color::new(190, 220, 245),
+            hint_chain_off: Color::new(250, 210, 175),
+            hint_technique_text: Color::new(0, 114, 178),
+            ..Self::light()
+        }
+    }
 }